@@ -0,0 +1,180 @@
+use libc::nlattr;
+use std::{ffi::CStr, io, str};
+
+/// A single netlink attribute parsed from a message payload.
+#[derive(Clone, Copy, Debug)]
+pub struct Attr<'a> {
+    nla_type: u16,
+    payload: &'a [u8],
+}
+
+impl<'a> Attr<'a> {
+    /// Returns the attribute type, with the `NLA_F_NESTED` and `NLA_F_NET_BYTEORDER` flags
+    /// masked out.
+    pub fn attr_type(&self) -> u16 {
+        self.nla_type & libc::NLA_TYPE_MASK as u16
+    }
+
+    /// Returns true if the attribute has the `NLA_F_NESTED` flag set. Not all subsystems set
+    /// this flag on nested attributes.
+    pub fn is_nested(&self) -> bool {
+        self.nla_type & libc::NLA_F_NESTED as u16 != 0
+    }
+
+    /// Returns true if the attribute has the `NLA_F_NET_BYTEORDER` flag set.
+    pub fn is_net_byteorder(&self) -> bool {
+        self.nla_type & libc::NLA_F_NET_BYTEORDER as u16 != 0
+    }
+
+    /// Returns the payload of the attribute, without padding.
+    pub fn payload(&self) -> &'a [u8] {
+        self.payload
+    }
+
+    /// Returns the payload as an `u8`.
+    pub fn get_u8(&self) -> io::Result<u8> {
+        self.get_array().map(u8::from_ne_bytes)
+    }
+
+    /// Returns the payload as an `u16` in host byte order.
+    pub fn get_u16(&self) -> io::Result<u16> {
+        self.get_array().map(u16::from_ne_bytes)
+    }
+
+    /// Returns the payload as an `u32` in host byte order.
+    pub fn get_u32(&self) -> io::Result<u32> {
+        self.get_array().map(u32::from_ne_bytes)
+    }
+
+    /// Returns the payload as an `u64` in host byte order.
+    pub fn get_u64(&self) -> io::Result<u64> {
+        self.get_array().map(u64::from_ne_bytes)
+    }
+
+    /// Returns the payload as an `u16` in network byte order.
+    pub fn get_be16(&self) -> io::Result<u16> {
+        self.get_array().map(u16::from_be_bytes)
+    }
+
+    /// Returns the payload as an `u32` in network byte order.
+    pub fn get_be32(&self) -> io::Result<u32> {
+        self.get_array().map(u32::from_be_bytes)
+    }
+
+    /// Returns the payload as an `u64` in network byte order.
+    pub fn get_be64(&self) -> io::Result<u64> {
+        self.get_array().map(u64::from_be_bytes)
+    }
+
+    /// Returns the payload as a string. The string ends at the first NUL byte, if any.
+    pub fn get_str(&self) -> io::Result<&'a str> {
+        let bytes = match CStr::from_bytes_until_nul(self.payload) {
+            Ok(s) => s.to_bytes(),
+            Err(_) => self.payload,
+        };
+        str::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Returns an iterator over the attributes nested in this attribute.
+    pub fn nested(&self) -> Attrs<'a> {
+        Attrs::new(self.payload)
+    }
+
+    /// Returns the payload as a fixed size array. Fails if the payload has any other length.
+    pub fn get_array<const N: usize>(&self) -> io::Result<[u8; N]> {
+        self.payload.try_into().map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Attribute {} has length {}, expected {N}",
+                    self.attr_type(),
+                    self.payload.len()
+                ),
+            )
+        })
+    }
+}
+
+/// Iterator over a byte buffer of netlink attributes, such as the payload of a message after
+/// the extra header, or the payload of a nested attribute.
+#[derive(Clone)]
+pub struct Attrs<'a> {
+    buffer: &'a [u8],
+}
+
+impl<'a> Attrs<'a> {
+    /// Iterate over the attributes in `buffer`.
+    pub fn new(buffer: &'a [u8]) -> Self {
+        Self { buffer }
+    }
+}
+
+impl<'a> Iterator for Attrs<'a> {
+    type Item = io::Result<Attr<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buffer.len() < size_of::<nlattr>() {
+            return None;
+        }
+
+        let nla_len = u16::from_ne_bytes([self.buffer[0], self.buffer[1]]) as usize;
+        let nla_type = u16::from_ne_bytes([self.buffer[2], self.buffer[3]]);
+
+        if nla_len < size_of::<nlattr>() || nla_len > self.buffer.len() {
+            let remaining = self.buffer.len();
+            self.buffer = &[];
+            return Some(Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid netlink attribute length {nla_len}, remaining buffer {remaining}"),
+            )));
+        }
+
+        let payload = &self.buffer[size_of::<nlattr>()..nla_len];
+        let padded_len = nla_len.next_multiple_of(libc::NLA_ALIGNTO as usize);
+        self.buffer = self.buffer.get(padded_len..).unwrap_or(&[]);
+
+        Some(Ok(Attr { nla_type, payload }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NlMsg, NlMsgBuilder, buffer::AlignedBuffer};
+
+    #[test]
+    fn test_parse_built_attributes() {
+        let mut buffer = AlignedBuffer::new(128);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        msg.put_u16(1, 0x1234).unwrap();
+        msg.put_str(2, "wireguard").unwrap();
+        msg.put_nested(3 | libc::NLA_F_NESTED as u16, |msg| {
+            msg.put_u64(1, u64::MAX - 1)?;
+            msg.put(2, &8080u16.to_be_bytes())
+        })
+        .unwrap();
+
+        let msg = NlMsg::new(msg.as_bytes()).unwrap();
+        let attrs = msg.attrs(0).collect::<io::Result<Vec<_>>>().unwrap();
+        assert_eq!(attrs.len(), 3);
+        assert_eq!(attrs[0].attr_type(), 1);
+        assert_eq!(attrs[0].get_u16().unwrap(), 0x1234);
+        assert!(attrs[0].get_u32().is_err());
+        assert_eq!(attrs[1].get_str().unwrap(), "wireguard");
+        assert_eq!(attrs[2].attr_type(), 3);
+        assert!(attrs[2].is_nested());
+
+        let nested = attrs[2].nested().collect::<io::Result<Vec<_>>>().unwrap();
+        assert_eq!(nested[0].get_u64().unwrap(), u64::MAX - 1);
+        assert_eq!(nested[1].get_be16().unwrap(), 8080);
+    }
+
+    #[test]
+    fn test_parse_invalid_attribute_length() {
+        let buffer = [8, 0, 1, 0, 0, 0, 0, 0, 12, 0, 2, 0, 0, 0, 0, 0];
+        let mut attrs = Attrs::new(&buffer);
+        assert!(attrs.next().unwrap().is_ok());
+        assert!(attrs.next().unwrap().is_err());
+        assert!(attrs.next().is_none());
+    }
+}
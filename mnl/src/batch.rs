@@ -0,0 +1,223 @@
+use std::{io, slice};
+
+use crate::{NlMsgBuilder, buffer::AlignedBuffer, cvt::cvt};
+
+/// A batch of netlink messages sent to the kernel in one go. Wraps the `mnl_nlmsg_batch_*` API in
+/// `libmnl` and owns the buffer the messages are written to.
+///
+/// Messages are added by first writing them with the builder returned from [`msg_builder`] and
+/// then committing them with [`next`]. When [`next`] returns `false` the batch is full, and should
+/// be sent with [`Socket::send`] using [`as_bytes`], then cleared with [`reset`]. The message that
+/// did not fit is kept and becomes the first message of the batch after the reset.
+///
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// let socket = mnl::Socket::new(mnl::Bus::Netfilter)?;
+/// let mut batch = mnl::NlBatch::new()?;
+/// for seq in 0..1000 {
+///     let mut msg = batch.msg_builder()?;
+///     msg.set_seq(seq);
+///     // Fill in the message here.
+///     if !batch.next() {
+///         socket.send(batch.as_bytes())?;
+///         batch.reset();
+///     }
+/// }
+/// if !batch.is_empty() {
+///     socket.send(batch.as_bytes())?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`msg_builder`]: #method.msg_builder
+/// [`next`]: #method.next
+/// [`as_bytes`]: #method.as_bytes
+/// [`reset`]: #method.reset
+/// [`Socket::send`]: crate::Socket::send
+pub struct NlBatch {
+    batch: *mut mnl_sys::mnl_nlmsg_batch,
+    buffer: AlignedBuffer,
+    limit: usize,
+    /// A message has been written to the current slot but not yet committed with `next`.
+    written: bool,
+    /// The last committed message did not fit within the limit.
+    overflow: bool,
+}
+
+impl NlBatch {
+    /// Create a new batch with a limit of [`MNL_SOCKET_BUFFER_SIZE`] bytes.
+    ///
+    /// [`MNL_SOCKET_BUFFER_SIZE`]: mnl_sys::MNL_SOCKET_BUFFER_SIZE
+    pub fn new() -> io::Result<Self> {
+        Self::with_limit(mnl_sys::MNL_SOCKET_BUFFER_SIZE() as usize)
+    }
+
+    /// Create a new batch that is considered full when it grows beyond `limit` bytes. A single
+    /// message can be at most `limit` bytes.
+    pub fn with_limit(limit: usize) -> io::Result<Self> {
+        // libmnl needs room for one message exceeding the limit, so the buffer must be twice
+        // the size of the limit.
+        let mut buffer = AlignedBuffer::new(limit * 2);
+        let batch = cvt(unsafe {
+            mnl_sys::mnl_nlmsg_batch_start(buffer.as_mut_bytes().as_mut_ptr().cast(), limit)
+        })?;
+        Ok(NlBatch {
+            batch,
+            buffer,
+            limit,
+            written: false,
+            overflow: false,
+        })
+    }
+
+    /// Returns a builder for the next message in the batch. Any message previously written with
+    /// a builder but not committed with [`next`] is overwritten.
+    ///
+    /// Fails if the batch is full and has not yet been [`reset`].
+    ///
+    /// [`next`]: #method.next
+    /// [`reset`]: #method.reset
+    pub fn msg_builder(&mut self) -> io::Result<NlMsgBuilder<'_>> {
+        if self.overflow {
+            return Err(io::Error::other("Netlink batch is full and must be reset"));
+        }
+        self.written = true;
+        // Safety: `current` points into `buffer` at offset `size()`, which is never beyond the
+        // limit while not overflowed. The buffer holds twice the limit, so there is at least
+        // `limit` bytes available.
+        unsafe {
+            let current = mnl_sys::mnl_nlmsg_batch_current(self.batch);
+            NlMsgBuilder::from_raw(current, self.limit)
+        }
+    }
+
+    /// Commit the message written with the latest builder to the batch. Returns `false` if the
+    /// batch is full and must be sent and [`reset`] before more messages can be added. In that
+    /// case the message is not part of [`as_bytes`], but is kept for after the reset.
+    ///
+    /// Does nothing except report the current state if no message has been written since the
+    /// last call.
+    ///
+    /// [`reset`]: #method.reset
+    /// [`as_bytes`]: #method.as_bytes
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> bool {
+        if self.written {
+            self.written = false;
+            self.overflow = !unsafe { mnl_sys::mnl_nlmsg_batch_next(self.batch) };
+        }
+        !self.overflow
+    }
+
+    /// Returns true if the batch is full, meaning [`next`] returned `false` since the last reset.
+    ///
+    /// [`next`]: #method.next
+    pub fn is_full(&self) -> bool {
+        self.overflow
+    }
+
+    /// Clear all committed messages so the batch can be reused without reallocating. If the
+    /// batch was full, the message that did not fit is moved to the start of the batch.
+    pub fn reset(&mut self) {
+        unsafe { mnl_sys::mnl_nlmsg_batch_reset(self.batch) };
+        self.written = false;
+        self.overflow = false;
+    }
+
+    /// Returns the size in bytes of all committed messages in the batch.
+    pub fn size(&self) -> usize {
+        unsafe { mnl_sys::mnl_nlmsg_batch_size(self.batch) }
+    }
+
+    /// Returns the limit in bytes after which the batch is considered full.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Returns true if no messages have been committed to the batch.
+    pub fn is_empty(&self) -> bool {
+        unsafe { mnl_sys::mnl_nlmsg_batch_is_empty(self.batch) }
+    }
+
+    /// Returns the bytes of all committed messages, ready to be sent with [`Socket::send`].
+    ///
+    /// [`Socket::send`]: crate::Socket::send
+    pub fn as_bytes(&self) -> &[u8] {
+        let size = self.size();
+        debug_assert!(size <= self.buffer.len());
+        unsafe {
+            let head = mnl_sys::mnl_nlmsg_batch_head(self.batch);
+            slice::from_raw_parts(head.cast::<u8>(), size)
+        }
+    }
+
+    /// Return the pointer to the underlying C struct. Can be used with the `mnl_sys` crate to
+    /// perform actions not yet exposed in this safe abstraction.
+    pub fn as_raw_batch(&self) -> *mut mnl_sys::mnl_nlmsg_batch {
+        self.batch
+    }
+}
+
+impl Drop for NlBatch {
+    fn drop(&mut self) {
+        unsafe { mnl_sys::mnl_nlmsg_batch_stop(self.batch) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NlMessages;
+
+    fn add_message(batch: &mut NlBatch, seq: u32, payload_len: usize) -> bool {
+        let mut msg = batch.msg_builder().unwrap();
+        msg.set_seq(seq);
+        msg.put(1, &vec![0xff; payload_len]).unwrap();
+        batch.next()
+    }
+
+    #[test]
+    fn test_batch_fills_up_and_resets() {
+        let mut batch = NlBatch::with_limit(100).unwrap();
+        assert!(batch.is_empty());
+        // Each message is 16 + 4 + 12 = 32 bytes.
+        assert!(add_message(&mut batch, 1, 12));
+        assert!(add_message(&mut batch, 2, 12));
+        assert!(add_message(&mut batch, 3, 12));
+        assert_eq!(batch.size(), 96);
+        assert!(!add_message(&mut batch, 4, 12));
+        assert!(batch.is_full());
+        assert!(batch.msg_builder().is_err());
+        assert_eq!(batch.size(), 96);
+
+        let seqs = NlMessages::new(batch.as_bytes())
+            .map(|msg| u32::from_ne_bytes(msg.unwrap()[8..12].try_into().unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(seqs, [1, 2, 3]);
+
+        batch.reset();
+        assert!(!batch.is_full());
+        assert_eq!(batch.size(), 32);
+        assert_eq!(&batch.as_bytes()[8..12], &4u32.to_ne_bytes());
+    }
+
+    #[test]
+    fn test_uncommitted_message_is_not_included() {
+        let mut batch = NlBatch::with_limit(100).unwrap();
+        assert!(add_message(&mut batch, 1, 4));
+        batch.msg_builder().unwrap().set_seq(2);
+        assert_eq!(batch.size(), 24);
+        assert!(batch.next());
+        assert_eq!(batch.size(), 40);
+        assert!(batch.next());
+        assert_eq!(batch.size(), 40);
+    }
+
+    #[test]
+    fn test_message_larger_than_limit() {
+        let mut batch = NlBatch::with_limit(64).unwrap();
+        let mut msg = batch.msg_builder().unwrap();
+        assert!(msg.put(1, &[0; 64]).is_err());
+    }
+}
@@ -0,0 +1,29 @@
+use libc::nlmsghdr;
+use std::slice;
+
+/// Heap allocated byte buffer aligned to `align_of::<nlmsghdr>()`. Suitable both for building
+/// netlink messages and for receiving them with [`Socket::recv`](crate::Socket::recv).
+pub(crate) struct AlignedBuffer {
+    buffer: Box<[u32]>,
+    len: usize,
+}
+
+const _: () = assert!(align_of::<u32>() >= align_of::<nlmsghdr>());
+
+impl AlignedBuffer {
+    /// Allocate a zeroed buffer of at least `len` bytes.
+    pub fn new(len: usize) -> Self {
+        AlignedBuffer {
+            buffer: vec![0; len.div_ceil(size_of::<u32>())].into_boxed_slice(),
+            len,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn as_mut_bytes(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.buffer.as_mut_ptr().cast(), self.len) }
+    }
+}
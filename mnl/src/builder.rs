@@ -0,0 +1,241 @@
+use libc::nlmsghdr;
+use mnl_sys::{self, libc::c_void};
+use std::{ffi::CString, io, marker::PhantomData, slice};
+
+/// Builder writing a single netlink message into a caller provided buffer. Wraps the
+/// `mnl_nlmsg_put_*` and `mnl_attr_put_*_check` family of functions in `libmnl`.
+///
+/// The netlink header is written as soon as the builder is created. After that an optional extra
+/// header (such as `nfgenmsg` or `genlmsghdr`) can be added, followed by any number of
+/// attributes. All write operations check that the message still fits in the buffer and return
+/// an error if it does not.
+pub struct NlMsgBuilder<'a> {
+    nlh: *mut nlmsghdr,
+    buflen: usize,
+    _buffer: PhantomData<&'a mut [u8]>,
+}
+
+impl<'a> NlMsgBuilder<'a> {
+    /// Start a new netlink message at the beginning of `buffer`. The header is zeroed except for
+    /// `nlmsg_len`.
+    ///
+    /// `buffer` must be aligned to `align_of::<nlmsghdr>()`, or this fails.
+    pub fn new(buffer: &'a mut [u8]) -> io::Result<Self> {
+        if !buffer.as_ptr().cast::<nlmsghdr>().is_aligned() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Buffer is not aligned to align_of::<nlmsghdr>()",
+            ));
+        }
+        // Safety: The buffer is valid for writes of `buffer.len()` bytes and properly aligned.
+        unsafe { Self::from_raw(buffer.as_mut_ptr().cast(), buffer.len()) }
+    }
+
+    /// Start a new netlink message at `buf`.
+    ///
+    /// # Safety
+    ///
+    /// `buf` must be aligned to `nlmsghdr` and valid for writes of `buflen` bytes for as long as
+    /// the returned builder lives.
+    pub(crate) unsafe fn from_raw(buf: *mut c_void, buflen: usize) -> io::Result<Self> {
+        if buflen < mnl_sys::MNL_ALIGN(size_of::<nlmsghdr>() as i32) as usize {
+            return Err(no_space());
+        }
+        let nlh = unsafe { mnl_sys::mnl_nlmsg_put_header(buf) };
+        Ok(NlMsgBuilder {
+            nlh,
+            buflen,
+            _buffer: PhantomData,
+        })
+    }
+
+    /// Set the message type (`nlmsg_type`).
+    pub fn set_type(&mut self, msg_type: u16) {
+        self.header_mut().nlmsg_type = msg_type;
+    }
+
+    /// Set the message flags (`nlmsg_flags`), for example `NLM_F_REQUEST | NLM_F_ACK`.
+    pub fn set_flags(&mut self, flags: u16) {
+        self.header_mut().nlmsg_flags = flags;
+    }
+
+    /// Set the sequence number (`nlmsg_seq`).
+    pub fn set_seq(&mut self, seq: u32) {
+        self.header_mut().nlmsg_seq = seq;
+    }
+
+    /// Set the port id (`nlmsg_pid`).
+    pub fn set_portid(&mut self, portid: u32) {
+        self.header_mut().nlmsg_pid = portid;
+    }
+
+    /// Returns the netlink header of the message being built.
+    pub fn header(&self) -> &nlmsghdr {
+        unsafe { &*self.nlh }
+    }
+
+    /// Mutable access to the header is kept private since changing `nlmsg_len` would allow
+    /// reading and writing outside of the message.
+    fn header_mut(&mut self) -> &mut nlmsghdr {
+        unsafe { &mut *self.nlh }
+    }
+
+    /// Append an extra header after the netlink header. The data is padded to the netlink
+    /// alignment. This must be called before any attribute is added, except inside nested
+    /// attributes that start with a header, such as the peer of a veth device.
+    pub fn put_extra_header(&mut self, data: &[u8]) -> io::Result<()> {
+        let padded_len = mnl_sys::MNL_ALIGN(data.len() as i32) as usize;
+        if self.len() + padded_len > self.buflen {
+            return Err(no_space());
+        }
+        unsafe {
+            let ptr = mnl_sys::mnl_nlmsg_put_extra_header(self.nlh, data.len()).cast::<u8>();
+            ptr.copy_from_nonoverlapping(data.as_ptr(), data.len());
+        }
+        Ok(())
+    }
+
+    /// Append an attribute with the given type and payload.
+    pub fn put(&mut self, attr_type: u16, data: &[u8]) -> io::Result<()> {
+        check(unsafe {
+            mnl_sys::mnl_attr_put_check(
+                self.nlh,
+                self.buflen,
+                attr_type,
+                data.len(),
+                data.as_ptr().cast(),
+            )
+        })
+    }
+
+    /// Append an attribute with an empty payload. Used for flag attributes.
+    pub fn put_flag(&mut self, attr_type: u16) -> io::Result<()> {
+        self.put(attr_type, &[])
+    }
+
+    /// Append an `u8` attribute.
+    pub fn put_u8(&mut self, attr_type: u16, data: u8) -> io::Result<()> {
+        check(unsafe { mnl_sys::mnl_attr_put_u8_check(self.nlh, self.buflen, attr_type, data) })
+    }
+
+    /// Append an `u16` attribute in host byte order.
+    pub fn put_u16(&mut self, attr_type: u16, data: u16) -> io::Result<()> {
+        check(unsafe { mnl_sys::mnl_attr_put_u16_check(self.nlh, self.buflen, attr_type, data) })
+    }
+
+    /// Append an `u32` attribute in host byte order.
+    pub fn put_u32(&mut self, attr_type: u16, data: u32) -> io::Result<()> {
+        check(unsafe { mnl_sys::mnl_attr_put_u32_check(self.nlh, self.buflen, attr_type, data) })
+    }
+
+    /// Append an `u64` attribute in host byte order.
+    pub fn put_u64(&mut self, attr_type: u16, data: u64) -> io::Result<()> {
+        check(unsafe { mnl_sys::mnl_attr_put_u64_check(self.nlh, self.buflen, attr_type, data) })
+    }
+
+    /// Append a NUL terminated string attribute. Fails if `data` contains a NUL byte.
+    pub fn put_str(&mut self, attr_type: u16, data: &str) -> io::Result<()> {
+        let data = CString::new(data)?;
+        check(unsafe {
+            mnl_sys::mnl_attr_put_strz_check(self.nlh, self.buflen, attr_type, data.as_ptr())
+        })
+    }
+
+    /// Append a nested attribute of the given type. The attributes added to the builder by `f`
+    /// end up inside the nest. If `f` fails the entire nest is removed from the message again.
+    pub fn put_nested<F>(&mut self, attr_type: u16, f: F) -> io::Result<()>
+    where
+        F: FnOnce(&mut Self) -> io::Result<()>,
+    {
+        let nest = unsafe { mnl_sys::mnl_attr_nest_start_check(self.nlh, self.buflen, attr_type) };
+        if nest.is_null() {
+            return Err(no_space());
+        }
+        match f(self) {
+            Ok(()) => {
+                unsafe { mnl_sys::mnl_attr_nest_end(self.nlh, nest) };
+                Ok(())
+            }
+            Err(e) => {
+                unsafe { mnl_sys::mnl_attr_nest_cancel(self.nlh, nest) };
+                Err(e)
+            }
+        }
+    }
+
+    /// Returns the current length of the message, including the header.
+    pub fn len(&self) -> usize {
+        self.header().nlmsg_len as usize
+    }
+
+    /// Returns true if the message consists of only the netlink header.
+    pub fn is_empty(&self) -> bool {
+        self.len() <= size_of::<nlmsghdr>()
+    }
+
+    /// Returns the bytes of the message built so far.
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.nlh.cast::<u8>(), self.len()) }
+    }
+}
+
+fn check(fits: bool) -> io::Result<()> {
+    if fits { Ok(()) } else { Err(no_space()) }
+}
+
+pub(crate) fn no_space() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::OutOfMemory,
+        "Netlink message does not fit in the buffer",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::AlignedBuffer;
+
+    #[test]
+    fn test_build_message_with_attributes() {
+        let mut buffer = AlignedBuffer::new(256);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        msg.set_type(18);
+        msg.set_flags(libc::NLM_F_REQUEST as u16);
+        msg.set_seq(7);
+        msg.put_extra_header(&[1, 2]).unwrap();
+        msg.put_u32(3, 0xdeadbeef).unwrap();
+        msg.put_nested(4, |msg| msg.put_str(1, "lo")).unwrap();
+
+        // header + padded extra header + u32 attr + nest(attr header + "lo\0" padded)
+        assert_eq!(msg.len(), 16 + 4 + 8 + 4 + 8);
+        let bytes = msg.as_bytes();
+        assert_eq!(&bytes[4..6], &18u16.to_ne_bytes());
+        assert_eq!(&bytes[8..12], &7u32.to_ne_bytes());
+        assert_eq!(&bytes[16..20], &[1, 2, 0, 0]);
+        assert_eq!(&bytes[24..28], &0xdeadbeefu32.to_ne_bytes());
+        assert_eq!(&bytes[28..30], &12u16.to_ne_bytes());
+        assert_eq!(&bytes[36..39], b"lo\0");
+    }
+
+    #[test]
+    fn test_message_does_not_fit() {
+        let mut buffer = AlignedBuffer::new(24);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        msg.put_u32(1, 1).unwrap();
+        assert!(msg.put_u32(2, 2).is_err());
+        assert!(msg.put_nested(3, |_| Ok(())).is_err());
+        assert_eq!(msg.len(), 24);
+    }
+
+    #[test]
+    fn test_failed_nest_is_cancelled() {
+        let mut buffer = AlignedBuffer::new(64);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        let result = msg.put_nested(1, |msg| {
+            msg.put_u32(2, 2)?;
+            msg.put_str(3, "in\0valid")
+        });
+        assert!(result.is_err());
+        assert!(msg.is_empty());
+    }
+}
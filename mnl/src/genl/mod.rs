@@ -0,0 +1,389 @@
+//! Generic netlink (`NETLINK_GENERIC`) support.
+//!
+//! Generic netlink families are identified by name, and get a message type id assigned by the
+//! kernel when registered. [`GenlFamily::resolve`] asks the controller family (`nlctrl`) for the
+//! id and other properties of a family by name. Requests to the family can then be built with
+//! [`GenlFamily::request`].
+//!
+//! ```no_run
+//! # fn main() -> std::io::Result<()> {
+//! use mnl::genl::GenlFamily;
+//!
+//! let socket = mnl::Socket::new(mnl::Bus::Generic)?;
+//! let family = GenlFamily::resolve(&socket, "wireguard")?;
+//! println!("wireguard has family id {} and version {}", family.id, family.version);
+//! # Ok(())
+//! # }
+//! ```
+
+use mnl_sys::libc;
+use std::{error, fmt, io};
+
+use crate::{Attr, Bus, NlMsg, NlMsgBuilder, Socket, buffer::AlignedBuffer};
+
+mod policy;
+pub use self::policy::*;
+
+pub mod wireguard;
+
+/// Size of the generic netlink header (`genlmsghdr`) following the netlink header.
+pub const GENL_HDRLEN: usize = size_of::<libc::genlmsghdr>();
+
+/// Append a generic netlink header (`genlmsghdr`) with the given command and family version to
+/// `msg`.
+pub fn put_header(msg: &mut NlMsgBuilder<'_>, cmd: u8, version: u8) -> io::Result<()> {
+    msg.put_extra_header(&[cmd, version, 0, 0])
+}
+
+/// Returns the command and version from the generic netlink header of `msg`.
+pub fn parse_header(msg: &NlMsg<'_>) -> io::Result<(u8, u8)> {
+    match msg.payload() {
+        [cmd, version, _, _, ..] => Ok((*cmd, *version)),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Message too short for generic netlink header",
+        )),
+    }
+}
+
+/// A generic netlink family, as described by the generic netlink controller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenlFamily {
+    /// The message type id assigned to the family.
+    pub id: u16,
+    /// Name of the family.
+    pub name: String,
+    /// Version of the family protocol.
+    pub version: u32,
+    /// Size of the family specific header following the generic netlink header.
+    pub hdr_size: u32,
+    /// Highest attribute type the family accepts.
+    pub max_attr: u32,
+    /// Operations (commands) supported by the family.
+    pub ops: Vec<GenlOp>,
+    /// Multicast groups the family sends notifications to.
+    pub mcast_groups: Vec<GenlMcastGroup>,
+}
+
+/// An operation supported by a generic netlink family.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenlOp {
+    /// The command id.
+    pub id: u32,
+    /// `GENL_*` flags of the operation, such as `GENL_ADMIN_PERM` and `GENL_CMD_CAP_DUMP`.
+    pub flags: u32,
+}
+
+/// A multicast group of a generic netlink family.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenlMcastGroup {
+    /// Name of the group.
+    pub name: String,
+    /// The group id to use when subscribing to the group.
+    pub id: u32,
+}
+
+impl GenlFamily {
+    /// Look up the generic netlink family with the given name by sending `CTRL_CMD_GETFAMILY`
+    /// to the controller on `socket`, which must be a [`Bus::Generic`] socket.
+    ///
+    /// The result is cached per socket, so only the first lookup of a family sends a request.
+    /// Fails with `NotFound` if no family with that name is registered in the kernel.
+    pub fn resolve(socket: &Socket, name: &str) -> io::Result<GenlFamily> {
+        if let Some(family) = socket.genl_families.borrow().get(name) {
+            return Ok(family.clone());
+        }
+        let family = Self::query(socket, name)?;
+        socket
+            .genl_families
+            .borrow_mut()
+            .insert(name.to_owned(), family.clone());
+        Ok(family)
+    }
+
+    fn query(socket: &Socket, name: &str) -> io::Result<GenlFamily> {
+        if socket.bus() != Bus::Generic {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Generic netlink families can only be resolved on a Bus::Generic socket",
+            ));
+        }
+        let mut buffer = AlignedBuffer::new(256);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes())?;
+        msg.set_type(libc::GENL_ID_CTRL as u16);
+        msg.set_flags(libc::NLM_F_REQUEST as u16);
+        msg.set_seq(socket.next_seq());
+        put_header(&mut msg, libc::CTRL_CMD_GETFAMILY as u8, 1)?;
+        msg.put_str(libc::CTRL_ATTR_FAMILY_NAME as u16, name)?;
+
+        let mut family = None;
+        socket.request(msg.as_bytes(), |reply| {
+            family = Some(Self::from_msg(&reply)?);
+            Ok(())
+        })?;
+        family.ok_or_else(|| io::Error::other("No reply from the generic netlink controller"))
+    }
+
+    /// Parse a `CTRL_CMD_NEWFAMILY` message from the generic netlink controller. This is both
+    /// the reply to `CTRL_CMD_GETFAMILY` and the notification sent when a family is registered.
+    pub fn from_msg(msg: &NlMsg<'_>) -> io::Result<GenlFamily> {
+        let mut family = GenlFamily {
+            id: 0,
+            name: String::new(),
+            version: 0,
+            hdr_size: 0,
+            max_attr: 0,
+            ops: Vec::new(),
+            mcast_groups: Vec::new(),
+        };
+        for attr in msg.attrs(GENL_HDRLEN) {
+            let attr = attr?;
+            match attr.attr_type() as i32 {
+                libc::CTRL_ATTR_FAMILY_ID => family.id = attr.get_u16()?,
+                libc::CTRL_ATTR_FAMILY_NAME => family.name = attr.get_str()?.to_owned(),
+                libc::CTRL_ATTR_VERSION => family.version = attr.get_u32()?,
+                libc::CTRL_ATTR_HDRSIZE => family.hdr_size = attr.get_u32()?,
+                libc::CTRL_ATTR_MAXATTR => family.max_attr = attr.get_u32()?,
+                libc::CTRL_ATTR_OPS => {
+                    for op in attr.nested() {
+                        family.ops.push(parse_op(op?)?);
+                    }
+                }
+                libc::CTRL_ATTR_MCAST_GROUPS => {
+                    for group in attr.nested() {
+                        family.mcast_groups.push(parse_mcast_group(group?)?);
+                    }
+                }
+                _ => (),
+            }
+        }
+        if family.id == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Generic netlink family without family id",
+            ));
+        }
+        Ok(family)
+    }
+
+    /// Start building a request to this family in `buffer`. Writes the netlink header with the
+    /// family id as message type and `NLM_F_REQUEST` set, followed by the generic netlink header
+    /// with the given command and the family version.
+    ///
+    /// Sequence number and further flags are left for the caller to set.
+    pub fn request<'a>(&self, buffer: &'a mut [u8], cmd: u8) -> io::Result<NlMsgBuilder<'a>> {
+        let mut msg = NlMsgBuilder::new(buffer)?;
+        msg.set_type(self.id);
+        msg.set_flags(libc::NLM_F_REQUEST as u16);
+        put_header(&mut msg, cmd, self.version as u8)?;
+        Ok(msg)
+    }
+
+    /// Returns the operation with the given command id, if the family supports it.
+    pub fn op(&self, cmd: u32) -> Option<&GenlOp> {
+        self.ops.iter().find(|op| op.id == cmd)
+    }
+
+    /// Returns the multicast group with the given name, if the family has one.
+    pub fn mcast_group(&self, name: &str) -> Option<&GenlMcastGroup> {
+        self.mcast_groups.iter().find(|group| group.name == name)
+    }
+}
+
+impl Socket {
+    /// Subscribe to the multicast group named `group` of the generic netlink family `family`.
+    /// The group id is looked up with [`GenlFamily::resolve`] and then joined with
+    /// [`join_group`]. Returns the id of the joined group.
+    ///
+    /// [`join_group`]: #method.join_group
+    pub fn join_genl_group(&self, family: &str, group: &str) -> Result<u32, GenlError> {
+        let id = mcast_group_id(GenlFamily::resolve(self, family), family, group)?;
+        self.join_group(id)?;
+        Ok(id)
+    }
+}
+
+/// Returns the id of `group` from the result of resolving `family`.
+fn mcast_group_id(
+    resolved: io::Result<GenlFamily>,
+    family: &str,
+    group: &str,
+) -> Result<u32, GenlError> {
+    let genl_family = match resolved {
+        Ok(genl_family) => genl_family,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return Err(GenlError::FamilyNotFound(family.to_owned()));
+        }
+        Err(e) => return Err(GenlError::Io(e)),
+    };
+    genl_family
+        .mcast_group(group)
+        .map(|group| group.id)
+        .ok_or_else(|| GenlError::GroupNotFound {
+            family: family.to_owned(),
+            group: group.to_owned(),
+        })
+}
+
+/// Errors from generic netlink operations that look up families and groups by name.
+#[derive(Debug)]
+pub enum GenlError {
+    /// No generic netlink family with the given name is registered in the kernel.
+    FamilyNotFound(String),
+    /// The family exists, but does not have a multicast group with the given name.
+    GroupNotFound {
+        /// Name of the family.
+        family: String,
+        /// Name of the missing group.
+        group: String,
+    },
+    /// Communicating with the kernel failed.
+    Io(io::Error),
+}
+
+impl fmt::Display for GenlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenlError::FamilyNotFound(family) => {
+                write!(f, "Generic netlink family \"{family}\" does not exist")
+            }
+            GenlError::GroupNotFound { family, group } => write!(
+                f,
+                "Generic netlink family \"{family}\" has no multicast group \"{group}\""
+            ),
+            GenlError::Io(e) => write!(f, "Generic netlink request failed: {e}"),
+        }
+    }
+}
+
+impl error::Error for GenlError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            GenlError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for GenlError {
+    fn from(error: io::Error) -> Self {
+        GenlError::Io(error)
+    }
+}
+
+fn parse_op(attr: Attr<'_>) -> io::Result<GenlOp> {
+    let mut op = GenlOp { id: 0, flags: 0 };
+    for attr in attr.nested() {
+        let attr = attr?;
+        match attr.attr_type() as i32 {
+            libc::CTRL_ATTR_OP_ID => op.id = attr.get_u32()?,
+            libc::CTRL_ATTR_OP_FLAGS => op.flags = attr.get_u32()?,
+            _ => (),
+        }
+    }
+    Ok(op)
+}
+
+fn parse_mcast_group(attr: Attr<'_>) -> io::Result<GenlMcastGroup> {
+    let mut group = GenlMcastGroup {
+        name: String::new(),
+        id: 0,
+    };
+    for attr in attr.nested() {
+        let attr = attr?;
+        match attr.attr_type() as i32 {
+            libc::CTRL_ATTR_MCAST_GRP_NAME => group.name = attr.get_str()?.to_owned(),
+            libc::CTRL_ATTR_MCAST_GRP_ID => group.id = attr.get_u32()?,
+            _ => (),
+        }
+    }
+    Ok(group)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write a `CTRL_CMD_NEWFAMILY` reply describing the wireguard family.
+    fn put_wireguard_family(msg: &mut NlMsgBuilder<'_>) {
+        msg.set_type(libc::GENL_ID_CTRL as u16);
+        put_header(msg, libc::CTRL_CMD_NEWFAMILY as u8, 2).unwrap();
+        msg.put_str(libc::CTRL_ATTR_FAMILY_NAME as u16, "wireguard")
+            .unwrap();
+        msg.put_u16(libc::CTRL_ATTR_FAMILY_ID as u16, 0x20).unwrap();
+        msg.put_u32(libc::CTRL_ATTR_VERSION as u16, 1).unwrap();
+        msg.put_u32(libc::CTRL_ATTR_HDRSIZE as u16, 0).unwrap();
+        msg.put_u32(libc::CTRL_ATTR_MAXATTR as u16, 8).unwrap();
+        msg.put_nested(libc::CTRL_ATTR_OPS as u16, |msg| {
+            for (i, cmd) in [0u32, 1].into_iter().enumerate() {
+                msg.put_nested(i as u16 + 1, |msg| {
+                    msg.put_u32(libc::CTRL_ATTR_OP_ID as u16, cmd)?;
+                    msg.put_u32(libc::CTRL_ATTR_OP_FLAGS as u16, 0x1)
+                })?;
+            }
+            Ok(())
+        })
+        .unwrap();
+        msg.put_nested(libc::CTRL_ATTR_MCAST_GROUPS as u16, |msg| {
+            msg.put_nested(1, |msg| {
+                msg.put_str(libc::CTRL_ATTR_MCAST_GRP_NAME as u16, "peers")?;
+                msg.put_u32(libc::CTRL_ATTR_MCAST_GRP_ID as u16, 12)
+            })
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_parse_family() {
+        let mut buffer = AlignedBuffer::new(512);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        put_wireguard_family(&mut msg);
+
+        let msg = NlMsg::new(msg.as_bytes()).unwrap();
+        assert_eq!(
+            parse_header(&msg).unwrap(),
+            (libc::CTRL_CMD_NEWFAMILY as u8, 2)
+        );
+        let family = GenlFamily::from_msg(&msg).unwrap();
+        assert_eq!(family.name, "wireguard");
+        assert_eq!(family.id, 0x20);
+        assert_eq!(family.version, 1);
+        assert_eq!(family.max_attr, 8);
+        assert_eq!(
+            family.ops,
+            [GenlOp { id: 0, flags: 1 }, GenlOp { id: 1, flags: 1 }]
+        );
+        assert_eq!(family.mcast_group("peers").map(|g| g.id), Some(12));
+        assert!(family.mcast_group("mlme").is_none());
+    }
+
+    #[test]
+    fn test_mcast_group_id() {
+        let mut buffer = AlignedBuffer::new(512);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        put_wireguard_family(&mut msg);
+        let family = GenlFamily::from_msg(&NlMsg::new(msg.as_bytes()).unwrap());
+        assert_eq!(mcast_group_id(family, "wireguard", "peers").unwrap(), 12);
+
+        let family = GenlFamily::from_msg(&NlMsg::new(msg.as_bytes()).unwrap());
+        match mcast_group_id(family, "wireguard", "mlme") {
+            Err(GenlError::GroupNotFound { family, group }) => {
+                assert_eq!((family.as_str(), group.as_str()), ("wireguard", "mlme"));
+            }
+            result => panic!("Expected GroupNotFound, got {result:?}"),
+        }
+
+        // The controller replies with ENOENT to CTRL_CMD_GETFAMILY for unknown families.
+        let mut buffer = AlignedBuffer::new(512);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        msg.set_type(libc::NLMSG_ERROR as u16);
+        msg.put_extra_header(&(-libc::ENOENT).to_ne_bytes())
+            .unwrap();
+        msg.put_extra_header(&[0; 16]).unwrap();
+        let error = NlMsg::new(msg.as_bytes()).unwrap().error().unwrap();
+        match mcast_group_id(Err(error.unwrap_err()), "nl80211", "mlme") {
+            Err(GenlError::FamilyNotFound(family)) => assert_eq!(family, "nl80211"),
+            result => panic!("Expected FamilyNotFound, got {result:?}"),
+        }
+    }
+}
@@ -0,0 +1,341 @@
+use mnl_sys::libc;
+use std::{collections::BTreeMap, io};
+
+use super::{GENL_HDRLEN, GenlFamily, put_header};
+use crate::{Attr, NlMsg, NlMsgBuilder, Socket, buffer::AlignedBuffer};
+
+// Constants from `linux/genetlink.h` and `linux/netlink.h` not yet exposed by `libc`.
+const CTRL_CMD_GETPOLICY: u8 = 10;
+const CTRL_ATTR_POLICY: u16 = 8;
+const CTRL_ATTR_OP_POLICY: u16 = 9;
+const CTRL_ATTR_OP: u16 = 10;
+const CTRL_ATTR_POLICY_DO: u16 = 1;
+const CTRL_ATTR_POLICY_DUMP: u16 = 2;
+
+const NL_POLICY_TYPE_ATTR_TYPE: u16 = 1;
+const NL_POLICY_TYPE_ATTR_MIN_VALUE_S: u16 = 2;
+const NL_POLICY_TYPE_ATTR_MAX_VALUE_S: u16 = 3;
+const NL_POLICY_TYPE_ATTR_MIN_VALUE_U: u16 = 4;
+const NL_POLICY_TYPE_ATTR_MAX_VALUE_U: u16 = 5;
+const NL_POLICY_TYPE_ATTR_MIN_LENGTH: u16 = 6;
+const NL_POLICY_TYPE_ATTR_MAX_LENGTH: u16 = 7;
+const NL_POLICY_TYPE_ATTR_POLICY_IDX: u16 = 8;
+const NL_POLICY_TYPE_ATTR_POLICY_MAXTYPE: u16 = 9;
+const NL_POLICY_TYPE_ATTR_BITFIELD32_MASK: u16 = 10;
+const NL_POLICY_TYPE_ATTR_MASK: u16 = 12;
+
+/// The attribute policies of a generic netlink family, as reported by the kernel through
+/// `CTRL_CMD_GETPOLICY`. Requires Linux 5.7 or newer. Per operation policies require Linux 5.10.
+///
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// use mnl::genl::{FamilyPolicy, GenlFamily};
+///
+/// const ETHTOOL_MSG_LINKINFO_GET: u32 = 2;
+/// const ETHTOOL_A_LINKINFO_HEADER: u16 = 1;
+///
+/// let socket = mnl::Socket::new(mnl::Bus::Generic)?;
+/// let family = GenlFamily::resolve(&socket, "ethtool")?;
+/// let policy = FamilyPolicy::get(&socket, &family, None)?;
+/// if policy.supports_attr(ETHTOOL_MSG_LINKINFO_GET, ETHTOOL_A_LINKINFO_HEADER) {
+///     println!("The kernel accepts the link info header attribute");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FamilyPolicy {
+    /// All policies of the family, keyed by policy index. Nested attributes refer to the policy
+    /// of their content by this index.
+    pub policies: BTreeMap<u32, Policy>,
+    /// Which policies each operation uses, keyed by command id. Empty on kernels that only
+    /// report a single policy for the whole family.
+    pub ops: BTreeMap<u32, OpPolicy>,
+}
+
+/// The policy of a set of attributes, keyed by attribute type. Attributes not in the map are
+/// rejected by the kernel.
+pub type Policy = BTreeMap<u16, AttrPolicy>;
+
+/// The policy indices used for the `do` and `dump` variants of an operation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OpPolicy {
+    /// Index of the policy for regular requests, if the operation supports them.
+    pub do_policy: Option<u32>,
+    /// Index of the policy for dump requests, if the operation supports them.
+    pub dump_policy: Option<u32>,
+}
+
+/// The policy of a single attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttrPolicy {
+    /// The type of data the attribute carries.
+    pub attr_type: AttrType,
+    /// Minimum value, for signed integer attributes.
+    pub min_value_s: Option<i64>,
+    /// Maximum value, for signed integer attributes.
+    pub max_value_s: Option<i64>,
+    /// Minimum value, for unsigned integer attributes.
+    pub min_value_u: Option<u64>,
+    /// Maximum value, for unsigned integer attributes.
+    pub max_value_u: Option<u64>,
+    /// Minimum payload length.
+    pub min_length: Option<u32>,
+    /// Maximum payload length.
+    pub max_length: Option<u32>,
+    /// Index of the policy of the attributes nested in this attribute.
+    pub policy_idx: Option<u32>,
+    /// Highest attribute type accepted in the nested policy.
+    pub policy_maxtype: Option<u32>,
+    /// Allowed bits, for bitfield32 attributes.
+    pub bitfield32_mask: Option<u32>,
+    /// Allowed bits, for unsigned integer attributes.
+    pub mask: Option<u64>,
+}
+
+/// The type of data an attribute carries. Mirrors `enum netlink_attribute_type`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum AttrType {
+    Invalid,
+    Flag,
+    U8,
+    U16,
+    U32,
+    U64,
+    S8,
+    S16,
+    S32,
+    S64,
+    Binary,
+    String,
+    NulString,
+    Nested,
+    NestedArray,
+    Bitfield32,
+    Sint,
+    Uint,
+    /// A type not known to this crate.
+    Other(u32),
+}
+
+impl From<u32> for AttrType {
+    fn from(value: u32) -> Self {
+        use AttrType::*;
+        match value {
+            0 => Invalid,
+            1 => Flag,
+            2 => U8,
+            3 => U16,
+            4 => U32,
+            5 => U64,
+            6 => S8,
+            7 => S16,
+            8 => S32,
+            9 => S64,
+            10 => Binary,
+            11 => String,
+            12 => NulString,
+            13 => Nested,
+            14 => NestedArray,
+            15 => Bitfield32,
+            16 => Sint,
+            17 => Uint,
+            other => Other(other),
+        }
+    }
+}
+
+impl FamilyPolicy {
+    /// Dump the policies of `family` using `CTRL_CMD_GETPOLICY` on `socket`. If `op` is given,
+    /// only the policies used by that command are returned.
+    pub fn get(socket: &Socket, family: &GenlFamily, op: Option<u32>) -> io::Result<Self> {
+        let mut buffer = AlignedBuffer::new(256);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes())?;
+        msg.set_type(libc::GENL_ID_CTRL as u16);
+        msg.set_flags((libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16);
+        msg.set_seq(socket.next_seq());
+        put_header(&mut msg, CTRL_CMD_GETPOLICY, 1)?;
+        msg.put_u16(libc::CTRL_ATTR_FAMILY_ID as u16, family.id)?;
+        if let Some(op) = op {
+            msg.put_u32(CTRL_ATTR_OP, op)?;
+        }
+
+        let mut policy = FamilyPolicy::default();
+        socket.request(msg.as_bytes(), |reply| policy.add_msg(&reply))?;
+        Ok(policy)
+    }
+
+    /// Add the content of one `CTRL_CMD_GETPOLICY` reply message.
+    fn add_msg(&mut self, msg: &NlMsg<'_>) -> io::Result<()> {
+        for attr in msg.attrs(GENL_HDRLEN) {
+            let attr = attr?;
+            match attr.attr_type() {
+                CTRL_ATTR_POLICY => {
+                    for policy in attr.nested() {
+                        let policy = policy?;
+                        let entry = self.policies.entry(policy.attr_type().into()).or_default();
+                        for attr_policy in policy.nested() {
+                            let attr_policy = attr_policy?;
+                            entry.insert(attr_policy.attr_type(), parse_attr_policy(attr_policy)?);
+                        }
+                    }
+                }
+                CTRL_ATTR_OP_POLICY => {
+                    for op in attr.nested() {
+                        let op = op?;
+                        let entry = self.ops.entry(op.attr_type().into()).or_default();
+                        for policy in op.nested() {
+                            let policy = policy?;
+                            match policy.attr_type() {
+                                CTRL_ATTR_POLICY_DO => entry.do_policy = Some(policy.get_u32()?),
+                                CTRL_ATTR_POLICY_DUMP => {
+                                    entry.dump_policy = Some(policy.get_u32()?)
+                                }
+                                _ => (),
+                            }
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the policy with the given index.
+    pub fn policy(&self, idx: u32) -> Option<&Policy> {
+        self.policies.get(&idx)
+    }
+
+    /// Returns the policy used for regular (non-dump) requests of the command `cmd`. On kernels
+    /// without per operation policies this is the policy of the whole family.
+    pub fn op_policy(&self, cmd: u32) -> Option<&Policy> {
+        if self.ops.is_empty() {
+            return self.policy(0);
+        }
+        self.policy(self.ops.get(&cmd)?.do_policy?)
+    }
+
+    /// Returns the policy used for dump requests of the command `cmd`. On kernels without per
+    /// operation policies this is the policy of the whole family.
+    pub fn op_dump_policy(&self, cmd: u32) -> Option<&Policy> {
+        if self.ops.is_empty() {
+            return self.policy(0);
+        }
+        self.policy(self.ops.get(&cmd)?.dump_policy?)
+    }
+
+    /// Returns true if regular requests of the command `cmd` accept top level attributes of
+    /// type `attr_type`.
+    pub fn supports_attr(&self, cmd: u32, attr_type: u16) -> bool {
+        self.op_policy(cmd)
+            .is_some_and(|policy| policy.contains_key(&attr_type))
+    }
+
+    /// Returns the policy of the attributes nested inside attributes with the given policy.
+    pub fn nested_policy(&self, attr: &AttrPolicy) -> Option<&Policy> {
+        self.policy(attr.policy_idx?)
+    }
+}
+
+fn parse_attr_policy(attr: Attr<'_>) -> io::Result<AttrPolicy> {
+    let mut policy = AttrPolicy {
+        attr_type: AttrType::Invalid,
+        min_value_s: None,
+        max_value_s: None,
+        min_value_u: None,
+        max_value_u: None,
+        min_length: None,
+        max_length: None,
+        policy_idx: None,
+        policy_maxtype: None,
+        bitfield32_mask: None,
+        mask: None,
+    };
+    for attr in attr.nested() {
+        let attr = attr?;
+        match attr.attr_type() {
+            NL_POLICY_TYPE_ATTR_TYPE => policy.attr_type = attr.get_u32()?.into(),
+            NL_POLICY_TYPE_ATTR_MIN_VALUE_S => policy.min_value_s = Some(attr.get_u64()? as i64),
+            NL_POLICY_TYPE_ATTR_MAX_VALUE_S => policy.max_value_s = Some(attr.get_u64()? as i64),
+            NL_POLICY_TYPE_ATTR_MIN_VALUE_U => policy.min_value_u = Some(attr.get_u64()?),
+            NL_POLICY_TYPE_ATTR_MAX_VALUE_U => policy.max_value_u = Some(attr.get_u64()?),
+            NL_POLICY_TYPE_ATTR_MIN_LENGTH => policy.min_length = Some(attr.get_u32()?),
+            NL_POLICY_TYPE_ATTR_MAX_LENGTH => policy.max_length = Some(attr.get_u32()?),
+            NL_POLICY_TYPE_ATTR_POLICY_IDX => policy.policy_idx = Some(attr.get_u32()?),
+            NL_POLICY_TYPE_ATTR_POLICY_MAXTYPE => policy.policy_maxtype = Some(attr.get_u32()?),
+            NL_POLICY_TYPE_ATTR_BITFIELD32_MASK => policy.bitfield32_mask = Some(attr.get_u32()?),
+            NL_POLICY_TYPE_ATTR_MASK => policy.mask = Some(attr.get_u64()?),
+            _ => (),
+        }
+    }
+    Ok(policy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_policy_messages() {
+        let mut buffer = AlignedBuffer::new(512);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        put_header(&mut msg, CTRL_CMD_GETPOLICY, 1).unwrap();
+        msg.put_u16(libc::CTRL_ATTR_FAMILY_ID as u16, 21).unwrap();
+        msg.put_nested(CTRL_ATTR_POLICY, |msg| {
+            msg.put_nested(0, |msg| {
+                msg.put_nested(1, |msg| {
+                    msg.put_u32(NL_POLICY_TYPE_ATTR_TYPE, 13)?;
+                    msg.put_u32(NL_POLICY_TYPE_ATTR_POLICY_IDX, 1)?;
+                    msg.put_u32(NL_POLICY_TYPE_ATTR_POLICY_MAXTYPE, 3)
+                })?;
+                msg.put_nested(2, |msg| {
+                    msg.put_u32(NL_POLICY_TYPE_ATTR_TYPE, 2)?;
+                    msg.put_u64(NL_POLICY_TYPE_ATTR_MIN_VALUE_U, 0)?;
+                    msg.put_u64(NL_POLICY_TYPE_ATTR_MAX_VALUE_U, 1)
+                })
+            })
+        })
+        .unwrap();
+        let first = NlMsg::new(msg.as_bytes()).unwrap();
+
+        let mut buffer = AlignedBuffer::new(512);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        put_header(&mut msg, CTRL_CMD_GETPOLICY, 1).unwrap();
+        msg.put_nested(CTRL_ATTR_POLICY, |msg| {
+            msg.put_nested(1, |msg| {
+                msg.put_nested(3, |msg| {
+                    msg.put_u32(NL_POLICY_TYPE_ATTR_TYPE, 11)?;
+                    msg.put_u32(NL_POLICY_TYPE_ATTR_MAX_LENGTH, 15)
+                })
+            })
+        })
+        .unwrap();
+        msg.put_nested(CTRL_ATTR_OP_POLICY, |msg| {
+            msg.put_nested(2, |msg| {
+                msg.put_u32(CTRL_ATTR_POLICY_DO, 0)?;
+                msg.put_u32(CTRL_ATTR_POLICY_DUMP, 0)
+            })
+        })
+        .unwrap();
+        let second = NlMsg::new(msg.as_bytes()).unwrap();
+
+        let mut policy = FamilyPolicy::default();
+        policy.add_msg(&first).unwrap();
+        policy.add_msg(&second).unwrap();
+
+        assert!(policy.supports_attr(2, 1));
+        assert!(policy.supports_attr(2, 2));
+        assert!(!policy.supports_attr(2, 3));
+        assert!(!policy.supports_attr(1, 1));
+
+        let op_policy = policy.op_policy(2).unwrap();
+        assert_eq!(op_policy[&1].attr_type, AttrType::Nested);
+        assert_eq!(op_policy[&2].attr_type, AttrType::U8);
+        assert_eq!(op_policy[&2].max_value_u, Some(1));
+        let nested = policy.nested_policy(&op_policy[&1]).unwrap();
+        assert_eq!(nested[&3].attr_type, AttrType::String);
+        assert_eq!(nested[&3].max_length, Some(15));
+    }
+}
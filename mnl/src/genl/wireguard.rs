@@ -0,0 +1,747 @@
+//! Typed client for the `wireguard` generic netlink family.
+//!
+//! [`device`] and [`device_by_index`] read the configuration and state of a WireGuard interface
+//! with `WG_CMD_GET_DEVICE`, and [`SetDevice`] changes it with `WG_CMD_SET_DEVICE`. The kernel
+//! splits devices with many peers over several messages in both directions, which is handled
+//! here.
+//!
+//! ```no_run
+//! # fn main() -> std::io::Result<()> {
+//! use mnl::genl::wireguard::{self, PeerConfig, SetDevice};
+//!
+//! let socket = mnl::Socket::new(mnl::Bus::Generic)?;
+//! # let (private_key, peer_key) = ([1; 32], [2; 32]);
+//! SetDevice::new("wg0")
+//!     .private_key(private_key)
+//!     .listen_port(51820)
+//!     .replace_peers()
+//!     .peer(
+//!         PeerConfig::new(peer_key)
+//!             .endpoint("192.0.2.1:51820".parse().unwrap())
+//!             .allowed_ip("10.0.0.0".parse().unwrap(), 8),
+//!     )
+//!     .apply(&socket)?;
+//!
+//! for peer in wireguard::device(&socket, "wg0")?.peers {
+//!     println!("{:?}: rx {} tx {}", peer.endpoint, peer.rx_bytes, peer.tx_bytes);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use mnl_sys::libc;
+use std::{
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+    time::{Duration, SystemTime},
+};
+
+use super::{GENL_HDRLEN, GenlFamily};
+use crate::{Attr, NlMsg, NlMsgBuilder, Socket, buffer::AlignedBuffer};
+
+/// Name of the WireGuard generic netlink family.
+pub const FAMILY_NAME: &str = "wireguard";
+
+/// Length of WireGuard keys in bytes.
+pub const KEY_LEN: usize = 32;
+
+/// A Curve25519 private or public key, or a symmetric preshared key.
+pub type Key = [u8; KEY_LEN];
+
+const WG_CMD_GET_DEVICE: u8 = 0;
+const WG_CMD_SET_DEVICE: u8 = 1;
+
+const WGDEVICE_F_REPLACE_PEERS: u32 = 1 << 0;
+
+const WGDEVICE_A_IFINDEX: u16 = 1;
+const WGDEVICE_A_IFNAME: u16 = 2;
+const WGDEVICE_A_PRIVATE_KEY: u16 = 3;
+const WGDEVICE_A_PUBLIC_KEY: u16 = 4;
+const WGDEVICE_A_FLAGS: u16 = 5;
+const WGDEVICE_A_LISTEN_PORT: u16 = 6;
+const WGDEVICE_A_FWMARK: u16 = 7;
+const WGDEVICE_A_PEERS: u16 = 8;
+
+const WGPEER_F_REMOVE_ME: u32 = 1 << 0;
+const WGPEER_F_REPLACE_ALLOWEDIPS: u32 = 1 << 1;
+const WGPEER_F_UPDATE_ONLY: u32 = 1 << 2;
+
+const WGPEER_A_PUBLIC_KEY: u16 = 1;
+const WGPEER_A_PRESHARED_KEY: u16 = 2;
+const WGPEER_A_FLAGS: u16 = 3;
+const WGPEER_A_ENDPOINT: u16 = 4;
+const WGPEER_A_PERSISTENT_KEEPALIVE_INTERVAL: u16 = 5;
+const WGPEER_A_LAST_HANDSHAKE_TIME: u16 = 6;
+const WGPEER_A_RX_BYTES: u16 = 7;
+const WGPEER_A_TX_BYTES: u16 = 8;
+const WGPEER_A_ALLOWEDIPS: u16 = 9;
+const WGPEER_A_PROTOCOL_VERSION: u16 = 10;
+
+const WGALLOWEDIP_A_FAMILY: u16 = 1;
+const WGALLOWEDIP_A_IPADDR: u16 = 2;
+const WGALLOWEDIP_A_CIDR_MASK: u16 = 3;
+
+/// Size of each `WG_CMD_SET_DEVICE` message. Peers that do not fit go into further messages.
+const MESSAGE_SIZE: usize = 4096;
+
+/// A WireGuard interface, as described by `WG_CMD_GET_DEVICE`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Device {
+    /// Interface index.
+    pub index: u32,
+    /// Interface name.
+    pub name: String,
+    /// Private key of the interface, if one is set.
+    pub private_key: Option<Key>,
+    /// Public key derived from the private key, if one is set.
+    pub public_key: Option<Key>,
+    /// UDP port the interface listens on.
+    pub listen_port: u16,
+    /// Firewall mark of outgoing packets, zero if unset.
+    pub fwmark: u32,
+    /// The peers of the interface.
+    pub peers: Vec<Peer>,
+}
+
+/// A peer of a WireGuard interface, with its configuration and state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Peer {
+    /// Public key identifying the peer.
+    pub public_key: Key,
+    /// Preshared key mixed into the handshake, if one is set.
+    pub preshared_key: Option<Key>,
+    /// Address the peer was configured with or last sent an authenticated packet from.
+    pub endpoint: Option<SocketAddr>,
+    /// Interval in seconds of keepalive packets sent to the peer, zero if disabled.
+    pub persistent_keepalive_interval: u16,
+    /// Time of the last completed handshake, if there has been one.
+    pub last_handshake: Option<SystemTime>,
+    /// Bytes received from the peer.
+    pub rx_bytes: u64,
+    /// Bytes sent to the peer.
+    pub tx_bytes: u64,
+    /// Networks routed to the peer, and accepted as source of packets from it.
+    pub allowed_ips: Vec<AllowedIp>,
+    /// Version of the WireGuard protocol spoken with the peer.
+    pub protocol_version: u32,
+}
+
+/// A network allowed to and from a peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AllowedIp {
+    /// Network address.
+    pub addr: IpAddr,
+    /// Length of the network prefix.
+    pub prefix_len: u8,
+}
+
+impl Device {
+    /// Parse a `WG_CMD_GET_DEVICE` reply. A device with many peers is split over several
+    /// messages, of which only the first one has the device attributes set. [`device`] merges
+    /// them into one.
+    pub fn from_msg(msg: &NlMsg<'_>) -> io::Result<Device> {
+        let mut device = Device::default();
+        for attr in msg.attrs(GENL_HDRLEN) {
+            let attr = attr?;
+            match attr.attr_type() {
+                WGDEVICE_A_IFINDEX => device.index = attr.get_u32()?,
+                WGDEVICE_A_IFNAME => device.name = attr.get_str()?.to_owned(),
+                WGDEVICE_A_PRIVATE_KEY => device.private_key = Some(attr.get_array()?),
+                WGDEVICE_A_PUBLIC_KEY => device.public_key = Some(attr.get_array()?),
+                WGDEVICE_A_LISTEN_PORT => device.listen_port = attr.get_u16()?,
+                WGDEVICE_A_FWMARK => device.fwmark = attr.get_u32()?,
+                WGDEVICE_A_PEERS => {
+                    for peer in attr.nested() {
+                        device.peers.push(Peer::parse(peer?)?);
+                    }
+                }
+                _ => (),
+            }
+        }
+        Ok(device)
+    }
+
+    /// Add the peers of a following part of a split device. The first peer continues the last
+    /// one of the previous part if it has the same public key.
+    fn merge(&mut self, part: Device) {
+        let mut peers = part.peers.into_iter();
+        if let Some(first) = peers.next() {
+            match self.peers.last_mut() {
+                Some(last) if last.public_key == first.public_key => {
+                    last.allowed_ips.extend(first.allowed_ips)
+                }
+                _ => self.peers.push(first),
+            }
+        }
+        self.peers.extend(peers);
+    }
+
+    /// Returns the peer with the given public key, if the device has one.
+    pub fn peer(&self, public_key: &Key) -> Option<&Peer> {
+        self.peers
+            .iter()
+            .find(|peer| peer.public_key == *public_key)
+    }
+}
+
+impl Peer {
+    fn parse(attr: Attr<'_>) -> io::Result<Peer> {
+        let mut peer = Peer {
+            public_key: [0; KEY_LEN],
+            preshared_key: None,
+            endpoint: None,
+            persistent_keepalive_interval: 0,
+            last_handshake: None,
+            rx_bytes: 0,
+            tx_bytes: 0,
+            allowed_ips: Vec::new(),
+            protocol_version: 0,
+        };
+        for attr in attr.nested() {
+            let attr = attr?;
+            match attr.attr_type() {
+                WGPEER_A_PUBLIC_KEY => peer.public_key = attr.get_array()?,
+                WGPEER_A_PRESHARED_KEY => {
+                    // The kernel reports an all-zero key for peers without one.
+                    let key: Key = attr.get_array()?;
+                    peer.preshared_key = Some(key).filter(|key| *key != [0; KEY_LEN]);
+                }
+                WGPEER_A_ENDPOINT => peer.endpoint = parse_endpoint(attr.payload())?,
+                WGPEER_A_PERSISTENT_KEEPALIVE_INTERVAL => {
+                    peer.persistent_keepalive_interval = attr.get_u16()?
+                }
+                WGPEER_A_LAST_HANDSHAKE_TIME => {
+                    peer.last_handshake = parse_timespec(attr.get_array::<16>()?)?
+                }
+                WGPEER_A_RX_BYTES => peer.rx_bytes = attr.get_u64()?,
+                WGPEER_A_TX_BYTES => peer.tx_bytes = attr.get_u64()?,
+                WGPEER_A_ALLOWEDIPS => {
+                    for allowed_ip in attr.nested() {
+                        peer.allowed_ips.push(AllowedIp::parse(allowed_ip?)?);
+                    }
+                }
+                WGPEER_A_PROTOCOL_VERSION => peer.protocol_version = attr.get_u32()?,
+                _ => (),
+            }
+        }
+        Ok(peer)
+    }
+}
+
+impl AllowedIp {
+    fn parse(attr: Attr<'_>) -> io::Result<AllowedIp> {
+        let mut addr = None;
+        let mut prefix_len = 0;
+        for attr in attr.nested() {
+            let attr = attr?;
+            match attr.attr_type() {
+                WGALLOWEDIP_A_IPADDR => {
+                    addr = match attr.payload().len() {
+                        4 => Some(Ipv4Addr::from(attr.get_array::<4>()?).into()),
+                        16 => Some(Ipv6Addr::from(attr.get_array::<16>()?).into()),
+                        _ => None,
+                    }
+                }
+                WGALLOWEDIP_A_CIDR_MASK => prefix_len = attr.get_u8()?,
+                _ => (),
+            }
+        }
+        let addr = addr.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "WireGuard allowed IP without a valid address",
+            )
+        })?;
+        Ok(AllowedIp { addr, prefix_len })
+    }
+
+    fn put(&self, msg: &mut NlMsgBuilder<'_>) -> io::Result<()> {
+        match self.addr {
+            IpAddr::V4(addr) => {
+                msg.put_u16(WGALLOWEDIP_A_FAMILY, libc::AF_INET as u16)?;
+                msg.put(WGALLOWEDIP_A_IPADDR, &addr.octets())?;
+            }
+            IpAddr::V6(addr) => {
+                msg.put_u16(WGALLOWEDIP_A_FAMILY, libc::AF_INET6 as u16)?;
+                msg.put(WGALLOWEDIP_A_IPADDR, &addr.octets())?;
+            }
+        }
+        msg.put_u8(WGALLOWEDIP_A_CIDR_MASK, self.prefix_len)
+    }
+}
+
+/// Get the WireGuard interface with the given name. Fails with `NotFound` if the `wireguard`
+/// family is not registered, that is the kernel module is not loaded, and with `ENODEV` if
+/// there is no such interface.
+pub fn device(socket: &Socket, name: &str) -> io::Result<Device> {
+    get_device(socket, |msg| msg.put_str(WGDEVICE_A_IFNAME, name))
+}
+
+/// Get the WireGuard interface with the given interface index, see [`device`].
+pub fn device_by_index(socket: &Socket, index: u32) -> io::Result<Device> {
+    get_device(socket, |msg| msg.put_u32(WGDEVICE_A_IFINDEX, index))
+}
+
+fn get_device<F>(socket: &Socket, put_target: F) -> io::Result<Device>
+where
+    F: FnOnce(&mut NlMsgBuilder<'_>) -> io::Result<()>,
+{
+    let family = GenlFamily::resolve(socket, FAMILY_NAME)?;
+    let mut buffer = AlignedBuffer::new(MESSAGE_SIZE);
+    let mut msg = family.request(buffer.as_mut_bytes(), WG_CMD_GET_DEVICE)?;
+    msg.set_flags((libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16);
+    msg.set_seq(socket.next_seq());
+    put_target(&mut msg)?;
+
+    let mut device: Option<Device> = None;
+    socket.request(msg.as_bytes(), |reply| {
+        if reply.msg_type() == family.id {
+            let part = Device::from_msg(&reply)?;
+            match &mut device {
+                Some(device) => device.merge(part),
+                None => device = Some(part),
+            }
+        }
+        Ok(())
+    })?;
+    device.ok_or_else(|| io::Error::from_raw_os_error(libc::ENODEV))
+}
+
+/// Builder for changing the configuration of a WireGuard interface with `WG_CMD_SET_DEVICE`.
+/// Only the settings given to the builder are changed, and peers not given are kept unless
+/// [`replace_peers`] is used.
+///
+/// The configuration is split over several messages if it does not fit in one, as done by
+/// `wg setconf`. Each message is applied by the kernel on its own, so if a later message fails
+/// the earlier ones are still in effect.
+///
+/// [`replace_peers`]: SetDevice::replace_peers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetDevice {
+    target: Target,
+    private_key: Option<Key>,
+    listen_port: Option<u16>,
+    fwmark: Option<u32>,
+    flags: u32,
+    peers: Vec<PeerConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Target {
+    Index(u32),
+    Name(String),
+}
+
+impl SetDevice {
+    /// Prepare changes to the WireGuard interface with the given name.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self::with_target(Target::Name(name.into()))
+    }
+
+    /// Prepare changes to the WireGuard interface with the given interface index.
+    pub fn with_index(index: u32) -> Self {
+        Self::with_target(Target::Index(index))
+    }
+
+    fn with_target(target: Target) -> Self {
+        SetDevice {
+            target,
+            private_key: None,
+            listen_port: None,
+            fwmark: None,
+            flags: 0,
+            peers: Vec::new(),
+        }
+    }
+
+    /// Set the private key of the interface. An all-zero key removes it.
+    pub fn private_key(mut self, key: Key) -> Self {
+        self.private_key = Some(key);
+        self
+    }
+
+    /// Set the UDP port to listen on. Zero picks a random port.
+    pub fn listen_port(mut self, port: u16) -> Self {
+        self.listen_port = Some(port);
+        self
+    }
+
+    /// Set the firewall mark of outgoing packets. Zero removes it.
+    pub fn fwmark(mut self, fwmark: u32) -> Self {
+        self.fwmark = Some(fwmark);
+        self
+    }
+
+    /// Remove all peers not given to this builder.
+    pub fn replace_peers(mut self) -> Self {
+        self.flags |= WGDEVICE_F_REPLACE_PEERS;
+        self
+    }
+
+    /// Add or change a peer. Can be given several times.
+    pub fn peer(mut self, peer: PeerConfig) -> Self {
+        self.peers.push(peer);
+        self
+    }
+
+    /// Send the changes to the kernel. `socket` must be a [`Bus::Generic`] socket. Fails with
+    /// `NotFound` if the `wireguard` family is not registered, and with `ENODEV` if there is no
+    /// such WireGuard interface.
+    ///
+    /// [`Bus::Generic`]: crate::Bus::Generic
+    pub fn apply(&self, socket: &Socket) -> io::Result<()> {
+        let family = GenlFamily::resolve(socket, FAMILY_NAME)?;
+        self.build(&family, MESSAGE_SIZE, |msg| {
+            msg.set_seq(socket.next_seq());
+            socket.request(msg.as_bytes(), |_| Ok(()))
+        })
+    }
+
+    /// Build the messages of the change in a buffer of `size` bytes, and call `send` for each.
+    /// The device attributes only go into the first message. Peers that do not fit go into the
+    /// following ones, splitting the allowed IPs of a peer if needed.
+    fn build<F>(&self, family: &GenlFamily, size: usize, mut send: F) -> io::Result<()>
+    where
+        F: FnMut(&mut NlMsgBuilder<'_>) -> io::Result<()>,
+    {
+        let mut buffer = AlignedBuffer::new(size);
+        let (mut next_peer, mut next_ip) = (0, 0);
+        let mut first = true;
+        while first || next_peer < self.peers.len() {
+            let mut msg = family.request(buffer.as_mut_bytes(), WG_CMD_SET_DEVICE)?;
+            msg.set_flags((libc::NLM_F_REQUEST | libc::NLM_F_ACK) as u16);
+            match &self.target {
+                Target::Index(index) => msg.put_u32(WGDEVICE_A_IFINDEX, *index)?,
+                Target::Name(name) => msg.put_str(WGDEVICE_A_IFNAME, name)?,
+            }
+            if first {
+                self.put_device_attrs(&mut msg)?;
+            }
+            if next_peer < self.peers.len() {
+                let result = msg.put_nested(WGDEVICE_A_PEERS, |msg| {
+                    self.put_peers(msg, &mut next_peer, &mut next_ip)
+                });
+                match result {
+                    Ok(()) => (),
+                    // The device attributes filled the first message, the peers follow.
+                    Err(e) if first && e.kind() == io::ErrorKind::OutOfMemory => (),
+                    Err(e) => return Err(e),
+                }
+            }
+            send(&mut msg)?;
+            first = false;
+        }
+        Ok(())
+    }
+
+    fn put_device_attrs(&self, msg: &mut NlMsgBuilder<'_>) -> io::Result<()> {
+        if let Some(key) = &self.private_key {
+            msg.put(WGDEVICE_A_PRIVATE_KEY, key)?;
+        }
+        if let Some(port) = self.listen_port {
+            msg.put_u16(WGDEVICE_A_LISTEN_PORT, port)?;
+        }
+        if let Some(fwmark) = self.fwmark {
+            msg.put_u32(WGDEVICE_A_FWMARK, fwmark)?;
+        }
+        if self.flags != 0 {
+            msg.put_u32(WGDEVICE_A_FLAGS, self.flags)?;
+        }
+        Ok(())
+    }
+
+    /// Add as many peers as fit, starting at allowed IP `next_ip` of peer `next_peer`, and
+    /// advance both past what was added. Fails only if nothing fits.
+    fn put_peers(
+        &self,
+        msg: &mut NlMsgBuilder<'_>,
+        next_peer: &mut usize,
+        next_ip: &mut usize,
+    ) -> io::Result<()> {
+        let mut added = false;
+        while let Some(peer) = self.peers.get(*next_peer) {
+            let mut ip = *next_ip;
+            match msg.put_nested(0, |msg| peer.put(msg, &mut ip)) {
+                Ok(()) if ip < peer.allowed_ips.len() => {
+                    *next_ip = ip;
+                    return Ok(());
+                }
+                Ok(()) => {
+                    *next_peer += 1;
+                    *next_ip = 0;
+                    added = true;
+                }
+                Err(e) if added && e.kind() == io::ErrorKind::OutOfMemory => return Ok(()),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The configuration of a peer, given to [`SetDevice::peer`]. Only the settings given to the
+/// builder are changed on an existing peer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerConfig {
+    public_key: Key,
+    flags: u32,
+    preshared_key: Option<Key>,
+    endpoint: Option<SocketAddr>,
+    persistent_keepalive_interval: Option<u16>,
+    allowed_ips: Vec<AllowedIp>,
+}
+
+impl PeerConfig {
+    /// Configure the peer with the given public key. The peer is created if it does not exist.
+    pub fn new(public_key: Key) -> Self {
+        PeerConfig {
+            public_key,
+            flags: 0,
+            preshared_key: None,
+            endpoint: None,
+            persistent_keepalive_interval: None,
+            allowed_ips: Vec::new(),
+        }
+    }
+
+    /// Remove the peer instead of adding or changing it.
+    pub fn remove(mut self) -> Self {
+        self.flags |= WGPEER_F_REMOVE_ME;
+        self
+    }
+
+    /// Only change the peer if it exists, instead of creating it.
+    pub fn update_only(mut self) -> Self {
+        self.flags |= WGPEER_F_UPDATE_ONLY;
+        self
+    }
+
+    /// Set the preshared key. An all-zero key removes it.
+    pub fn preshared_key(mut self, key: Key) -> Self {
+        self.preshared_key = Some(key);
+        self
+    }
+
+    /// Set the address to send packets to the peer at.
+    pub fn endpoint(mut self, endpoint: SocketAddr) -> Self {
+        self.endpoint = Some(endpoint);
+        self
+    }
+
+    /// Set the interval in seconds of keepalive packets sent to the peer. Zero disables them.
+    pub fn persistent_keepalive_interval(mut self, interval: u16) -> Self {
+        self.persistent_keepalive_interval = Some(interval);
+        self
+    }
+
+    /// Remove the allowed IPs of the peer not given to this builder.
+    pub fn replace_allowed_ips(mut self) -> Self {
+        self.flags |= WGPEER_F_REPLACE_ALLOWEDIPS;
+        self
+    }
+
+    /// Add a network to the allowed IPs of the peer. Can be given several times.
+    pub fn allowed_ip(mut self, addr: IpAddr, prefix_len: u8) -> Self {
+        self.allowed_ips.push(AllowedIp { addr, prefix_len });
+        self
+    }
+
+    /// Add the peer starting at allowed IP `next_ip`, and advance it past the allowed IPs that
+    /// fit. The settings of the peer only go into the part starting at the first allowed IP,
+    /// later parts only repeat the public key. Fails only if no allowed IP fits.
+    fn put(&self, msg: &mut NlMsgBuilder<'_>, next_ip: &mut usize) -> io::Result<()> {
+        msg.put(WGPEER_A_PUBLIC_KEY, &self.public_key)?;
+        if *next_ip == 0 {
+            if self.flags != 0 {
+                msg.put_u32(WGPEER_A_FLAGS, self.flags)?;
+            }
+            if let Some(key) = &self.preshared_key {
+                msg.put(WGPEER_A_PRESHARED_KEY, key)?;
+            }
+            if let Some(endpoint) = &self.endpoint {
+                msg.put(WGPEER_A_ENDPOINT, &endpoint_bytes(endpoint))?;
+            }
+            if let Some(interval) = self.persistent_keepalive_interval {
+                msg.put_u16(WGPEER_A_PERSISTENT_KEEPALIVE_INTERVAL, interval)?;
+            }
+        }
+        if *next_ip < self.allowed_ips.len() {
+            msg.put_nested(WGPEER_A_ALLOWEDIPS, |msg| {
+                let start = *next_ip;
+                while let Some(allowed_ip) = self.allowed_ips.get(*next_ip) {
+                    match msg.put_nested(0, |msg| allowed_ip.put(msg)) {
+                        Ok(()) => *next_ip += 1,
+                        Err(e) if *next_ip > start && e.kind() == io::ErrorKind::OutOfMemory => {
+                            return Ok(());
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+                Ok(())
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Encode `endpoint` as `struct sockaddr_in` or `struct sockaddr_in6`.
+fn endpoint_bytes(endpoint: &SocketAddr) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    match endpoint {
+        SocketAddr::V4(endpoint) => {
+            bytes.extend((libc::AF_INET as u16).to_ne_bytes());
+            bytes.extend(endpoint.port().to_be_bytes());
+            bytes.extend(endpoint.ip().octets());
+            bytes.extend([0; 8]);
+        }
+        SocketAddr::V6(endpoint) => {
+            bytes.extend((libc::AF_INET6 as u16).to_ne_bytes());
+            bytes.extend(endpoint.port().to_be_bytes());
+            bytes.extend(endpoint.flowinfo().to_be_bytes());
+            bytes.extend(endpoint.ip().octets());
+            bytes.extend(endpoint.scope_id().to_ne_bytes());
+        }
+    }
+    bytes
+}
+
+/// Parse a `struct sockaddr_in` or `struct sockaddr_in6`. An unspecified family means no
+/// endpoint.
+fn parse_endpoint(bytes: &[u8]) -> io::Result<Option<SocketAddr>> {
+    let invalid = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid WireGuard endpoint of length {}", bytes.len()),
+        )
+    };
+    let family = bytes.get(..2).ok_or_else(invalid)?;
+    let family = u16::from_ne_bytes(family.try_into().unwrap()) as i32;
+    match family {
+        libc::AF_INET if bytes.len() >= 16 => {
+            let port = u16::from_be_bytes([bytes[2], bytes[3]]);
+            let addr = Ipv4Addr::new(bytes[4], bytes[5], bytes[6], bytes[7]);
+            Ok(Some(SocketAddrV4::new(addr, port).into()))
+        }
+        libc::AF_INET6 if bytes.len() >= 28 => {
+            let port = u16::from_be_bytes([bytes[2], bytes[3]]);
+            let flowinfo = u32::from_be_bytes(bytes[4..8].try_into().unwrap());
+            let addr = Ipv6Addr::from(<[u8; 16]>::try_from(&bytes[8..24]).unwrap());
+            let scope_id = u32::from_ne_bytes(bytes[24..28].try_into().unwrap());
+            Ok(Some(
+                SocketAddrV6::new(addr, port, flowinfo, scope_id).into(),
+            ))
+        }
+        libc::AF_UNSPEC => Ok(None),
+        _ => Err(invalid()),
+    }
+}
+
+/// Parse a `struct __kernel_timespec`, where zero means never.
+fn parse_timespec(bytes: [u8; 16]) -> io::Result<Option<SystemTime>> {
+    let secs = i64::from_ne_bytes(bytes[..8].try_into().unwrap());
+    let nanos = i64::from_ne_bytes(bytes[8..].try_into().unwrap());
+    if secs == 0 && nanos == 0 {
+        return Ok(None);
+    }
+    let invalid = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid WireGuard handshake time {secs}.{nanos:09}"),
+        )
+    };
+    let secs = u64::try_from(secs).map_err(|_| invalid())?;
+    let nanos = u32::try_from(nanos)
+        .ok()
+        .filter(|&nanos| nanos < 1_000_000_000)
+        .ok_or_else(invalid)?;
+    SystemTime::UNIX_EPOCH
+        .checked_add(Duration::new(secs, nanos))
+        .map(Some)
+        .ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_and_merge() {
+        let family = GenlFamily {
+            id: 0x20,
+            name: FAMILY_NAME.to_owned(),
+            version: 1,
+            hdr_size: 0,
+            max_attr: 8,
+            ops: Vec::new(),
+            mcast_groups: Vec::new(),
+        };
+        let endpoint: SocketAddr = "[2001:db8::1%3]:51820".parse().unwrap();
+        let many_ips = PeerConfig::new([1; KEY_LEN])
+            .endpoint(endpoint)
+            .replace_allowed_ips();
+        let many_ips = (0..40u8).fold(many_ips, |peer, i| {
+            peer.allowed_ip(Ipv4Addr::new(10, i, 0, 0).into(), 16)
+        });
+        let config = SetDevice::new("wg0")
+            .private_key([7; KEY_LEN])
+            .listen_port(51820)
+            .replace_peers()
+            .peer(many_ips)
+            .peer(
+                PeerConfig::new([2; KEY_LEN])
+                    .preshared_key([3; KEY_LEN])
+                    .allowed_ip("2001:db8::".parse().unwrap(), 32),
+            );
+
+        // The attribute layout of the set messages is the same as of the get replies.
+        let mut parts = Vec::new();
+        config
+            .build(&family, 512, |msg| {
+                assert!(msg.len() <= 512);
+                parts.push(Device::from_msg(&NlMsg::new(msg.as_bytes())?)?);
+                Ok(())
+            })
+            .unwrap();
+        assert!(parts.len() > 2);
+        let mut parts = parts.into_iter();
+        let mut device = parts.next().unwrap();
+        assert_eq!(device.name, "wg0");
+        assert_eq!(device.private_key, Some([7; KEY_LEN]));
+        assert_eq!(device.listen_port, 51820);
+        for part in parts {
+            assert_eq!(part.private_key, None);
+            device.merge(part);
+        }
+
+        assert_eq!(device.peers.len(), 2);
+        let peer = device.peer(&[1; KEY_LEN]).unwrap();
+        assert_eq!(peer.endpoint, Some(endpoint));
+        assert_eq!(peer.allowed_ips.len(), 40);
+        assert_eq!(
+            peer.allowed_ips[39],
+            AllowedIp {
+                addr: Ipv4Addr::new(10, 39, 0, 0).into(),
+                prefix_len: 16
+            }
+        );
+        let peer = device.peer(&[2; KEY_LEN]).unwrap();
+        assert_eq!(peer.preshared_key, Some([3; KEY_LEN]));
+        assert_eq!(
+            peer.allowed_ips[0].addr,
+            "2001:db8::".parse::<IpAddr>().unwrap()
+        );
+
+        let mut timespec = [0; 16];
+        assert_eq!(parse_timespec(timespec).unwrap(), None);
+        timespec[..8].copy_from_slice(&10i64.to_ne_bytes());
+        assert_eq!(
+            parse_timespec(timespec).unwrap(),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(10))
+        );
+        timespec[8..].copy_from_slice(&1_000_000_000i64.to_ne_bytes());
+        assert!(parse_timespec(timespec).is_err());
+        timespec[..8].copy_from_slice(&(-10i64).to_ne_bytes());
+        timespec[8..].copy_from_slice(&0i64.to_ne_bytes());
+        assert!(parse_timespec(timespec).is_err());
+    }
+}
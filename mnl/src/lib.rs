@@ -54,3 +54,23 @@ pub use crate::socket::*;
 
 mod messages;
 pub use crate::messages::*;
+
+mod buffer;
+
+mod attr;
+pub use crate::attr::*;
+
+mod builder;
+pub use crate::builder::*;
+
+mod batch;
+pub use crate::batch::*;
+
+mod netfilter;
+pub use crate::netfilter::*;
+
+pub mod genl;
+
+pub mod nftables;
+
+pub mod rtnl;
@@ -2,6 +2,8 @@ use std::io;
 
 use libc::nlmsghdr;
 
+use crate::Attrs;
+
 /// Iterator over a byte buffer of netlink messages.
 #[derive(Clone)]
 pub struct NlMessages<'a> {
@@ -76,6 +78,101 @@ impl<'a> Iterator for NlMessages<'a> {
     }
 }
 
+/// A single netlink message, split into its header and payload.
+#[derive(Clone, Copy)]
+pub struct NlMsg<'a> {
+    header: nlmsghdr,
+    payload: &'a [u8],
+}
+
+impl<'a> NlMsg<'a> {
+    /// Parse a single netlink message, such as the ones returned by [`NlMessages`].
+    ///
+    /// Fails if `message` is shorter than the header or the length in the header.
+    pub fn new(message: &'a [u8]) -> io::Result<Self> {
+        if message.len() < size_of::<nlmsghdr>() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Netlink message is shorter than the header",
+            ));
+        }
+        // Safety: nlmsghdr is a C struct valid for all bit-patterns and we've checked the length.
+        let header = unsafe { message.as_ptr().cast::<nlmsghdr>().read_unaligned() };
+        let msg_len = header.nlmsg_len as usize;
+        if msg_len < size_of::<nlmsghdr>() || msg_len > message.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid netlink message length: {msg_len}"),
+            ));
+        }
+        Ok(NlMsg {
+            header,
+            payload: &message[size_of::<nlmsghdr>()..msg_len],
+        })
+    }
+
+    /// Returns the netlink header of this message.
+    pub fn header(&self) -> &nlmsghdr {
+        &self.header
+    }
+
+    /// Returns the message type (`nlmsg_type`).
+    pub fn msg_type(&self) -> u16 {
+        self.header.nlmsg_type
+    }
+
+    /// Returns the message flags (`nlmsg_flags`).
+    pub fn flags(&self) -> u16 {
+        self.header.nlmsg_flags
+    }
+
+    /// Returns the sequence number (`nlmsg_seq`).
+    pub fn seq(&self) -> u32 {
+        self.header.nlmsg_seq
+    }
+
+    /// Returns the port id (`nlmsg_pid`).
+    pub fn portid(&self) -> u32 {
+        self.header.nlmsg_pid
+    }
+
+    /// Returns everything after the netlink header. Includes any extra header.
+    pub fn payload(&self) -> &'a [u8] {
+        self.payload
+    }
+
+    /// Returns an iterator over the attributes of the message. `extra_header_len` is the size of
+    /// the subsystem specific header between the netlink header and the attributes, such as
+    /// `genlmsghdr` or `ifinfomsg`. It is padded to the netlink alignment.
+    pub fn attrs(&self, extra_header_len: usize) -> Attrs<'a> {
+        let offset = extra_header_len.next_multiple_of(mnl_sys::MNL_ALIGNTO as usize);
+        Attrs::new(self.payload.get(offset..).unwrap_or(&[]))
+    }
+
+    /// Returns true if this is a `NLMSG_DONE` message, terminating a dump.
+    pub fn is_done(&self) -> bool {
+        self.header.nlmsg_type == libc::NLMSG_DONE as u16
+    }
+
+    /// Returns `None` unless this is a `NLMSG_ERROR` message. For error messages, returns
+    /// `Ok(())` if it is an acknowledgement and the error reported by the kernel otherwise.
+    pub fn error(&self) -> Option<io::Result<()>> {
+        if self.header.nlmsg_type != libc::NLMSG_ERROR as u16 {
+            return None;
+        }
+        let Some(code) = self.payload.get(..size_of::<i32>()) else {
+            return Some(Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Truncated netlink error message",
+            )));
+        };
+        match i32::from_ne_bytes(code.try_into().unwrap()) {
+            0 => Some(Ok(())),
+            errno => Some(Err(io::Error::from_raw_os_error(-errno))),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -193,6 +290,24 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_parse_error_message() {
+        let mut buffer = vec![0u8; 36];
+        buffer[0..4].copy_from_slice(&36u32.to_ne_bytes());
+        buffer[4..6].copy_from_slice(&(libc::NLMSG_ERROR as u16).to_ne_bytes());
+        buffer[8..12].copy_from_slice(&5u32.to_ne_bytes());
+        buffer[16..20].copy_from_slice(&(-libc::ENOENT).to_ne_bytes());
+
+        let msg = NlMsg::new(&buffer).unwrap();
+        assert_eq!(msg.seq(), 5);
+        assert_eq!(msg.payload().len(), 20);
+        let error = msg.error().unwrap().unwrap_err();
+        assert_eq!(error.raw_os_error(), Some(libc::ENOENT));
+
+        buffer[16..20].copy_from_slice(&0i32.to_ne_bytes());
+        assert!(NlMsg::new(&buffer).unwrap().error().unwrap().is_ok());
+    }
+
     #[test]
     fn test_parse_minimum_valid_message() {
         // Minimum valid netlink message is 16 bytes (just the header)
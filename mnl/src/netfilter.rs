@@ -0,0 +1,331 @@
+use mnl_sys::libc::{self, c_int, c_void};
+use std::{
+    error, fmt, io,
+    os::unix::io::AsRawFd,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{NlBatch, NlMsg, NlMsgBuilder, Socket, buffer::AlignedBuffer};
+
+/// Message type marking the start of a netfilter batch. From `linux/netfilter/nfnetlink.h`.
+pub(crate) const NFNL_MSG_BATCH_BEGIN: u16 = libc::NLMSG_MIN_TYPE as u16;
+/// Message type marking the end of a netfilter batch.
+pub(crate) const NFNL_MSG_BATCH_END: u16 = libc::NLMSG_MIN_TYPE as u16 + 1;
+/// Subsystem id of nftables.
+pub(crate) const NFNL_SUBSYS_NFTABLES: u16 = 10;
+/// The only version of the `nfgenmsg` header.
+pub(crate) const NFNETLINK_V0: u8 = 0;
+
+/// Size of a message consisting of only the netlink and `nfgenmsg` headers.
+const NFGENMSG_MSG_LEN: usize = size_of::<libc::nlmsghdr>() + 4;
+
+/// Size of an error reply without the request it echoes: the netlink header and the error code.
+const NLMSGERR_HEADER_LEN: usize = size_of::<libc::nlmsghdr>() + size_of::<c_int>();
+
+/// Rough upper bound of how much receive buffer space the kernel accounts for each ACK.
+const RCVBUF_PER_ACK: usize = 1024;
+
+/// Append the `nfgenmsg` extra header to `msg`.
+pub(crate) fn put_nfgenmsg(msg: &mut NlMsgBuilder<'_>, family: u8, res_id: u16) -> io::Result<()> {
+    let [res_id_hi, res_id_lo] = res_id.to_be_bytes();
+    msg.put_extra_header(&[family, NFNETLINK_V0, res_id_hi, res_id_lo])
+}
+
+/// An nftables transaction. Builds on top of [`NlBatch`] and frames the messages with the
+/// `NFNL_MSG_BATCH_BEGIN` and `NFNL_MSG_BATCH_END` messages the kernel requires for changing the
+/// nftables ruleset.
+///
+/// Every message gets its own sequence number and requests an acknowledgement, so that
+/// [`send`] can tell exactly which messages the kernel rejected. The kernel applies either all
+/// or none of the messages in a transaction.
+///
+/// Since the whole transaction must be sent in one go, it is limited in size by the limit of the
+/// underlying batch.
+///
+/// [`send`]: #method.send
+pub struct NfBatch {
+    batch: NlBatch,
+    first_seq: u32,
+    len: usize,
+}
+
+impl NfBatch {
+    /// Create a new transaction with the default [`NlBatch`] limit.
+    pub fn new() -> io::Result<Self> {
+        Self::from_batch(NlBatch::new()?)
+    }
+
+    /// Create a new transaction that can grow to at most `limit` bytes.
+    pub fn with_limit(limit: usize) -> io::Result<Self> {
+        Self::from_batch(NlBatch::with_limit(limit)?)
+    }
+
+    fn from_batch(mut batch: NlBatch) -> io::Result<Self> {
+        // Same as libmnl examples and nft, use the current time as the initial sequence number
+        // to make it unlikely to mix up replies between program runs.
+        let first_seq = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_secs() as u32)
+            .unwrap_or(0);
+
+        let mut msg = batch.msg_builder()?;
+        msg.set_type(NFNL_MSG_BATCH_BEGIN);
+        msg.set_flags(libc::NLM_F_REQUEST as u16);
+        msg.set_seq(first_seq);
+        put_nfgenmsg(&mut msg, libc::AF_UNSPEC as u8, NFNL_SUBSYS_NFTABLES)?;
+        batch.next();
+
+        Ok(NfBatch {
+            batch,
+            first_seq,
+            len: 0,
+        })
+    }
+
+    /// Add a message to the transaction. `msg_type` is one of the `NFT_MSG_*` commands and
+    /// `family` the `NFPROTO_*` family it applies to. `NLM_F_REQUEST` and `NLM_F_ACK` are always
+    /// set in addition to `flags`. The attributes of the message are written by `f`.
+    ///
+    /// Returns the index of the message in the transaction. Fails without changing the
+    /// transaction if the message does not fit.
+    pub fn add<F>(&mut self, msg_type: u16, family: u8, flags: u16, f: F) -> io::Result<usize>
+    where
+        F: FnOnce(&mut NlMsgBuilder<'_>) -> io::Result<()>,
+    {
+        let index = self.len;
+        let seq = self.seq_of(index);
+        let limit = self.batch.limit();
+        let size = self.batch.size();
+
+        let mut msg = self.batch.msg_builder()?;
+        msg.set_type((NFNL_SUBSYS_NFTABLES << 8) | msg_type);
+        msg.set_flags(flags | (libc::NLM_F_REQUEST | libc::NLM_F_ACK) as u16);
+        msg.set_seq(seq);
+        put_nfgenmsg(&mut msg, family, 0)?;
+        f(&mut msg)?;
+        // Always leave room for the end message.
+        if size + msg.len() + NFGENMSG_MSG_LEN > limit {
+            return Err(crate::builder::no_space());
+        }
+        self.batch.next();
+        self.len += 1;
+        Ok(index)
+    }
+
+    /// Returns the number of messages added to the transaction, not counting the begin and end
+    /// markers.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if no messages have been added to the transaction.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the sequence number of the message with the given index.
+    pub fn seq_of(&self, index: usize) -> u32 {
+        self.first_seq.wrapping_add(1).wrapping_add(index as u32)
+    }
+
+    /// Returns the index of the message with the given sequence number, if it belongs to this
+    /// transaction.
+    pub fn index_of(&self, seq: u32) -> Option<usize> {
+        let index = seq.wrapping_sub(self.first_seq.wrapping_add(1)) as usize;
+        (index < self.len).then_some(index)
+    }
+
+    /// Finish the transaction with the end marker and return the bytes to send.
+    fn finish(&mut self) -> io::Result<&[u8]> {
+        let seq = self.seq_of(self.len);
+        let mut msg = self.batch.msg_builder()?;
+        msg.set_type(NFNL_MSG_BATCH_END);
+        msg.set_flags(libc::NLM_F_REQUEST as u16);
+        msg.set_seq(seq);
+        put_nfgenmsg(&mut msg, libc::AF_UNSPEC as u8, NFNL_SUBSYS_NFTABLES)?;
+        self.batch.next();
+        Ok(self.batch.as_bytes())
+    }
+
+    /// Send the transaction on `socket` and wait for the kernel to acknowledge every message.
+    ///
+    /// On failure, the returned error lists every message the kernel reported an error for,
+    /// identified by its index in the transaction.
+    pub fn send(mut self, socket: &Socket) -> Result<(), NfBatchError> {
+        // Each message gets an ACK. Make sure the receive buffer can hold them all, since they
+        // are all queued before we get to read any of them. Best effort, ignore failures.
+        grow_rcvbuf(socket, self.len * RCVBUF_PER_ACK);
+
+        let data = self.finish()?;
+        if socket.send(data)? < data.len() {
+            return Err(io::Error::other("sendto did not send entire message").into());
+        }
+
+        // nfnetlink processes the whole batch while sending, so all replies are available as
+        // soon as the send returns. Error replies echo the whole message that failed, which
+        // can be as large as the batch limit. A smaller buffer would truncate them and lose the
+        // error.
+        let buffer_size = (self.batch.limit() + NLMSGERR_HEADER_LEN)
+            .max(mnl_sys::MNL_SOCKET_BUFFER_SIZE() as usize);
+        let mut buffer = AlignedBuffer::new(buffer_size);
+        let mut acked = 0;
+        let mut errors = Vec::new();
+        while acked < self.len {
+            let Some(messages) = socket.try_recv(buffer.as_mut_bytes())? else {
+                break;
+            };
+            for message in messages {
+                let msg = NlMsg::new(message?)?;
+                let Some(result) = msg.error() else {
+                    continue;
+                };
+                match (self.index_of(msg.seq()), result) {
+                    (Some(_), Ok(())) => acked += 1,
+                    (Some(index), Err(error)) => {
+                        acked += 1;
+                        errors.push(NfMsgError {
+                            index,
+                            seq: msg.seq(),
+                            error,
+                        });
+                    }
+                    // The batch framing itself was rejected, so no messages were processed.
+                    (None, Err(error)) if msg.seq() == self.first_seq => {
+                        return Err(NfBatchError::Batch(error));
+                    }
+                    (None, _) => (),
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            Err(NfBatchError::Messages(errors))
+        } else if acked < self.len {
+            Err(io::Error::other("Did not receive acknowledgement for all messages").into())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn grow_rcvbuf(socket: &Socket, size: usize) {
+    let fd = socket.as_raw_fd();
+    let mut current: c_int = 0;
+    let mut len = size_of::<c_int>() as libc::socklen_t;
+    let ptr = &mut current as *mut c_int as *mut c_void;
+    if unsafe { libc::getsockopt(fd, libc::SOL_SOCKET, libc::SO_RCVBUF, ptr, &mut len) } != 0 {
+        return;
+    }
+    if (current as usize) < size {
+        let size = c_int::try_from(size).unwrap_or(c_int::MAX);
+        let ptr = &size as *const c_int as *const c_void;
+        unsafe { libc::setsockopt(fd, libc::SOL_SOCKET, libc::SO_RCVBUF, ptr, len) };
+    }
+}
+
+/// An error the kernel reported for a single message in an [`NfBatch`].
+#[derive(Debug)]
+pub struct NfMsgError {
+    /// Index of the message in the transaction, as returned by [`NfBatch::add`].
+    pub index: usize,
+    /// Sequence number of the message.
+    pub seq: u32,
+    /// The error reported by the kernel.
+    pub error: io::Error,
+}
+
+/// Error returned from [`NfBatch::send`].
+#[derive(Debug)]
+pub enum NfBatchError {
+    /// Sending the transaction or receiving the replies failed.
+    Io(io::Error),
+    /// The kernel rejected the transaction as a whole, for example due to missing permissions.
+    Batch(io::Error),
+    /// The kernel rejected one or more messages, and aborted the transaction.
+    Messages(Vec<NfMsgError>),
+}
+
+impl fmt::Display for NfBatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NfBatchError::Io(e) => write!(f, "Failed to send nftables batch: {e}"),
+            NfBatchError::Batch(e) => write!(f, "The kernel rejected the nftables batch: {e}"),
+            NfBatchError::Messages(errors) => {
+                write!(
+                    f,
+                    "The kernel rejected {} message(s) in the batch",
+                    errors.len()
+                )?;
+                for error in errors {
+                    write!(f, "; message {}: {}", error.index, error.error)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl error::Error for NfBatchError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            NfBatchError::Io(e) | NfBatchError::Batch(e) => Some(e),
+            NfBatchError::Messages(errors) => errors.first().map(|e| &e.error as _),
+        }
+    }
+}
+
+impl From<io::Error> for NfBatchError {
+    fn from(error: io::Error) -> Self {
+        NfBatchError::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NlMessages;
+
+    #[test]
+    fn test_batch_framing() {
+        let mut batch = NfBatch::new().unwrap();
+        let first = batch
+            .add(0, libc::NFPROTO_INET as u8, 0, |msg| {
+                msg.put_str(1, "filter")
+            })
+            .unwrap();
+        let second = batch
+            .add(2, libc::NFPROTO_INET as u8, 0, |_| Ok(()))
+            .unwrap();
+        assert_eq!((first, second), (0, 1));
+        assert_eq!(batch.index_of(batch.seq_of(1)), Some(1));
+        assert_eq!(batch.index_of(batch.seq_of(2)), None);
+        assert_eq!(batch.index_of(batch.first_seq), None);
+
+        let first_seq = batch.first_seq;
+        let msgs = NlMessages::new(batch.finish().unwrap())
+            .map(|msg| {
+                let msg = NlMsg::new(msg.unwrap()).unwrap();
+                (msg.msg_type(), msg.seq(), msg.payload()[..4].to_vec())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            msgs,
+            [
+                (NFNL_MSG_BATCH_BEGIN, first_seq, vec![0, 0, 0, 10]),
+                (0x0a00, first_seq + 1, vec![1, 0, 0, 0]),
+                (0x0a02, first_seq + 2, vec![1, 0, 0, 0]),
+                (NFNL_MSG_BATCH_END, first_seq + 3, vec![0, 0, 0, 10]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_message_too_large_for_batch() {
+        let mut batch = NfBatch::with_limit(128).unwrap();
+        let result = batch.add(0, 0, 0, |msg| msg.put(1, &[0; 72]));
+        assert!(result.is_err());
+        assert!(batch.is_empty());
+        batch.add(0, 0, 0, |msg| msg.put(1, &[0; 32])).unwrap();
+        assert_eq!(batch.len(), 1);
+    }
+}
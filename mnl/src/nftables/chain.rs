@@ -0,0 +1,327 @@
+use mnl_sys::libc;
+use std::io;
+
+use super::{Family, NFGENMSG_LEN, Table, family_of, get, get_one, put_be32, put_be64, sys};
+use crate::{Attr, NfBatch, NlMsg, NlMsgBuilder, Socket};
+
+/// Hook of the `ip`, `ip6`, `inet` and `bridge` families: packets before routing.
+pub const NF_INET_PRE_ROUTING: u32 = 0;
+/// Hook: packets routed to the local host.
+pub const NF_INET_LOCAL_IN: u32 = 1;
+/// Hook: packets routed through the host.
+pub const NF_INET_FORWARD: u32 = 2;
+/// Hook: packets sent by the local host.
+pub const NF_INET_LOCAL_OUT: u32 = 3;
+/// Hook: packets after routing, about to leave the host.
+pub const NF_INET_POST_ROUTING: u32 = 4;
+/// Hook of the `inet` family: packets received by the devices of the hook, before
+/// `NF_INET_PRE_ROUTING`.
+pub const NF_INET_INGRESS: u32 = 5;
+/// Hook of the `netdev` family: packets received by the devices of the hook.
+pub const NF_NETDEV_INGRESS: u32 = 0;
+/// Hook of the `netdev` family: packets sent by the devices of the hook.
+pub const NF_NETDEV_EGRESS: u32 = 1;
+
+/// Chain flag: the chain is a base chain, registered with a hook. Set by the kernel.
+pub const NFT_CHAIN_BASE: u32 = 1 << 0;
+/// Chain flag: offload the chain to hardware.
+pub const NFT_CHAIN_HW_OFFLOAD: u32 = 1 << 1;
+
+/// An nftables chain, as described by a `NFT_MSG_NEWCHAIN` message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chain {
+    /// The address family of the table the chain is in.
+    pub family: Family,
+    /// Name of the table the chain is in.
+    pub table: String,
+    /// Name of the chain, unique within its table.
+    pub name: String,
+    /// Handle assigned by the kernel.
+    pub handle: Option<u64>,
+    /// The hook a base chain is registered with. Regular chains, which are only evaluated when
+    /// jumped to, have no hook.
+    pub hook: Option<Hook>,
+    /// What happens to packets reaching the end of a base chain.
+    pub policy: Option<Policy>,
+    /// Type of a base chain: `"filter"`, `"nat"` or `"route"`.
+    pub chain_type: Option<String>,
+    /// A combination of the `NFT_CHAIN_*` flags.
+    pub flags: u32,
+    /// Number of rules and other references to the chain. Only set by the kernel.
+    pub use_count: u32,
+    /// Opaque data stored with the chain, such as comments added by `nft`.
+    pub userdata: Option<Vec<u8>>,
+}
+
+/// The hook of a base chain or flowtable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hook {
+    /// The hook number, one of the `NF_INET_*` or `NF_NETDEV_*` constants depending on family.
+    pub hooknum: u32,
+    /// Priority among the chains on the same hook, lower values are evaluated first.
+    pub priority: i32,
+    /// Network devices the hook applies to, for the `netdev` family, the `inet` ingress hook and
+    /// flowtables.
+    pub devices: Vec<String>,
+}
+
+impl Hook {
+    /// Describe a hook with the given number and priority, without devices.
+    pub fn new(hooknum: u32, priority: i32) -> Self {
+        Hook {
+            hooknum,
+            priority,
+            devices: Vec::new(),
+        }
+    }
+
+    /// Parse the nested hook attributes of a chain (`NFTA_HOOK_*`) or flowtable
+    /// (`NFTA_FLOWTABLE_HOOK_*`), which are numbered the same except for the devices.
+    pub(super) fn parse(attr: Attr<'_>, devs_type: u16) -> io::Result<Hook> {
+        let mut hook = Hook::new(0, 0);
+        for attr in attr.nested() {
+            let attr = attr?;
+            match attr.attr_type() {
+                sys::NFTA_HOOK_HOOKNUM => hook.hooknum = attr.get_be32()?,
+                sys::NFTA_HOOK_PRIORITY => hook.priority = attr.get_be32()? as i32,
+                sys::NFTA_HOOK_DEV if devs_type != sys::NFTA_HOOK_DEV => {
+                    hook.devices.push(attr.get_str()?.to_owned())
+                }
+                attr_type if attr_type == devs_type => {
+                    for device in attr.nested() {
+                        hook.devices.push(device?.get_str()?.to_owned());
+                    }
+                }
+                _ => (),
+            }
+        }
+        Ok(hook)
+    }
+
+    /// Add the hook as a nested attribute of the given type. A single device of a chain goes
+    /// into `NFTA_HOOK_DEV` as `nft` does, otherwise the devices are nested in `devs_type`.
+    pub(super) fn put(
+        &self,
+        msg: &mut NlMsgBuilder<'_>,
+        attr_type: u16,
+        devs_type: u16,
+    ) -> io::Result<()> {
+        msg.put_nested(attr_type, |msg| {
+            put_be32(msg, sys::NFTA_HOOK_HOOKNUM, self.hooknum)?;
+            put_be32(msg, sys::NFTA_HOOK_PRIORITY, self.priority as u32)?;
+            match &self.devices[..] {
+                [] => Ok(()),
+                [device] if devs_type == sys::NFTA_HOOK_DEVS => {
+                    msg.put_str(sys::NFTA_HOOK_DEV, device)
+                }
+                devices => msg.put_nested(devs_type, |msg| {
+                    for device in devices {
+                        msg.put_str(sys::NFTA_DEVICE_NAME, device)?;
+                    }
+                    Ok(())
+                }),
+            }
+        })
+    }
+}
+
+/// The policy of a base chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    /// Accept packets reaching the end of the chain.
+    Accept,
+    /// Drop packets reaching the end of the chain.
+    Drop,
+    /// A policy not known to this crate.
+    Other(u32),
+}
+
+impl From<u32> for Policy {
+    fn from(value: u32) -> Self {
+        match value as i32 {
+            sys::NF_ACCEPT => Policy::Accept,
+            sys::NF_DROP => Policy::Drop,
+            _ => Policy::Other(value),
+        }
+    }
+}
+
+impl From<Policy> for u32 {
+    fn from(policy: Policy) -> u32 {
+        match policy {
+            Policy::Accept => sys::NF_ACCEPT as u32,
+            Policy::Drop => sys::NF_DROP as u32,
+            Policy::Other(other) => other,
+        }
+    }
+}
+
+impl Chain {
+    /// Describe a regular chain with the given name in `table`.
+    pub fn new(table: &Table, name: impl Into<String>) -> Self {
+        Chain {
+            family: table.family,
+            table: table.name.clone(),
+            name: name.into(),
+            handle: None,
+            hook: None,
+            policy: None,
+            chain_type: None,
+            flags: 0,
+            use_count: 0,
+            userdata: None,
+        }
+    }
+
+    /// Parse a `NFT_MSG_NEWCHAIN` message.
+    pub fn from_msg(msg: &NlMsg<'_>) -> io::Result<Chain> {
+        let mut chain = Chain::new(&Table::new(family_of(msg)?, ""), "");
+        for attr in msg.attrs(NFGENMSG_LEN) {
+            let attr = attr?;
+            match attr.attr_type() {
+                sys::NFTA_CHAIN_TABLE => chain.table = attr.get_str()?.to_owned(),
+                sys::NFTA_CHAIN_NAME => chain.name = attr.get_str()?.to_owned(),
+                sys::NFTA_CHAIN_HANDLE => chain.handle = Some(attr.get_be64()?),
+                sys::NFTA_CHAIN_HOOK => chain.hook = Some(Hook::parse(attr, sys::NFTA_HOOK_DEVS)?),
+                sys::NFTA_CHAIN_POLICY => chain.policy = Some(attr.get_be32()?.into()),
+                sys::NFTA_CHAIN_TYPE => chain.chain_type = Some(attr.get_str()?.to_owned()),
+                sys::NFTA_CHAIN_FLAGS => chain.flags = attr.get_be32()?,
+                sys::NFTA_CHAIN_USE => chain.use_count = attr.get_be32()?,
+                sys::NFTA_CHAIN_USERDATA => chain.userdata = Some(attr.payload().to_vec()),
+                _ => (),
+            }
+        }
+        Ok(chain)
+    }
+
+    /// Returns true if the chain is a base chain, registered with a hook.
+    pub fn is_base_chain(&self) -> bool {
+        self.hook.is_some()
+    }
+
+    fn put_attrs(&self, msg: &mut NlMsgBuilder<'_>) -> io::Result<()> {
+        msg.put_str(sys::NFTA_CHAIN_TABLE, &self.table)?;
+        msg.put_str(sys::NFTA_CHAIN_NAME, &self.name)?;
+        if let Some(hook) = &self.hook {
+            hook.put(msg, sys::NFTA_CHAIN_HOOK, sys::NFTA_HOOK_DEVS)?;
+        }
+        if let Some(policy) = self.policy {
+            put_be32(msg, sys::NFTA_CHAIN_POLICY, policy.into())?;
+        }
+        if let Some(chain_type) = &self.chain_type {
+            msg.put_str(sys::NFTA_CHAIN_TYPE, chain_type)?;
+        }
+        if self.flags != 0 {
+            put_be32(msg, sys::NFTA_CHAIN_FLAGS, self.flags)?;
+        }
+        if let Some(userdata) = &self.userdata {
+            msg.put(sys::NFTA_CHAIN_USERDATA, userdata)?;
+        }
+        Ok(())
+    }
+
+    /// Add a `NFT_MSG_NEWCHAIN` message to `batch`, creating the chain or updating the policy
+    /// of an existing base chain. Returns the index of the message in the batch.
+    pub fn add(&self, batch: &mut NfBatch) -> io::Result<usize> {
+        batch.add(
+            sys::NFT_MSG_NEWCHAIN,
+            self.family.into(),
+            libc::NLM_F_CREATE as u16,
+            |msg| self.put_attrs(msg),
+        )
+    }
+
+    /// Add a `NFT_MSG_NEWCHAIN` message to `batch` that fails with `EEXIST` if the chain
+    /// exists.
+    pub fn create(&self, batch: &mut NfBatch) -> io::Result<usize> {
+        batch.add(
+            sys::NFT_MSG_NEWCHAIN,
+            self.family.into(),
+            (libc::NLM_F_CREATE | libc::NLM_F_EXCL) as u16,
+            |msg| self.put_attrs(msg),
+        )
+    }
+
+    /// Add a `NFT_MSG_DELCHAIN` message to `batch`. The chain is identified by its handle if
+    /// set, otherwise by its name. Fails with `EBUSY` when sent if the chain still has rules or
+    /// is jumped to.
+    pub fn delete(&self, batch: &mut NfBatch) -> io::Result<usize> {
+        batch.add(sys::NFT_MSG_DELCHAIN, self.family.into(), 0, |msg| {
+            msg.put_str(sys::NFTA_CHAIN_TABLE, &self.table)?;
+            match self.handle {
+                Some(handle) => put_be64(msg, sys::NFTA_CHAIN_HANDLE, handle),
+                None => msg.put_str(sys::NFTA_CHAIN_NAME, &self.name),
+            }
+        })
+    }
+}
+
+/// Dump the chains of all tables (`NFT_MSG_GETCHAIN`). `socket` must be a [`Bus::Netfilter`]
+/// socket.
+///
+/// [`Bus::Netfilter`]: crate::Bus::Netfilter
+pub fn chains(socket: &Socket) -> io::Result<Vec<Chain>> {
+    get(
+        socket,
+        sys::NFT_MSG_GETCHAIN,
+        libc::NFPROTO_UNSPEC as u8,
+        true,
+        |_| Ok(()),
+        Chain::from_msg,
+    )
+}
+
+/// Get the chain with the given name in `table`. Fails with `ENOENT` if there is no such chain.
+pub fn chain(socket: &Socket, table: &Table, name: &str) -> io::Result<Chain> {
+    get_one(
+        socket,
+        sys::NFT_MSG_GETCHAIN,
+        table.family.into(),
+        |msg| {
+            msg.put_str(sys::NFTA_CHAIN_TABLE, &table.name)?;
+            msg.put_str(sys::NFTA_CHAIN_NAME, name)
+        },
+        Chain::from_msg,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::AlignedBuffer;
+
+    #[test]
+    fn test_chain_round_trip() {
+        let table = Table::new(Family::Netdev, "filter");
+        let chains = [
+            Chain::new(&table, "regular"),
+            Chain {
+                hook: Some(Hook {
+                    devices: vec!["eth0".to_owned()],
+                    ..Hook::new(NF_NETDEV_INGRESS, -500)
+                }),
+                policy: Some(Policy::Drop),
+                chain_type: Some("filter".to_owned()),
+                ..Chain::new(&table, "ingress")
+            },
+            Chain {
+                hook: Some(Hook {
+                    devices: vec!["eth0".to_owned(), "eth1".to_owned()],
+                    ..Hook::new(NF_NETDEV_EGRESS, 0)
+                }),
+                flags: NFT_CHAIN_BASE,
+                ..Chain::new(&table, "egress")
+            },
+        ];
+        for chain in chains {
+            let mut buffer = AlignedBuffer::new(512);
+            let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+            crate::netfilter::put_nfgenmsg(&mut msg, chain.family.into(), 0).unwrap();
+            chain.put_attrs(&mut msg).unwrap();
+
+            let parsed = Chain::from_msg(&NlMsg::new(msg.as_bytes()).unwrap()).unwrap();
+            assert_eq!(parsed, chain);
+            assert_eq!(parsed.is_base_chain(), chain.name != "regular");
+        }
+    }
+}
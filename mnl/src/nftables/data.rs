@@ -0,0 +1,141 @@
+use std::io;
+
+use super::{put_be32, sys};
+use crate::{Attr, NlMsgBuilder};
+
+/// What happens to a packet, as the policy of a chain, the data of a verdict map or the result
+/// of a rule.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Verdict {
+    /// Accept the packet and stop evaluating the hook.
+    Accept,
+    /// Drop the packet.
+    Drop,
+    /// Pass the packet to user space through nfqueue.
+    Queue,
+    /// Continue with the next expression of the rule.
+    Continue,
+    /// Stop evaluating the rule and continue with the next one.
+    Break,
+    /// Return to the calling chain, or apply the policy in a base chain.
+    Return,
+    /// Continue in the given chain, and return here afterwards.
+    Jump(String),
+    /// Continue in the given chain, without returning here.
+    Goto(String),
+    /// A verdict code not known to this crate.
+    Other(i32),
+}
+
+impl Verdict {
+    /// Returns the verdict code, one of the `NF_*` or `NFT_*` verdict constants.
+    pub fn code(&self) -> i32 {
+        match self {
+            Verdict::Accept => sys::NF_ACCEPT,
+            Verdict::Drop => sys::NF_DROP,
+            Verdict::Queue => sys::NF_QUEUE,
+            Verdict::Continue => sys::NFT_CONTINUE,
+            Verdict::Break => sys::NFT_BREAK,
+            Verdict::Return => sys::NFT_RETURN,
+            Verdict::Jump(_) => sys::NFT_JUMP,
+            Verdict::Goto(_) => sys::NFT_GOTO,
+            Verdict::Other(code) => *code,
+        }
+    }
+
+    /// Parse the nested `NFTA_VERDICT_*` attributes of `attr`.
+    pub(super) fn parse(attr: Attr<'_>) -> io::Result<Verdict> {
+        let mut code = None;
+        let mut chain = None;
+        for attr in attr.nested() {
+            let attr = attr?;
+            match attr.attr_type() {
+                sys::NFTA_VERDICT_CODE => code = Some(attr.get_be32()? as i32),
+                sys::NFTA_VERDICT_CHAIN => chain = Some(attr.get_str()?.to_owned()),
+                _ => (),
+            }
+        }
+        let code = code.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "Verdict without verdict code")
+        })?;
+        Ok(match (code, chain) {
+            (sys::NF_ACCEPT, _) => Verdict::Accept,
+            (sys::NF_DROP, _) => Verdict::Drop,
+            (sys::NF_QUEUE, _) => Verdict::Queue,
+            (sys::NFT_CONTINUE, _) => Verdict::Continue,
+            (sys::NFT_BREAK, _) => Verdict::Break,
+            (sys::NFT_RETURN, _) => Verdict::Return,
+            (sys::NFT_JUMP, Some(chain)) => Verdict::Jump(chain),
+            (sys::NFT_GOTO, Some(chain)) => Verdict::Goto(chain),
+            (code, _) => Verdict::Other(code),
+        })
+    }
+
+    /// Add the verdict as a nested attribute of the given type.
+    pub(super) fn put(&self, msg: &mut NlMsgBuilder<'_>, attr_type: u16) -> io::Result<()> {
+        msg.put_nested(attr_type, |msg| {
+            put_be32(msg, sys::NFTA_VERDICT_CODE, self.code() as u32)?;
+            match self {
+                Verdict::Jump(chain) | Verdict::Goto(chain) => {
+                    msg.put_str(sys::NFTA_VERDICT_CHAIN, chain)
+                }
+                _ => Ok(()),
+            }
+        })
+    }
+}
+
+/// A value in a register, set element or map, as described by the nested `NFTA_DATA_*`
+/// attributes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Data {
+    /// Raw bytes, in the byte order of the packet field or meta data they are compared with.
+    Value(Vec<u8>),
+    /// A verdict.
+    Verdict(Verdict),
+}
+
+impl Data {
+    /// Parse the nested `NFTA_DATA_*` attributes of `attr`.
+    pub(super) fn parse(attr: Attr<'_>) -> io::Result<Data> {
+        for attr in attr.nested() {
+            let attr = attr?;
+            match attr.attr_type() {
+                sys::NFTA_DATA_VALUE => return Ok(Data::Value(attr.payload().to_vec())),
+                sys::NFTA_DATA_VERDICT => return Ok(Data::Verdict(Verdict::parse(attr)?)),
+                _ => (),
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Data attribute without value or verdict",
+        ))
+    }
+
+    /// Add the data as a nested attribute of the given type.
+    pub(super) fn put(&self, msg: &mut NlMsgBuilder<'_>, attr_type: u16) -> io::Result<()> {
+        match self {
+            Data::Value(value) => put_value(msg, attr_type, value),
+            Data::Verdict(verdict) => {
+                msg.put_nested(attr_type, |msg| verdict.put(msg, sys::NFTA_DATA_VERDICT))
+            }
+        }
+    }
+
+    /// Returns the raw bytes of a value, or `None` for a verdict.
+    pub fn value(&self) -> Option<&[u8]> {
+        match self {
+            Data::Value(value) => Some(value),
+            Data::Verdict(_) => None,
+        }
+    }
+}
+
+/// Add `value` as a nested `NFTA_DATA_VALUE` attribute of the given type.
+pub(super) fn put_value(
+    msg: &mut NlMsgBuilder<'_>,
+    attr_type: u16,
+    value: &[u8],
+) -> io::Result<()> {
+    msg.put_nested(attr_type, |msg| msg.put(sys::NFTA_DATA_VALUE, value))
+}
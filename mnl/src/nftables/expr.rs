@@ -0,0 +1,1763 @@
+use std::{fmt, io};
+
+use super::{Data, Family, NESTED, Verdict, data::put_value, put_be32, put_be64, sys};
+use crate::{Attr, Attrs, NlMsgBuilder};
+
+/// The verdict register. Loading a verdict into it with an [`ImmediateExpr`] ends the rule.
+pub const NFT_REG_VERDICT: u32 = 0;
+/// The first 128 bit data register.
+pub const NFT_REG_1: u32 = 1;
+/// The second 128 bit data register.
+pub const NFT_REG_2: u32 = 2;
+/// The third 128 bit data register.
+pub const NFT_REG_3: u32 = 3;
+/// The fourth 128 bit data register.
+pub const NFT_REG_4: u32 = 4;
+/// The first 32 bit data register. The sixteen 32 bit registers `NFT_REG32_00 + n` overlay the
+/// four 128 bit ones, `nft` uses the 128 bit numbers where possible.
+pub const NFT_REG32_00: u32 = 8;
+
+/// Payload base: the link layer header.
+pub const NFT_PAYLOAD_LL_HEADER: u32 = 0;
+/// Payload base: the network header.
+pub const NFT_PAYLOAD_NETWORK_HEADER: u32 = 1;
+/// Payload base: the transport header.
+pub const NFT_PAYLOAD_TRANSPORT_HEADER: u32 = 2;
+/// Payload base: the payload after the transport header.
+pub const NFT_PAYLOAD_INNER_HEADER: u32 = 3;
+
+/// Payload checksum type: don't update a checksum.
+pub const NFT_PAYLOAD_CSUM_NONE: u32 = 0;
+/// Payload checksum type: update the internet checksum at the checksum offset.
+pub const NFT_PAYLOAD_CSUM_INET: u32 = 1;
+/// Payload checksum type: update the SCTP CRC32c checksum.
+pub const NFT_PAYLOAD_CSUM_SCTP: u32 = 2;
+/// Payload checksum flag: also update the layer 4 checksum covering the pseudo header.
+pub const NFT_PAYLOAD_L4CSUM_PSEUDOHDR: u32 = 1;
+
+/// Meta key: length of the packet.
+pub const NFT_META_LEN: u32 = 0;
+/// Meta key: ethernet protocol of the packet.
+pub const NFT_META_PROTOCOL: u32 = 1;
+/// Meta key: traffic control priority.
+pub const NFT_META_PRIORITY: u32 = 2;
+/// Meta key: packet mark.
+pub const NFT_META_MARK: u32 = 3;
+/// Meta key: index of the input interface.
+pub const NFT_META_IIF: u32 = 4;
+/// Meta key: index of the output interface.
+pub const NFT_META_OIF: u32 = 5;
+/// Meta key: name of the input interface.
+pub const NFT_META_IIFNAME: u32 = 6;
+/// Meta key: name of the output interface.
+pub const NFT_META_OIFNAME: u32 = 7;
+/// Meta key: hardware type of the input interface.
+pub const NFT_META_IIFTYPE: u32 = 8;
+/// Meta key: hardware type of the output interface.
+pub const NFT_META_OIFTYPE: u32 = 9;
+/// Meta key: user id of the originating socket.
+pub const NFT_META_SKUID: u32 = 10;
+/// Meta key: group id of the originating socket.
+pub const NFT_META_SKGID: u32 = 11;
+/// Meta key: packet tracing flag.
+pub const NFT_META_NFTRACE: u32 = 12;
+/// Meta key: realm of the route.
+pub const NFT_META_RTCLASSID: u32 = 13;
+/// Meta key: security mark.
+pub const NFT_META_SECMARK: u32 = 14;
+/// Meta key: netfilter protocol family of the hook.
+pub const NFT_META_NFPROTO: u32 = 15;
+/// Meta key: layer 4 protocol number.
+pub const NFT_META_L4PROTO: u32 = 16;
+/// Meta key: name of the bridge the packet came in through.
+pub const NFT_META_BRI_IIFNAME: u32 = 17;
+/// Meta key: name of the bridge the packet goes out through.
+pub const NFT_META_BRI_OIFNAME: u32 = 18;
+/// Meta key: packet type, such as unicast or broadcast.
+pub const NFT_META_PKTTYPE: u32 = 19;
+/// Meta key: the CPU processing the packet.
+pub const NFT_META_CPU: u32 = 20;
+/// Meta key: group of the input interface.
+pub const NFT_META_IIFGROUP: u32 = 21;
+/// Meta key: group of the output interface.
+pub const NFT_META_OIFGROUP: u32 = 22;
+/// Meta key: net_cls cgroup class id of the originating socket.
+pub const NFT_META_CGROUP: u32 = 23;
+/// Meta key: a random number.
+pub const NFT_META_PRANDOM: u32 = 24;
+/// Meta key: whether the packet has an IPsec security path.
+pub const NFT_META_SECPATH: u32 = 25;
+/// Meta key: kind of the input interface, such as `"veth"`.
+pub const NFT_META_IIFKIND: u32 = 26;
+/// Meta key: kind of the output interface.
+pub const NFT_META_OIFKIND: u32 = 27;
+/// Meta key: port VLAN id of the bridge port the packet came in through.
+pub const NFT_META_BRI_IIFPVID: u32 = 28;
+/// Meta key: VLAN protocol of the bridge the packet came in through.
+pub const NFT_META_BRI_IIFVPROTO: u32 = 29;
+/// Meta key: time of day in nanoseconds since the epoch.
+pub const NFT_META_TIME_NS: u32 = 30;
+/// Meta key: day of the week.
+pub const NFT_META_TIME_DAY: u32 = 31;
+/// Meta key: seconds since midnight.
+pub const NFT_META_TIME_HOUR: u32 = 32;
+/// Meta key: index of the slave interface of a VRF.
+pub const NFT_META_SDIF: u32 = 33;
+/// Meta key: name of the slave interface of a VRF.
+pub const NFT_META_SDIFNAME: u32 = 34;
+
+/// Comparison: equal.
+pub const NFT_CMP_EQ: u32 = 0;
+/// Comparison: not equal.
+pub const NFT_CMP_NEQ: u32 = 1;
+/// Comparison: less than.
+pub const NFT_CMP_LT: u32 = 2;
+/// Comparison: less than or equal.
+pub const NFT_CMP_LTE: u32 = 3;
+/// Comparison: greater than.
+pub const NFT_CMP_GT: u32 = 4;
+/// Comparison: greater than or equal.
+pub const NFT_CMP_GTE: u32 = 5;
+
+/// Lookup flag: match if the key is not in the set.
+pub const NFT_LOOKUP_F_INV: u32 = 1;
+
+/// Bitwise operation: `(reg & mask) ^ xor`.
+pub const NFT_BITWISE_BOOL: u32 = 0;
+/// Bitwise operation: shift left.
+pub const NFT_BITWISE_LSHIFT: u32 = 1;
+/// Bitwise operation: shift right.
+pub const NFT_BITWISE_RSHIFT: u32 = 2;
+
+/// Conntrack key: state, a combination of the `NF_CT_STATE_*` bits.
+pub const NFT_CT_STATE: u32 = 0;
+/// Conntrack key: direction of the packet.
+pub const NFT_CT_DIRECTION: u32 = 1;
+/// Conntrack key: status bits of the connection.
+pub const NFT_CT_STATUS: u32 = 2;
+/// Conntrack key: connection mark.
+pub const NFT_CT_MARK: u32 = 3;
+/// Conntrack key: security mark.
+pub const NFT_CT_SECMARK: u32 = 4;
+/// Conntrack key: time until the connection expires, in milliseconds.
+pub const NFT_CT_EXPIRATION: u32 = 5;
+/// Conntrack key: name of the connection tracking helper.
+pub const NFT_CT_HELPER: u32 = 6;
+/// Conntrack key: layer 3 protocol family.
+pub const NFT_CT_L3PROTOCOL: u32 = 7;
+/// Conntrack key: source address of the direction.
+pub const NFT_CT_SRC: u32 = 8;
+/// Conntrack key: destination address of the direction.
+pub const NFT_CT_DST: u32 = 9;
+/// Conntrack key: layer 4 protocol number.
+pub const NFT_CT_PROTOCOL: u32 = 10;
+/// Conntrack key: layer 4 source port of the direction.
+pub const NFT_CT_PROTO_SRC: u32 = 11;
+/// Conntrack key: layer 4 destination port of the direction.
+pub const NFT_CT_PROTO_DST: u32 = 12;
+/// Conntrack key: connection labels.
+pub const NFT_CT_LABELS: u32 = 13;
+/// Conntrack key: number of packets.
+pub const NFT_CT_PKTS: u32 = 14;
+/// Conntrack key: number of bytes.
+pub const NFT_CT_BYTES: u32 = 15;
+/// Conntrack key: average packet size.
+pub const NFT_CT_AVGPKT: u32 = 16;
+/// Conntrack key: conntrack zone.
+pub const NFT_CT_ZONE: u32 = 17;
+/// Conntrack key: mask of the events reported for the connection.
+pub const NFT_CT_EVENTMASK: u32 = 18;
+/// Conntrack key: IPv4 source address of the direction.
+pub const NFT_CT_SRC_IP: u32 = 19;
+/// Conntrack key: IPv4 destination address of the direction.
+pub const NFT_CT_DST_IP: u32 = 20;
+/// Conntrack key: IPv6 source address of the direction.
+pub const NFT_CT_SRC_IP6: u32 = 21;
+/// Conntrack key: IPv6 destination address of the direction.
+pub const NFT_CT_DST_IP6: u32 = 22;
+/// Conntrack key: id of the connection.
+pub const NFT_CT_ID: u32 = 23;
+
+/// Conntrack direction: from the originator of the connection.
+pub const IP_CT_DIR_ORIGINAL: u8 = 0;
+/// Conntrack direction: from the responder of the connection.
+pub const IP_CT_DIR_REPLY: u8 = 1;
+
+/// Conntrack state bit: the packet does not belong to a valid connection.
+pub const NF_CT_STATE_INVALID_BIT: u32 = 1 << 0;
+/// Conntrack state bit: the connection has seen packets in both directions.
+pub const NF_CT_STATE_ESTABLISHED_BIT: u32 = 1 << 1;
+/// Conntrack state bit: the connection is related to another one, such as an FTP data
+/// connection.
+pub const NF_CT_STATE_RELATED_BIT: u32 = 1 << 2;
+/// Conntrack state bit: the packet starts a new connection.
+pub const NF_CT_STATE_NEW_BIT: u32 = 1 << 3;
+/// Conntrack state bit: the packet is not tracked.
+pub const NF_CT_STATE_UNTRACKED_BIT: u32 = 1 << 6;
+
+/// NAT type: rewrite the source address.
+pub const NFT_NAT_SNAT: u32 = 0;
+/// NAT type: rewrite the destination address.
+pub const NFT_NAT_DNAT: u32 = 1;
+
+/// NAT flag: an address range is given.
+pub const NF_NAT_RANGE_MAP_IPS: u32 = 1 << 0;
+/// NAT flag: a port range is given.
+pub const NF_NAT_RANGE_PROTO_SPECIFIED: u32 = 1 << 1;
+/// NAT flag: pick ports randomly.
+pub const NF_NAT_RANGE_PROTO_RANDOM: u32 = 1 << 2;
+/// NAT flag: map a client to the same address for all its connections.
+pub const NF_NAT_RANGE_PERSISTENT: u32 = 1 << 3;
+/// NAT flag: pick ports fully randomly.
+pub const NF_NAT_RANGE_PROTO_RANDOM_FULLY: u32 = 1 << 4;
+
+/// Reject type: send an ICMP or ICMPv6 unreachable message with a family specific code.
+pub const NFT_REJECT_ICMP_UNREACH: u32 = 0;
+/// Reject type: send a TCP reset.
+pub const NFT_REJECT_TCP_RST: u32 = 1;
+/// Reject type: send an unreachable message with one of the `NFT_REJECT_ICMPX_*` codes, for
+/// either family.
+pub const NFT_REJECT_ICMPX_UNREACH: u32 = 2;
+
+/// Family independent reject code: no route to host.
+pub const NFT_REJECT_ICMPX_NO_ROUTE: u8 = 0;
+/// Family independent reject code: port unreachable.
+pub const NFT_REJECT_ICMPX_PORT_UNREACH: u8 = 1;
+/// Family independent reject code: host unreachable.
+pub const NFT_REJECT_ICMPX_HOST_UNREACH: u8 = 2;
+/// Family independent reject code: administratively prohibited.
+pub const NFT_REJECT_ICMPX_ADMIN_PROHIBITED: u8 = 3;
+
+/// Limit type: limit the number of packets.
+pub const NFT_LIMIT_PKTS: u32 = 0;
+/// Limit type: limit the number of bytes.
+pub const NFT_LIMIT_PKT_BYTES: u32 = 1;
+/// Limit flag: match when the rate is exceeded, instead of while it is not.
+pub const NFT_LIMIT_F_INV: u32 = 1;
+
+/// FIB result: nothing, only check whether a route exists.
+pub const NFT_FIB_RESULT_UNSPEC: u32 = 0;
+/// FIB result: index of the output interface.
+pub const NFT_FIB_RESULT_OIF: u32 = 1;
+/// FIB result: name of the output interface.
+pub const NFT_FIB_RESULT_OIFNAME: u32 = 2;
+/// FIB result: type of the address, such as local or unicast.
+pub const NFT_FIB_RESULT_ADDRTYPE: u32 = 3;
+
+/// FIB flag: look up the source address.
+pub const NFTA_FIB_F_SADDR: u32 = 1 << 0;
+/// FIB flag: look up the destination address.
+pub const NFTA_FIB_F_DADDR: u32 = 1 << 1;
+/// FIB flag: use the packet mark.
+pub const NFTA_FIB_F_MARK: u32 = 1 << 2;
+/// FIB flag: use the input interface.
+pub const NFTA_FIB_F_IIF: u32 = 1 << 3;
+/// FIB flag: use the output interface.
+pub const NFTA_FIB_F_OIF: u32 = 1 << 4;
+/// FIB flag: only check whether the result exists.
+pub const NFTA_FIB_F_PRESENT: u32 = 1 << 5;
+
+/// Socket key: whether the socket has `IP_TRANSPARENT` set.
+pub const NFT_SOCKET_TRANSPARENT: u32 = 0;
+/// Socket key: the socket mark.
+pub const NFT_SOCKET_MARK: u32 = 1;
+/// Socket key: whether the socket is bound to the wildcard address.
+pub const NFT_SOCKET_WILDCARD: u32 = 2;
+/// Socket key: the cgroup v2 id of the socket at the ancestor level given by the expression.
+pub const NFT_SOCKET_CGROUPV2: u32 = 3;
+
+/// An expression of a rule.
+///
+/// The [`Display`] implementation formats an expression the way `nft --debug=netlink` prints
+/// it, such as `[ payload load 4b @ network header + 12 => reg 1 ]`.
+///
+/// [`Display`]: fmt::Display
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Expr {
+    /// Load from or write to the packet.
+    Payload(PayloadExpr),
+    /// Load or set packet meta data.
+    Meta(MetaExpr),
+    /// Compare a register with a value.
+    Cmp(CmpExpr),
+    /// Load a value or verdict into a register.
+    Immediate(ImmediateExpr),
+    /// Look up a register in a set or map.
+    Lookup(LookupExpr),
+    /// Mask, xor or shift a register.
+    Bitwise(BitwiseExpr),
+    /// Load or set connection tracking data.
+    Ct(CtExpr),
+    /// Count packets and bytes.
+    Counter(CounterExpr),
+    /// Log the packet.
+    Log(LogExpr),
+    /// Source or destination NAT.
+    Nat(NatExpr),
+    /// Masquerade to the address of the output interface.
+    Masq(MasqExpr),
+    /// Reject the packet with an error.
+    Reject(RejectExpr),
+    /// Match packets up to a rate.
+    Limit(LimitExpr),
+    /// Look up the packet in the routing table.
+    Fib(FibExpr),
+    /// Load data of the socket the packet belongs to.
+    Socket(SocketExpr),
+    /// An expression not known to this crate.
+    Other(RawExpr),
+}
+
+/// An expression as its name and the raw nested attributes of its data.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RawExpr {
+    /// Name of the expression, such as `"quota"`.
+    pub name: String,
+    /// The nested attributes of the expression (`NFTA_EXPR_DATA`).
+    pub data: Vec<u8>,
+}
+
+/// The `payload` expression, loading `len` bytes at `offset` of a header into a register, or
+/// writing them from a register.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct PayloadExpr {
+    /// The header the offset is relative to, one of the `NFT_PAYLOAD_*_HEADER` constants.
+    pub base: u32,
+    /// Offset in bytes.
+    pub offset: u32,
+    /// Length in bytes.
+    pub len: u32,
+    /// Register to load into.
+    pub dreg: Option<u32>,
+    /// Register to write to the packet from.
+    pub sreg: Option<u32>,
+    /// Checksum to update when writing, one of the `NFT_PAYLOAD_CSUM_*` constants.
+    pub csum_type: Option<u32>,
+    /// Offset of the checksum from the base.
+    pub csum_offset: Option<u32>,
+    /// Checksum flags, such as [`NFT_PAYLOAD_L4CSUM_PSEUDOHDR`].
+    pub csum_flags: Option<u32>,
+}
+
+impl PayloadExpr {
+    /// Load `len` bytes at `offset` of `base` into `dreg`.
+    pub fn load(base: u32, offset: u32, len: u32, dreg: u32) -> Self {
+        PayloadExpr {
+            base,
+            offset,
+            len,
+            dreg: Some(dreg),
+            ..Default::default()
+        }
+    }
+}
+
+/// The `meta` expression, loading packet meta data into a register or setting it from one.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct MetaExpr {
+    /// What to load or set, one of the `NFT_META_*` constants.
+    pub key: u32,
+    /// Register to load into.
+    pub dreg: Option<u32>,
+    /// Register to set the meta data from.
+    pub sreg: Option<u32>,
+}
+
+impl MetaExpr {
+    /// Load `key` into `dreg`.
+    pub fn load(key: u32, dreg: u32) -> Self {
+        MetaExpr {
+            key,
+            dreg: Some(dreg),
+            sreg: None,
+        }
+    }
+}
+
+/// The `cmp` expression, ending the rule unless a register compares to a value.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct CmpExpr {
+    /// The register to compare.
+    pub sreg: u32,
+    /// The comparison, one of the `NFT_CMP_*` constants.
+    pub op: u32,
+    /// The value to compare with.
+    pub data: Vec<u8>,
+}
+
+impl CmpExpr {
+    /// Compare `sreg` with `data` using `op`.
+    pub fn new(op: u32, sreg: u32, data: impl Into<Vec<u8>>) -> Self {
+        CmpExpr {
+            sreg,
+            op,
+            data: data.into(),
+        }
+    }
+}
+
+/// The `immediate` expression, loading a value into a data register or a verdict into the
+/// verdict register.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ImmediateExpr {
+    /// The register to load into.
+    pub dreg: u32,
+    /// The value or verdict.
+    pub data: Data,
+}
+
+impl ImmediateExpr {
+    /// Load `verdict` into the verdict register.
+    pub fn verdict(verdict: Verdict) -> Self {
+        ImmediateExpr {
+            dreg: NFT_REG_VERDICT,
+            data: Data::Verdict(verdict),
+        }
+    }
+
+    /// Load `value` into `dreg`.
+    pub fn value(dreg: u32, value: impl Into<Vec<u8>>) -> Self {
+        ImmediateExpr {
+            dreg,
+            data: Data::Value(value.into()),
+        }
+    }
+}
+
+/// The `lookup` expression, ending the rule unless a register is in a set, or loading the data
+/// of the matching element of a map.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct LookupExpr {
+    /// Name of the set, in the table of the rule.
+    pub set: String,
+    /// Id of the set, when it is added in the same batch.
+    pub set_id: Option<u32>,
+    /// The register holding the key.
+    pub sreg: u32,
+    /// Register to load the data of a map element into.
+    pub dreg: Option<u32>,
+    /// Lookup flags, such as [`NFT_LOOKUP_F_INV`].
+    pub flags: u32,
+}
+
+impl LookupExpr {
+    /// Look up `sreg` in the set named `set`.
+    pub fn new(set: impl Into<String>, sreg: u32) -> Self {
+        LookupExpr {
+            set: set.into(),
+            sreg,
+            ..Default::default()
+        }
+    }
+}
+
+/// The `bitwise` expression, masking and xoring or shifting a register.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BitwiseExpr {
+    /// The register to read.
+    pub sreg: u32,
+    /// The register to store the result in.
+    pub dreg: u32,
+    /// Number of bytes to operate on.
+    pub len: u32,
+    /// The operation, one of the `NFT_BITWISE_*` constants.
+    pub op: u32,
+    /// The mask of [`NFT_BITWISE_BOOL`].
+    pub mask: Option<Vec<u8>>,
+    /// The value to xor with for [`NFT_BITWISE_BOOL`].
+    pub xor: Option<Vec<u8>>,
+    /// The number of bits to shift by, as a `u32` in host byte order.
+    pub data: Option<Vec<u8>>,
+}
+
+impl BitwiseExpr {
+    /// Compute `(reg & mask) ^ xor` in place. `mask` and `xor` must have the same length.
+    pub fn mask_xor(reg: u32, mask: impl Into<Vec<u8>>, xor: impl Into<Vec<u8>>) -> Self {
+        let mask = mask.into();
+        BitwiseExpr {
+            sreg: reg,
+            dreg: reg,
+            len: mask.len() as u32,
+            op: NFT_BITWISE_BOOL,
+            mask: Some(mask),
+            xor: Some(xor.into()),
+            data: None,
+        }
+    }
+}
+
+/// The `ct` expression, loading connection tracking data into a register or setting it from
+/// one.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct CtExpr {
+    /// What to load or set, one of the `NFT_CT_*` constants.
+    pub key: u32,
+    /// Register to load into.
+    pub dreg: Option<u32>,
+    /// Register to set the data from.
+    pub sreg: Option<u32>,
+    /// Direction for keys of a direction, such as [`NFT_CT_SRC`].
+    pub direction: Option<u8>,
+}
+
+impl CtExpr {
+    /// Load `key` into `dreg`.
+    pub fn load(key: u32, dreg: u32) -> Self {
+        CtExpr {
+            key,
+            dreg: Some(dreg),
+            ..Default::default()
+        }
+    }
+}
+
+/// The `counter` expression. The values are the initial ones when adding a rule, and the
+/// current ones in dumps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CounterExpr {
+    /// Number of bytes.
+    pub bytes: u64,
+    /// Number of packets.
+    pub packets: u64,
+}
+
+/// The `log` expression, logging to the kernel log or, with a group, to `nfnetlink_log`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct LogExpr {
+    /// Prefix of the log messages.
+    pub prefix: Option<String>,
+    /// The `nfnetlink_log` group to send packets to.
+    pub group: Option<u16>,
+    /// Number of bytes of the packet to send to the group.
+    pub snaplen: Option<u32>,
+    /// Number of packets to queue before sending them to the group.
+    pub qthreshold: Option<u16>,
+    /// Syslog level of kernel log messages.
+    pub level: Option<u32>,
+    /// Flags selecting extra information to log.
+    pub flags: Option<u32>,
+}
+
+/// The `nat` expression, rewriting the source or destination of a connection to addresses and
+/// ports loaded into registers.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NatExpr {
+    /// [`NFT_NAT_SNAT`] or [`NFT_NAT_DNAT`].
+    pub nat_type: u32,
+    /// Family of the addresses.
+    pub family: Family,
+    /// Register holding the first address of the range.
+    pub reg_addr_min: Option<u32>,
+    /// Register holding the last address of the range.
+    pub reg_addr_max: Option<u32>,
+    /// Register holding the first port of the range.
+    pub reg_proto_min: Option<u32>,
+    /// Register holding the last port of the range.
+    pub reg_proto_max: Option<u32>,
+    /// A combination of the `NF_NAT_RANGE_*` flags.
+    pub flags: Option<u32>,
+}
+
+impl NatExpr {
+    /// Describe a NAT of `nat_type` for `family` without addresses or ports.
+    pub fn new(nat_type: u32, family: Family) -> Self {
+        NatExpr {
+            nat_type,
+            family,
+            reg_addr_min: None,
+            reg_addr_max: None,
+            reg_proto_min: None,
+            reg_proto_max: None,
+            flags: None,
+        }
+    }
+}
+
+/// The `masq` expression, rewriting the source of a connection to the address of the output
+/// interface.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct MasqExpr {
+    /// A combination of the `NF_NAT_RANGE_*` flags.
+    pub flags: Option<u32>,
+    /// Register holding the first port of the range.
+    pub reg_proto_min: Option<u32>,
+    /// Register holding the last port of the range.
+    pub reg_proto_max: Option<u32>,
+}
+
+/// The `reject` expression, dropping the packet and sending an error back.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct RejectExpr {
+    /// The kind of error, one of the `NFT_REJECT_*` types.
+    pub reject_type: u32,
+    /// ICMP code of unreachable messages. One of the `NFT_REJECT_ICMPX_*` codes for
+    /// [`NFT_REJECT_ICMPX_UNREACH`].
+    pub icmp_code: u8,
+}
+
+/// The `limit` expression, ending the rule once the rate is exceeded, with a token bucket.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct LimitExpr {
+    /// Packets or bytes per unit.
+    pub rate: u64,
+    /// The unit in seconds, such as 60 for a rate per minute.
+    pub unit: u64,
+    /// Packets or bytes allowed over the rate in bursts.
+    pub burst: u32,
+    /// [`NFT_LIMIT_PKTS`] or [`NFT_LIMIT_PKT_BYTES`].
+    pub limit_type: u32,
+    /// Limit flags, such as [`NFT_LIMIT_F_INV`].
+    pub flags: u32,
+}
+
+/// The `fib` expression, loading the result of a routing table lookup into a register.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct FibExpr {
+    /// What to look up, a combination of the `NFTA_FIB_F_*` flags.
+    pub flags: u32,
+    /// What to load, one of the `NFT_FIB_RESULT_*` constants.
+    pub result: u32,
+    /// Register to load into.
+    pub dreg: u32,
+}
+
+/// The `socket` expression, loading data of the socket the packet belongs to into a register.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct SocketExpr {
+    /// What to load, one of the `NFT_SOCKET_*` constants.
+    pub key: u32,
+    /// Register to load into.
+    pub dreg: u32,
+    /// Cgroup ancestor level for [`NFT_SOCKET_CGROUPV2`].
+    pub level: Option<u32>,
+}
+
+impl RawExpr {
+    /// Parse a `NFTA_LIST_ELEM` of `NFTA_RULE_EXPRESSIONS`.
+    fn parse(attr: Attr<'_>) -> io::Result<RawExpr> {
+        let mut expr = RawExpr {
+            name: String::new(),
+            data: Vec::new(),
+        };
+        for attr in attr.nested() {
+            let attr = attr?;
+            match attr.attr_type() {
+                sys::NFTA_EXPR_NAME => expr.name = attr.get_str()?.to_owned(),
+                sys::NFTA_EXPR_DATA => expr.data = attr.payload().to_vec(),
+                _ => (),
+            }
+        }
+        Ok(expr)
+    }
+
+    /// Add the expression as a `NFTA_LIST_ELEM`.
+    fn put(&self, msg: &mut NlMsgBuilder<'_>) -> io::Result<()> {
+        msg.put_nested(sys::NFTA_LIST_ELEM, |msg| {
+            msg.put_str(sys::NFTA_EXPR_NAME, &self.name)?;
+            msg.put(sys::NFTA_EXPR_DATA | NESTED, &self.data)
+        })
+    }
+}
+
+impl Expr {
+    /// Returns the name of the expression, such as `"payload"`.
+    pub fn name(&self) -> &str {
+        match self {
+            Expr::Payload(_) => "payload",
+            Expr::Meta(_) => "meta",
+            Expr::Cmp(_) => "cmp",
+            Expr::Immediate(_) => "immediate",
+            Expr::Lookup(_) => "lookup",
+            Expr::Bitwise(_) => "bitwise",
+            Expr::Ct(_) => "ct",
+            Expr::Counter(_) => "counter",
+            Expr::Log(_) => "log",
+            Expr::Nat(_) => "nat",
+            Expr::Masq(_) => "masq",
+            Expr::Reject(_) => "reject",
+            Expr::Limit(_) => "limit",
+            Expr::Fib(_) => "fib",
+            Expr::Socket(_) => "socket",
+            Expr::Other(raw) => &raw.name,
+        }
+    }
+
+    /// Parse a `NFTA_LIST_ELEM` of `NFTA_RULE_EXPRESSIONS`.
+    pub(super) fn parse(attr: Attr<'_>) -> io::Result<Expr> {
+        Expr::from_raw(RawExpr::parse(attr)?)
+    }
+
+    /// Parse the data of an expression known to this crate, or keep it raw otherwise.
+    pub(super) fn from_raw(raw: RawExpr) -> io::Result<Expr> {
+        let attrs = Attrs::new(&raw.data);
+        Ok(match raw.name.as_str() {
+            "payload" => {
+                let mut expr = PayloadExpr::default();
+                for attr in attrs {
+                    let attr = attr?;
+                    match attr.attr_type() {
+                        sys::NFTA_PAYLOAD_DREG => expr.dreg = Some(attr.get_be32()?),
+                        sys::NFTA_PAYLOAD_BASE => expr.base = attr.get_be32()?,
+                        sys::NFTA_PAYLOAD_OFFSET => expr.offset = attr.get_be32()?,
+                        sys::NFTA_PAYLOAD_LEN => expr.len = attr.get_be32()?,
+                        sys::NFTA_PAYLOAD_SREG => expr.sreg = Some(attr.get_be32()?),
+                        sys::NFTA_PAYLOAD_CSUM_TYPE => expr.csum_type = Some(attr.get_be32()?),
+                        sys::NFTA_PAYLOAD_CSUM_OFFSET => expr.csum_offset = Some(attr.get_be32()?),
+                        sys::NFTA_PAYLOAD_CSUM_FLAGS => expr.csum_flags = Some(attr.get_be32()?),
+                        _ => (),
+                    }
+                }
+                Expr::Payload(expr)
+            }
+            "meta" => {
+                let mut expr = MetaExpr::default();
+                for attr in attrs {
+                    let attr = attr?;
+                    match attr.attr_type() {
+                        sys::NFTA_META_DREG => expr.dreg = Some(attr.get_be32()?),
+                        sys::NFTA_META_KEY => expr.key = attr.get_be32()?,
+                        sys::NFTA_META_SREG => expr.sreg = Some(attr.get_be32()?),
+                        _ => (),
+                    }
+                }
+                Expr::Meta(expr)
+            }
+            "cmp" => {
+                let mut expr = CmpExpr::default();
+                for attr in attrs {
+                    let attr = attr?;
+                    match attr.attr_type() {
+                        sys::NFTA_CMP_SREG => expr.sreg = attr.get_be32()?,
+                        sys::NFTA_CMP_OP => expr.op = attr.get_be32()?,
+                        sys::NFTA_CMP_DATA => expr.data = parse_value(attr)?,
+                        _ => (),
+                    }
+                }
+                Expr::Cmp(expr)
+            }
+            "immediate" => {
+                let mut dreg = NFT_REG_VERDICT;
+                let mut data = None;
+                for attr in attrs {
+                    let attr = attr?;
+                    match attr.attr_type() {
+                        sys::NFTA_IMMEDIATE_DREG => dreg = attr.get_be32()?,
+                        sys::NFTA_IMMEDIATE_DATA => data = Some(Data::parse(attr)?),
+                        _ => (),
+                    }
+                }
+                let data = data.ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "Immediate expression without data",
+                    )
+                })?;
+                Expr::Immediate(ImmediateExpr { dreg, data })
+            }
+            "lookup" => {
+                let mut expr = LookupExpr::default();
+                for attr in attrs {
+                    let attr = attr?;
+                    match attr.attr_type() {
+                        sys::NFTA_LOOKUP_SET => expr.set = attr.get_str()?.to_owned(),
+                        sys::NFTA_LOOKUP_SREG => expr.sreg = attr.get_be32()?,
+                        sys::NFTA_LOOKUP_DREG => expr.dreg = Some(attr.get_be32()?),
+                        sys::NFTA_LOOKUP_SET_ID => expr.set_id = Some(attr.get_be32()?),
+                        sys::NFTA_LOOKUP_FLAGS => expr.flags = attr.get_be32()?,
+                        _ => (),
+                    }
+                }
+                Expr::Lookup(expr)
+            }
+            "bitwise" => {
+                let mut expr = BitwiseExpr::default();
+                for attr in attrs {
+                    let attr = attr?;
+                    match attr.attr_type() {
+                        sys::NFTA_BITWISE_SREG => expr.sreg = attr.get_be32()?,
+                        sys::NFTA_BITWISE_DREG => expr.dreg = attr.get_be32()?,
+                        sys::NFTA_BITWISE_LEN => expr.len = attr.get_be32()?,
+                        sys::NFTA_BITWISE_OP => expr.op = attr.get_be32()?,
+                        sys::NFTA_BITWISE_MASK => expr.mask = Some(parse_value(attr)?),
+                        sys::NFTA_BITWISE_XOR => expr.xor = Some(parse_value(attr)?),
+                        sys::NFTA_BITWISE_DATA => expr.data = Some(parse_value(attr)?),
+                        _ => (),
+                    }
+                }
+                Expr::Bitwise(expr)
+            }
+            "ct" => {
+                let mut expr = CtExpr::default();
+                for attr in attrs {
+                    let attr = attr?;
+                    match attr.attr_type() {
+                        sys::NFTA_CT_DREG => expr.dreg = Some(attr.get_be32()?),
+                        sys::NFTA_CT_KEY => expr.key = attr.get_be32()?,
+                        sys::NFTA_CT_DIRECTION => expr.direction = Some(attr.get_u8()?),
+                        sys::NFTA_CT_SREG => expr.sreg = Some(attr.get_be32()?),
+                        _ => (),
+                    }
+                }
+                Expr::Ct(expr)
+            }
+            "counter" => {
+                let mut expr = CounterExpr::default();
+                for attr in attrs {
+                    let attr = attr?;
+                    match attr.attr_type() {
+                        sys::NFTA_COUNTER_BYTES => expr.bytes = attr.get_be64()?,
+                        sys::NFTA_COUNTER_PACKETS => expr.packets = attr.get_be64()?,
+                        _ => (),
+                    }
+                }
+                Expr::Counter(expr)
+            }
+            "log" => {
+                let mut expr = LogExpr::default();
+                for attr in attrs {
+                    let attr = attr?;
+                    match attr.attr_type() {
+                        sys::NFTA_LOG_GROUP => expr.group = Some(attr.get_be16()?),
+                        sys::NFTA_LOG_PREFIX => expr.prefix = Some(attr.get_str()?.to_owned()),
+                        sys::NFTA_LOG_SNAPLEN => expr.snaplen = Some(attr.get_be32()?),
+                        sys::NFTA_LOG_QTHRESHOLD => expr.qthreshold = Some(attr.get_be16()?),
+                        sys::NFTA_LOG_LEVEL => expr.level = Some(attr.get_be32()?),
+                        sys::NFTA_LOG_FLAGS => expr.flags = Some(attr.get_be32()?),
+                        _ => (),
+                    }
+                }
+                Expr::Log(expr)
+            }
+            "nat" => {
+                let mut expr = NatExpr::new(NFT_NAT_SNAT, Family::Other(0));
+                for attr in attrs {
+                    let attr = attr?;
+                    match attr.attr_type() {
+                        sys::NFTA_NAT_TYPE => expr.nat_type = attr.get_be32()?,
+                        sys::NFTA_NAT_FAMILY => expr.family = Family::from(attr.get_be32()? as u8),
+                        sys::NFTA_NAT_REG_ADDR_MIN => expr.reg_addr_min = Some(attr.get_be32()?),
+                        sys::NFTA_NAT_REG_ADDR_MAX => expr.reg_addr_max = Some(attr.get_be32()?),
+                        sys::NFTA_NAT_REG_PROTO_MIN => expr.reg_proto_min = Some(attr.get_be32()?),
+                        sys::NFTA_NAT_REG_PROTO_MAX => expr.reg_proto_max = Some(attr.get_be32()?),
+                        sys::NFTA_NAT_FLAGS => expr.flags = Some(attr.get_be32()?),
+                        _ => (),
+                    }
+                }
+                Expr::Nat(expr)
+            }
+            "masq" => {
+                let mut expr = MasqExpr::default();
+                for attr in attrs {
+                    let attr = attr?;
+                    match attr.attr_type() {
+                        sys::NFTA_MASQ_FLAGS => expr.flags = Some(attr.get_be32()?),
+                        sys::NFTA_MASQ_REG_PROTO_MIN => expr.reg_proto_min = Some(attr.get_be32()?),
+                        sys::NFTA_MASQ_REG_PROTO_MAX => expr.reg_proto_max = Some(attr.get_be32()?),
+                        _ => (),
+                    }
+                }
+                Expr::Masq(expr)
+            }
+            "reject" => {
+                let mut expr = RejectExpr::default();
+                for attr in attrs {
+                    let attr = attr?;
+                    match attr.attr_type() {
+                        sys::NFTA_REJECT_TYPE => expr.reject_type = attr.get_be32()?,
+                        sys::NFTA_REJECT_ICMP_CODE => expr.icmp_code = attr.get_u8()?,
+                        _ => (),
+                    }
+                }
+                Expr::Reject(expr)
+            }
+            "limit" => {
+                let mut expr = LimitExpr::default();
+                for attr in attrs {
+                    let attr = attr?;
+                    match attr.attr_type() {
+                        sys::NFTA_LIMIT_RATE => expr.rate = attr.get_be64()?,
+                        sys::NFTA_LIMIT_UNIT => expr.unit = attr.get_be64()?,
+                        sys::NFTA_LIMIT_BURST => expr.burst = attr.get_be32()?,
+                        sys::NFTA_LIMIT_TYPE => expr.limit_type = attr.get_be32()?,
+                        sys::NFTA_LIMIT_FLAGS => expr.flags = attr.get_be32()?,
+                        _ => (),
+                    }
+                }
+                Expr::Limit(expr)
+            }
+            "fib" => {
+                let mut expr = FibExpr::default();
+                for attr in attrs {
+                    let attr = attr?;
+                    match attr.attr_type() {
+                        sys::NFTA_FIB_DREG => expr.dreg = attr.get_be32()?,
+                        sys::NFTA_FIB_RESULT => expr.result = attr.get_be32()?,
+                        sys::NFTA_FIB_FLAGS => expr.flags = attr.get_be32()?,
+                        _ => (),
+                    }
+                }
+                Expr::Fib(expr)
+            }
+            "socket" => {
+                let mut expr = SocketExpr::default();
+                for attr in attrs {
+                    let attr = attr?;
+                    match attr.attr_type() {
+                        sys::NFTA_SOCKET_KEY => expr.key = attr.get_be32()?,
+                        sys::NFTA_SOCKET_DREG => expr.dreg = attr.get_be32()?,
+                        sys::NFTA_SOCKET_LEVEL => expr.level = Some(attr.get_be32()?),
+                        _ => (),
+                    }
+                }
+                Expr::Socket(expr)
+            }
+            _ => Expr::Other(raw),
+        })
+    }
+
+    /// Add the expression as a `NFTA_LIST_ELEM`. Attributes are added in the order libnftnl
+    /// adds them, so messages are identical to the ones `nft` sends.
+    pub(super) fn put(&self, msg: &mut NlMsgBuilder<'_>) -> io::Result<()> {
+        if let Expr::Other(raw) = self {
+            return raw.put(msg);
+        }
+        msg.put_nested(sys::NFTA_LIST_ELEM, |msg| {
+            msg.put_str(sys::NFTA_EXPR_NAME, self.name())?;
+            msg.put_nested(sys::NFTA_EXPR_DATA, |msg| self.put_data(msg))
+        })
+    }
+
+    pub(super) fn put_data(&self, msg: &mut NlMsgBuilder<'_>) -> io::Result<()> {
+        match self {
+            Expr::Payload(expr) => {
+                put_opt_be32(msg, sys::NFTA_PAYLOAD_SREG, expr.sreg)?;
+                put_opt_be32(msg, sys::NFTA_PAYLOAD_DREG, expr.dreg)?;
+                put_be32(msg, sys::NFTA_PAYLOAD_BASE, expr.base)?;
+                put_be32(msg, sys::NFTA_PAYLOAD_OFFSET, expr.offset)?;
+                put_be32(msg, sys::NFTA_PAYLOAD_LEN, expr.len)?;
+                put_opt_be32(msg, sys::NFTA_PAYLOAD_CSUM_TYPE, expr.csum_type)?;
+                put_opt_be32(msg, sys::NFTA_PAYLOAD_CSUM_OFFSET, expr.csum_offset)?;
+                put_opt_be32(msg, sys::NFTA_PAYLOAD_CSUM_FLAGS, expr.csum_flags)
+            }
+            Expr::Meta(expr) => {
+                put_be32(msg, sys::NFTA_META_KEY, expr.key)?;
+                put_opt_be32(msg, sys::NFTA_META_DREG, expr.dreg)?;
+                put_opt_be32(msg, sys::NFTA_META_SREG, expr.sreg)
+            }
+            Expr::Cmp(expr) => {
+                put_be32(msg, sys::NFTA_CMP_SREG, expr.sreg)?;
+                put_be32(msg, sys::NFTA_CMP_OP, expr.op)?;
+                put_value(msg, sys::NFTA_CMP_DATA, &expr.data)
+            }
+            Expr::Immediate(expr) => {
+                put_be32(msg, sys::NFTA_IMMEDIATE_DREG, expr.dreg)?;
+                expr.data.put(msg, sys::NFTA_IMMEDIATE_DATA)
+            }
+            Expr::Lookup(expr) => {
+                put_be32(msg, sys::NFTA_LOOKUP_SREG, expr.sreg)?;
+                put_opt_be32(msg, sys::NFTA_LOOKUP_DREG, expr.dreg)?;
+                msg.put_str(sys::NFTA_LOOKUP_SET, &expr.set)?;
+                put_opt_be32(msg, sys::NFTA_LOOKUP_SET_ID, expr.set_id)?;
+                if expr.flags != 0 {
+                    put_be32(msg, sys::NFTA_LOOKUP_FLAGS, expr.flags)?;
+                }
+                Ok(())
+            }
+            Expr::Bitwise(expr) => {
+                put_be32(msg, sys::NFTA_BITWISE_SREG, expr.sreg)?;
+                put_be32(msg, sys::NFTA_BITWISE_DREG, expr.dreg)?;
+                put_be32(msg, sys::NFTA_BITWISE_OP, expr.op)?;
+                put_be32(msg, sys::NFTA_BITWISE_LEN, expr.len)?;
+                for (attr_type, value) in [
+                    (sys::NFTA_BITWISE_MASK, &expr.mask),
+                    (sys::NFTA_BITWISE_XOR, &expr.xor),
+                    (sys::NFTA_BITWISE_DATA, &expr.data),
+                ] {
+                    if let Some(value) = value {
+                        put_value(msg, attr_type, value)?;
+                    }
+                }
+                Ok(())
+            }
+            Expr::Ct(expr) => {
+                put_be32(msg, sys::NFTA_CT_KEY, expr.key)?;
+                put_opt_be32(msg, sys::NFTA_CT_DREG, expr.dreg)?;
+                if let Some(direction) = expr.direction {
+                    msg.put_u8(sys::NFTA_CT_DIRECTION, direction)?;
+                }
+                put_opt_be32(msg, sys::NFTA_CT_SREG, expr.sreg)
+            }
+            Expr::Counter(expr) => {
+                // nft leaves out counters starting at zero.
+                if expr.bytes != 0 {
+                    put_be64(msg, sys::NFTA_COUNTER_BYTES, expr.bytes)?;
+                }
+                if expr.packets != 0 {
+                    put_be64(msg, sys::NFTA_COUNTER_PACKETS, expr.packets)?;
+                }
+                Ok(())
+            }
+            Expr::Log(expr) => {
+                if let Some(prefix) = &expr.prefix {
+                    msg.put_str(sys::NFTA_LOG_PREFIX, prefix)?;
+                }
+                if let Some(group) = expr.group {
+                    msg.put(sys::NFTA_LOG_GROUP, &group.to_be_bytes())?;
+                }
+                put_opt_be32(msg, sys::NFTA_LOG_SNAPLEN, expr.snaplen)?;
+                if let Some(qthreshold) = expr.qthreshold {
+                    msg.put(sys::NFTA_LOG_QTHRESHOLD, &qthreshold.to_be_bytes())?;
+                }
+                put_opt_be32(msg, sys::NFTA_LOG_LEVEL, expr.level)?;
+                put_opt_be32(msg, sys::NFTA_LOG_FLAGS, expr.flags)
+            }
+            Expr::Nat(expr) => {
+                put_be32(msg, sys::NFTA_NAT_TYPE, expr.nat_type)?;
+                put_be32(msg, sys::NFTA_NAT_FAMILY, u8::from(expr.family).into())?;
+                put_opt_be32(msg, sys::NFTA_NAT_REG_ADDR_MIN, expr.reg_addr_min)?;
+                put_opt_be32(msg, sys::NFTA_NAT_REG_ADDR_MAX, expr.reg_addr_max)?;
+                put_opt_be32(msg, sys::NFTA_NAT_REG_PROTO_MIN, expr.reg_proto_min)?;
+                put_opt_be32(msg, sys::NFTA_NAT_REG_PROTO_MAX, expr.reg_proto_max)?;
+                put_opt_be32(msg, sys::NFTA_NAT_FLAGS, expr.flags)
+            }
+            Expr::Masq(expr) => {
+                put_opt_be32(msg, sys::NFTA_MASQ_FLAGS, expr.flags)?;
+                put_opt_be32(msg, sys::NFTA_MASQ_REG_PROTO_MIN, expr.reg_proto_min)?;
+                put_opt_be32(msg, sys::NFTA_MASQ_REG_PROTO_MAX, expr.reg_proto_max)
+            }
+            Expr::Reject(expr) => {
+                put_be32(msg, sys::NFTA_REJECT_TYPE, expr.reject_type)?;
+                msg.put_u8(sys::NFTA_REJECT_ICMP_CODE, expr.icmp_code)
+            }
+            Expr::Limit(expr) => {
+                put_be64(msg, sys::NFTA_LIMIT_RATE, expr.rate)?;
+                put_be64(msg, sys::NFTA_LIMIT_UNIT, expr.unit)?;
+                put_be32(msg, sys::NFTA_LIMIT_BURST, expr.burst)?;
+                put_be32(msg, sys::NFTA_LIMIT_TYPE, expr.limit_type)?;
+                put_be32(msg, sys::NFTA_LIMIT_FLAGS, expr.flags)
+            }
+            Expr::Fib(expr) => {
+                put_be32(msg, sys::NFTA_FIB_FLAGS, expr.flags)?;
+                put_be32(msg, sys::NFTA_FIB_RESULT, expr.result)?;
+                put_be32(msg, sys::NFTA_FIB_DREG, expr.dreg)
+            }
+            Expr::Socket(expr) => {
+                put_be32(msg, sys::NFTA_SOCKET_DREG, expr.dreg)?;
+                put_be32(msg, sys::NFTA_SOCKET_KEY, expr.key)?;
+                put_opt_be32(msg, sys::NFTA_SOCKET_LEVEL, expr.level)
+            }
+            // Added as is by `put`.
+            Expr::Other(_) => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[ {} ", self.name())?;
+        match self {
+            Expr::Payload(expr) => match expr.sreg {
+                Some(sreg) => write!(
+                    f,
+                    "write reg {sreg} => {}b @ {} header + {} csum_type {} csum_off {} \
+                     csum_flags 0x{:x} ",
+                    expr.len,
+                    payload_base_name(expr.base),
+                    expr.offset,
+                    expr.csum_type.unwrap_or(0),
+                    expr.csum_offset.unwrap_or(0),
+                    expr.csum_flags.unwrap_or(0),
+                )?,
+                None => write!(
+                    f,
+                    "load {}b @ {} header + {} => reg {} ",
+                    expr.len,
+                    payload_base_name(expr.base),
+                    expr.offset,
+                    expr.dreg.unwrap_or(0),
+                )?,
+            },
+            Expr::Meta(expr) => match expr.sreg {
+                Some(sreg) => write!(f, "set {} with reg {sreg} ", meta_key_name(expr.key))?,
+                None => write!(
+                    f,
+                    "load {} => reg {} ",
+                    meta_key_name(expr.key),
+                    expr.dreg.unwrap_or(0)
+                )?,
+            },
+            Expr::Cmp(expr) => {
+                write!(f, "{} reg {} ", cmp_op_name(expr.op), expr.sreg)?;
+                fmt_value(f, &expr.data)?;
+            }
+            Expr::Immediate(expr) => {
+                write!(f, "reg {} ", expr.dreg)?;
+                match &expr.data {
+                    Data::Value(value) => fmt_value(f, value)?,
+                    Data::Verdict(verdict) => {
+                        write!(f, "{} ", verdict_name(verdict.code()))?;
+                        if let Verdict::Jump(chain) | Verdict::Goto(chain) = verdict {
+                            write!(f, "-> {chain} ")?;
+                        }
+                    }
+                }
+            }
+            Expr::Lookup(expr) => {
+                write!(f, "reg {} set {} ", expr.sreg, expr.set)?;
+                if let Some(dreg) = expr.dreg {
+                    write!(f, "dreg {dreg} ")?;
+                }
+                if expr.flags & NFT_LOOKUP_F_INV != 0 {
+                    write!(f, "0x{:x} ", expr.flags)?;
+                }
+            }
+            Expr::Bitwise(expr) => {
+                write!(f, "reg {} = ( reg {} ", expr.dreg, expr.sreg)?;
+                match expr.op {
+                    NFT_BITWISE_LSHIFT | NFT_BITWISE_RSHIFT => {
+                        let op = if expr.op == NFT_BITWISE_LSHIFT {
+                            "<<"
+                        } else {
+                            ">>"
+                        };
+                        write!(f, "{op} ")?;
+                        fmt_value(f, expr.data.as_deref().unwrap_or_default())?;
+                        f.write_str(") ")?;
+                    }
+                    _ => {
+                        f.write_str("& ")?;
+                        fmt_value(f, expr.mask.as_deref().unwrap_or_default())?;
+                        f.write_str(") ^ ")?;
+                        fmt_value(f, expr.xor.as_deref().unwrap_or_default())?;
+                    }
+                }
+            }
+            Expr::Ct(expr) => {
+                if let Some(sreg) = expr.sreg {
+                    write!(f, "set {} with reg {sreg} ", ct_key_name(expr.key))?;
+                }
+                if let Some(dreg) = expr.dreg {
+                    write!(f, "load {} => reg {dreg} ", ct_key_name(expr.key))?;
+                }
+                match expr.direction {
+                    Some(IP_CT_DIR_ORIGINAL) => f.write_str(", dir original ")?,
+                    Some(IP_CT_DIR_REPLY) => f.write_str(", dir reply ")?,
+                    Some(_) => f.write_str(", dir unknown ")?,
+                    None => (),
+                }
+            }
+            Expr::Counter(expr) => write!(f, "pkts {} bytes {} ", expr.packets, expr.bytes)?,
+            Expr::Log(expr) => {
+                if let Some(prefix) = &expr.prefix {
+                    write!(f, "prefix {prefix} ")?;
+                }
+                if let Some(group) = expr.group {
+                    write!(
+                        f,
+                        "group {group} snaplen {} qthreshold {} ",
+                        expr.snaplen.unwrap_or(0),
+                        expr.qthreshold.unwrap_or(0)
+                    )?;
+                } else {
+                    if let Some(level) = expr.level {
+                        write!(f, "level {level} ")?;
+                    }
+                    if let Some(flags) = expr.flags {
+                        write!(f, "flags {flags} ")?;
+                    }
+                }
+            }
+            Expr::Nat(expr) => {
+                let nat_type = match expr.nat_type {
+                    NFT_NAT_SNAT => "snat",
+                    NFT_NAT_DNAT => "dnat",
+                    _ => "unknown",
+                };
+                write!(f, "{nat_type} {} ", expr.family)?;
+                fmt_reg(f, "addr_min", expr.reg_addr_min)?;
+                fmt_reg(f, "addr_max", expr.reg_addr_max)?;
+                fmt_reg(f, "proto_min", expr.reg_proto_min)?;
+                fmt_reg(f, "proto_max", expr.reg_proto_max)?;
+                if let Some(flags) = expr.flags {
+                    write!(f, "flags 0x{flags:x} ")?;
+                }
+            }
+            Expr::Masq(expr) => {
+                if let Some(flags) = expr.flags {
+                    write!(f, "flags 0x{flags:x} ")?;
+                }
+                fmt_reg(f, "proto_min", expr.reg_proto_min)?;
+                fmt_reg(f, "proto_max", expr.reg_proto_max)?;
+            }
+            Expr::Reject(expr) => write!(f, "type {} code {} ", expr.reject_type, expr.icmp_code)?,
+            Expr::Limit(expr) => write!(
+                f,
+                "rate {}/{} burst {} type {} flags 0x{:x} ",
+                expr.rate,
+                time_unit_name(expr.unit),
+                expr.burst,
+                if expr.limit_type == NFT_LIMIT_PKT_BYTES {
+                    "bytes"
+                } else {
+                    "packets"
+                },
+                expr.flags
+            )?,
+            Expr::Fib(expr) => {
+                let names = [
+                    (NFTA_FIB_F_SADDR, "saddr"),
+                    (NFTA_FIB_F_DADDR, "daddr"),
+                    (NFTA_FIB_F_MARK, "mark"),
+                    (NFTA_FIB_F_IIF, "iif"),
+                    (NFTA_FIB_F_OIF, "oif"),
+                ];
+                let mut first = true;
+                for (flag, name) in names {
+                    if expr.flags & flag != 0 {
+                        if !first {
+                            f.write_str(" . ")?;
+                        }
+                        f.write_str(name)?;
+                        first = false;
+                    }
+                }
+                let result = match expr.result {
+                    NFT_FIB_RESULT_UNSPEC => "unspec",
+                    NFT_FIB_RESULT_OIF => "oif",
+                    NFT_FIB_RESULT_OIFNAME => "oifname",
+                    NFT_FIB_RESULT_ADDRTYPE => "type",
+                    _ => "unknown",
+                };
+                let present = if expr.flags & NFTA_FIB_F_PRESENT != 0 {
+                    " present"
+                } else {
+                    ""
+                };
+                write!(f, " {result}{present} => reg {} ", expr.dreg)?;
+            }
+            Expr::Socket(expr) => {
+                let key = match expr.key {
+                    NFT_SOCKET_TRANSPARENT => "transparent",
+                    NFT_SOCKET_MARK => "mark",
+                    NFT_SOCKET_WILDCARD => "wildcard",
+                    NFT_SOCKET_CGROUPV2 => "cgroupv2",
+                    _ => "unknown",
+                };
+                write!(f, "load {key} => reg {} ", expr.dreg)?;
+                if let Some(level) = expr.level {
+                    write!(f, "level {level} ")?;
+                }
+            }
+            Expr::Other(_) => (),
+        }
+        f.write_str("]")
+    }
+}
+
+/// Parse the nested `NFTA_DATA_VALUE` of `attr`.
+fn parse_value(attr: Attr<'_>) -> io::Result<Vec<u8>> {
+    match Data::parse(attr)? {
+        Data::Value(value) => Ok(value),
+        Data::Verdict(_) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Expected a value, got a verdict",
+        )),
+    }
+}
+
+fn put_opt_be32(msg: &mut NlMsgBuilder<'_>, attr_type: u16, value: Option<u32>) -> io::Result<()> {
+    match value {
+        Some(value) => put_be32(msg, attr_type, value),
+        None => Ok(()),
+    }
+}
+
+/// Format a value as 32 bit words in host byte order, the way libnftnl prints register data.
+fn fmt_value(f: &mut fmt::Formatter<'_>, value: &[u8]) -> fmt::Result {
+    for chunk in value.chunks(4) {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        write!(f, "0x{:08x} ", u32::from_ne_bytes(word))?;
+    }
+    Ok(())
+}
+
+fn fmt_reg(f: &mut fmt::Formatter<'_>, name: &str, reg: Option<u32>) -> fmt::Result {
+    match reg {
+        Some(reg) => write!(f, "{name} reg {reg} "),
+        None => Ok(()),
+    }
+}
+
+/// Returns the name libnftnl uses for a payload base, such as `"network"`.
+pub(super) fn payload_base_name(base: u32) -> &'static str {
+    match base {
+        NFT_PAYLOAD_LL_HEADER => "link",
+        NFT_PAYLOAD_NETWORK_HEADER => "network",
+        NFT_PAYLOAD_TRANSPORT_HEADER => "transport",
+        NFT_PAYLOAD_INNER_HEADER => "inner",
+        _ => "unknown",
+    }
+}
+
+/// Returns the name libnftnl uses for a meta key, such as `"l4proto"`.
+pub(super) fn meta_key_name(key: u32) -> &'static str {
+    const NAMES: [&str; 35] = [
+        "len",
+        "protocol",
+        "priority",
+        "mark",
+        "iif",
+        "oif",
+        "iifname",
+        "oifname",
+        "iiftype",
+        "oiftype",
+        "skuid",
+        "skgid",
+        "nftrace",
+        "rtclassid",
+        "secmark",
+        "nfproto",
+        "l4proto",
+        "bri_iifname",
+        "bri_oifname",
+        "pkttype",
+        "cpu",
+        "iifgroup",
+        "oifgroup",
+        "cgroup",
+        "prandom",
+        "secpath",
+        "iifkind",
+        "oifkind",
+        "bri_iifpvid",
+        "bri_iifvproto",
+        "time",
+        "day",
+        "hour",
+        "sdif",
+        "sdifname",
+    ];
+    NAMES.get(key as usize).copied().unwrap_or("unknown")
+}
+
+/// Returns the name libnftnl uses for a conntrack key, such as `"state"`.
+pub(super) fn ct_key_name(key: u32) -> &'static str {
+    const NAMES: [&str; 24] = [
+        "state",
+        "direction",
+        "status",
+        "mark",
+        "secmark",
+        "expiration",
+        "helper",
+        "l3protocol",
+        "src",
+        "dst",
+        "protocol",
+        "proto_src",
+        "proto_dst",
+        "label",
+        "packets",
+        "bytes",
+        "avgpkt",
+        "zone",
+        "event",
+        "src_ip",
+        "dst_ip",
+        "src_ip6",
+        "dst_ip6",
+        "ct_id",
+    ];
+    NAMES.get(key as usize).copied().unwrap_or("unknown")
+}
+
+fn cmp_op_name(op: u32) -> &'static str {
+    match op {
+        NFT_CMP_EQ => "eq",
+        NFT_CMP_NEQ => "neq",
+        NFT_CMP_LT => "lt",
+        NFT_CMP_LTE => "lte",
+        NFT_CMP_GT => "gt",
+        NFT_CMP_GTE => "gte",
+        _ => "unknown",
+    }
+}
+
+fn verdict_name(code: i32) -> &'static str {
+    match code {
+        sys::NF_ACCEPT => "accept",
+        sys::NF_DROP => "drop",
+        sys::NF_QUEUE => "queue",
+        sys::NFT_CONTINUE => "continue",
+        sys::NFT_BREAK => "break",
+        sys::NFT_JUMP => "jump",
+        sys::NFT_GOTO => "goto",
+        sys::NFT_RETURN => "return",
+        _ => "unknown",
+    }
+}
+
+/// Returns the name libnftnl uses for a time unit in seconds, such as `"minute"`.
+pub(super) fn time_unit_name(unit: u64) -> &'static str {
+    match unit {
+        1 => "second",
+        60 => "minute",
+        3600 => "hour",
+        86400 => "day",
+        604800 => "week",
+        _ => "error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NlMsg, buffer::AlignedBuffer};
+
+    #[test]
+    fn test_exprs_match_nft() {
+        // nft --debug=netlink add rule inet filter input tcp dport 22 \
+        //     ct state established,related counter accept
+        let exprs = [
+            Expr::Meta(MetaExpr::load(NFT_META_L4PROTO, NFT_REG_1)),
+            Expr::Cmp(CmpExpr::new(NFT_CMP_EQ, NFT_REG_1, [6])),
+            Expr::Payload(PayloadExpr::load(
+                NFT_PAYLOAD_TRANSPORT_HEADER,
+                2,
+                2,
+                NFT_REG_1,
+            )),
+            Expr::Cmp(CmpExpr::new(NFT_CMP_EQ, NFT_REG_1, 22u16.to_be_bytes())),
+            Expr::Ct(CtExpr::load(NFT_CT_STATE, NFT_REG_1)),
+            Expr::Bitwise(BitwiseExpr::mask_xor(
+                NFT_REG_1,
+                (NF_CT_STATE_ESTABLISHED_BIT | NF_CT_STATE_RELATED_BIT).to_ne_bytes(),
+                [0; 4],
+            )),
+            Expr::Cmp(CmpExpr::new(NFT_CMP_NEQ, NFT_REG_1, [0; 4])),
+            Expr::Counter(CounterExpr::default()),
+            Expr::Immediate(ImmediateExpr::verdict(Verdict::Accept)),
+        ];
+        let lines: Vec<String> = exprs.iter().map(ToString::to_string).collect();
+        // Values are printed as 32 bit words in host byte order.
+        let dport = u32::from_ne_bytes([0, 22, 0, 0]);
+        let tcp = u32::from_ne_bytes([6, 0, 0, 0]);
+        assert_eq!(
+            lines,
+            [
+                "[ meta load l4proto => reg 1 ]".to_owned(),
+                format!("[ cmp eq reg 1 0x{tcp:08x} ]"),
+                "[ payload load 2b @ transport header + 2 => reg 1 ]".to_owned(),
+                format!("[ cmp eq reg 1 0x{dport:08x} ]"),
+                "[ ct load state => reg 1 ]".to_owned(),
+                "[ bitwise reg 1 = ( reg 1 & 0x00000006 ) ^ 0x00000000 ]".to_owned(),
+                "[ cmp neq reg 1 0x00000000 ]".to_owned(),
+                "[ counter pkts 0 bytes 0 ]".to_owned(),
+                "[ immediate reg 0 accept ]".to_owned(),
+            ]
+        );
+
+        let mut buffer = AlignedBuffer::new(1024);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        for expr in &exprs {
+            expr.put(&mut msg).unwrap();
+        }
+        // The first three expressions, as printed by nft --debug=mnl.
+        #[rustfmt::skip]
+        let expected: &[u8] = &[
+            36, 0, 1, 0x80,
+                9, 0, 1, 0, b'm', b'e', b't', b'a', 0, 0, 0, 0,
+                20, 0, 2, 0x80,
+                    8, 0, 2, 0, 0, 0, 0, 16,
+                    8, 0, 1, 0, 0, 0, 0, 1,
+            44, 0, 1, 0x80,
+                8, 0, 1, 0, b'c', b'm', b'p', 0,
+                32, 0, 2, 0x80,
+                    8, 0, 1, 0, 0, 0, 0, 1,
+                    8, 0, 2, 0, 0, 0, 0, 0,
+                    12, 0, 3, 0x80,
+                        5, 0, 1, 0, 6, 0, 0, 0,
+            52, 0, 1, 0x80,
+                12, 0, 1, 0, b'p', b'a', b'y', b'l', b'o', b'a', b'd', 0,
+                36, 0, 2, 0x80,
+                    8, 0, 1, 0, 0, 0, 0, 1,
+                    8, 0, 2, 0, 0, 0, 0, 2,
+                    8, 0, 3, 0, 0, 0, 0, 2,
+                    8, 0, 4, 0, 0, 0, 0, 2,
+        ];
+        if cfg!(target_endian = "little") {
+            assert_eq!(&msg.as_bytes()[16..16 + expected.len()], expected);
+        }
+        #[rustfmt::skip]
+        let accept: &[u8] = &[
+            48, 0, 1, 0x80,
+                14, 0, 1, 0, b'i', b'm', b'm', b'e', b'd', b'i', b'a', b't', b'e', 0, 0, 0,
+                28, 0, 2, 0x80,
+                    8, 0, 1, 0, 0, 0, 0, 0,
+                    16, 0, 2, 0x80,
+                        12, 0, 2, 0x80,
+                            8, 0, 1, 0, 0, 0, 0, 1,
+        ];
+        if cfg!(target_endian = "little") {
+            assert!(msg.as_bytes().ends_with(accept));
+        }
+
+        let msg = NlMsg::new(msg.as_bytes()).unwrap();
+        let parsed: Vec<Expr> = msg
+            .attrs(0)
+            .map(|attr| Expr::parse(attr.unwrap()).unwrap())
+            .collect();
+        assert_eq!(parsed, exprs);
+    }
+
+    #[test]
+    fn test_expr_encoding() {
+        // Each expression with its `nft --debug=netlink` line and its `NFTA_LIST_ELEM` as sent
+        // by nft and printed by `nft --debug=mnl`, for the rule given in the comment.
+        #[rustfmt::skip]
+        let cases: [(Expr, String, &[u8]); 15] = [
+            // nft add rule inet filter input meta l4proto tcp
+            (
+                Expr::Meta(MetaExpr::load(NFT_META_L4PROTO, NFT_REG_1)),
+                "[ meta load l4proto => reg 1 ]".to_owned(),
+                &[
+                    36, 0, 1, 0x80,
+                        9, 0, 1, 0, b'm', b'e', b't', b'a', 0, 0, 0, 0,
+                        20, 0, 2, 0x80,
+                            8, 0, 2, 0, 0, 0, 0, 16,
+                            8, 0, 1, 0, 0, 0, 0, 1,
+                ],
+            ),
+            (
+                Expr::Cmp(CmpExpr::new(NFT_CMP_EQ, NFT_REG_1, [6])),
+                format!("[ cmp eq reg 1 0x{:08x} ]", u32::from_ne_bytes([6, 0, 0, 0])),
+                &[
+                    44, 0, 1, 0x80,
+                        8, 0, 1, 0, b'c', b'm', b'p', 0,
+                        32, 0, 2, 0x80,
+                            8, 0, 1, 0, 0, 0, 0, 1,
+                            8, 0, 2, 0, 0, 0, 0, 0,
+                            12, 0, 3, 0x80,
+                                5, 0, 1, 0, 6, 0, 0, 0,
+                ],
+            ),
+            // nft add rule inet filter input tcp dport 22
+            (
+                Expr::Payload(PayloadExpr::load(
+                    NFT_PAYLOAD_TRANSPORT_HEADER, 2, 2, NFT_REG_1,
+                )),
+                "[ payload load 2b @ transport header + 2 => reg 1 ]".to_owned(),
+                &[
+                    52, 0, 1, 0x80,
+                        12, 0, 1, 0, b'p', b'a', b'y', b'l', b'o', b'a', b'd', 0,
+                        36, 0, 2, 0x80,
+                            8, 0, 1, 0, 0, 0, 0, 1,
+                            8, 0, 2, 0, 0, 0, 0, 2,
+                            8, 0, 3, 0, 0, 0, 0, 2,
+                            8, 0, 4, 0, 0, 0, 0, 2,
+                ],
+            ),
+            // nft add rule inet filter input jump sub
+            (
+                Expr::Immediate(ImmediateExpr::verdict(Verdict::Jump("sub".to_owned()))),
+                "[ immediate reg 0 jump -> sub ]".to_owned(),
+                &[
+                    56, 0, 1, 0x80,
+                        14, 0, 1, 0, b'i', b'm', b'm', b'e', b'd', b'i', b'a', b't', b'e', 0, 0, 0,
+                        36, 0, 2, 0x80,
+                            8, 0, 1, 0, 0, 0, 0, 0,
+                            24, 0, 2, 0x80,
+                                20, 0, 2, 0x80,
+                                    8, 0, 1, 0, 255, 255, 255, 253,
+                                    8, 0, 2, 0, b's', b'u', b'b', 0,
+                ],
+            ),
+            // nft add rule inet filter input tcp dport { 22, 80 }
+            (
+                Expr::Lookup(LookupExpr {
+                    set_id: Some(1),
+                    ..LookupExpr::new("__set0", NFT_REG_1)
+                }),
+                "[ lookup reg 1 set __set0 ]".to_owned(),
+                &[
+                    48, 0, 1, 0x80,
+                        11, 0, 1, 0, b'l', b'o', b'o', b'k', b'u', b'p', 0, 0,
+                        32, 0, 2, 0x80,
+                            8, 0, 2, 0, 0, 0, 0, 1,
+                            11, 0, 1, 0, 95, 95, b's', b'e', b't', b'0', 0, 0,
+                            8, 0, 4, 0, 0, 0, 0, 1,
+                ],
+            ),
+            // nft add rule inet filter input ct state established,related
+            (
+                Expr::Bitwise(BitwiseExpr::mask_xor(
+                    NFT_REG_1,
+                    (NF_CT_STATE_ESTABLISHED_BIT | NF_CT_STATE_RELATED_BIT).to_ne_bytes(),
+                    [0; 4],
+                )),
+                "[ bitwise reg 1 = ( reg 1 & 0x00000006 ) ^ 0x00000000 ]".to_owned(),
+                &[
+                    76, 0, 1, 0x80,
+                        12, 0, 1, 0, b'b', b'i', b't', b'w', b'i', b's', b'e', 0,
+                        60, 0, 2, 0x80,
+                            8, 0, 1, 0, 0, 0, 0, 1,
+                            8, 0, 2, 0, 0, 0, 0, 1,
+                            8, 0, 6, 0, 0, 0, 0, 0,
+                            8, 0, 3, 0, 0, 0, 0, 4,
+                            12, 0, 4, 0x80,
+                                8, 0, 1, 0, 6, 0, 0, 0,
+                            12, 0, 5, 0x80,
+                                8, 0, 1, 0, 0, 0, 0, 0,
+                ],
+            ),
+            (
+                Expr::Ct(CtExpr::load(NFT_CT_STATE, NFT_REG_1)),
+                "[ ct load state => reg 1 ]".to_owned(),
+                &[
+                    32, 0, 1, 0x80,
+                        7, 0, 1, 0, b'c', b't', 0, 0,
+                        20, 0, 2, 0x80,
+                            8, 0, 2, 0, 0, 0, 0, 0,
+                            8, 0, 1, 0, 0, 0, 0, 1,
+                ],
+            ),
+            // nft add rule inet filter input counter
+            (
+                Expr::Counter(CounterExpr::default()),
+                "[ counter pkts 0 bytes 0 ]".to_owned(),
+                &[
+                    20, 0, 1, 0x80,
+                        12, 0, 1, 0, b'c', b'o', b'u', b'n', b't', b'e', b'r', 0,
+                        4, 0, 2, 0x80,
+                ],
+            ),
+            // nft add rule inet filter input log prefix "mnl"
+            (
+                Expr::Log(LogExpr {
+                    prefix: Some("mnl".to_owned()),
+                    ..LogExpr::default()
+                }),
+                "[ log prefix mnl ]".to_owned(),
+                &[
+                    24, 0, 1, 0x80,
+                        8, 0, 1, 0, b'l', b'o', b'g', 0,
+                        12, 0, 2, 0x80,
+                            8, 0, 2, 0, b'm', b'n', b'l', 0,
+                ],
+            ),
+            // nft add rule inet nat postrouting snat ip to 192.0.2.1
+            (
+                Expr::Nat(NatExpr {
+                    reg_addr_min: Some(NFT_REG_1),
+                    ..NatExpr::new(NFT_NAT_SNAT, Family::Ipv4)
+                }),
+                "[ nat snat ip addr_min reg 1 ]".to_owned(),
+                &[
+                    40, 0, 1, 0x80,
+                        8, 0, 1, 0, b'n', b'a', b't', 0,
+                        28, 0, 2, 0x80,
+                            8, 0, 1, 0, 0, 0, 0, 0,
+                            8, 0, 2, 0, 0, 0, 0, 2,
+                            8, 0, 3, 0, 0, 0, 0, 1,
+                ],
+            ),
+            // nft add rule inet nat postrouting masquerade
+            (
+                Expr::Masq(MasqExpr::default()),
+                "[ masq ]".to_owned(),
+                &[
+                    20, 0, 1, 0x80,
+                        9, 0, 1, 0, b'm', b'a', b's', b'q', 0, 0, 0, 0,
+                        4, 0, 2, 0x80,
+                ],
+            ),
+            // nft add rule inet filter input reject
+            (
+                Expr::Reject(RejectExpr {
+                    reject_type: NFT_REJECT_ICMPX_UNREACH,
+                    icmp_code: NFT_REJECT_ICMPX_PORT_UNREACH,
+                }),
+                "[ reject type 2 code 1 ]".to_owned(),
+                &[
+                    36, 0, 1, 0x80,
+                        11, 0, 1, 0, b'r', b'e', b'j', b'e', b'c', b't', 0, 0,
+                        20, 0, 2, 0x80,
+                            8, 0, 1, 0, 0, 0, 0, 2,
+                            5, 0, 2, 0, 1, 0, 0, 0,
+                ],
+            ),
+            // nft add rule inet filter input limit rate 10/minute
+            (
+                Expr::Limit(LimitExpr {
+                    rate: 10,
+                    unit: 60,
+                    burst: 5,
+                    ..LimitExpr::default()
+                }),
+                "[ limit rate 10/minute burst 5 type packets flags 0x0 ]".to_owned(),
+                &[
+                    68, 0, 1, 0x80,
+                        10, 0, 1, 0, b'l', b'i', b'm', b'i', b't', 0, 0, 0,
+                        52, 0, 2, 0x80,
+                            12, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 10,
+                            12, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 60,
+                            8, 0, 3, 0, 0, 0, 0, 5,
+                            8, 0, 4, 0, 0, 0, 0, 0,
+                            8, 0, 5, 0, 0, 0, 0, 0,
+                ],
+            ),
+            // nft add rule inet filter input fib daddr . iif type local
+            (
+                Expr::Fib(FibExpr {
+                    flags: NFTA_FIB_F_DADDR | NFTA_FIB_F_IIF,
+                    result: NFT_FIB_RESULT_ADDRTYPE,
+                    dreg: NFT_REG_1,
+                }),
+                "[ fib daddr . iif type => reg 1 ]".to_owned(),
+                &[
+                    40, 0, 1, 0x80,
+                        8, 0, 1, 0, b'f', b'i', b'b', 0,
+                        28, 0, 2, 0x80,
+                            8, 0, 3, 0, 0, 0, 0, 10,
+                            8, 0, 2, 0, 0, 0, 0, 3,
+                            8, 0, 1, 0, 0, 0, 0, 1,
+                ],
+            ),
+            // nft add rule inet filter input socket transparent 1
+            (
+                Expr::Socket(SocketExpr {
+                    key: NFT_SOCKET_TRANSPARENT,
+                    dreg: NFT_REG_1,
+                    level: None,
+                }),
+                "[ socket load transparent => reg 1 ]".to_owned(),
+                &[
+                    36, 0, 1, 0x80,
+                        11, 0, 1, 0, b's', b'o', b'c', b'k', b'e', b't', 0, 0,
+                        20, 0, 2, 0x80,
+                            8, 0, 2, 0, 0, 0, 0, 1,
+                            8, 0, 1, 0, 0, 0, 0, 0,
+                ],
+            ),
+        ];
+        for (expr, line, expected) in cases {
+            assert_eq!(expr.to_string(), line);
+
+            let mut buffer = AlignedBuffer::new(256);
+            let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+            expr.put(&mut msg).unwrap();
+            let bytes = &msg.as_bytes()[size_of::<libc::nlmsghdr>()..];
+            let parsed = Expr::parse(Attrs::new(bytes).next().unwrap().unwrap()).unwrap();
+            assert_eq!(parsed, expr);
+
+            // The attribute headers in the fixtures are little endian.
+            if cfg!(target_endian = "little") {
+                assert_eq!(bytes, expected, "{line}");
+                let parsed = Expr::parse(Attrs::new(expected).next().unwrap().unwrap()).unwrap();
+                assert_eq!(parsed, expr);
+            }
+        }
+    }
+}
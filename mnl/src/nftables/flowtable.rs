@@ -0,0 +1,154 @@
+use mnl_sys::libc;
+use std::io;
+
+use super::{Family, Hook, NFGENMSG_LEN, Table, family_of, get, get_one, put_be32, put_be64, sys};
+use crate::{NfBatch, NlMsg, NlMsgBuilder, Socket};
+
+/// Flowtable flag: offload the flows to hardware.
+pub const NFT_FLOWTABLE_HW_OFFLOAD: u32 = 0x1;
+/// Flowtable flag: count the packets and bytes of the flows.
+pub const NFT_FLOWTABLE_COUNTER: u32 = 0x2;
+
+/// A flowtable, a fast path for established flows between its devices, as described by a
+/// `NFT_MSG_NEWFLOWTABLE` message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Flowtable {
+    /// The address family of the table the flowtable is in.
+    pub family: Family,
+    /// Name of the table the flowtable is in.
+    pub table: String,
+    /// Name of the flowtable, unique within its table.
+    pub name: String,
+    /// Handle assigned by the kernel.
+    pub handle: Option<u64>,
+    /// The ingress hook with the devices of the flowtable. Required when adding.
+    pub hook: Option<Hook>,
+    /// A combination of the `NFT_FLOWTABLE_*` flags.
+    pub flags: u32,
+    /// Number of rules referring to the flowtable. Only set by the kernel.
+    pub use_count: u32,
+}
+
+impl Flowtable {
+    /// Describe a flowtable with the given name in `table`.
+    pub fn new(table: &Table, name: impl Into<String>) -> Self {
+        Flowtable {
+            family: table.family,
+            table: table.name.clone(),
+            name: name.into(),
+            handle: None,
+            hook: None,
+            flags: 0,
+            use_count: 0,
+        }
+    }
+
+    /// Parse a `NFT_MSG_NEWFLOWTABLE` message.
+    pub fn from_msg(msg: &NlMsg<'_>) -> io::Result<Flowtable> {
+        let mut flowtable = Flowtable::new(&Table::new(family_of(msg)?, ""), "");
+        for attr in msg.attrs(NFGENMSG_LEN) {
+            let attr = attr?;
+            match attr.attr_type() {
+                sys::NFTA_FLOWTABLE_TABLE => flowtable.table = attr.get_str()?.to_owned(),
+                sys::NFTA_FLOWTABLE_NAME => flowtable.name = attr.get_str()?.to_owned(),
+                sys::NFTA_FLOWTABLE_HANDLE => flowtable.handle = Some(attr.get_be64()?),
+                sys::NFTA_FLOWTABLE_HOOK => {
+                    flowtable.hook = Some(Hook::parse(attr, sys::NFTA_FLOWTABLE_HOOK_DEVS)?)
+                }
+                sys::NFTA_FLOWTABLE_FLAGS => flowtable.flags = attr.get_be32()?,
+                sys::NFTA_FLOWTABLE_USE => flowtable.use_count = attr.get_be32()?,
+                _ => (),
+            }
+        }
+        Ok(flowtable)
+    }
+
+    fn put_attrs(&self, msg: &mut NlMsgBuilder<'_>) -> io::Result<()> {
+        msg.put_str(sys::NFTA_FLOWTABLE_TABLE, &self.table)?;
+        msg.put_str(sys::NFTA_FLOWTABLE_NAME, &self.name)?;
+        if let Some(hook) = &self.hook {
+            hook.put(msg, sys::NFTA_FLOWTABLE_HOOK, sys::NFTA_FLOWTABLE_HOOK_DEVS)?;
+        }
+        if self.flags != 0 {
+            put_be32(msg, sys::NFTA_FLOWTABLE_FLAGS, self.flags)?;
+        }
+        Ok(())
+    }
+
+    /// Add a `NFT_MSG_NEWFLOWTABLE` message to `batch`, creating the flowtable or adding
+    /// devices to an existing one. Returns the index of the message in the batch.
+    pub fn add(&self, batch: &mut NfBatch) -> io::Result<usize> {
+        batch.add(
+            sys::NFT_MSG_NEWFLOWTABLE,
+            self.family.into(),
+            libc::NLM_F_CREATE as u16,
+            |msg| self.put_attrs(msg),
+        )
+    }
+
+    /// Add a `NFT_MSG_DELFLOWTABLE` message to `batch`. The flowtable is identified by its
+    /// handle if set, otherwise by its name.
+    pub fn delete(&self, batch: &mut NfBatch) -> io::Result<usize> {
+        batch.add(sys::NFT_MSG_DELFLOWTABLE, self.family.into(), 0, |msg| {
+            msg.put_str(sys::NFTA_FLOWTABLE_TABLE, &self.table)?;
+            match self.handle {
+                Some(handle) => put_be64(msg, sys::NFTA_FLOWTABLE_HANDLE, handle),
+                None => msg.put_str(sys::NFTA_FLOWTABLE_NAME, &self.name),
+            }
+        })
+    }
+}
+
+/// Dump the flowtables of all tables (`NFT_MSG_GETFLOWTABLE`). `socket` must be a
+/// [`Bus::Netfilter`] socket.
+///
+/// [`Bus::Netfilter`]: crate::Bus::Netfilter
+pub fn flowtables(socket: &Socket) -> io::Result<Vec<Flowtable>> {
+    get(
+        socket,
+        sys::NFT_MSG_GETFLOWTABLE,
+        libc::NFPROTO_UNSPEC as u8,
+        true,
+        |_| Ok(()),
+        Flowtable::from_msg,
+    )
+}
+
+/// Get the flowtable with the given name in `table`. Fails with `ENOENT` if there is no such
+/// flowtable.
+pub fn flowtable(socket: &Socket, table: &Table, name: &str) -> io::Result<Flowtable> {
+    get_one(
+        socket,
+        sys::NFT_MSG_GETFLOWTABLE,
+        table.family.into(),
+        |msg| {
+            msg.put_str(sys::NFTA_FLOWTABLE_TABLE, &table.name)?;
+            msg.put_str(sys::NFTA_FLOWTABLE_NAME, name)
+        },
+        Flowtable::from_msg,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::AlignedBuffer;
+
+    #[test]
+    fn test_flowtable_round_trip() {
+        let flowtable = Flowtable {
+            hook: Some(Hook {
+                devices: vec!["eth0".to_owned()],
+                ..Hook::new(0, -100)
+            }),
+            flags: NFT_FLOWTABLE_COUNTER,
+            ..Flowtable::new(&Table::new(Family::Inet, "filter"), "fastpath")
+        };
+        let mut buffer = AlignedBuffer::new(512);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        crate::netfilter::put_nfgenmsg(&mut msg, flowtable.family.into(), 0).unwrap();
+        flowtable.put_attrs(&mut msg).unwrap();
+        let parsed = Flowtable::from_msg(&NlMsg::new(msg.as_bytes()).unwrap()).unwrap();
+        assert_eq!(parsed, flowtable);
+    }
+}
@@ -0,0 +1,221 @@
+//! Typed nftables (`NFNL_SUBSYS_NFTABLES`) messages.
+//!
+//! Tables, chains, rules, sets, set elements, stateful objects and flowtables are described by
+//! plain structs. They are added to and deleted from the ruleset by encoding them into an
+//! [`NfBatch`] transaction, and read back with dumps over a [`Bus::Netfilter`] socket.
+//! The expressions of rules are typed [`Expr`]s, encoded the way `nft` encodes them.
+//! [`ruleset`] dumps everything at once into a [`Ruleset`], which formats like
+//! `nft list ruleset`.
+//!
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use mnl::nftables::{self, Chain, Family, Hook, Policy, Table};
+//!
+//! let table = Table::new(Family::Inet, "filter");
+//! let chain = Chain {
+//!     hook: Some(Hook::new(nftables::NF_INET_LOCAL_IN, 0)),
+//!     chain_type: Some("filter".to_owned()),
+//!     policy: Some(Policy::Drop),
+//!     ..Chain::new(&table, "input")
+//! };
+//! let mut batch = mnl::NfBatch::new()?;
+//! table.add(&mut batch)?;
+//! chain.add(&mut batch)?;
+//!
+//! let socket = mnl::Socket::new(mnl::Bus::Netfilter)?;
+//! batch.send(&socket)?;
+//! for chain in nftables::chains(&socket)? {
+//!     println!("{} {} {}", chain.family, chain.table, chain.name);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`NfBatch`]: crate::NfBatch
+//! [`Bus::Netfilter`]: crate::Bus::Netfilter
+
+use mnl_sys::libc;
+use std::{fmt, io};
+
+use crate::{
+    Bus, NlMsg, NlMsgBuilder, Socket,
+    buffer::AlignedBuffer,
+    netfilter::{NFNL_SUBSYS_NFTABLES, put_nfgenmsg},
+};
+
+mod sys;
+
+mod data;
+pub use self::data::*;
+
+mod table;
+pub use self::table::*;
+
+mod chain;
+pub use self::chain::*;
+
+mod rule;
+pub use self::rule::*;
+
+mod expr;
+pub use self::expr::*;
+
+mod set;
+pub use self::set::*;
+
+mod object;
+pub use self::object::*;
+
+mod flowtable;
+pub use self::flowtable::*;
+
+mod ruleset;
+pub use self::ruleset::*;
+
+/// Size of the buffer requests are built in.
+const REQUEST_BUFFER_SIZE: usize = 4096;
+
+/// Size of the `nfgenmsg` header following the netlink header.
+const NFGENMSG_LEN: usize = 4;
+
+const NESTED: u16 = libc::NLA_F_NESTED as u16;
+
+/// The address family a table, and everything in it, applies to. Mirrors the `NFPROTO_*`
+/// constants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Family {
+    /// IPv4 and IPv6 (`inet`).
+    Inet,
+    /// IPv4 (`ip`).
+    Ipv4,
+    /// IPv6 (`ip6`).
+    Ipv6,
+    /// ARP (`arp`).
+    Arp,
+    /// Bridged traffic (`bridge`).
+    Bridge,
+    /// Traffic of a single network device, from ingress or to egress (`netdev`).
+    Netdev,
+    /// A family not known to this crate.
+    Other(u8),
+}
+
+impl From<u8> for Family {
+    fn from(value: u8) -> Self {
+        use Family::*;
+        match value as i32 {
+            libc::NFPROTO_INET => Inet,
+            libc::NFPROTO_IPV4 => Ipv4,
+            libc::NFPROTO_IPV6 => Ipv6,
+            libc::NFPROTO_ARP => Arp,
+            libc::NFPROTO_BRIDGE => Bridge,
+            libc::NFPROTO_NETDEV => Netdev,
+            _ => Other(value),
+        }
+    }
+}
+
+impl From<Family> for u8 {
+    fn from(family: Family) -> u8 {
+        use Family::*;
+        (match family {
+            Inet => libc::NFPROTO_INET,
+            Ipv4 => libc::NFPROTO_IPV4,
+            Ipv6 => libc::NFPROTO_IPV6,
+            Arp => libc::NFPROTO_ARP,
+            Bridge => libc::NFPROTO_BRIDGE,
+            Netdev => libc::NFPROTO_NETDEV,
+            Other(other) => other as i32,
+        }) as u8
+    }
+}
+
+impl fmt::Display for Family {
+    /// Formats the family the way `nft` names it.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Family::Inet => f.write_str("inet"),
+            Family::Ipv4 => f.write_str("ip"),
+            Family::Ipv6 => f.write_str("ip6"),
+            Family::Arp => f.write_str("arp"),
+            Family::Bridge => f.write_str("bridge"),
+            Family::Netdev => f.write_str("netdev"),
+            Family::Other(other) => write!(f, "family {other}"),
+        }
+    }
+}
+
+/// Returns the family from the `nfgenmsg` header of `msg`.
+fn family_of(msg: &NlMsg<'_>) -> io::Result<Family> {
+    match msg.payload().first() {
+        Some(family) if msg.payload().len() >= NFGENMSG_LEN => Ok(Family::from(*family)),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Message too short for nfgenmsg header",
+        )),
+    }
+}
+
+/// Add an `u32` attribute in network byte order, as nftables uses for all integers.
+fn put_be32(msg: &mut NlMsgBuilder<'_>, attr_type: u16, value: u32) -> io::Result<()> {
+    msg.put(attr_type, &value.to_be_bytes())
+}
+
+/// Add an `u64` attribute in network byte order.
+fn put_be64(msg: &mut NlMsgBuilder<'_>, attr_type: u16, value: u64) -> io::Result<()> {
+    msg.put(attr_type, &value.to_be_bytes())
+}
+
+/// Send a `NFT_MSG_GET*` request of type `msg_type` for `family`, with the attributes written by
+/// `put`, and parse every reply of the matching `NFT_MSG_NEW*` type with `parse`. Dumps all
+/// objects if `dump` is set, otherwise the kernel replies with the single object matching the
+/// attributes or fails with `ENOENT`.
+fn get<T, F, P>(
+    socket: &Socket,
+    msg_type: u16,
+    family: u8,
+    dump: bool,
+    put: F,
+    parse: P,
+) -> io::Result<Vec<T>>
+where
+    F: FnOnce(&mut NlMsgBuilder<'_>) -> io::Result<()>,
+    P: Fn(&NlMsg<'_>) -> io::Result<T>,
+{
+    if socket.bus() != Bus::Netfilter {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "nftables requests can only be sent on a Bus::Netfilter socket",
+        ));
+    }
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes())?;
+    msg.set_type((NFNL_SUBSYS_NFTABLES << 8) | msg_type);
+    let flags = if dump { libc::NLM_F_DUMP } else { 0 } | libc::NLM_F_REQUEST;
+    msg.set_flags(flags as u16);
+    msg.set_seq(socket.next_seq());
+    put_nfgenmsg(&mut msg, family, 0)?;
+    put(&mut msg)?;
+
+    // Every get command is one above the matching new command.
+    let reply_type = (NFNL_SUBSYS_NFTABLES << 8) | (msg_type - 1);
+    let mut result = Vec::new();
+    socket.request(msg.as_bytes(), |reply| {
+        if reply.msg_type() == reply_type {
+            result.push(parse(&reply)?);
+        }
+        Ok(())
+    })?;
+    Ok(result)
+}
+
+/// Like [`get`] for a single object, failing with `ENOENT` if the kernel did not return one.
+fn get_one<T, F, P>(socket: &Socket, msg_type: u16, family: u8, put: F, parse: P) -> io::Result<T>
+where
+    F: FnOnce(&mut NlMsgBuilder<'_>) -> io::Result<()>,
+    P: Fn(&NlMsg<'_>) -> io::Result<T>,
+{
+    get(socket, msg_type, family, false, put, parse)?
+        .pop()
+        .ok_or_else(|| io::Error::from_raw_os_error(libc::ENOENT))
+}
@@ -0,0 +1,392 @@
+use mnl_sys::libc;
+use std::io;
+
+use super::{
+    CounterExpr, Expr, Family, LimitExpr, NESTED, NFGENMSG_LEN, RawExpr, Table, family_of, get,
+    get_one, put_be32, put_be64, sys,
+};
+use crate::{Attrs, NfBatch, NlMsg, NlMsgBuilder, Socket};
+
+/// Object type: a named counter.
+pub const NFT_OBJECT_COUNTER: u32 = 1;
+/// Object type: a quota.
+pub const NFT_OBJECT_QUOTA: u32 = 2;
+/// Object type: a conntrack helper assignment.
+pub const NFT_OBJECT_CT_HELPER: u32 = 3;
+/// Object type: a rate limit.
+pub const NFT_OBJECT_LIMIT: u32 = 4;
+/// Object type: a limit of the number of connections.
+pub const NFT_OBJECT_CONNLIMIT: u32 = 5;
+/// Object type: conntrack timeout settings.
+pub const NFT_OBJECT_CT_TIMEOUT: u32 = 7;
+/// Object type: a security mark.
+pub const NFT_OBJECT_SECMARK: u32 = 8;
+/// Object type: a conntrack expectation.
+pub const NFT_OBJECT_CT_EXPECT: u32 = 9;
+/// Object type: SYN proxy settings.
+pub const NFT_OBJECT_SYNPROXY: u32 = 10;
+
+/// Quota flag: match once the quota is exceeded, instead of until it is.
+pub const NFT_QUOTA_F_INV: u32 = 1;
+/// Quota flag: the quota has been exceeded. Only set by the kernel.
+pub const NFT_QUOTA_F_DEPLETED: u32 = 2;
+
+/// A stateful object, such as a named counter or quota, as described by a `NFT_MSG_NEWOBJ`
+/// message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Object {
+    /// The address family of the table the object is in.
+    pub family: Family,
+    /// Name of the table the object is in.
+    pub table: String,
+    /// Name of the object, unique within its table and type.
+    pub name: String,
+    /// Handle assigned by the kernel.
+    pub handle: Option<u64>,
+    /// The type of the object and its type specific settings.
+    pub data: ObjectData,
+    /// Number of rules and set elements referring to the object. Only set by the kernel.
+    pub use_count: u32,
+    /// Opaque data stored with the object, such as comments added by `nft`.
+    pub userdata: Option<Vec<u8>>,
+}
+
+/// The type specific settings of an [`Object`] (`NFTA_OBJ_DATA`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ObjectData {
+    /// A named counter ([`NFT_OBJECT_COUNTER`]), with the same settings as the counter
+    /// expression.
+    Counter(CounterExpr),
+    /// A quota ([`NFT_OBJECT_QUOTA`]).
+    Quota(QuotaObject),
+    /// A conntrack helper assignment ([`NFT_OBJECT_CT_HELPER`]).
+    CtHelper(CtHelperObject),
+    /// A rate limit ([`NFT_OBJECT_LIMIT`]), with the same settings as the limit expression.
+    Limit(LimitExpr),
+    /// An object type not known to this crate.
+    Other {
+        /// Type of the object, one of the `NFT_OBJECT_*` constants.
+        obj_type: u32,
+        /// The raw nested attributes of the object.
+        data: Vec<u8>,
+    },
+}
+
+/// A quota object, matching until `bytes` bytes have passed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct QuotaObject {
+    /// Number of bytes in the quota.
+    pub bytes: u64,
+    /// Number of bytes used so far. Can be set to start with a partly used quota.
+    pub consumed: u64,
+    /// Quota flags, such as [`NFT_QUOTA_F_INV`].
+    pub flags: u32,
+}
+
+/// A conntrack helper object, assigning a helper such as `"ftp"` to connections.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct CtHelperObject {
+    /// Name of the helper.
+    pub name: String,
+    /// The `NFPROTO_*` layer 3 protocol. Defaults to the family of the table.
+    pub l3proto: Option<u16>,
+    /// The `IPPROTO_*` layer 4 protocol.
+    pub l4proto: u8,
+}
+
+impl ObjectData {
+    /// Returns the type of the object, one of the `NFT_OBJECT_*` constants.
+    pub fn obj_type(&self) -> u32 {
+        match self {
+            ObjectData::Counter(_) => NFT_OBJECT_COUNTER,
+            ObjectData::Quota(_) => NFT_OBJECT_QUOTA,
+            ObjectData::CtHelper(_) => NFT_OBJECT_CT_HELPER,
+            ObjectData::Limit(_) => NFT_OBJECT_LIMIT,
+            ObjectData::Other { obj_type, .. } => *obj_type,
+        }
+    }
+
+    /// Parse the `NFTA_OBJ_DATA` of an object of type `obj_type`.
+    fn parse(obj_type: u32, data: Vec<u8>) -> io::Result<ObjectData> {
+        let expr = |name: &str, data| {
+            Expr::from_raw(RawExpr {
+                name: name.to_owned(),
+                data,
+            })
+        };
+        Ok(match obj_type {
+            NFT_OBJECT_COUNTER => match expr("counter", data)? {
+                Expr::Counter(counter) => ObjectData::Counter(counter),
+                _ => unreachable!(),
+            },
+            NFT_OBJECT_QUOTA => {
+                let mut quota = QuotaObject::default();
+                for attr in Attrs::new(&data) {
+                    let attr = attr?;
+                    match attr.attr_type() {
+                        sys::NFTA_QUOTA_BYTES => quota.bytes = attr.get_be64()?,
+                        sys::NFTA_QUOTA_FLAGS => quota.flags = attr.get_be32()?,
+                        sys::NFTA_QUOTA_CONSUMED => quota.consumed = attr.get_be64()?,
+                        _ => (),
+                    }
+                }
+                ObjectData::Quota(quota)
+            }
+            NFT_OBJECT_CT_HELPER => {
+                let mut helper = CtHelperObject::default();
+                for attr in Attrs::new(&data) {
+                    let attr = attr?;
+                    match attr.attr_type() {
+                        sys::NFTA_CT_HELPER_NAME => helper.name = attr.get_str()?.to_owned(),
+                        sys::NFTA_CT_HELPER_L3PROTO => helper.l3proto = Some(attr.get_be16()?),
+                        sys::NFTA_CT_HELPER_L4PROTO => helper.l4proto = attr.get_u8()?,
+                        _ => (),
+                    }
+                }
+                ObjectData::CtHelper(helper)
+            }
+            NFT_OBJECT_LIMIT => match expr("limit", data)? {
+                Expr::Limit(limit) => ObjectData::Limit(limit),
+                _ => unreachable!(),
+            },
+            _ => ObjectData::Other { obj_type, data },
+        })
+    }
+
+    /// Add the data as `NFTA_OBJ_DATA`, in the order libnftnl adds the attributes.
+    fn put(&self, msg: &mut NlMsgBuilder<'_>) -> io::Result<()> {
+        if let ObjectData::Other { data, .. } = self {
+            return msg.put(sys::NFTA_OBJ_DATA | NESTED, data);
+        }
+        msg.put_nested(sys::NFTA_OBJ_DATA, |msg| match self {
+            ObjectData::Counter(counter) => Expr::Counter(*counter).put_data(msg),
+            ObjectData::Quota(quota) => {
+                put_be64(msg, sys::NFTA_QUOTA_BYTES, quota.bytes)?;
+                put_be64(msg, sys::NFTA_QUOTA_CONSUMED, quota.consumed)?;
+                put_be32(msg, sys::NFTA_QUOTA_FLAGS, quota.flags)
+            }
+            ObjectData::CtHelper(helper) => {
+                msg.put_str(sys::NFTA_CT_HELPER_NAME, &helper.name)?;
+                if let Some(l3proto) = helper.l3proto {
+                    msg.put(sys::NFTA_CT_HELPER_L3PROTO, &l3proto.to_be_bytes())?;
+                }
+                msg.put_u8(sys::NFTA_CT_HELPER_L4PROTO, helper.l4proto)
+            }
+            ObjectData::Limit(limit) => Expr::Limit(*limit).put_data(msg),
+            ObjectData::Other { .. } => unreachable!(),
+        })
+    }
+}
+
+impl Object {
+    /// Describe an object with the given name in `table`, of the type given by `data`.
+    pub fn new(table: &Table, name: impl Into<String>, data: ObjectData) -> Self {
+        Object {
+            family: table.family,
+            table: table.name.clone(),
+            name: name.into(),
+            handle: None,
+            data,
+            use_count: 0,
+            userdata: None,
+        }
+    }
+
+    /// Parse a `NFT_MSG_NEWOBJ` message.
+    pub fn from_msg(msg: &NlMsg<'_>) -> io::Result<Object> {
+        let mut object = Object::new(
+            &Table::new(family_of(msg)?, ""),
+            "",
+            ObjectData::Other {
+                obj_type: 0,
+                data: Vec::new(),
+            },
+        );
+        let mut obj_type = 0;
+        let mut data = Vec::new();
+        for attr in msg.attrs(NFGENMSG_LEN) {
+            let attr = attr?;
+            match attr.attr_type() {
+                sys::NFTA_OBJ_TABLE => object.table = attr.get_str()?.to_owned(),
+                sys::NFTA_OBJ_NAME => object.name = attr.get_str()?.to_owned(),
+                sys::NFTA_OBJ_HANDLE => object.handle = Some(attr.get_be64()?),
+                sys::NFTA_OBJ_TYPE => obj_type = attr.get_be32()?,
+                sys::NFTA_OBJ_DATA => data = attr.payload().to_vec(),
+                sys::NFTA_OBJ_USE => object.use_count = attr.get_be32()?,
+                sys::NFTA_OBJ_USERDATA => object.userdata = Some(attr.payload().to_vec()),
+                _ => (),
+            }
+        }
+        object.data = ObjectData::parse(obj_type, data)?;
+        Ok(object)
+    }
+
+    fn put_attrs(&self, msg: &mut NlMsgBuilder<'_>) -> io::Result<()> {
+        msg.put_str(sys::NFTA_OBJ_TABLE, &self.table)?;
+        msg.put_str(sys::NFTA_OBJ_NAME, &self.name)?;
+        put_be32(msg, sys::NFTA_OBJ_TYPE, self.data.obj_type())?;
+        self.data.put(msg)?;
+        if let Some(userdata) = &self.userdata {
+            msg.put(sys::NFTA_OBJ_USERDATA, userdata)?;
+        }
+        Ok(())
+    }
+
+    /// Add a `NFT_MSG_NEWOBJ` message to `batch`, creating the object if it does not exist.
+    /// Returns the index of the message in the batch.
+    pub fn add(&self, batch: &mut NfBatch) -> io::Result<usize> {
+        batch.add(
+            sys::NFT_MSG_NEWOBJ,
+            self.family.into(),
+            libc::NLM_F_CREATE as u16,
+            |msg| self.put_attrs(msg),
+        )
+    }
+
+    /// Add a `NFT_MSG_DELOBJ` message to `batch`. The object is identified by its handle if
+    /// set, otherwise by its name.
+    pub fn delete(&self, batch: &mut NfBatch) -> io::Result<usize> {
+        batch.add(sys::NFT_MSG_DELOBJ, self.family.into(), 0, |msg| {
+            msg.put_str(sys::NFTA_OBJ_TABLE, &self.table)?;
+            match self.handle {
+                Some(handle) => put_be64(msg, sys::NFTA_OBJ_HANDLE, handle)?,
+                None => msg.put_str(sys::NFTA_OBJ_NAME, &self.name)?,
+            }
+            put_be32(msg, sys::NFTA_OBJ_TYPE, self.data.obj_type())
+        })
+    }
+}
+
+/// Dump the stateful objects of all tables (`NFT_MSG_GETOBJ`). `socket` must be a
+/// [`Bus::Netfilter`] socket.
+///
+/// [`Bus::Netfilter`]: crate::Bus::Netfilter
+pub fn objects(socket: &Socket) -> io::Result<Vec<Object>> {
+    get(
+        socket,
+        sys::NFT_MSG_GETOBJ,
+        libc::NFPROTO_UNSPEC as u8,
+        true,
+        |_| Ok(()),
+        Object::from_msg,
+    )
+}
+
+/// Get the object of type `obj_type` with the given name in `table`. Fails with `ENOENT` if
+/// there is no such object.
+pub fn object(socket: &Socket, table: &Table, name: &str, obj_type: u32) -> io::Result<Object> {
+    get_one(
+        socket,
+        sys::NFT_MSG_GETOBJ,
+        table.family.into(),
+        |msg| {
+            msg.put_str(sys::NFTA_OBJ_TABLE, &table.name)?;
+            msg.put_str(sys::NFTA_OBJ_NAME, name)?;
+            put_be32(msg, sys::NFTA_OBJ_TYPE, obj_type)
+        },
+        Object::from_msg,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::AlignedBuffer;
+
+    #[test]
+    fn test_object_round_trip() {
+        let table = Table::new(Family::Inet, "filter");
+        let objects = [
+            Object::new(
+                &table,
+                "http",
+                ObjectData::Counter(CounterExpr {
+                    bytes: 1500,
+                    packets: 1,
+                }),
+            ),
+            Object {
+                userdata: Some(b"\x00\x05quota".to_vec()),
+                ..Object::new(
+                    &table,
+                    "monthly",
+                    ObjectData::Quota(QuotaObject {
+                        bytes: 100 << 20,
+                        consumed: 4096,
+                        flags: NFT_QUOTA_F_INV,
+                    }),
+                )
+            },
+            Object::new(
+                &table,
+                "ftp",
+                ObjectData::CtHelper(CtHelperObject {
+                    name: "ftp".to_owned(),
+                    l3proto: Some(libc::NFPROTO_IPV4 as u16),
+                    l4proto: libc::IPPROTO_TCP as u8,
+                }),
+            ),
+            Object::new(
+                &table,
+                "slow",
+                ObjectData::Limit(LimitExpr {
+                    rate: 10,
+                    unit: 60,
+                    burst: 5,
+                    ..LimitExpr::default()
+                }),
+            ),
+            Object::new(
+                &table,
+                "unknown",
+                ObjectData::Other {
+                    obj_type: NFT_OBJECT_SYNPROXY,
+                    data: vec![6, 0, 1, 0, 0x05, 0xb4, 0, 0],
+                },
+            ),
+        ];
+        for object in objects {
+            let mut buffer = AlignedBuffer::new(512);
+            let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+            crate::netfilter::put_nfgenmsg(&mut msg, object.family.into(), 0).unwrap();
+            object.put_attrs(&mut msg).unwrap();
+
+            let parsed = Object::from_msg(&NlMsg::new(msg.as_bytes()).unwrap()).unwrap();
+            assert_eq!(parsed, object);
+        }
+    }
+
+    #[test]
+    fn test_quota_attrs() {
+        let quota = ObjectData::Quota(QuotaObject {
+            bytes: 1 << 32,
+            consumed: 0,
+            flags: NFT_QUOTA_F_INV,
+        });
+        let mut buffer = AlignedBuffer::new(512);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        quota.put(&mut msg).unwrap();
+        let bytes = &msg.as_bytes()[size_of::<libc::nlmsghdr>()..];
+        let data = Attrs::new(bytes).next().unwrap().unwrap();
+        assert_eq!(data.attr_type(), sys::NFTA_OBJ_DATA);
+        assert!(data.is_nested());
+        let attrs = data
+            .nested()
+            .map(|attr| {
+                let attr = attr.unwrap();
+                (attr.attr_type(), attr.payload().to_vec())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            attrs,
+            [
+                (sys::NFTA_QUOTA_BYTES, (1u64 << 32).to_be_bytes().to_vec()),
+                (sys::NFTA_QUOTA_CONSUMED, 0u64.to_be_bytes().to_vec()),
+                (
+                    sys::NFTA_QUOTA_FLAGS,
+                    NFT_QUOTA_F_INV.to_be_bytes().to_vec()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,238 @@
+use mnl_sys::libc;
+use std::io;
+
+use super::{Chain, Expr, Family, NFGENMSG_LEN, family_of, get, get_one, put_be64, sys};
+use crate::{NfBatch, NlMsg, NlMsgBuilder, Socket};
+
+/// An nftables rule, as described by a `NFT_MSG_NEWRULE` message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    /// The address family of the table the rule is in.
+    pub family: Family,
+    /// Name of the table the rule is in.
+    pub table: String,
+    /// Name of the chain the rule is in.
+    pub chain: String,
+    /// Handle assigned by the kernel, identifying the rule within its chain.
+    pub handle: Option<u64>,
+    /// Handle of the rule to add this rule after, or insert it before. Only used when adding.
+    pub position: Option<u64>,
+    /// The expressions of the rule, evaluated in order.
+    pub exprs: Vec<Expr>,
+    /// Opaque data stored with the rule, such as comments added by `nft`.
+    pub userdata: Option<Vec<u8>>,
+}
+
+impl Rule {
+    /// Describe an empty rule in `chain`.
+    pub fn new(chain: &Chain) -> Self {
+        Rule {
+            family: chain.family,
+            table: chain.table.clone(),
+            chain: chain.name.clone(),
+            handle: None,
+            position: None,
+            exprs: Vec::new(),
+            userdata: None,
+        }
+    }
+
+    /// Parse a `NFT_MSG_NEWRULE` message.
+    pub fn from_msg(msg: &NlMsg<'_>) -> io::Result<Rule> {
+        let mut rule = Rule {
+            family: family_of(msg)?,
+            table: String::new(),
+            chain: String::new(),
+            handle: None,
+            position: None,
+            exprs: Vec::new(),
+            userdata: None,
+        };
+        for attr in msg.attrs(NFGENMSG_LEN) {
+            let attr = attr?;
+            match attr.attr_type() {
+                sys::NFTA_RULE_TABLE => rule.table = attr.get_str()?.to_owned(),
+                sys::NFTA_RULE_CHAIN => rule.chain = attr.get_str()?.to_owned(),
+                sys::NFTA_RULE_HANDLE => rule.handle = Some(attr.get_be64()?),
+                sys::NFTA_RULE_POSITION => rule.position = Some(attr.get_be64()?),
+                sys::NFTA_RULE_USERDATA => rule.userdata = Some(attr.payload().to_vec()),
+                sys::NFTA_RULE_EXPRESSIONS => {
+                    for expr in attr.nested() {
+                        rule.exprs.push(Expr::parse(expr?)?);
+                    }
+                }
+                _ => (),
+            }
+        }
+        Ok(rule)
+    }
+
+    fn put_attrs(&self, msg: &mut NlMsgBuilder<'_>, handle: bool) -> io::Result<()> {
+        msg.put_str(sys::NFTA_RULE_TABLE, &self.table)?;
+        msg.put_str(sys::NFTA_RULE_CHAIN, &self.chain)?;
+        if handle {
+            put_be64(msg, sys::NFTA_RULE_HANDLE, self.handle()?)?;
+        }
+        if let Some(position) = self.position {
+            put_be64(msg, sys::NFTA_RULE_POSITION, position)?;
+        }
+        if let Some(userdata) = &self.userdata {
+            msg.put(sys::NFTA_RULE_USERDATA, userdata)?;
+        }
+        if !self.exprs.is_empty() {
+            msg.put_nested(sys::NFTA_RULE_EXPRESSIONS, |msg| {
+                self.exprs.iter().try_for_each(|expr| expr.put(msg))
+            })?;
+        }
+        Ok(())
+    }
+
+    fn handle(&self) -> io::Result<u64> {
+        self.handle.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "The rule has no handle to identify it by",
+            )
+        })
+    }
+
+    /// Add a `NFT_MSG_NEWRULE` message to `batch`, appending the rule to the end of its chain,
+    /// or after the rule with the handle given in [`position`]. Returns the index of the
+    /// message in the batch.
+    ///
+    /// [`position`]: Rule::position
+    pub fn add(&self, batch: &mut NfBatch) -> io::Result<usize> {
+        batch.add(
+            sys::NFT_MSG_NEWRULE,
+            self.family.into(),
+            (libc::NLM_F_CREATE | libc::NLM_F_APPEND) as u16,
+            |msg| self.put_attrs(msg, false),
+        )
+    }
+
+    /// Add a `NFT_MSG_NEWRULE` message to `batch`, inserting the rule at the start of its
+    /// chain, or before the rule with the handle given in [`position`].
+    ///
+    /// [`position`]: Rule::position
+    pub fn insert(&self, batch: &mut NfBatch) -> io::Result<usize> {
+        batch.add(
+            sys::NFT_MSG_NEWRULE,
+            self.family.into(),
+            libc::NLM_F_CREATE as u16,
+            |msg| self.put_attrs(msg, false),
+        )
+    }
+
+    /// Add a `NFT_MSG_NEWRULE` message to `batch`, replacing the rule with the same handle.
+    /// Fails if the rule has no handle.
+    pub fn replace(&self, batch: &mut NfBatch) -> io::Result<usize> {
+        self.handle()?;
+        batch.add(
+            sys::NFT_MSG_NEWRULE,
+            self.family.into(),
+            libc::NLM_F_REPLACE as u16,
+            |msg| self.put_attrs(msg, true),
+        )
+    }
+
+    /// Add a `NFT_MSG_DELRULE` message to `batch`, deleting the rule with the same handle.
+    /// Fails if the rule has no handle.
+    pub fn delete(&self, batch: &mut NfBatch) -> io::Result<usize> {
+        let handle = self.handle()?;
+        batch.add(sys::NFT_MSG_DELRULE, self.family.into(), 0, |msg| {
+            msg.put_str(sys::NFTA_RULE_TABLE, &self.table)?;
+            msg.put_str(sys::NFTA_RULE_CHAIN, &self.chain)?;
+            put_be64(msg, sys::NFTA_RULE_HANDLE, handle)
+        })
+    }
+}
+
+/// Add a `NFT_MSG_DELRULE` message to `batch` deleting all rules in `chain`, like
+/// `nft flush chain`.
+pub fn flush_chain(batch: &mut NfBatch, chain: &Chain) -> io::Result<usize> {
+    batch.add(sys::NFT_MSG_DELRULE, chain.family.into(), 0, |msg| {
+        msg.put_str(sys::NFTA_RULE_TABLE, &chain.table)?;
+        msg.put_str(sys::NFTA_RULE_CHAIN, &chain.name)
+    })
+}
+
+/// Dump the rules of all chains (`NFT_MSG_GETRULE`). `socket` must be a [`Bus::Netfilter`]
+/// socket.
+///
+/// [`Bus::Netfilter`]: crate::Bus::Netfilter
+pub fn rules(socket: &Socket) -> io::Result<Vec<Rule>> {
+    get(
+        socket,
+        sys::NFT_MSG_GETRULE,
+        libc::NFPROTO_UNSPEC as u8,
+        true,
+        |_| Ok(()),
+        Rule::from_msg,
+    )
+}
+
+/// Dump the rules of `chain`, in order.
+pub fn chain_rules(socket: &Socket, chain: &Chain) -> io::Result<Vec<Rule>> {
+    get(
+        socket,
+        sys::NFT_MSG_GETRULE,
+        chain.family.into(),
+        true,
+        |msg| {
+            msg.put_str(sys::NFTA_RULE_TABLE, &chain.table)?;
+            msg.put_str(sys::NFTA_RULE_CHAIN, &chain.name)
+        },
+        Rule::from_msg,
+    )
+}
+
+/// Get the rule with the given handle in `chain`. Fails with `ENOENT` if there is no such rule.
+pub fn rule(socket: &Socket, chain: &Chain, handle: u64) -> io::Result<Rule> {
+    get_one(
+        socket,
+        sys::NFT_MSG_GETRULE,
+        chain.family.into(),
+        |msg| {
+            msg.put_str(sys::NFTA_RULE_TABLE, &chain.table)?;
+            msg.put_str(sys::NFTA_RULE_CHAIN, &chain.name)?;
+            put_be64(msg, sys::NFTA_RULE_HANDLE, handle)
+        },
+        Rule::from_msg,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        buffer::AlignedBuffer,
+        nftables::{CounterExpr, RawExpr, Table},
+    };
+
+    #[test]
+    fn test_rule_round_trip() {
+        let chain = Chain::new(&Table::new(Family::Inet, "filter"), "input");
+        let rule = Rule {
+            handle: Some(4),
+            exprs: vec![
+                Expr::Counter(CounterExpr::default()),
+                // An expression without a typed variant is kept as is.
+                Expr::Other(RawExpr {
+                    name: "quota".to_owned(),
+                    data: vec![12, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 100],
+                }),
+            ],
+            ..Rule::new(&chain)
+        };
+        let mut buffer = AlignedBuffer::new(512);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        crate::netfilter::put_nfgenmsg(&mut msg, rule.family.into(), 0).unwrap();
+        rule.put_attrs(&mut msg, true).unwrap();
+        let parsed = Rule::from_msg(&NlMsg::new(msg.as_bytes()).unwrap()).unwrap();
+        assert_eq!(parsed, rule);
+
+        let mut batch = NfBatch::new().unwrap();
+        assert!(Rule::new(&chain).delete(&mut batch).is_err());
+        assert_eq!(rule.delete(&mut batch).unwrap(), 0);
+    }
+}
@@ -0,0 +1,1914 @@
+use mnl_sys::libc;
+use std::{
+    collections::HashMap,
+    fmt, io,
+    net::{Ipv4Addr, Ipv6Addr},
+};
+
+use super::{
+    Chain, Data, Expr, Family, NF_INET_FORWARD, NF_INET_INGRESS, NF_INET_LOCAL_IN,
+    NF_INET_LOCAL_OUT, NF_INET_POST_ROUTING, NF_INET_PRE_ROUTING, NF_NETDEV_EGRESS,
+    NF_NETDEV_INGRESS, NFGENMSG_LEN, NFT_SET_ANONYMOUS, NFT_SET_CONSTANT,
+    NFT_SET_ELEM_INTERVAL_END, NFT_SET_EVAL, NFT_SET_INTERVAL, NFT_SET_TIMEOUT,
+    NFT_TABLE_F_DORMANT, Policy, Rule, Set, SetElement, Table, Verdict, chains, expr::*, get_one,
+    rules, set_elements, sets, sys, tables,
+};
+use crate::{NlMsg, Socket};
+
+/// How often to dump the ruleset again when it changed while it was dumped.
+const MAX_DUMP_ATTEMPTS: usize = 10;
+
+/// The data type `nft` gives verdict maps.
+const NFT_DATA_VERDICT: u32 = 0xffff_ff00;
+
+/// A snapshot of all tables, chains, sets and rules.
+///
+/// The [`Display`] implementation renders the ruleset like `nft list ruleset`. Expressions
+/// that can't be translated back to `nft` syntax are rendered the way `nft --debug=netlink`
+/// prints them.
+///
+/// [`Display`]: fmt::Display
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Ruleset {
+    /// All tables.
+    pub tables: Vec<Table>,
+    /// The chains of all tables.
+    pub chains: Vec<Chain>,
+    /// The sets and maps of all tables, including anonymous ones, with their elements.
+    pub sets: Vec<(Set, Vec<SetElement>)>,
+    /// The rules of all chains, in order.
+    pub rules: Vec<Rule>,
+}
+
+/// Dump the whole ruleset with `NFT_MSG_GET*` requests. `socket` must be a [`Bus::Netfilter`]
+/// socket.
+///
+/// The ruleset is dumped again if it changed in between, as told by the generation id of the
+/// ruleset or by a set disappearing before its elements were dumped. Fails with `EINTR` if it
+/// keeps changing.
+///
+/// [`Bus::Netfilter`]: crate::Bus::Netfilter
+pub fn ruleset(socket: &Socket) -> io::Result<Ruleset> {
+    'dump: for _ in 0..MAX_DUMP_ATTEMPTS {
+        let before = generation(socket)?;
+        let mut ruleset = Ruleset {
+            tables: tables(socket)?,
+            chains: chains(socket)?,
+            sets: Vec::new(),
+            rules: rules(socket)?,
+        };
+        for set in sets(socket)? {
+            // A set deleted since the sets were dumped means the ruleset changed.
+            let elements = match set_elements(socket, &set) {
+                Err(error) if error.raw_os_error() == Some(libc::ENOENT) => continue 'dump,
+                result => result?,
+            };
+            ruleset.sets.push((set, elements));
+        }
+        if generation(socket)? == before {
+            return Ok(ruleset);
+        }
+    }
+    Err(io::Error::from_raw_os_error(libc::EINTR))
+}
+
+/// Returns the generation id of the ruleset, which changes with every transaction.
+fn generation(socket: &Socket) -> io::Result<u32> {
+    get_one(
+        socket,
+        sys::NFT_MSG_GETGEN,
+        libc::NFPROTO_UNSPEC as u8,
+        |_| Ok(()),
+        |msg: &NlMsg<'_>| {
+            for attr in msg.attrs(NFGENMSG_LEN) {
+                let attr = attr?;
+                if attr.attr_type() == sys::NFTA_GEN_ID {
+                    return attr.get_be32();
+                }
+            }
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "NFT_MSG_NEWGEN without generation id",
+            ))
+        },
+    )
+}
+
+impl Ruleset {
+    fn fmt_set(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        set: &Set,
+        elements: &[SetElement],
+    ) -> fmt::Result {
+        let kind = if set.is_map() { "map" } else { "set" };
+        writeln!(f, "\t{kind} {} {{", set.name)?;
+        write!(f, "\t\ttype {}", type_name(set.key_type))?;
+        if let Some(data_type) = set.data_type.filter(|_| set.is_map()) {
+            write!(f, " : {}", type_name(data_type))?;
+        }
+        writeln!(f)?;
+        let flags: Vec<&str> = [
+            (NFT_SET_CONSTANT, "constant"),
+            (NFT_SET_INTERVAL, "interval"),
+            (NFT_SET_TIMEOUT, "timeout"),
+            (NFT_SET_EVAL, "dynamic"),
+        ]
+        .into_iter()
+        .filter(|(flag, _)| set.flags & flag != 0)
+        .map(|(_, name)| name)
+        .collect();
+        if !flags.is_empty() {
+            writeln!(f, "\t\tflags {}", flags.join(","))?;
+        }
+        if let Some(timeout) = set.timeout {
+            writeln!(f, "\t\ttimeout {}", Duration(timeout))?;
+        }
+        if let Some(gc_interval) = set.gc_interval {
+            writeln!(f, "\t\tgc-interval {}", Duration(gc_interval.into()))?;
+        }
+        if let Some(size) = set.size {
+            writeln!(f, "\t\tsize {size}")?;
+        }
+        if !elements.is_empty() {
+            writeln!(
+                f,
+                "\t\telements = {}",
+                format_elements(set, elements, Kind::Raw)
+            )?;
+        }
+        writeln!(f, "\t}}")
+    }
+
+    fn fmt_chain(&self, f: &mut fmt::Formatter<'_>, chain: &Chain) -> fmt::Result {
+        writeln!(f, "\tchain {} {{", chain.name)?;
+        if let Some(hook) = &chain.hook {
+            write!(
+                f,
+                "\t\ttype {} hook {}",
+                chain.chain_type.as_deref().unwrap_or("filter"),
+                hook_name(chain.family, hook.hooknum)
+            )?;
+            match hook.devices.as_slice() {
+                [] => (),
+                [device] => write!(f, " device \"{device}\"")?,
+                devices => write!(f, " devices = {{ {} }}", devices.join(", "))?,
+            }
+            write!(
+                f,
+                " priority {};",
+                priority_name(chain.family, hook.priority)
+            )?;
+            match chain.policy {
+                Some(Policy::Accept) => write!(f, " policy accept;")?,
+                Some(Policy::Drop) => write!(f, " policy drop;")?,
+                Some(Policy::Other(_)) | None => (),
+            }
+            writeln!(f)?;
+        }
+        if let Some(comment) = chain.userdata.as_deref().and_then(comment) {
+            writeln!(f, "\t\tcomment \"{comment}\"")?;
+        }
+        for rule in self
+            .rules
+            .iter()
+            .filter(|rule| rule.family == chain.family && rule.table == chain.table)
+            .filter(|rule| rule.chain == chain.name)
+        {
+            writeln!(
+                f,
+                "\t\t{}",
+                RuleFormatter::new(rule, &self.sets).format(rule)
+            )?;
+        }
+        writeln!(f, "\t}}")
+    }
+}
+
+impl fmt::Display for Ruleset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, table) in self.tables.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            let in_table =
+                |family: Family, name: &str| family == table.family && name == table.name;
+            writeln!(f, "table {} {} {{", table.family, table.name)?;
+            if table.flags & NFT_TABLE_F_DORMANT != 0 {
+                writeln!(f, "\tflags dormant")?;
+            }
+            if let Some(comment) = table.userdata.as_deref().and_then(comment) {
+                writeln!(f, "\tcomment \"{comment}\"")?;
+            }
+            let mut first = true;
+            for (set, elements) in &self.sets {
+                if in_table(set.family, &set.table) && set.flags & NFT_SET_ANONYMOUS == 0 {
+                    if !first {
+                        writeln!(f)?;
+                    }
+                    first = false;
+                    self.fmt_set(f, set, elements)?;
+                }
+            }
+            for chain in &self.chains {
+                if in_table(chain.family, &chain.table) {
+                    if !first {
+                        writeln!(f)?;
+                    }
+                    first = false;
+                    self.fmt_chain(f, chain)?;
+                }
+            }
+            writeln!(f, "}}")?;
+        }
+        Ok(())
+    }
+}
+
+/// How to format a value, depending on what it was compared with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Proto,
+    NfProto,
+    IfName,
+    Mark,
+    Port,
+    Ipv4,
+    Ipv6,
+    Addr,
+    Ether,
+    EtherType,
+    CtState,
+    CtStatus,
+    CtDir,
+    PktType,
+    FibType,
+    Bool,
+    /// An integer in host byte order.
+    Int,
+    /// An integer in network byte order.
+    BeInt,
+    Raw,
+}
+
+/// What a rule loaded into a register.
+#[derive(Debug, Clone)]
+enum Reg {
+    /// A packet field, meta key or similar, as written in `nft` syntax.
+    Field { name: String, kind: Kind },
+    /// A field masked with `mask` and xored with `xor`.
+    Masked {
+        name: String,
+        kind: Kind,
+        mask: Vec<u8>,
+        xor: Vec<u8>,
+    },
+    /// A constant.
+    Value(Vec<u8>),
+}
+
+/// Statements `nft` adds implicitly, which it leaves out again when listing a rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dependency {
+    /// The network protocol, a dependency of network header fields.
+    Network,
+    /// The transport protocol, a dependency of transport header fields.
+    Transport,
+}
+
+/// Translates the expressions of a rule back to `nft` syntax, by tracking what is loaded into
+/// the registers.
+struct RuleFormatter<'a> {
+    family: Family,
+    table: &'a str,
+    sets: &'a [(Set, Vec<SetElement>)],
+    regs: HashMap<u32, Reg>,
+    /// The `NFPROTO_*` value matched so far, implied by the family for ip and ip6 tables.
+    nfproto: Option<u8>,
+    /// The layer 4 protocol matched so far.
+    l4proto: Option<u8>,
+    /// Dependencies that are left out if a later field implies them.
+    pending: Vec<(Dependency, String)>,
+    statements: Vec<String>,
+}
+
+impl<'a> RuleFormatter<'a> {
+    fn new(rule: &'a Rule, sets: &'a [(Set, Vec<SetElement>)]) -> Self {
+        RuleFormatter {
+            family: rule.family,
+            table: &rule.table,
+            sets,
+            regs: HashMap::new(),
+            nfproto: match rule.family {
+                Family::Ipv4 => Some(libc::NFPROTO_IPV4 as u8),
+                Family::Ipv6 => Some(libc::NFPROTO_IPV6 as u8),
+                _ => None,
+            },
+            l4proto: None,
+            pending: Vec::new(),
+            statements: Vec::new(),
+        }
+    }
+
+    fn format(mut self, rule: &Rule) -> String {
+        for expr in &rule.exprs {
+            self.expr(expr);
+        }
+        self.flush();
+        if let Some(comment) = rule.userdata.as_deref().and_then(comment) {
+            self.statements.push(format!("comment \"{comment}\""));
+        }
+        self.statements.join(" ")
+    }
+
+    /// Add a statement, after the dependencies that turned out not to be implied.
+    fn push(&mut self, statement: String) {
+        self.flush();
+        self.statements.push(statement);
+    }
+
+    fn flush(&mut self) {
+        let pending = std::mem::take(&mut self.pending);
+        self.statements
+            .extend(pending.into_iter().map(|(_, statement)| statement));
+    }
+
+    /// Add an expression that can't be translated as it is printed by `nft --debug=netlink`.
+    fn raw(&mut self, expr: &Expr) {
+        self.push(expr.to_string());
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Payload(payload) => {
+                let (name, kind, dependency) = self.payload_field(payload);
+                if let Some(sreg) = payload.sreg {
+                    let value = self.operand(sreg, kind);
+                    self.push(format!("{name} set {value}"));
+                    return;
+                }
+                if let Some(dependency) = dependency {
+                    self.pending.retain(|(pending, _)| *pending != dependency);
+                }
+                self.load(payload.dreg, name, kind);
+            }
+            Expr::Meta(meta) => {
+                let key = match meta.key {
+                    NFT_META_PRANDOM => "random",
+                    key => meta_key_name(key),
+                };
+                let name = match meta.key {
+                    NFT_META_IIF | NFT_META_OIF | NFT_META_IIFNAME | NFT_META_OIFNAME
+                    | NFT_META_IIFGROUP | NFT_META_OIFGROUP => key.to_owned(),
+                    _ => format!("meta {key}"),
+                };
+                let kind = match meta.key {
+                    NFT_META_L4PROTO => Kind::Proto,
+                    NFT_META_NFPROTO => Kind::NfProto,
+                    NFT_META_PROTOCOL => Kind::EtherType,
+                    NFT_META_IIFNAME | NFT_META_OIFNAME | NFT_META_BRI_IIFNAME
+                    | NFT_META_BRI_OIFNAME | NFT_META_IIFKIND | NFT_META_OIFKIND
+                    | NFT_META_SDIFNAME => Kind::IfName,
+                    NFT_META_MARK => Kind::Mark,
+                    NFT_META_PKTTYPE => Kind::PktType,
+                    _ => Kind::Int,
+                };
+                match meta.sreg {
+                    Some(sreg) => {
+                        let value = self.operand(sreg, kind);
+                        self.push(format!("{name} set {value}"));
+                    }
+                    None => self.load(meta.dreg, name, kind),
+                }
+            }
+            Expr::Ct(ct) => {
+                let key = match ct.key {
+                    NFT_CT_L3PROTOCOL => "l3proto",
+                    NFT_CT_SRC => "saddr",
+                    NFT_CT_DST => "daddr",
+                    NFT_CT_PROTO_SRC => "proto-src",
+                    NFT_CT_PROTO_DST => "proto-dst",
+                    NFT_CT_SRC_IP => "ip saddr",
+                    NFT_CT_DST_IP => "ip daddr",
+                    NFT_CT_SRC_IP6 => "ip6 saddr",
+                    NFT_CT_DST_IP6 => "ip6 daddr",
+                    NFT_CT_ID => "id",
+                    key => ct_key_name(key),
+                };
+                let name = match ct.direction {
+                    Some(IP_CT_DIR_ORIGINAL) => format!("ct original {key}"),
+                    Some(IP_CT_DIR_REPLY) => format!("ct reply {key}"),
+                    _ => format!("ct {key}"),
+                };
+                let kind = match ct.key {
+                    NFT_CT_STATE => Kind::CtState,
+                    NFT_CT_STATUS => Kind::CtStatus,
+                    NFT_CT_DIRECTION => Kind::CtDir,
+                    NFT_CT_MARK => Kind::Mark,
+                    NFT_CT_PROTOCOL => Kind::Proto,
+                    NFT_CT_L3PROTOCOL => Kind::NfProto,
+                    NFT_CT_PROTO_SRC | NFT_CT_PROTO_DST => Kind::Port,
+                    NFT_CT_SRC_IP | NFT_CT_DST_IP => Kind::Ipv4,
+                    NFT_CT_SRC_IP6 | NFT_CT_DST_IP6 => Kind::Ipv6,
+                    NFT_CT_SRC | NFT_CT_DST => Kind::Addr,
+                    NFT_CT_HELPER => Kind::IfName,
+                    _ => Kind::Int,
+                };
+                match ct.sreg {
+                    Some(sreg) => {
+                        let value = self.operand(sreg, kind);
+                        self.push(format!("{name} set {value}"));
+                    }
+                    None => self.load(ct.dreg, name, kind),
+                }
+            }
+            Expr::Fib(fib) => {
+                let mut name = String::from("fib ");
+                let inputs: Vec<&str> = [
+                    (NFTA_FIB_F_SADDR, "saddr"),
+                    (NFTA_FIB_F_DADDR, "daddr"),
+                    (NFTA_FIB_F_MARK, "mark"),
+                    (NFTA_FIB_F_IIF, "iif"),
+                    (NFTA_FIB_F_OIF, "oif"),
+                ]
+                .into_iter()
+                .filter(|(flag, _)| fib.flags & flag != 0)
+                .map(|(_, name)| name)
+                .collect();
+                name.push_str(&inputs.join(" . "));
+                let (result, kind) = match fib.result {
+                    NFT_FIB_RESULT_OIF => ("oif", Kind::Int),
+                    NFT_FIB_RESULT_OIFNAME => ("oifname", Kind::IfName),
+                    NFT_FIB_RESULT_ADDRTYPE => ("type", Kind::FibType),
+                    _ => ("unspec", Kind::Raw),
+                };
+                name.push(' ');
+                name.push_str(result);
+                let kind = if fib.flags & NFTA_FIB_F_PRESENT != 0 {
+                    Kind::Bool
+                } else {
+                    kind
+                };
+                self.load(Some(fib.dreg), name, kind);
+            }
+            Expr::Socket(socket) => {
+                let (name, kind) = match socket.key {
+                    NFT_SOCKET_TRANSPARENT => ("socket transparent".to_owned(), Kind::Int),
+                    NFT_SOCKET_MARK => ("socket mark".to_owned(), Kind::Mark),
+                    NFT_SOCKET_WILDCARD => ("socket wildcard".to_owned(), Kind::Int),
+                    NFT_SOCKET_CGROUPV2 => (
+                        format!("socket cgroupv2 level {}", socket.level.unwrap_or(0)),
+                        Kind::Int,
+                    ),
+                    _ => return self.raw(expr),
+                };
+                self.load(Some(socket.dreg), name, kind);
+            }
+            Expr::Immediate(immediate) => match &immediate.data {
+                Data::Verdict(verdict) => self.push(verdict_str(verdict)),
+                Data::Value(value) => {
+                    self.regs.insert(immediate.dreg, Reg::Value(value.clone()));
+                }
+            },
+            Expr::Bitwise(bitwise) => match self.regs.get(&bitwise.sreg) {
+                Some(Reg::Field { name, kind })
+                    if bitwise.op == NFT_BITWISE_BOOL
+                        && bitwise.mask.is_some()
+                        && bitwise.xor.is_some() =>
+                {
+                    let reg = Reg::Masked {
+                        name: name.clone(),
+                        kind: *kind,
+                        mask: bitwise.mask.clone().unwrap_or_default(),
+                        xor: bitwise.xor.clone().unwrap_or_default(),
+                    };
+                    self.regs.insert(bitwise.dreg, reg);
+                }
+                _ => {
+                    self.regs.remove(&bitwise.dreg);
+                    self.raw(expr);
+                }
+            },
+            Expr::Cmp(cmp) => self.cmp(expr, cmp),
+            Expr::Lookup(lookup) => self.lookup(expr, lookup),
+            Expr::Counter(counter) => self.push(format!(
+                "counter packets {} bytes {}",
+                counter.packets, counter.bytes
+            )),
+            Expr::Log(log) => {
+                let mut statement = String::from("log");
+                if let Some(prefix) = &log.prefix {
+                    statement.push_str(&format!(" prefix \"{prefix}\""));
+                }
+                if let Some(group) = log.group {
+                    statement.push_str(&format!(" group {group}"));
+                }
+                if let Some(snaplen) = log.snaplen {
+                    statement.push_str(&format!(" snaplen {snaplen}"));
+                }
+                if let Some(qthreshold) = log.qthreshold {
+                    statement.push_str(&format!(" queue-threshold {qthreshold}"));
+                }
+                // The kernel fills in warn as the default level.
+                if let Some(level) = log.level.filter(|level| *level != 4) {
+                    const LEVELS: [&str; 9] = [
+                        "emerg", "alert", "crit", "err", "warn", "notice", "info", "debug", "audit",
+                    ];
+                    match LEVELS.get(level as usize) {
+                        Some(name) => statement.push_str(&format!(" level {name}")),
+                        None => statement.push_str(&format!(" level {level}")),
+                    }
+                }
+                self.push(statement);
+            }
+            Expr::Nat(nat) => {
+                let mut statement = match nat.nat_type {
+                    NFT_NAT_SNAT => String::from("snat"),
+                    NFT_NAT_DNAT => String::from("dnat"),
+                    _ => return self.raw(expr),
+                };
+                if self.family == Family::Inet {
+                    // The address family implies the match on it.
+                    self.pending
+                        .retain(|(pending, _)| *pending != Dependency::Network);
+                    statement.push_str(&format!(" {}", nat.family));
+                }
+                let kind = match nat.family {
+                    Family::Ipv4 => Kind::Ipv4,
+                    Family::Ipv6 => Kind::Ipv6,
+                    _ => Kind::Addr,
+                };
+                let mut to = String::new();
+                if let Some(min) = nat.reg_addr_min {
+                    let addr = self.range(min, nat.reg_addr_max, kind);
+                    if nat.family == Family::Ipv6 && nat.reg_proto_min.is_some() {
+                        to.push_str(&format!("[{addr}]"));
+                    } else {
+                        to.push_str(&addr);
+                    }
+                }
+                if let Some(min) = nat.reg_proto_min {
+                    to.push(':');
+                    to.push_str(&self.range(min, nat.reg_proto_max, Kind::Port));
+                }
+                if !to.is_empty() {
+                    statement.push_str(&format!(" to {to}"));
+                }
+                statement.push_str(&nat_flags(nat.flags.unwrap_or(0)));
+                self.push(statement);
+            }
+            Expr::Masq(masq) => {
+                let mut statement = String::from("masquerade");
+                if let Some(min) = masq.reg_proto_min {
+                    let ports = self.range(min, masq.reg_proto_max, Kind::Port);
+                    statement.push_str(&format!(" to :{ports}"));
+                }
+                statement.push_str(&nat_flags(masq.flags.unwrap_or(0)));
+                self.push(statement);
+            }
+            Expr::Reject(reject) => {
+                let statement = match reject.reject_type {
+                    NFT_REJECT_TCP_RST => {
+                        self.pending
+                            .retain(|(pending, _)| *pending != Dependency::Transport);
+                        "reject with tcp reset".to_owned()
+                    }
+                    NFT_REJECT_ICMPX_UNREACH => match reject.icmp_code {
+                        NFT_REJECT_ICMPX_PORT_UNREACH => "reject".to_owned(),
+                        code => format!("reject with icmpx {}", icmpx_code_name(code)),
+                    },
+                    NFT_REJECT_ICMP_UNREACH => {
+                        self.pending
+                            .retain(|(pending, _)| *pending != Dependency::Network);
+                        match (self.nfproto.map(i32::from), reject.icmp_code) {
+                            (Some(libc::NFPROTO_IPV6), 4) if self.family == Family::Ipv6 => {
+                                "reject".to_owned()
+                            }
+                            (Some(libc::NFPROTO_IPV6), code) => {
+                                format!("reject with icmpv6 {}", icmpv6_code_name(code))
+                            }
+                            (_, 3) if self.family == Family::Ipv4 => "reject".to_owned(),
+                            (_, code) => format!("reject with icmp {}", icmp_code_name(code)),
+                        }
+                    }
+                    _ => return self.raw(expr),
+                };
+                self.push(statement);
+            }
+            Expr::Limit(limit) => {
+                let over = if limit.flags & NFT_LIMIT_F_INV != 0 {
+                    "over "
+                } else {
+                    ""
+                };
+                let unit = time_unit_name(limit.unit);
+                let mut statement = if limit.limit_type == NFT_LIMIT_PKT_BYTES {
+                    format!("limit rate {over}{} bytes/{unit}", limit.rate)
+                } else {
+                    format!("limit rate {over}{}/{unit}", limit.rate)
+                };
+                if limit.limit_type == NFT_LIMIT_PKT_BYTES && limit.burst != 0 {
+                    statement.push_str(&format!(" burst {} bytes", limit.burst));
+                } else if limit.limit_type != NFT_LIMIT_PKT_BYTES && limit.burst != 5 {
+                    statement.push_str(&format!(" burst {} packets", limit.burst));
+                }
+                self.push(statement);
+            }
+            Expr::Other(_) => self.raw(expr),
+        }
+    }
+
+    fn load(&mut self, dreg: Option<u32>, name: String, kind: Kind) {
+        if let Some(dreg) = dreg {
+            self.regs.insert(dreg, Reg::Field { name, kind });
+        }
+    }
+
+    /// Returns the name of a payload field in `nft` syntax and how to format its values, and
+    /// the dependency the field implies.
+    fn payload_field(&self, payload: &PayloadExpr) -> (String, Kind, Option<Dependency>) {
+        let field = match (payload.base, payload.offset, payload.len) {
+            (NFT_PAYLOAD_LL_HEADER, 0, 6) => Some(("ether daddr".to_owned(), Kind::Ether, None)),
+            (NFT_PAYLOAD_LL_HEADER, 6, 6) => Some(("ether saddr".to_owned(), Kind::Ether, None)),
+            (NFT_PAYLOAD_LL_HEADER, 12, 2) => {
+                Some(("ether type".to_owned(), Kind::EtherType, None))
+            }
+            (NFT_PAYLOAD_NETWORK_HEADER, offset, len) => {
+                let field = match (self.nfproto.map(i32::from), offset, len) {
+                    (Some(libc::NFPROTO_IPV4), 2, 2) => Some(("ip length", Kind::BeInt)),
+                    (Some(libc::NFPROTO_IPV4), 8, 1) => Some(("ip ttl", Kind::BeInt)),
+                    (Some(libc::NFPROTO_IPV4), 9, 1) => Some(("ip protocol", Kind::Proto)),
+                    (Some(libc::NFPROTO_IPV4), 12, 4) => Some(("ip saddr", Kind::Ipv4)),
+                    (Some(libc::NFPROTO_IPV4), 16, 4) => Some(("ip daddr", Kind::Ipv4)),
+                    (Some(libc::NFPROTO_IPV6), 6, 1) => Some(("ip6 nexthdr", Kind::Proto)),
+                    (Some(libc::NFPROTO_IPV6), 7, 1) => Some(("ip6 hoplimit", Kind::BeInt)),
+                    (Some(libc::NFPROTO_IPV6), 8, 16) => Some(("ip6 saddr", Kind::Ipv6)),
+                    (Some(libc::NFPROTO_IPV6), 24, 16) => Some(("ip6 daddr", Kind::Ipv6)),
+                    _ => None,
+                };
+                field.map(|(name, kind)| (name.to_owned(), kind, Some(Dependency::Network)))
+            }
+            (NFT_PAYLOAD_TRANSPORT_HEADER, offset, len) => {
+                let proto = match self.l4proto {
+                    Some(6) => Some("tcp"),
+                    Some(17) => Some("udp"),
+                    Some(33) => Some("dccp"),
+                    Some(132) => Some("sctp"),
+                    Some(136) => Some("udplite"),
+                    _ => None,
+                };
+                match (proto, self.l4proto, offset, len) {
+                    (Some(proto), _, 0, 2) => Some((format!("{proto} sport"), Kind::Port)),
+                    (Some(proto), _, 2, 2) => Some((format!("{proto} dport"), Kind::Port)),
+                    (None, Some(1), 0, 1) => Some(("icmp type".to_owned(), Kind::BeInt)),
+                    (None, Some(1), 1, 1) => Some(("icmp code".to_owned(), Kind::BeInt)),
+                    (None, Some(58), 0, 1) => Some(("icmpv6 type".to_owned(), Kind::BeInt)),
+                    (None, Some(58), 1, 1) => Some(("icmpv6 code".to_owned(), Kind::BeInt)),
+                    (None, _, 0, 2) => Some(("th sport".to_owned(), Kind::Port)),
+                    (None, _, 2, 2) => Some(("th dport".to_owned(), Kind::Port)),
+                    _ => None,
+                }
+                .map(|(name, kind)| {
+                    let dependency = self.l4proto.map(|_| Dependency::Transport);
+                    (name, kind, dependency)
+                })
+            }
+            _ => None,
+        };
+        field.unwrap_or_else(|| {
+            let base = match payload.base {
+                NFT_PAYLOAD_LL_HEADER => "ll",
+                NFT_PAYLOAD_NETWORK_HEADER => "nh",
+                NFT_PAYLOAD_TRANSPORT_HEADER => "th",
+                _ => "ih",
+            };
+            let name = format!("@{base},{},{}", payload.offset * 8, payload.len * 8);
+            (name, Kind::Raw, None)
+        })
+    }
+
+    /// Returns what a statement uses from register `reg`, formatting constants as `kind`.
+    fn operand(&self, reg: u32, kind: Kind) -> String {
+        match self.regs.get(&reg) {
+            Some(Reg::Value(value)) => format_value(kind, value),
+            Some(Reg::Field { name, .. }) => name.clone(),
+            Some(Reg::Masked { name, mask, .. }) => {
+                format!("{name} & {}", format_value(Kind::Raw, mask))
+            }
+            None => format!("reg {reg}"),
+        }
+    }
+
+    /// Returns the range loaded into the registers `min` and `max`, or a single value if they
+    /// are the same.
+    fn range(&self, min: u32, max: Option<u32>, kind: Kind) -> String {
+        let first = self.operand(min, kind);
+        match max.map(|max| self.operand(max, kind)) {
+            Some(last) if last != first => format!("{first}-{last}"),
+            _ => first,
+        }
+    }
+
+    fn cmp(&mut self, expr: &Expr, cmp: &CmpExpr) {
+        let op = match cmp.op {
+            NFT_CMP_EQ => "",
+            NFT_CMP_NEQ => "!= ",
+            NFT_CMP_LT => "< ",
+            NFT_CMP_LTE => "<= ",
+            NFT_CMP_GT => "> ",
+            NFT_CMP_GTE => ">= ",
+            _ => return self.raw(expr),
+        };
+        match self.regs.get(&cmp.sreg).cloned() {
+            Some(Reg::Field { name, kind }) => {
+                let statement = format!("{name} {op}{}", format_value(kind, &cmp.data));
+                // Remember the protocols matched, and leave the match out if a later field
+                // implies it.
+                if cmp.op == NFT_CMP_EQ && cmp.data.len() == 1 {
+                    match name.as_str() {
+                        "meta l4proto" | "ip protocol" | "ip6 nexthdr" => {
+                            self.l4proto = Some(cmp.data[0]);
+                            self.pending.push((Dependency::Transport, statement));
+                            return;
+                        }
+                        "meta nfproto" => {
+                            self.nfproto = Some(cmp.data[0]);
+                            self.pending.push((Dependency::Network, statement));
+                            return;
+                        }
+                        _ => (),
+                    }
+                }
+                if name == "meta protocol" || name == "ether type" {
+                    let nfproto = match cmp.data.as_slice() {
+                        [0x08, 0x00] => Some(libc::NFPROTO_IPV4 as u8),
+                        [0x86, 0xdd] => Some(libc::NFPROTO_IPV6 as u8),
+                        _ => None,
+                    };
+                    if cmp.op == NFT_CMP_EQ && nfproto.is_some() {
+                        self.nfproto = nfproto;
+                        self.pending.push((Dependency::Network, statement));
+                        return;
+                    }
+                }
+                self.push(statement);
+            }
+            Some(Reg::Masked {
+                name,
+                kind,
+                mask,
+                xor,
+            }) if xor.iter().all(|byte| *byte == 0) => {
+                let zero = cmp.data.iter().all(|byte| *byte == 0);
+                let statement = match kind {
+                    Kind::CtState | Kind::CtStatus if zero && cmp.op == NFT_CMP_NEQ => {
+                        format!("{name} {}", format_value(kind, &mask))
+                    }
+                    Kind::CtState | Kind::CtStatus if zero && cmp.op == NFT_CMP_EQ => {
+                        format!("{name} != {}", format_value(kind, &mask))
+                    }
+                    Kind::Ipv4 | Kind::Ipv6 | Kind::Addr
+                        if matches!(cmp.op, NFT_CMP_EQ | NFT_CMP_NEQ)
+                            && prefix_len(&mask).is_some() =>
+                    {
+                        format!(
+                            "{name} {op}{}/{}",
+                            format_value(kind, &cmp.data),
+                            prefix_len(&mask).unwrap_or(0)
+                        )
+                    }
+                    _ => {
+                        let op = match cmp.op {
+                            NFT_CMP_EQ => "== ",
+                            _ => op,
+                        };
+                        let mask_kind = match kind {
+                            Kind::Mark | Kind::CtState | Kind::CtStatus => kind,
+                            _ => Kind::Raw,
+                        };
+                        format!(
+                            "{name} & {} {op}{}",
+                            format_value(mask_kind, &mask),
+                            format_value(kind, &cmp.data)
+                        )
+                    }
+                };
+                self.push(statement);
+            }
+            _ => self.raw(expr),
+        }
+    }
+
+    fn lookup(&mut self, expr: &Expr, lookup: &LookupExpr) {
+        let (name, kind) = match self.regs.get(&lookup.sreg) {
+            Some(Reg::Field { name, kind }) => (name.clone(), *kind),
+            _ => return self.raw(expr),
+        };
+        let Some((set, elements)) = self.sets.iter().find(|(set, _)| {
+            set.family == self.family && set.table == self.table && set.name == lookup.set
+        }) else {
+            return self.raw(expr);
+        };
+        let set_ref = if set.flags & NFT_SET_ANONYMOUS != 0 {
+            format_elements(set, elements, kind)
+        } else {
+            format!("@{}", set.name)
+        };
+        let op = if lookup.flags & NFT_LOOKUP_F_INV != 0 {
+            "!= "
+        } else {
+            ""
+        };
+        match lookup.dreg {
+            None => self.push(format!("{name} {op}{set_ref}")),
+            Some(NFT_REG_VERDICT) => self.push(format!("{name} vmap {set_ref}")),
+            Some(dreg) => {
+                let kind = set.data_type.map_or(Kind::Raw, type_kind);
+                let name = format!("{name} map {set_ref}");
+                self.regs.insert(dreg, Reg::Field { name, kind });
+            }
+        }
+    }
+}
+
+/// Format the elements of `set` as `{ a, b }`, formatting keys by the key type of the set, or
+/// as `kind` if the type is not known.
+fn format_elements(set: &Set, elements: &[SetElement], kind: Kind) -> String {
+    let key_kind = match type_kind(set.key_type) {
+        Kind::Raw => kind,
+        key_kind => key_kind,
+    };
+    let data_kind = set.data_type.map_or(Kind::Raw, type_kind);
+    let mut items = Vec::new();
+    if set.flags & NFT_SET_INTERVAL != 0 {
+        let mut starts: Vec<&SetElement> = elements
+            .iter()
+            .filter(|element| element.flags & NFT_SET_ELEM_INTERVAL_END == 0)
+            .collect();
+        starts.sort_by(|a, b| a.key.cmp(&b.key));
+        let mut ends: Vec<&[u8]> = elements
+            .iter()
+            .filter(|element| element.flags & NFT_SET_ELEM_INTERVAL_END != 0)
+            .map(|element| element.key.as_slice())
+            .collect();
+        ends.sort();
+        for start in starts {
+            // The end element is the first key after the range.
+            let last = match &start.key_end {
+                Some(key_end) => key_end.clone(),
+                None => match ends.iter().find(|end| **end > start.key.as_slice()) {
+                    Some(end) => decrement(end),
+                    None => vec![0xff; start.key.len()],
+                },
+            };
+            let mut item = match prefix_of(&start.key, &last) {
+                _ if last == start.key => format_value(key_kind, &start.key),
+                Some(len) if len > 0 && matches!(key_kind, Kind::Ipv4 | Kind::Ipv6) => {
+                    format!("{}/{len}", format_value(key_kind, &start.key))
+                }
+                _ => format!(
+                    "{}-{}",
+                    format_value(key_kind, &start.key),
+                    format_value(key_kind, &last)
+                ),
+            };
+            if let Some(data) = &start.data {
+                item.push_str(&format!(" : {}", format_data(data_kind, data)));
+            }
+            items.push(item);
+        }
+    } else {
+        for element in elements {
+            let mut item = format_value(key_kind, &element.key);
+            if let Some(data) = &element.data {
+                item.push_str(&format!(" : {}", format_data(data_kind, data)));
+            }
+            items.push(item);
+        }
+    }
+    format!("{{ {} }}", items.join(", "))
+}
+
+fn format_data(kind: Kind, data: &Data) -> String {
+    match data {
+        Data::Value(value) => format_value(kind, value),
+        Data::Verdict(verdict) => verdict_str(verdict),
+    }
+}
+
+fn verdict_str(verdict: &Verdict) -> String {
+    match verdict {
+        Verdict::Accept => "accept".to_owned(),
+        Verdict::Drop => "drop".to_owned(),
+        Verdict::Queue => "queue".to_owned(),
+        Verdict::Continue => "continue".to_owned(),
+        Verdict::Break => "break".to_owned(),
+        Verdict::Return => "return".to_owned(),
+        Verdict::Jump(chain) => format!("jump {chain}"),
+        Verdict::Goto(chain) => format!("goto {chain}"),
+        Verdict::Other(code) => format!("verdict {code}"),
+    }
+}
+
+/// Format a value compared with, or stored in a set, in `nft` syntax.
+fn format_value(kind: Kind, value: &[u8]) -> String {
+    let ne32 = || value.try_into().map(u32::from_ne_bytes);
+    match (kind, value) {
+        (Kind::Proto, [proto]) => match proto {
+            1 => "icmp".to_owned(),
+            2 => "igmp".to_owned(),
+            6 => "tcp".to_owned(),
+            17 => "udp".to_owned(),
+            33 => "dccp".to_owned(),
+            47 => "gre".to_owned(),
+            50 => "esp".to_owned(),
+            51 => "ah".to_owned(),
+            58 => "ipv6-icmp".to_owned(),
+            132 => "sctp".to_owned(),
+            136 => "udplite".to_owned(),
+            proto => proto.to_string(),
+        },
+        (Kind::NfProto, [proto]) => match i32::from(*proto) {
+            libc::NFPROTO_IPV4 => "ipv4".to_owned(),
+            libc::NFPROTO_IPV6 => "ipv6".to_owned(),
+            _ => proto.to_string(),
+        },
+        (Kind::IfName, _) => {
+            // Names compared without their NUL terminator are prefixes, written `eth*`.
+            let (name, wildcard) = match value.iter().position(|byte| *byte == 0) {
+                Some(end) => (&value[..end], ""),
+                None => (value, "*"),
+            };
+            format!("\"{}{wildcard}\"", String::from_utf8_lossy(name))
+        }
+        (Kind::Mark, [_, _, _, _]) => format!("0x{:08x}", ne32().unwrap_or(0)),
+        (Kind::Port, [hi, lo]) => u16::from_be_bytes([*hi, *lo]).to_string(),
+        (Kind::Ipv4 | Kind::Addr, [a, b, c, d]) => Ipv4Addr::new(*a, *b, *c, *d).to_string(),
+        (Kind::Ipv6 | Kind::Addr, _) if value.len() == 16 => {
+            let octets: [u8; 16] = value.try_into().unwrap_or_default();
+            Ipv6Addr::from(octets).to_string()
+        }
+        (Kind::Ether, [_, _, _, _, _, _]) => value
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<Vec<_>>()
+            .join(":"),
+        (Kind::EtherType, [hi, lo]) => match u16::from_be_bytes([*hi, *lo]) {
+            0x0800 => "ip".to_owned(),
+            0x86dd => "ip6".to_owned(),
+            0x0806 => "arp".to_owned(),
+            0x8100 => "vlan".to_owned(),
+            ether_type => format!("0x{ether_type:04x}"),
+        },
+        (Kind::CtState, [_, _, _, _]) => format_flags(
+            ne32().unwrap_or(0),
+            &[
+                (NF_CT_STATE_INVALID_BIT, "invalid"),
+                (NF_CT_STATE_ESTABLISHED_BIT, "established"),
+                (NF_CT_STATE_RELATED_BIT, "related"),
+                (NF_CT_STATE_NEW_BIT, "new"),
+                (NF_CT_STATE_UNTRACKED_BIT, "untracked"),
+            ],
+        ),
+        (Kind::CtStatus, [_, _, _, _]) => format_flags(
+            ne32().unwrap_or(0),
+            &[
+                (1 << 0, "expected"),
+                (1 << 1, "seen-reply"),
+                (1 << 2, "assured"),
+                (1 << 3, "confirmed"),
+                (1 << 4, "snat"),
+                (1 << 5, "dnat"),
+                (1 << 9, "dying"),
+            ],
+        ),
+        (Kind::CtDir, [0]) => "original".to_owned(),
+        (Kind::CtDir, [1]) => "reply".to_owned(),
+        (Kind::PktType, [pkttype]) => match pkttype {
+            0 => "host".to_owned(),
+            1 => "broadcast".to_owned(),
+            2 => "multicast".to_owned(),
+            3 => "other".to_owned(),
+            pkttype => pkttype.to_string(),
+        },
+        (Kind::FibType, [_, _, _, _]) => {
+            const TYPES: [&str; 12] = [
+                "unspec",
+                "unicast",
+                "local",
+                "broadcast",
+                "anycast",
+                "multicast",
+                "blackhole",
+                "unreachable",
+                "prohibit",
+                "throw",
+                "nat",
+                "xresolve",
+            ];
+            let addrtype = ne32().unwrap_or(0);
+            TYPES
+                .get(addrtype as usize)
+                .map_or_else(|| addrtype.to_string(), |name| (*name).to_owned())
+        }
+        (Kind::Bool, _) if value.len() <= 4 => if value.iter().any(|byte| *byte != 0) {
+            "exists"
+        } else {
+            "missing"
+        }
+        .to_owned(),
+        (Kind::Int, [byte]) | (Kind::BeInt, [byte]) => byte.to_string(),
+        (Kind::Int, [_, _]) => u16::from_ne_bytes([value[0], value[1]]).to_string(),
+        (Kind::Int, [_, _, _, _]) => ne32().unwrap_or(0).to_string(),
+        (Kind::BeInt, [hi, lo]) => u16::from_be_bytes([*hi, *lo]).to_string(),
+        (Kind::BeInt, [_, _, _, _]) => value
+            .try_into()
+            .map(u32::from_be_bytes)
+            .unwrap_or(0)
+            .to_string(),
+        _ => {
+            let hex: String = value.iter().map(|byte| format!("{byte:02x}")).collect();
+            format!("0x{hex}")
+        }
+    }
+}
+
+fn format_flags(value: u32, names: &[(u32, &str)]) -> String {
+    let mut flags: Vec<String> = names
+        .iter()
+        .filter(|(flag, _)| value & flag != 0)
+        .map(|(_, name)| (*name).to_owned())
+        .collect();
+    let unknown = names.iter().fold(value, |value, (flag, _)| value & !flag);
+    if unknown != 0 || flags.is_empty() {
+        flags.push(format!("0x{unknown:x}"));
+    }
+    flags.join(",")
+}
+
+fn nat_flags(flags: u32) -> String {
+    [
+        (NF_NAT_RANGE_PROTO_RANDOM, " random"),
+        (NF_NAT_RANGE_PROTO_RANDOM_FULLY, " fully-random"),
+        (NF_NAT_RANGE_PERSISTENT, " persistent"),
+    ]
+    .into_iter()
+    .filter(|(flag, _)| flags & flag != 0)
+    .map(|(_, name)| name)
+    .collect()
+}
+
+/// Returns the prefix length of a network mask, or `None` if it is not a prefix.
+fn prefix_len(mask: &[u8]) -> Option<u32> {
+    let bits: u32 = mask.iter().map(|byte| byte.count_ones()).sum();
+    let mut rest = bits;
+    for byte in mask {
+        let expected = (0xff00u16 >> rest.min(8)) as u8;
+        if *byte != expected {
+            return None;
+        }
+        rest = rest.saturating_sub(8);
+    }
+    Some(bits)
+}
+
+/// Returns the prefix length if the range from `first` to `last` is a network prefix.
+fn prefix_of(first: &[u8], last: &[u8]) -> Option<u32> {
+    if first.len() != last.len() {
+        return None;
+    }
+    let mask: Vec<u8> = first.iter().zip(last).map(|(a, b)| !(a ^ b)).collect();
+    let len = prefix_len(&mask)?;
+    let host_bits_clear = first
+        .iter()
+        .zip(&mask)
+        .all(|(byte, mask)| byte & !mask == 0);
+    let host_bits_set = last
+        .iter()
+        .zip(&mask)
+        .all(|(byte, mask)| byte | mask == 0xff);
+    (host_bits_clear && host_bits_set).then_some(len)
+}
+
+/// Subtract one from a big endian number.
+fn decrement(value: &[u8]) -> Vec<u8> {
+    let mut value = value.to_vec();
+    for byte in value.iter_mut().rev() {
+        let (result, borrow) = byte.overflowing_sub(1);
+        *byte = result;
+        if !borrow {
+            break;
+        }
+    }
+    value
+}
+
+/// Returns the comment `nft` stores in the userdata of tables, chains and rules.
+fn comment(userdata: &[u8]) -> Option<String> {
+    // A list of type, length and value. The comment has type 0 and is NUL terminated.
+    let mut rest = userdata;
+    while let [udata_type, len, tail @ ..] = rest {
+        let value = tail.get(..usize::from(*len))?;
+        if *udata_type == 0 {
+            let value = value.strip_suffix(&[0]).unwrap_or(value);
+            return Some(String::from_utf8_lossy(value).into_owned());
+        }
+        rest = &tail[usize::from(*len)..];
+    }
+    None
+}
+
+/// Data types of set keys and values, as numbered by `nft`.
+const TYPE_NAMES: [&str; 30] = [
+    "invalid",
+    "verdict",
+    "nf_proto",
+    "bitmask",
+    "integer",
+    "string",
+    "ll_addr",
+    "ipv4_addr",
+    "ipv6_addr",
+    "ether_addr",
+    "ether_type",
+    "arp_op",
+    "inet_proto",
+    "inet_service",
+    "icmp_type",
+    "tcp_flag",
+    "dccp_pkttype",
+    "mh_type",
+    "time",
+    "mark",
+    "iface_index",
+    "iface_type",
+    "realm",
+    "classid",
+    "uid",
+    "gid",
+    "ct_state",
+    "ct_dir",
+    "ct_status",
+    "icmpv6_type",
+];
+
+/// Returns the `nft` name of a data type. Types of concatenations hold the types of their parts
+/// in 6 bit groups.
+fn type_name(data_type: u32) -> String {
+    if data_type == NFT_DATA_VERDICT {
+        return "verdict".to_owned();
+    }
+    let mut parts = Vec::new();
+    let mut rest = data_type;
+    while rest != 0 {
+        parts.push(rest & 0x3f);
+        rest >>= 6;
+    }
+    parts
+        .iter()
+        .rev()
+        .map(|part| match TYPE_NAMES.get(*part as usize) {
+            Some(name) => (*name).to_owned(),
+            None => part.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" . ")
+}
+
+fn type_kind(data_type: u32) -> Kind {
+    match data_type {
+        2 => Kind::NfProto,
+        7 => Kind::Ipv4,
+        8 => Kind::Ipv6,
+        9 => Kind::Ether,
+        10 => Kind::EtherType,
+        12 => Kind::Proto,
+        13 => Kind::Port,
+        19 => Kind::Mark,
+        20 | 24 | 25 => Kind::Int,
+        26 => Kind::CtState,
+        27 => Kind::CtDir,
+        28 => Kind::CtStatus,
+        _ => Kind::Raw,
+    }
+}
+
+fn hook_name(family: Family, hooknum: u32) -> String {
+    let name = match (family, hooknum) {
+        (Family::Netdev, NF_NETDEV_INGRESS) => "ingress",
+        (Family::Netdev, NF_NETDEV_EGRESS) => "egress",
+        (Family::Arp, 0) => "input",
+        (Family::Arp, 1) => "output",
+        (Family::Arp, 2) => "forward",
+        (_, NF_INET_PRE_ROUTING) => "prerouting",
+        (_, NF_INET_LOCAL_IN) => "input",
+        (_, NF_INET_FORWARD) => "forward",
+        (_, NF_INET_LOCAL_OUT) => "output",
+        (_, NF_INET_POST_ROUTING) => "postrouting",
+        (_, NF_INET_INGRESS) => "ingress",
+        _ => return hooknum.to_string(),
+    };
+    name.to_owned()
+}
+
+/// Returns the name `nft` gives a standard priority, or the number.
+fn priority_name(family: Family, priority: i32) -> String {
+    let name = match (family, priority) {
+        (Family::Bridge, -300) => "dstnat",
+        (Family::Bridge, -200) => "filter",
+        (Family::Bridge, 100) => "out",
+        (Family::Bridge, 300) => "srcnat",
+        (Family::Bridge, _) => return priority.to_string(),
+        (_, 0) => "filter",
+        (Family::Ipv4 | Family::Ipv6 | Family::Inet, -300) => "raw",
+        (Family::Ipv4 | Family::Ipv6 | Family::Inet, -150) => "mangle",
+        (Family::Ipv4 | Family::Ipv6 | Family::Inet, -100) => "dstnat",
+        (Family::Ipv4 | Family::Ipv6 | Family::Inet, 50) => "security",
+        (Family::Ipv4 | Family::Ipv6 | Family::Inet, 100) => "srcnat",
+        _ => return priority.to_string(),
+    };
+    name.to_owned()
+}
+
+fn icmp_code_name(code: u8) -> String {
+    match code {
+        0 => "net-unreachable".to_owned(),
+        1 => "host-unreachable".to_owned(),
+        2 => "prot-unreachable".to_owned(),
+        3 => "port-unreachable".to_owned(),
+        9 => "net-prohibited".to_owned(),
+        10 => "host-prohibited".to_owned(),
+        13 => "admin-prohibited".to_owned(),
+        code => code.to_string(),
+    }
+}
+
+fn icmpv6_code_name(code: u8) -> String {
+    match code {
+        0 => "no-route".to_owned(),
+        1 => "admin-prohibited".to_owned(),
+        3 => "addr-unreachable".to_owned(),
+        4 => "port-unreachable".to_owned(),
+        5 => "policy-fail".to_owned(),
+        6 => "reject-route".to_owned(),
+        code => code.to_string(),
+    }
+}
+
+fn icmpx_code_name(code: u8) -> String {
+    match code {
+        NFT_REJECT_ICMPX_NO_ROUTE => "no-route".to_owned(),
+        NFT_REJECT_ICMPX_PORT_UNREACH => "port-unreachable".to_owned(),
+        NFT_REJECT_ICMPX_HOST_UNREACH => "host-unreachable".to_owned(),
+        NFT_REJECT_ICMPX_ADMIN_PROHIBITED => "admin-prohibited".to_owned(),
+        code => code.to_string(),
+    }
+}
+
+/// A duration in milliseconds, formatted like `1h30m`.
+struct Duration(u64);
+
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 == 0 {
+            return f.write_str("0s");
+        }
+        let mut rest = self.0;
+        for (unit, name) in [
+            (86_400_000, "d"),
+            (3_600_000, "h"),
+            (60_000, "m"),
+            (1000, "s"),
+            (1, "ms"),
+        ] {
+            if rest >= unit {
+                write!(f, "{}{name}", rest / unit)?;
+                rest %= unit;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nftables::{Hook, NF_INET_LOCAL_IN, NFT_SET_MAP};
+
+    fn meta(key: u32) -> Expr {
+        Expr::Meta(MetaExpr::load(key, NFT_REG_1))
+    }
+
+    fn cmp(op: u32, data: impl Into<Vec<u8>>) -> Expr {
+        Expr::Cmp(CmpExpr::new(op, NFT_REG_1, data))
+    }
+
+    fn payload(base: u32, offset: u32, len: u32) -> Expr {
+        Expr::Payload(PayloadExpr::load(base, offset, len, NFT_REG_1))
+    }
+
+    fn value(reg: u32, value: impl Into<Vec<u8>>) -> Expr {
+        Expr::Immediate(ImmediateExpr::value(reg, value))
+    }
+
+    /// The expressions of `tcp dport`, leaving the port in register 1.
+    fn tcp_dport() -> Vec<Expr> {
+        vec![
+            meta(NFT_META_L4PROTO),
+            cmp(NFT_CMP_EQ, [6]),
+            payload(NFT_PAYLOAD_TRANSPORT_HEADER, 2, 2),
+        ]
+    }
+
+    #[test]
+    fn test_ruleset_display() {
+        let table = Table::new(Family::Ipv4, "filter");
+        let nets = Set {
+            flags: NFT_SET_INTERVAL,
+            ..Set::new(&table, "nets", 7, 4)
+        };
+        // Intervals are stored as their start and the first key after them.
+        let interval_end = |key: [u8; 4]| SetElement {
+            flags: NFT_SET_ELEM_INTERVAL_END,
+            ..SetElement::new(key)
+        };
+        let nets_elements = vec![
+            interval_end([0, 0, 0, 0]),
+            SetElement::new([10, 0, 0, 0]),
+            interval_end([11, 0, 0, 0]),
+            SetElement::new([192, 168, 1, 1]),
+            interval_end([192, 168, 1, 2]),
+        ];
+        let ports = Set {
+            flags: NFT_SET_ANONYMOUS | NFT_SET_CONSTANT,
+            ..Set::new(&table, "__set0", 13, 2)
+        };
+        let ports_elements = vec![
+            SetElement::new(22u16.to_be_bytes()),
+            SetElement::new(443u16.to_be_bytes()),
+        ];
+        let input = Chain {
+            hook: Some(Hook::new(NF_INET_LOCAL_IN, 0)),
+            chain_type: Some("filter".to_owned()),
+            policy: Some(Policy::Drop),
+            ..Chain::new(&table, "input")
+        };
+        let rule = Rule {
+            exprs: vec![
+                Expr::Payload(PayloadExpr::load(
+                    NFT_PAYLOAD_NETWORK_HEADER,
+                    12,
+                    4,
+                    NFT_REG_1,
+                )),
+                Expr::Bitwise(BitwiseExpr::mask_xor(NFT_REG_1, [255, 0, 0, 0], [0; 4])),
+                Expr::Cmp(CmpExpr::new(NFT_CMP_EQ, NFT_REG_1, [10, 0, 0, 0])),
+                Expr::Meta(MetaExpr::load(NFT_META_L4PROTO, NFT_REG_1)),
+                Expr::Cmp(CmpExpr::new(NFT_CMP_EQ, NFT_REG_1, [6])),
+                Expr::Payload(PayloadExpr::load(
+                    NFT_PAYLOAD_TRANSPORT_HEADER,
+                    2,
+                    2,
+                    NFT_REG_1,
+                )),
+                Expr::Lookup(LookupExpr::new("__set0", NFT_REG_1)),
+                Expr::Counter(CounterExpr {
+                    bytes: 120,
+                    packets: 2,
+                }),
+                Expr::Immediate(ImmediateExpr::verdict(Verdict::Accept)),
+            ],
+            userdata: Some(b"\x00\x04ssh\x00".to_vec()),
+            ..Rule::new(&input)
+        };
+        let ruleset = Ruleset {
+            tables: vec![table],
+            chains: vec![input],
+            sets: vec![(nets, nets_elements), (ports, ports_elements)],
+            rules: vec![rule],
+        };
+        assert_eq!(
+            ruleset.to_string(),
+            "table ip filter {\n\
+             \tset nets {\n\
+             \t\ttype ipv4_addr\n\
+             \t\tflags interval\n\
+             \t\telements = { 10.0.0.0/8, 192.168.1.1 }\n\
+             \t}\n\
+             \n\
+             \tchain input {\n\
+             \t\ttype filter hook input priority filter; policy drop;\n\
+             \t\tip saddr 10.0.0.0/8 tcp dport { 22, 443 } counter packets 2 bytes 120 accept \
+             comment \"ssh\"\n\
+             \t}\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn test_rule_display() {
+        let table = Table::new(Family::Inet, "filter");
+        let verdict_map = |name: &str, flags: u32| Set {
+            flags: NFT_SET_MAP | flags,
+            data_type: Some(NFT_DATA_VERDICT),
+            ..Set::new(&table, name, 13, 2)
+        };
+        let element = |port: u16, data: Data| SetElement {
+            data: Some(data),
+            ..SetElement::new(port.to_be_bytes())
+        };
+        let anonymous = NFT_SET_ANONYMOUS | NFT_SET_CONSTANT;
+        let sets = [
+            (verdict_map("ports", 0), Vec::new()),
+            (
+                verdict_map("__map0", anonymous),
+                vec![
+                    element(22, Data::Verdict(Verdict::Accept)),
+                    element(80, Data::Verdict(Verdict::Jump("web".to_owned()))),
+                ],
+            ),
+            (
+                Set {
+                    flags: NFT_SET_MAP | anonymous,
+                    data_type: Some(7),
+                    ..Set::new(&table, "__map1", 13, 2)
+                },
+                vec![element(80, Data::Value(vec![10, 0, 0, 1]))],
+            ),
+            (Set::new(&table, "allowed", 7, 4), Vec::new()),
+        ];
+        let lookup = |set: &str, dreg: Option<u32>, flags: u32| {
+            Expr::Lookup(LookupExpr {
+                dreg,
+                flags,
+                ..LookupExpr::new(set, NFT_REG_1)
+            })
+        };
+        let nat = |nat_type: u32, family: Family| NatExpr::new(nat_type, family);
+        let established = NF_CT_STATE_ESTABLISHED_BIT | NF_CT_STATE_RELATED_BIT;
+
+        let cases: Vec<(Family, Vec<Expr>, &str)> = vec![
+            // nat, with the family implying the match on it in inet tables.
+            (
+                Family::Inet,
+                vec![
+                    meta(NFT_META_NFPROTO),
+                    cmp(NFT_CMP_EQ, [libc::NFPROTO_IPV4 as u8]),
+                    value(NFT_REG_1, [192, 0, 2, 1]),
+                    Expr::Nat(NatExpr {
+                        reg_addr_min: Some(NFT_REG_1),
+                        ..nat(NFT_NAT_SNAT, Family::Ipv4)
+                    }),
+                ],
+                "snat ip to 192.0.2.1",
+            ),
+            (
+                Family::Inet,
+                [
+                    tcp_dport(),
+                    vec![
+                        cmp(NFT_CMP_EQ, 80u16.to_be_bytes()),
+                        value(
+                            NFT_REG_1,
+                            "2001:db8::1".parse::<Ipv6Addr>().unwrap().octets(),
+                        ),
+                        value(NFT_REG_2, 8080u16.to_be_bytes()),
+                        Expr::Nat(NatExpr {
+                            reg_addr_min: Some(NFT_REG_1),
+                            reg_proto_min: Some(NFT_REG_2),
+                            ..nat(NFT_NAT_DNAT, Family::Ipv6)
+                        }),
+                    ],
+                ]
+                .concat(),
+                "tcp dport 80 dnat ip6 to [2001:db8::1]:8080",
+            ),
+            (
+                Family::Ipv4,
+                vec![
+                    value(NFT_REG_1, [10, 0, 0, 1]),
+                    value(NFT_REG_2, [10, 0, 0, 9]),
+                    Expr::Nat(NatExpr {
+                        reg_addr_min: Some(NFT_REG_1),
+                        reg_addr_max: Some(NFT_REG_2),
+                        flags: Some(NF_NAT_RANGE_PROTO_RANDOM | NF_NAT_RANGE_PERSISTENT),
+                        ..nat(NFT_NAT_SNAT, Family::Ipv4)
+                    }),
+                ],
+                "snat to 10.0.0.1-10.0.0.9 random persistent",
+            ),
+            // masquerade
+            (
+                Family::Inet,
+                vec![
+                    meta(NFT_META_OIFNAME),
+                    cmp(NFT_CMP_EQ, *b"eth0\0\0\0\0\0\0\0\0\0\0\0\0"),
+                    Expr::Masq(MasqExpr::default()),
+                ],
+                "oifname \"eth0\" masquerade",
+            ),
+            (
+                Family::Ipv4,
+                vec![
+                    value(NFT_REG_1, 1024u16.to_be_bytes()),
+                    value(NFT_REG_2, 2048u16.to_be_bytes()),
+                    Expr::Masq(MasqExpr {
+                        flags: Some(NF_NAT_RANGE_PROTO_RANDOM_FULLY),
+                        reg_proto_min: Some(NFT_REG_1),
+                        reg_proto_max: Some(NFT_REG_2),
+                    }),
+                ],
+                "masquerade to :1024-2048 fully-random",
+            ),
+            // reject
+            (
+                Family::Inet,
+                vec![
+                    meta(NFT_META_L4PROTO),
+                    cmp(NFT_CMP_EQ, [6]),
+                    Expr::Reject(RejectExpr {
+                        reject_type: NFT_REJECT_TCP_RST,
+                        icmp_code: 0,
+                    }),
+                ],
+                "reject with tcp reset",
+            ),
+            (
+                Family::Inet,
+                vec![Expr::Reject(RejectExpr {
+                    reject_type: NFT_REJECT_ICMPX_UNREACH,
+                    icmp_code: NFT_REJECT_ICMPX_PORT_UNREACH,
+                })],
+                "reject",
+            ),
+            (
+                Family::Inet,
+                vec![Expr::Reject(RejectExpr {
+                    reject_type: NFT_REJECT_ICMPX_UNREACH,
+                    icmp_code: NFT_REJECT_ICMPX_ADMIN_PROHIBITED,
+                })],
+                "reject with icmpx admin-prohibited",
+            ),
+            (
+                Family::Ipv4,
+                vec![Expr::Reject(RejectExpr {
+                    reject_type: NFT_REJECT_ICMP_UNREACH,
+                    icmp_code: 3,
+                })],
+                "reject",
+            ),
+            (
+                Family::Ipv4,
+                vec![Expr::Reject(RejectExpr {
+                    reject_type: NFT_REJECT_ICMP_UNREACH,
+                    icmp_code: 13,
+                })],
+                "reject with icmp admin-prohibited",
+            ),
+            (
+                Family::Ipv6,
+                vec![Expr::Reject(RejectExpr {
+                    reject_type: NFT_REJECT_ICMP_UNREACH,
+                    icmp_code: 4,
+                })],
+                "reject",
+            ),
+            (
+                Family::Inet,
+                vec![
+                    meta(NFT_META_NFPROTO),
+                    cmp(NFT_CMP_EQ, [libc::NFPROTO_IPV6 as u8]),
+                    Expr::Reject(RejectExpr {
+                        reject_type: NFT_REJECT_ICMP_UNREACH,
+                        icmp_code: 1,
+                    }),
+                ],
+                "reject with icmpv6 admin-prohibited",
+            ),
+            // limit
+            (
+                Family::Inet,
+                vec![Expr::Limit(LimitExpr {
+                    rate: 10,
+                    unit: 60,
+                    burst: 5,
+                    ..LimitExpr::default()
+                })],
+                "limit rate 10/minute",
+            ),
+            (
+                Family::Inet,
+                vec![Expr::Limit(LimitExpr {
+                    rate: 10,
+                    unit: 3600,
+                    burst: 20,
+                    ..LimitExpr::default()
+                })],
+                "limit rate 10/hour burst 20 packets",
+            ),
+            (
+                Family::Inet,
+                vec![Expr::Limit(LimitExpr {
+                    rate: 1 << 20,
+                    unit: 1,
+                    burst: 0,
+                    limit_type: NFT_LIMIT_PKT_BYTES,
+                    flags: NFT_LIMIT_F_INV,
+                })],
+                "limit rate over 1048576 bytes/second",
+            ),
+            // log, leaving out the default level
+            (
+                Family::Inet,
+                vec![Expr::Log(LogExpr {
+                    prefix: Some("drop ".to_owned()),
+                    group: Some(2),
+                    ..LogExpr::default()
+                })],
+                "log prefix \"drop \" group 2",
+            ),
+            (
+                Family::Inet,
+                vec![Expr::Log(LogExpr {
+                    level: Some(4),
+                    ..LogExpr::default()
+                })],
+                "log",
+            ),
+            (
+                Family::Inet,
+                vec![Expr::Log(LogExpr {
+                    level: Some(6),
+                    ..LogExpr::default()
+                })],
+                "log level info",
+            ),
+            // fib
+            (
+                Family::Inet,
+                vec![
+                    Expr::Fib(FibExpr {
+                        flags: NFTA_FIB_F_DADDR | NFTA_FIB_F_IIF,
+                        result: NFT_FIB_RESULT_ADDRTYPE,
+                        dreg: NFT_REG_1,
+                    }),
+                    cmp(NFT_CMP_EQ, 2u32.to_ne_bytes()),
+                ],
+                "fib daddr . iif type local",
+            ),
+            (
+                Family::Inet,
+                vec![
+                    Expr::Fib(FibExpr {
+                        flags: NFTA_FIB_F_DADDR | NFTA_FIB_F_PRESENT,
+                        result: NFT_FIB_RESULT_OIF,
+                        dreg: NFT_REG_1,
+                    }),
+                    cmp(NFT_CMP_EQ, [0; 4]),
+                ],
+                "fib daddr oif missing",
+            ),
+            // socket
+            (
+                Family::Inet,
+                vec![
+                    Expr::Socket(SocketExpr {
+                        key: NFT_SOCKET_TRANSPARENT,
+                        dreg: NFT_REG_1,
+                        level: None,
+                    }),
+                    cmp(NFT_CMP_EQ, [1]),
+                ],
+                "socket transparent 1",
+            ),
+            (
+                Family::Inet,
+                vec![
+                    Expr::Socket(SocketExpr {
+                        key: NFT_SOCKET_MARK,
+                        dreg: NFT_REG_1,
+                        level: None,
+                    }),
+                    cmp(NFT_CMP_EQ, 42u32.to_ne_bytes()),
+                ],
+                "socket mark 0x0000002a",
+            ),
+            // ct, with masked states compared to zero
+            (
+                Family::Inet,
+                vec![
+                    Expr::Ct(CtExpr::load(NFT_CT_STATE, NFT_REG_1)),
+                    Expr::Bitwise(BitwiseExpr::mask_xor(
+                        NFT_REG_1,
+                        established.to_ne_bytes(),
+                        [0; 4],
+                    )),
+                    cmp(NFT_CMP_NEQ, [0; 4]),
+                ],
+                "ct state established,related",
+            ),
+            (
+                Family::Inet,
+                vec![
+                    Expr::Ct(CtExpr::load(NFT_CT_STATE, NFT_REG_1)),
+                    Expr::Bitwise(BitwiseExpr::mask_xor(
+                        NFT_REG_1,
+                        established.to_ne_bytes(),
+                        [0; 4],
+                    )),
+                    cmp(NFT_CMP_EQ, [0; 4]),
+                ],
+                "ct state != established,related",
+            ),
+            (
+                Family::Inet,
+                vec![
+                    Expr::Ct(CtExpr::load(NFT_CT_STATE, NFT_REG_1)),
+                    cmp(NFT_CMP_EQ, NF_CT_STATE_NEW_BIT.to_ne_bytes()),
+                ],
+                "ct state new",
+            ),
+            (
+                Family::Inet,
+                vec![
+                    Expr::Ct(CtExpr {
+                        direction: Some(IP_CT_DIR_ORIGINAL),
+                        ..CtExpr::load(NFT_CT_SRC_IP, NFT_REG_1)
+                    }),
+                    cmp(NFT_CMP_EQ, [10, 0, 0, 1]),
+                ],
+                "ct original ip saddr 10.0.0.1",
+            ),
+            (
+                Family::Inet,
+                vec![
+                    value(NFT_REG_1, 42u32.to_ne_bytes()),
+                    Expr::Ct(CtExpr {
+                        key: NFT_CT_MARK,
+                        sreg: Some(NFT_REG_1),
+                        ..CtExpr::default()
+                    }),
+                ],
+                "ct mark set 0x0000002a",
+            ),
+            // maps and verdict maps
+            (
+                Family::Inet,
+                [tcp_dport(), vec![lookup("ports", Some(NFT_REG_VERDICT), 0)]].concat(),
+                "tcp dport vmap @ports",
+            ),
+            (
+                Family::Inet,
+                [
+                    tcp_dport(),
+                    vec![lookup("__map0", Some(NFT_REG_VERDICT), 0)],
+                ]
+                .concat(),
+                "tcp dport vmap { 22 : accept, 80 : jump web }",
+            ),
+            (
+                Family::Inet,
+                [
+                    tcp_dport(),
+                    vec![
+                        lookup("__map1", Some(NFT_REG_1), 0),
+                        Expr::Nat(NatExpr {
+                            reg_addr_min: Some(NFT_REG_1),
+                            ..nat(NFT_NAT_DNAT, Family::Ipv4)
+                        }),
+                    ],
+                ]
+                .concat(),
+                "dnat ip to tcp dport map { 80 : 10.0.0.1 }",
+            ),
+            (
+                Family::Inet,
+                vec![
+                    meta(NFT_META_NFPROTO),
+                    cmp(NFT_CMP_EQ, [libc::NFPROTO_IPV4 as u8]),
+                    payload(NFT_PAYLOAD_NETWORK_HEADER, 12, 4),
+                    lookup("allowed", None, NFT_LOOKUP_F_INV),
+                ],
+                "ip saddr != @allowed",
+            ),
+            // dependencies left out only when a later field implies them
+            (
+                Family::Inet,
+                vec![
+                    meta(NFT_META_NFPROTO),
+                    cmp(NFT_CMP_EQ, [libc::NFPROTO_IPV4 as u8]),
+                    Expr::Counter(CounterExpr::default()),
+                ],
+                "meta nfproto ipv4 counter packets 0 bytes 0",
+            ),
+            (
+                Family::Inet,
+                vec![
+                    meta(NFT_META_L4PROTO),
+                    cmp(NFT_CMP_EQ, [17]),
+                    payload(NFT_PAYLOAD_TRANSPORT_HEADER, 2, 2),
+                    cmp(NFT_CMP_EQ, 53u16.to_be_bytes()),
+                ],
+                "udp dport 53",
+            ),
+            (
+                Family::Inet,
+                vec![
+                    meta(NFT_META_L4PROTO),
+                    cmp(NFT_CMP_EQ, [6]),
+                    Expr::Counter(CounterExpr::default()),
+                ],
+                "meta l4proto tcp counter packets 0 bytes 0",
+            ),
+            (
+                Family::Bridge,
+                vec![
+                    meta(NFT_META_PROTOCOL),
+                    cmp(NFT_CMP_EQ, [0x08, 0x00]),
+                    payload(NFT_PAYLOAD_NETWORK_HEADER, 16, 4),
+                    cmp(NFT_CMP_NEQ, [192, 0, 2, 1]),
+                ],
+                "ip daddr != 192.0.2.1",
+            ),
+            // values
+            (
+                Family::Bridge,
+                vec![
+                    payload(NFT_PAYLOAD_LL_HEADER, 6, 6),
+                    cmp(NFT_CMP_EQ, [0x00, 0x11, 0x22, 0x33, 0x44, 0x55]),
+                ],
+                "ether saddr 00:11:22:33:44:55",
+            ),
+            (
+                Family::Inet,
+                vec![meta(NFT_META_MARK), cmp(NFT_CMP_EQ, 42u32.to_ne_bytes())],
+                "meta mark 0x0000002a",
+            ),
+            (
+                Family::Inet,
+                vec![
+                    meta(NFT_META_MARK),
+                    Expr::Bitwise(BitwiseExpr::mask_xor(
+                        NFT_REG_1,
+                        0xff00u32.to_ne_bytes(),
+                        [0; 4],
+                    )),
+                    cmp(NFT_CMP_EQ, 0x100u32.to_ne_bytes()),
+                ],
+                "meta mark & 0x0000ff00 == 0x00000100",
+            ),
+            (
+                Family::Inet,
+                vec![meta(NFT_META_IIFNAME), cmp(NFT_CMP_EQ, *b"eth")],
+                "iifname \"eth*\"",
+            ),
+        ];
+        for (family, exprs, expected) in cases {
+            let table = Table::new(family, "filter");
+            let rule = Rule {
+                exprs,
+                ..Rule::new(&Chain::new(&table, "input"))
+            };
+            assert_eq!(RuleFormatter::new(&rule, &sets).format(&rule), expected);
+        }
+    }
+
+    #[test]
+    fn test_format_value() {
+        let cases: [(Kind, &[u8], &str); 10] = [
+            (Kind::Mark, &42u32.to_ne_bytes(), "0x0000002a"),
+            (
+                Kind::Ether,
+                &[0xde, 0xad, 0xbe, 0xef, 0, 1],
+                "de:ad:be:ef:00:01",
+            ),
+            (Kind::IfName, b"wg0\0", "\"wg0\""),
+            (Kind::IfName, b"veth", "\"veth*\""),
+            (Kind::EtherType, &[0x81, 0x00], "vlan"),
+            (Kind::Port, &[0x01, 0xbb], "443"),
+            (Kind::Addr, &[192, 0, 2, 1], "192.0.2.1"),
+            (Kind::CtState, &0x100u32.to_ne_bytes(), "0x100"),
+            (Kind::BeInt, &[0, 0, 1, 0], "256"),
+            (Kind::Raw, &[1, 2], "0x0102"),
+        ];
+        for (kind, value, expected) in cases {
+            assert_eq!(format_value(kind, value), expected, "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn test_duration_and_priority() {
+        assert_eq!(Duration(0).to_string(), "0s");
+        assert_eq!(Duration(1500).to_string(), "1s500ms");
+        assert_eq!(Duration(5_400_000).to_string(), "1h30m");
+        assert_eq!(Duration(90_061_001).to_string(), "1d1h1m1s1ms");
+
+        assert_eq!(priority_name(Family::Inet, -150), "mangle");
+        assert_eq!(priority_name(Family::Ipv4, 100), "srcnat");
+        assert_eq!(priority_name(Family::Netdev, 0), "filter");
+        assert_eq!(priority_name(Family::Netdev, -100), "-100");
+        assert_eq!(priority_name(Family::Bridge, -200), "filter");
+        assert_eq!(priority_name(Family::Bridge, 0), "0");
+        assert_eq!(priority_name(Family::Ipv6, 10), "10");
+    }
+}
@@ -0,0 +1,461 @@
+use mnl_sys::libc;
+use std::io;
+
+use super::{
+    Data, Family, NFGENMSG_LEN, Table, data::put_value, family_of, get, get_one, put_be32,
+    put_be64, sys,
+};
+use crate::{Attr, NfBatch, NlMsg, NlMsgBuilder, Socket};
+
+/// Set flag: the set is anonymous, bound to the rule that uses it and deleted along with it.
+pub const NFT_SET_ANONYMOUS: u32 = 0x1;
+/// Set flag: the elements of the set can not change once it is created.
+pub const NFT_SET_CONSTANT: u32 = 0x2;
+/// Set flag: the elements are ranges, given as a start element and an element with
+/// [`NFT_SET_ELEM_INTERVAL_END`] set.
+pub const NFT_SET_INTERVAL: u32 = 0x4;
+/// Set flag: the set is a map, every element has data.
+pub const NFT_SET_MAP: u32 = 0x8;
+/// Set flag: elements can have a timeout.
+pub const NFT_SET_TIMEOUT: u32 = 0x10;
+/// Set flag: elements can be added from the packet path.
+pub const NFT_SET_EVAL: u32 = 0x20;
+/// Set flag: the set is a map to stateful objects.
+pub const NFT_SET_OBJECT: u32 = 0x40;
+/// Set flag: the keys are concatenations of several fields.
+pub const NFT_SET_CONCAT: u32 = 0x80;
+
+/// Set element flag: the element ends the range started by the previous element.
+pub const NFT_SET_ELEM_INTERVAL_END: u32 = 0x1;
+
+/// An nftables set or map, as described by a `NFT_MSG_NEWSET` message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Set {
+    /// The address family of the table the set is in.
+    pub family: Family,
+    /// Name of the table the set is in.
+    pub table: String,
+    /// Name of the set, unique within its table. Anonymous sets are named `__set%d` and get
+    /// their number assigned by the kernel.
+    pub name: String,
+    /// Handle assigned by the kernel.
+    pub handle: Option<u64>,
+    /// A combination of the `NFT_SET_*` flags.
+    pub flags: u32,
+    /// Data type of the keys, as defined by `nft`, such as 7 for `ipv4_addr`. Only used by
+    /// user space.
+    pub key_type: u32,
+    /// Length of the keys in bytes.
+    pub key_len: u32,
+    /// Data type of the values of a map, as defined by `nft`. `0xffffff00` is the verdict type.
+    pub data_type: Option<u32>,
+    /// Length of the values of a map in bytes.
+    pub data_len: Option<u32>,
+    /// Type of the objects of an object map, one of the `NFT_OBJECT_*` constants.
+    pub obj_type: Option<u32>,
+    /// Id referring to the set from other messages in the same batch, before it has a handle.
+    /// The kernel requires an id when adding a set, so 0 is sent if this is `None`.
+    pub id: Option<u32>,
+    /// Whether to optimize for performance (0) or memory (1) when picking the set backend.
+    pub policy: Option<u32>,
+    /// Expected number of elements.
+    pub size: Option<u32>,
+    /// Default timeout of elements in milliseconds.
+    pub timeout: Option<u64>,
+    /// Interval of garbage collection of timed out elements in milliseconds.
+    pub gc_interval: Option<u32>,
+    /// Opaque data stored with the set, such as the key byte order added by `nft`.
+    pub userdata: Option<Vec<u8>>,
+}
+
+impl Set {
+    /// Describe a set with the given name in `table`, with keys of type `key_type` and
+    /// `key_len` bytes.
+    pub fn new(table: &Table, name: impl Into<String>, key_type: u32, key_len: u32) -> Self {
+        Set {
+            family: table.family,
+            table: table.name.clone(),
+            name: name.into(),
+            handle: None,
+            flags: 0,
+            key_type,
+            key_len,
+            data_type: None,
+            data_len: None,
+            obj_type: None,
+            id: None,
+            policy: None,
+            size: None,
+            timeout: None,
+            gc_interval: None,
+            userdata: None,
+        }
+    }
+
+    /// Parse a `NFT_MSG_NEWSET` message.
+    pub fn from_msg(msg: &NlMsg<'_>) -> io::Result<Set> {
+        let mut set = Set::new(&Table::new(family_of(msg)?, ""), "", 0, 0);
+        for attr in msg.attrs(NFGENMSG_LEN) {
+            let attr = attr?;
+            match attr.attr_type() {
+                sys::NFTA_SET_TABLE => set.table = attr.get_str()?.to_owned(),
+                sys::NFTA_SET_NAME => set.name = attr.get_str()?.to_owned(),
+                sys::NFTA_SET_HANDLE => set.handle = Some(attr.get_be64()?),
+                sys::NFTA_SET_FLAGS => set.flags = attr.get_be32()?,
+                sys::NFTA_SET_KEY_TYPE => set.key_type = attr.get_be32()?,
+                sys::NFTA_SET_KEY_LEN => set.key_len = attr.get_be32()?,
+                sys::NFTA_SET_DATA_TYPE => set.data_type = Some(attr.get_be32()?),
+                sys::NFTA_SET_DATA_LEN => set.data_len = Some(attr.get_be32()?),
+                sys::NFTA_SET_OBJ_TYPE => set.obj_type = Some(attr.get_be32()?),
+                sys::NFTA_SET_ID => set.id = Some(attr.get_be32()?),
+                sys::NFTA_SET_POLICY => set.policy = Some(attr.get_be32()?),
+                sys::NFTA_SET_DESC => {
+                    for attr in attr.nested() {
+                        let attr = attr?;
+                        if attr.attr_type() == sys::NFTA_SET_DESC_SIZE {
+                            set.size = Some(attr.get_be32()?);
+                        }
+                    }
+                }
+                sys::NFTA_SET_TIMEOUT => set.timeout = Some(attr.get_be64()?),
+                sys::NFTA_SET_GC_INTERVAL => set.gc_interval = Some(attr.get_be32()?),
+                sys::NFTA_SET_USERDATA => set.userdata = Some(attr.payload().to_vec()),
+                _ => (),
+            }
+        }
+        Ok(set)
+    }
+
+    /// Returns true if the set is a map, with data for every element.
+    pub fn is_map(&self) -> bool {
+        self.flags & NFT_SET_MAP != 0
+    }
+
+    fn put_attrs(&self, msg: &mut NlMsgBuilder<'_>) -> io::Result<()> {
+        msg.put_str(sys::NFTA_SET_TABLE, &self.table)?;
+        msg.put_str(sys::NFTA_SET_NAME, &self.name)?;
+        put_be32(msg, sys::NFTA_SET_FLAGS, self.flags)?;
+        put_be32(msg, sys::NFTA_SET_KEY_TYPE, self.key_type)?;
+        put_be32(msg, sys::NFTA_SET_KEY_LEN, self.key_len)?;
+        if let Some(data_type) = self.data_type {
+            put_be32(msg, sys::NFTA_SET_DATA_TYPE, data_type)?;
+        }
+        if let Some(data_len) = self.data_len {
+            put_be32(msg, sys::NFTA_SET_DATA_LEN, data_len)?;
+        }
+        if let Some(obj_type) = self.obj_type {
+            put_be32(msg, sys::NFTA_SET_OBJ_TYPE, obj_type)?;
+        }
+        put_be32(msg, sys::NFTA_SET_ID, self.id.unwrap_or(0))?;
+        if let Some(policy) = self.policy {
+            put_be32(msg, sys::NFTA_SET_POLICY, policy)?;
+        }
+        if let Some(size) = self.size {
+            msg.put_nested(sys::NFTA_SET_DESC, |msg| {
+                put_be32(msg, sys::NFTA_SET_DESC_SIZE, size)
+            })?;
+        }
+        if let Some(timeout) = self.timeout {
+            put_be64(msg, sys::NFTA_SET_TIMEOUT, timeout)?;
+        }
+        if let Some(gc_interval) = self.gc_interval {
+            put_be32(msg, sys::NFTA_SET_GC_INTERVAL, gc_interval)?;
+        }
+        if let Some(userdata) = &self.userdata {
+            msg.put(sys::NFTA_SET_USERDATA, userdata)?;
+        }
+        Ok(())
+    }
+
+    /// Add a `NFT_MSG_NEWSET` message to `batch`, creating the set if it does not exist.
+    /// Returns the index of the message in the batch.
+    pub fn add(&self, batch: &mut NfBatch) -> io::Result<usize> {
+        batch.add(
+            sys::NFT_MSG_NEWSET,
+            self.family.into(),
+            libc::NLM_F_CREATE as u16,
+            |msg| self.put_attrs(msg),
+        )
+    }
+
+    /// Add a `NFT_MSG_NEWSET` message to `batch` that fails with `EEXIST` if the set exists.
+    pub fn create(&self, batch: &mut NfBatch) -> io::Result<usize> {
+        batch.add(
+            sys::NFT_MSG_NEWSET,
+            self.family.into(),
+            (libc::NLM_F_CREATE | libc::NLM_F_EXCL) as u16,
+            |msg| self.put_attrs(msg),
+        )
+    }
+
+    /// Add a `NFT_MSG_DELSET` message to `batch`, deleting the set with its elements. The set
+    /// is identified by its handle if set, otherwise by its name.
+    pub fn delete(&self, batch: &mut NfBatch) -> io::Result<usize> {
+        batch.add(sys::NFT_MSG_DELSET, self.family.into(), 0, |msg| {
+            msg.put_str(sys::NFTA_SET_TABLE, &self.table)?;
+            match self.handle {
+                Some(handle) => put_be64(msg, sys::NFTA_SET_HANDLE, handle),
+                None => msg.put_str(sys::NFTA_SET_NAME, &self.name),
+            }
+        })
+    }
+}
+
+/// A list of elements of a set, as described by a `NFT_MSG_NEWSETELEM` message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetElements {
+    /// The address family of the table the set is in.
+    pub family: Family,
+    /// Name of the table the set is in.
+    pub table: String,
+    /// Name of the set.
+    pub set: String,
+    /// Id of a set added earlier in the same batch, used instead of the name if set.
+    pub set_id: Option<u32>,
+    /// The elements.
+    pub elements: Vec<SetElement>,
+}
+
+/// An element of a set or map.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SetElement {
+    /// The key, in the byte order of the data it is matched against.
+    pub key: Vec<u8>,
+    /// End of the range of an element of a set with both [`NFT_SET_INTERVAL`] and
+    /// [`NFT_SET_CONCAT`] set.
+    pub key_end: Option<Vec<u8>>,
+    /// The value of an element of a map.
+    pub data: Option<Data>,
+    /// A combination of the `NFT_SET_ELEM_*` flags.
+    pub flags: u32,
+    /// Timeout of the element in milliseconds.
+    pub timeout: Option<u64>,
+    /// Milliseconds until the element times out. Only set by the kernel.
+    pub expiration: Option<u64>,
+    /// Name of the stateful object an element of an object map refers to.
+    pub objref: Option<String>,
+    /// Opaque data stored with the element, such as comments added by `nft`.
+    pub userdata: Option<Vec<u8>>,
+}
+
+impl SetElement {
+    /// Describe an element with the given key.
+    pub fn new(key: impl Into<Vec<u8>>) -> Self {
+        SetElement {
+            key: key.into(),
+            ..SetElement::default()
+        }
+    }
+
+    fn parse(attr: Attr<'_>) -> io::Result<SetElement> {
+        let mut element = SetElement::default();
+        for attr in attr.nested() {
+            let attr = attr?;
+            match attr.attr_type() {
+                sys::NFTA_SET_ELEM_KEY => element.key = parse_value(attr)?,
+                sys::NFTA_SET_ELEM_KEY_END => element.key_end = Some(parse_value(attr)?),
+                sys::NFTA_SET_ELEM_DATA => element.data = Some(Data::parse(attr)?),
+                sys::NFTA_SET_ELEM_FLAGS => element.flags = attr.get_be32()?,
+                sys::NFTA_SET_ELEM_TIMEOUT => element.timeout = Some(attr.get_be64()?),
+                sys::NFTA_SET_ELEM_EXPIRATION => element.expiration = Some(attr.get_be64()?),
+                sys::NFTA_SET_ELEM_OBJREF => element.objref = Some(attr.get_str()?.to_owned()),
+                sys::NFTA_SET_ELEM_USERDATA => element.userdata = Some(attr.payload().to_vec()),
+                _ => (),
+            }
+        }
+        Ok(element)
+    }
+
+    fn put(&self, msg: &mut NlMsgBuilder<'_>) -> io::Result<()> {
+        msg.put_nested(sys::NFTA_LIST_ELEM, |msg| {
+            if self.flags != 0 {
+                put_be32(msg, sys::NFTA_SET_ELEM_FLAGS, self.flags)?;
+            }
+            if let Some(timeout) = self.timeout {
+                put_be64(msg, sys::NFTA_SET_ELEM_TIMEOUT, timeout)?;
+            }
+            put_value(msg, sys::NFTA_SET_ELEM_KEY, &self.key)?;
+            if let Some(key_end) = &self.key_end {
+                put_value(msg, sys::NFTA_SET_ELEM_KEY_END, key_end)?;
+            }
+            if let Some(data) = &self.data {
+                data.put(msg, sys::NFTA_SET_ELEM_DATA)?;
+            }
+            if let Some(objref) = &self.objref {
+                msg.put_str(sys::NFTA_SET_ELEM_OBJREF, objref)?;
+            }
+            if let Some(userdata) = &self.userdata {
+                msg.put(sys::NFTA_SET_ELEM_USERDATA, userdata)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+fn parse_value(attr: Attr<'_>) -> io::Result<Vec<u8>> {
+    match Data::parse(attr)? {
+        Data::Value(value) => Ok(value),
+        Data::Verdict(_) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Set element key is a verdict",
+        )),
+    }
+}
+
+impl SetElements {
+    /// Describe an empty list of elements of `set`.
+    pub fn new(set: &Set) -> Self {
+        SetElements {
+            family: set.family,
+            table: set.table.clone(),
+            set: set.name.clone(),
+            set_id: set.id,
+            elements: Vec::new(),
+        }
+    }
+
+    /// Parse a `NFT_MSG_NEWSETELEM` message.
+    pub fn from_msg(msg: &NlMsg<'_>) -> io::Result<SetElements> {
+        let mut elements = SetElements {
+            family: family_of(msg)?,
+            table: String::new(),
+            set: String::new(),
+            set_id: None,
+            elements: Vec::new(),
+        };
+        for attr in msg.attrs(NFGENMSG_LEN) {
+            let attr = attr?;
+            match attr.attr_type() {
+                sys::NFTA_SET_ELEM_LIST_TABLE => elements.table = attr.get_str()?.to_owned(),
+                sys::NFTA_SET_ELEM_LIST_SET => elements.set = attr.get_str()?.to_owned(),
+                sys::NFTA_SET_ELEM_LIST_SET_ID => elements.set_id = Some(attr.get_be32()?),
+                sys::NFTA_SET_ELEM_LIST_ELEMENTS => {
+                    for element in attr.nested() {
+                        elements.elements.push(SetElement::parse(element?)?);
+                    }
+                }
+                _ => (),
+            }
+        }
+        Ok(elements)
+    }
+
+    fn put_attrs(&self, msg: &mut NlMsgBuilder<'_>) -> io::Result<()> {
+        msg.put_str(sys::NFTA_SET_ELEM_LIST_SET, &self.set)?;
+        if let Some(set_id) = self.set_id {
+            put_be32(msg, sys::NFTA_SET_ELEM_LIST_SET_ID, set_id)?;
+        }
+        msg.put_str(sys::NFTA_SET_ELEM_LIST_TABLE, &self.table)?;
+        msg.put_nested(sys::NFTA_SET_ELEM_LIST_ELEMENTS, |msg| {
+            self.elements
+                .iter()
+                .try_for_each(|element| element.put(msg))
+        })
+    }
+
+    /// Add a `NFT_MSG_NEWSETELEM` message to `batch`, adding the elements to the set. Returns
+    /// the index of the message in the batch.
+    pub fn add(&self, batch: &mut NfBatch) -> io::Result<usize> {
+        batch.add(
+            sys::NFT_MSG_NEWSETELEM,
+            self.family.into(),
+            libc::NLM_F_CREATE as u16,
+            |msg| self.put_attrs(msg),
+        )
+    }
+
+    /// Add a `NFT_MSG_DELSETELEM` message to `batch`, deleting the elements with the same keys
+    /// from the set.
+    pub fn delete(&self, batch: &mut NfBatch) -> io::Result<usize> {
+        batch.add(sys::NFT_MSG_DELSETELEM, self.family.into(), 0, |msg| {
+            self.put_attrs(msg)
+        })
+    }
+}
+
+/// Dump the sets and maps of all tables (`NFT_MSG_GETSET`), without their elements. `socket`
+/// must be a [`Bus::Netfilter`] socket.
+///
+/// [`Bus::Netfilter`]: crate::Bus::Netfilter
+pub fn sets(socket: &Socket) -> io::Result<Vec<Set>> {
+    get(
+        socket,
+        sys::NFT_MSG_GETSET,
+        libc::NFPROTO_UNSPEC as u8,
+        true,
+        |_| Ok(()),
+        Set::from_msg,
+    )
+}
+
+/// Get the set with the given name in `table`. Fails with `ENOENT` if there is no such set.
+pub fn set(socket: &Socket, table: &Table, name: &str) -> io::Result<Set> {
+    get_one(
+        socket,
+        sys::NFT_MSG_GETSET,
+        table.family.into(),
+        |msg| {
+            msg.put_str(sys::NFTA_SET_TABLE, &table.name)?;
+            msg.put_str(sys::NFTA_SET_NAME, name)
+        },
+        Set::from_msg,
+    )
+}
+
+/// Dump the elements of `set` (`NFT_MSG_GETSETELEM`).
+pub fn set_elements(socket: &Socket, set: &Set) -> io::Result<Vec<SetElement>> {
+    let lists = get(
+        socket,
+        sys::NFT_MSG_GETSETELEM,
+        set.family.into(),
+        true,
+        |msg| {
+            msg.put_str(sys::NFTA_SET_ELEM_LIST_TABLE, &set.table)?;
+            msg.put_str(sys::NFTA_SET_ELEM_LIST_SET, &set.name)
+        },
+        SetElements::from_msg,
+    )?;
+    Ok(lists.into_iter().flat_map(|list| list.elements).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{buffer::AlignedBuffer, nftables::Verdict};
+
+    #[test]
+    fn test_set_and_elements_round_trip() {
+        let table = Table::new(Family::Ipv4, "filter");
+        let set = Set {
+            flags: NFT_SET_MAP | NFT_SET_INTERVAL,
+            data_type: Some(0xffffff00),
+            data_len: Some(0),
+            id: Some(1),
+            size: Some(64),
+            ..Set::new(&table, "blocked", 7, 4)
+        };
+        let mut buffer = AlignedBuffer::new(512);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        crate::netfilter::put_nfgenmsg(&mut msg, set.family.into(), 0).unwrap();
+        set.put_attrs(&mut msg).unwrap();
+        let parsed = Set::from_msg(&NlMsg::new(msg.as_bytes()).unwrap()).unwrap();
+        assert_eq!(parsed, set);
+        assert!(parsed.is_map());
+
+        let mut elements = SetElements::new(&set);
+        elements.elements.push(SetElement {
+            data: Some(Data::Verdict(Verdict::Jump("blocked".to_owned()))),
+            ..SetElement::new([192, 0, 2, 0])
+        });
+        elements.elements.push(SetElement {
+            flags: NFT_SET_ELEM_INTERVAL_END,
+            ..SetElement::new([192, 0, 3, 0])
+        });
+        let mut buffer = AlignedBuffer::new(512);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        crate::netfilter::put_nfgenmsg(&mut msg, set.family.into(), 0).unwrap();
+        elements.put_attrs(&mut msg).unwrap();
+        let parsed = SetElements::from_msg(&NlMsg::new(msg.as_bytes()).unwrap()).unwrap();
+        assert_eq!(parsed, elements);
+    }
+}
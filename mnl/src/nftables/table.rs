@@ -0,0 +1,164 @@
+use mnl_sys::libc;
+use std::io;
+
+use super::{Family, NFGENMSG_LEN, family_of, get, get_one, put_be32, put_be64, sys};
+use crate::{NfBatch, NlMsg, NlMsgBuilder, Socket};
+
+/// Table flag: the table is dormant, its base chains are not registered with their hooks.
+pub const NFT_TABLE_F_DORMANT: u32 = 1 << 0;
+/// Table flag: the table belongs to the netlink socket that created it, and is removed when the
+/// socket is closed.
+pub const NFT_TABLE_F_OWNER: u32 = 1 << 1;
+
+/// An nftables table, as described by a `NFT_MSG_NEWTABLE` message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    /// The address family of the table.
+    pub family: Family,
+    /// Name of the table, unique within its family.
+    pub name: String,
+    /// A combination of the `NFT_TABLE_F_*` flags.
+    pub flags: u32,
+    /// Handle assigned by the kernel.
+    pub handle: Option<u64>,
+    /// Number of chains, sets and other objects in the table. Only set by the kernel.
+    pub use_count: u32,
+    /// Opaque data stored with the table, such as comments added by `nft`.
+    pub userdata: Option<Vec<u8>>,
+}
+
+impl Table {
+    /// Describe a table with the given family and name, and no flags.
+    pub fn new(family: Family, name: impl Into<String>) -> Self {
+        Table {
+            family,
+            name: name.into(),
+            flags: 0,
+            handle: None,
+            use_count: 0,
+            userdata: None,
+        }
+    }
+
+    /// Parse a `NFT_MSG_NEWTABLE` message.
+    pub fn from_msg(msg: &NlMsg<'_>) -> io::Result<Table> {
+        let mut table = Table::new(family_of(msg)?, "");
+        for attr in msg.attrs(NFGENMSG_LEN) {
+            let attr = attr?;
+            match attr.attr_type() {
+                sys::NFTA_TABLE_NAME => table.name = attr.get_str()?.to_owned(),
+                sys::NFTA_TABLE_FLAGS => table.flags = attr.get_be32()?,
+                sys::NFTA_TABLE_USE => table.use_count = attr.get_be32()?,
+                sys::NFTA_TABLE_HANDLE => table.handle = Some(attr.get_be64()?),
+                sys::NFTA_TABLE_USERDATA => table.userdata = Some(attr.payload().to_vec()),
+                _ => (),
+            }
+        }
+        Ok(table)
+    }
+
+    fn put_attrs(&self, msg: &mut NlMsgBuilder<'_>) -> io::Result<()> {
+        msg.put_str(sys::NFTA_TABLE_NAME, &self.name)?;
+        put_be32(msg, sys::NFTA_TABLE_FLAGS, self.flags)?;
+        if let Some(userdata) = &self.userdata {
+            msg.put(sys::NFTA_TABLE_USERDATA, userdata)?;
+        }
+        Ok(())
+    }
+
+    /// Add a `NFT_MSG_NEWTABLE` message to `batch`, creating the table or updating its flags if
+    /// it exists. Returns the index of the message in the batch.
+    pub fn add(&self, batch: &mut NfBatch) -> io::Result<usize> {
+        batch.add(
+            sys::NFT_MSG_NEWTABLE,
+            self.family.into(),
+            libc::NLM_F_CREATE as u16,
+            |msg| self.put_attrs(msg),
+        )
+    }
+
+    /// Add a `NFT_MSG_NEWTABLE` message to `batch` that fails with `EEXIST` if the table
+    /// exists.
+    pub fn create(&self, batch: &mut NfBatch) -> io::Result<usize> {
+        batch.add(
+            sys::NFT_MSG_NEWTABLE,
+            self.family.into(),
+            (libc::NLM_F_CREATE | libc::NLM_F_EXCL) as u16,
+            |msg| self.put_attrs(msg),
+        )
+    }
+
+    /// Add a `NFT_MSG_DELTABLE` message to `batch`, deleting the table with everything in it.
+    /// The table is identified by its handle if set, otherwise by its name.
+    pub fn delete(&self, batch: &mut NfBatch) -> io::Result<usize> {
+        batch.add(
+            sys::NFT_MSG_DELTABLE,
+            self.family.into(),
+            0,
+            |msg| match self.handle {
+                Some(handle) => put_be64(msg, sys::NFTA_TABLE_HANDLE, handle),
+                None => msg.put_str(sys::NFTA_TABLE_NAME, &self.name),
+            },
+        )
+    }
+}
+
+/// Dump the tables of all families (`NFT_MSG_GETTABLE`). `socket` must be a
+/// [`Bus::Netfilter`] socket.
+///
+/// [`Bus::Netfilter`]: crate::Bus::Netfilter
+pub fn tables(socket: &Socket) -> io::Result<Vec<Table>> {
+    get(
+        socket,
+        sys::NFT_MSG_GETTABLE,
+        libc::NFPROTO_UNSPEC as u8,
+        true,
+        |_| Ok(()),
+        Table::from_msg,
+    )
+}
+
+/// Get the table with the given family and name. Fails with `ENOENT` if there is no such
+/// table.
+pub fn table(socket: &Socket, family: Family, name: &str) -> io::Result<Table> {
+    get_one(
+        socket,
+        sys::NFT_MSG_GETTABLE,
+        family.into(),
+        |msg| msg.put_str(sys::NFTA_TABLE_NAME, name),
+        Table::from_msg,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::AlignedBuffer;
+
+    #[test]
+    fn test_table_round_trip() {
+        let table = Table {
+            flags: NFT_TABLE_F_DORMANT,
+            userdata: Some(b"\x00\x05test\x00".to_vec()),
+            ..Table::new(Family::Ipv6, "filter")
+        };
+        let mut buffer = AlignedBuffer::new(256);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        crate::netfilter::put_nfgenmsg(&mut msg, table.family.into(), 0).unwrap();
+        table.put_attrs(&mut msg).unwrap();
+        put_be32(&mut msg, sys::NFTA_TABLE_USE, 3).unwrap();
+        put_be64(&mut msg, sys::NFTA_TABLE_HANDLE, 12).unwrap();
+
+        let parsed = Table::from_msg(&NlMsg::new(msg.as_bytes()).unwrap()).unwrap();
+        assert_eq!(
+            parsed,
+            Table {
+                handle: Some(12),
+                use_count: 3,
+                ..table
+            }
+        );
+        // Integers are in network byte order.
+        assert_eq!(&msg.as_bytes()[32..40], &[8, 0, 2, 0, 0, 0, 0, 1]);
+    }
+}
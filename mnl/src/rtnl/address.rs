@@ -0,0 +1,354 @@
+use mnl_sys::libc;
+use std::{
+    io,
+    net::{IpAddr, Ipv4Addr},
+};
+
+use super::{
+    REQUEST_BUFFER_SIZE, RawAttrs, Scope, ack, dump, family_of, get_ip, new_request, put_ip,
+    sys::{self, Pod, ifa_cacheinfo, ifaddrmsg},
+};
+use crate::{NlMsg, NlMsgBuilder, Socket, buffer::AlignedBuffer};
+
+/// Address flag: the address is a secondary address of its prefix.
+pub const IFA_F_SECONDARY: u32 = 0x01;
+/// Address flag: skip duplicate address detection for an IPv6 address.
+pub const IFA_F_NODAD: u32 = 0x02;
+/// Address flag: optimistic duplicate address detection (RFC 4429) is used.
+pub const IFA_F_OPTIMISTIC: u32 = 0x04;
+/// Address flag: duplicate address detection failed for the address.
+pub const IFA_F_DADFAILED: u32 = 0x08;
+/// Address flag: the address is a home address (RFC 6275).
+pub const IFA_F_HOMEADDRESS: u32 = 0x10;
+/// Address flag: the preferred lifetime of the address has expired.
+pub const IFA_F_DEPRECATED: u32 = 0x20;
+/// Address flag: duplicate address detection has not completed yet.
+pub const IFA_F_TENTATIVE: u32 = 0x40;
+/// Address flag: the address never expires.
+pub const IFA_F_PERMANENT: u32 = 0x80;
+/// Address flag: generate temporary addresses from this address.
+pub const IFA_F_MANAGETEMPADDR: u32 = 0x100;
+/// Address flag: don't add a prefix route for the address.
+pub const IFA_F_NOPREFIXROUTE: u32 = 0x200;
+/// Address flag: join the solicited-node multicast group automatically.
+pub const IFA_F_MCAUTOJOIN: u32 = 0x400;
+/// Address flag: the address is a stable privacy address (RFC 7217).
+pub const IFA_F_STABLE_PRIVACY: u32 = 0x800;
+
+/// Lifetime value meaning that an address never expires.
+pub const INFINITY_LIFE_TIME: u32 = u32::MAX;
+
+/// An address assigned to a network interface, as described by a `RTM_NEWADDR` message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Address {
+    /// Index of the interface the address is assigned to.
+    pub index: u32,
+    /// The local address (`IFA_LOCAL`, or `IFA_ADDRESS` if there is no `IFA_LOCAL`).
+    pub address: IpAddr,
+    /// Length of the network prefix.
+    pub prefix_len: u8,
+    /// Scope of the address.
+    pub scope: Scope,
+    /// Address flags, a combination of the `IFA_F_*` constants in this module.
+    pub flags: u32,
+    /// Address of the remote end of a point-to-point link, if it differs from `address`
+    /// (`IFA_ADDRESS`).
+    pub peer: Option<IpAddr>,
+    /// IPv4 broadcast address (`IFA_BROADCAST`).
+    pub broadcast: Option<Ipv4Addr>,
+    /// IPv4 address label (`IFA_LABEL`).
+    pub label: Option<String>,
+    /// Lifetimes and timestamps (`IFA_CACHEINFO`).
+    pub cache_info: Option<AddressCacheInfo>,
+    /// Attributes without a typed field above.
+    pub other_attrs: RawAttrs,
+}
+
+/// Lifetimes and timestamps of an address. Mirrors `struct ifa_cacheinfo`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AddressCacheInfo {
+    /// Remaining preferred lifetime in seconds, or [`INFINITY_LIFE_TIME`].
+    pub preferred_lifetime: u32,
+    /// Remaining valid lifetime in seconds, or [`INFINITY_LIFE_TIME`].
+    pub valid_lifetime: u32,
+    /// When the address was created, in hundredths of seconds since boot.
+    pub created: u32,
+    /// When the address was last updated, in hundredths of seconds since boot.
+    pub updated: u32,
+}
+
+impl Address {
+    /// Parse a `RTM_NEWADDR` or `RTM_DELADDR` message.
+    pub fn from_msg(msg: &NlMsg<'_>) -> io::Result<Address> {
+        let header = ifaddrmsg::from_bytes(msg.payload())?;
+        let mut local = None;
+        let mut address = None;
+        let mut flags = u32::from(header.ifa_flags);
+        let mut broadcast = None;
+        let mut label = None;
+        let mut cache_info = None;
+        let mut other_attrs = RawAttrs::new();
+        for attr in msg.attrs(size_of::<ifaddrmsg>()) {
+            let attr = attr?;
+            match attr.attr_type() {
+                sys::IFA_LOCAL => local = Some(get_ip(&attr)?),
+                sys::IFA_ADDRESS => address = Some(get_ip(&attr)?),
+                sys::IFA_FLAGS => flags = attr.get_u32()?,
+                sys::IFA_BROADCAST => broadcast = Some(Ipv4Addr::from(attr.get_array::<4>()?)),
+                sys::IFA_LABEL => label = Some(attr.get_str()?.to_owned()),
+                sys::IFA_CACHEINFO => {
+                    let info = ifa_cacheinfo::from_bytes(attr.payload())?;
+                    cache_info = Some(AddressCacheInfo {
+                        preferred_lifetime: info.ifa_prefered,
+                        valid_lifetime: info.ifa_valid,
+                        created: info.cstamp,
+                        updated: info.tstamp,
+                    });
+                }
+                other => other_attrs.push((other, attr.payload().to_vec())),
+            }
+        }
+        let (address, peer) = match (local, address) {
+            (Some(local), Some(address)) if local != address => (local, Some(address)),
+            (Some(address), _) | (None, Some(address)) => (address, None),
+            (None, None) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Address message without IFA_LOCAL or IFA_ADDRESS",
+                ));
+            }
+        };
+        Ok(Address {
+            index: header.ifa_index,
+            address,
+            prefix_len: header.ifa_prefixlen,
+            scope: header.ifa_scope.into(),
+            flags,
+            peer,
+            broadcast,
+            label,
+            cache_info,
+            other_attrs,
+        })
+    }
+}
+
+/// Dump all IPv4 and IPv6 addresses in the network namespace of `socket`, which must be a
+/// [`Bus::Route`] socket.
+///
+/// [`Bus::Route`]: crate::Bus::Route
+pub fn addresses(socket: &Socket) -> io::Result<Vec<Address>> {
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let msg = new_request(
+        socket,
+        &mut buffer,
+        sys::RTM_GETADDR,
+        libc::NLM_F_DUMP as u16,
+        &ifaddrmsg::default(),
+    )?;
+    dump(socket, &msg, sys::RTM_NEWADDR, Address::from_msg)
+}
+
+/// Builder for assigning an address to an interface with `RTM_NEWADDR`.
+///
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// use mnl::rtnl::{self, NewAddress};
+///
+/// let socket = mnl::Socket::new(mnl::Bus::Route)?;
+/// let tun = rtnl::link_by_name(&socket, "wg0")?;
+/// NewAddress::new(tun.index, "10.64.0.2".parse().unwrap(), 32).add(&socket)?;
+/// NewAddress::new(tun.index, "fc00:bbbb:bbbb:bb01::2".parse().unwrap(), 128)
+///     .flags(rtnl::IFA_F_NODAD)
+///     .add(&socket)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewAddress {
+    index: u32,
+    address: IpAddr,
+    prefix_len: u8,
+    scope: Scope,
+    flags: u32,
+    peer: Option<IpAddr>,
+    broadcast: Option<Ipv4Addr>,
+    label: Option<String>,
+    lifetimes: Option<(u32, u32)>,
+}
+
+impl NewAddress {
+    /// Describe the address `address/prefix_len` on the interface with the given index.
+    pub fn new(index: u32, address: IpAddr, prefix_len: u8) -> Self {
+        NewAddress {
+            index,
+            address,
+            prefix_len,
+            scope: Scope::Universe,
+            flags: 0,
+            peer: None,
+            broadcast: None,
+            label: None,
+            lifetimes: None,
+        }
+    }
+
+    /// Set the scope of the address. Defaults to [`Scope::Universe`].
+    pub fn scope(mut self, scope: Scope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Set the address flags, a combination of the `IFA_F_*` constants in this module, such as
+    /// [`IFA_F_NODAD`] and [`IFA_F_NOPREFIXROUTE`].
+    pub fn flags(mut self, flags: u32) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Set the address of the remote end of a point-to-point link.
+    pub fn peer(mut self, peer: IpAddr) -> Self {
+        self.peer = Some(peer);
+        self
+    }
+
+    /// Set the IPv4 broadcast address.
+    pub fn broadcast(mut self, broadcast: Ipv4Addr) -> Self {
+        self.broadcast = Some(broadcast);
+        self
+    }
+
+    /// Set the IPv4 address label. The label must start with the interface name.
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Set the valid and preferred lifetimes in seconds. The address is removed when the valid
+    /// lifetime expires. Defaults to [`INFINITY_LIFE_TIME`] for both.
+    pub fn lifetimes(mut self, valid: u32, preferred: u32) -> Self {
+        self.lifetimes = Some((valid, preferred));
+        self
+    }
+
+    /// Add the address on `socket`, which must be a [`Bus::Route`] socket. Fails with `EEXIST`
+    /// if the address is already assigned to the interface.
+    ///
+    /// [`Bus::Route`]: crate::Bus::Route
+    pub fn add(&self, socket: &Socket) -> io::Result<()> {
+        self.send(socket, libc::NLM_F_EXCL)
+    }
+
+    /// Add the address on `socket`, or update its settings if it is already assigned to the
+    /// interface.
+    pub fn replace(&self, socket: &Socket) -> io::Result<()> {
+        self.send(socket, libc::NLM_F_REPLACE)
+    }
+
+    fn send(&self, socket: &Socket, flags: libc::c_int) -> io::Result<()> {
+        let flags = flags | libc::NLM_F_CREATE | libc::NLM_F_ACK;
+        let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+        let mut msg = new_request(
+            socket,
+            &mut buffer,
+            sys::RTM_NEWADDR,
+            flags as u16,
+            &self.header(),
+        )?;
+        self.put_attrs(&mut msg)?;
+        ack(socket, &msg)
+    }
+
+    fn header(&self) -> ifaddrmsg {
+        ifaddrmsg {
+            ifa_family: family_of(&self.address),
+            ifa_prefixlen: self.prefix_len,
+            ifa_flags: self.flags as u8,
+            ifa_scope: self.scope.into(),
+            ifa_index: self.index,
+        }
+    }
+
+    fn put_attrs(&self, msg: &mut NlMsgBuilder<'_>) -> io::Result<()> {
+        put_ip(msg, sys::IFA_LOCAL, &self.address)?;
+        put_ip(
+            msg,
+            sys::IFA_ADDRESS,
+            self.peer.as_ref().unwrap_or(&self.address),
+        )?;
+        if self.flags > u32::from(u8::MAX) {
+            msg.put_u32(sys::IFA_FLAGS, self.flags)?;
+        }
+        if let Some(broadcast) = self.broadcast {
+            msg.put(sys::IFA_BROADCAST, &broadcast.octets())?;
+        }
+        if let Some(label) = &self.label {
+            msg.put_str(sys::IFA_LABEL, label)?;
+        }
+        if let Some((valid, preferred)) = self.lifetimes {
+            let info = ifa_cacheinfo {
+                ifa_prefered: preferred,
+                ifa_valid: valid,
+                ..ifa_cacheinfo::default()
+            };
+            msg.put(sys::IFA_CACHEINFO, info.as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+/// Remove the address `address/prefix_len` from the interface with the given index with
+/// `RTM_DELADDR`.
+pub fn delete_address(
+    socket: &Socket,
+    index: u32,
+    address: IpAddr,
+    prefix_len: u8,
+) -> io::Result<()> {
+    let header = ifaddrmsg {
+        ifa_family: family_of(&address),
+        ifa_prefixlen: prefix_len,
+        ifa_index: index,
+        ..ifaddrmsg::default()
+    };
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let mut msg = new_request(
+        socket,
+        &mut buffer,
+        sys::RTM_DELADDR,
+        libc::NLM_F_ACK as u16,
+        &header,
+    )?;
+    put_ip(&mut msg, sys::IFA_LOCAL, &address)?;
+    ack(socket, &msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_address_round_trip() {
+        let new = NewAddress::new(4, "10.0.0.1".parse().unwrap(), 24)
+            .peer("10.0.0.2".parse().unwrap())
+            .flags(IFA_F_NOPREFIXROUTE | IFA_F_NODAD)
+            .label("tun0:1")
+            .lifetimes(300, 100);
+        let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        msg.put_extra_header(new.header().as_bytes()).unwrap();
+        new.put_attrs(&mut msg).unwrap();
+
+        let address = Address::from_msg(&NlMsg::new(msg.as_bytes()).unwrap()).unwrap();
+        assert_eq!(address.index, 4);
+        assert_eq!(address.address, "10.0.0.1".parse::<IpAddr>().unwrap());
+        assert_eq!(address.peer, Some("10.0.0.2".parse().unwrap()));
+        assert_eq!(address.prefix_len, 24);
+        assert_eq!(address.scope, Scope::Universe);
+        assert_eq!(address.flags, IFA_F_NOPREFIXROUTE | IFA_F_NODAD);
+        assert_eq!(address.label.as_deref(), Some("tun0:1"));
+        let cache_info = address.cache_info.unwrap();
+        assert_eq!(cache_info.valid_lifetime, 300);
+        assert_eq!(cache_info.preferred_lifetime, 100);
+    }
+}
@@ -0,0 +1,605 @@
+use mnl_sys::libc;
+use std::io;
+
+use super::{
+    REQUEST_BUFFER_SIZE, RawAttrs, ack, dump, new_request,
+    sys::{self, Pod, br_vlan_msg, bridge_vlan_info, ifinfomsg},
+};
+use crate::{Attr, NlMsg, NlMsgBuilder, Socket, buffer::AlignedBuffer};
+
+/// Bridge port state: the port is disabled.
+pub const BR_STATE_DISABLED: u8 = 0;
+/// Bridge port state: STP is listening on the port.
+pub const BR_STATE_LISTENING: u8 = 1;
+/// Bridge port state: the port learns addresses but does not forward yet.
+pub const BR_STATE_LEARNING: u8 = 2;
+/// Bridge port state: the port forwards traffic.
+pub const BR_STATE_FORWARDING: u8 = 3;
+/// Bridge port state: STP blocks the port.
+pub const BR_STATE_BLOCKING: u8 = 4;
+
+/// Bridge VLAN flag: the VLAN is configured on the bridge master rather than the port.
+pub const BRIDGE_VLAN_INFO_MASTER: u16 = 0x01;
+/// Bridge VLAN flag: untagged ingress traffic is assigned to this VLAN.
+pub const BRIDGE_VLAN_INFO_PVID: u16 = 0x02;
+/// Bridge VLAN flag: egress traffic leaves untagged.
+pub const BRIDGE_VLAN_INFO_UNTAGGED: u16 = 0x04;
+/// Bridge VLAN flag: the first VLAN of a range.
+pub const BRIDGE_VLAN_INFO_RANGE_BEGIN: u16 = 0x08;
+/// Bridge VLAN flag: the last VLAN of a range.
+pub const BRIDGE_VLAN_INFO_RANGE_END: u16 = 0x10;
+/// Bridge VLAN flag: the VLAN is a global entry on the bridge device itself.
+pub const BRIDGE_VLAN_INFO_BRENTRY: u16 = 0x20;
+
+/// A VLAN a bridge port is a member of (`IFLA_BRIDGE_VLAN_INFO`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BridgeVlan {
+    /// VLAN id.
+    pub vid: u16,
+    /// Flags, a combination of the `BRIDGE_VLAN_INFO_*` constants.
+    pub flags: u16,
+}
+
+impl BridgeVlan {
+    /// Returns true if untagged ingress traffic is assigned to this VLAN.
+    pub fn is_pvid(&self) -> bool {
+        self.flags & BRIDGE_VLAN_INFO_PVID != 0
+    }
+
+    /// Returns true if egress traffic in this VLAN leaves untagged.
+    pub fn is_untagged(&self) -> bool {
+        self.flags & BRIDGE_VLAN_INFO_UNTAGGED != 0
+    }
+}
+
+/// A bridge port, or a bridge itself, as described by a `RTM_NEWLINK` message of the
+/// `AF_BRIDGE` family.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BridgePort {
+    /// Interface index.
+    pub index: u32,
+    /// Interface name (`IFLA_IFNAME`).
+    pub name: String,
+    /// Index of the bridge the port is attached to (`IFLA_MASTER`).
+    pub master: Option<u32>,
+    /// STP state, one of the `BR_STATE_*` constants (`IFLA_BRPORT_STATE`).
+    pub state: Option<u8>,
+    /// STP port priority (`IFLA_BRPORT_PRIORITY`).
+    pub priority: Option<u16>,
+    /// STP path cost (`IFLA_BRPORT_COST`).
+    pub cost: Option<u32>,
+    /// Whether source addresses are learned (`IFLA_BRPORT_LEARNING`).
+    pub learning: Option<bool>,
+    /// Whether unicast traffic to unknown destinations is flooded to the port
+    /// (`IFLA_BRPORT_UNICAST_FLOOD`).
+    pub flood: Option<bool>,
+    /// Whether unknown multicast traffic is flooded to the port (`IFLA_BRPORT_MCAST_FLOOD`).
+    pub multicast_flood: Option<bool>,
+    /// Whether broadcast traffic is flooded to the port (`IFLA_BRPORT_BCAST_FLOOD`).
+    pub broadcast_flood: Option<bool>,
+    /// Whether the port is isolated from other isolated ports (`IFLA_BRPORT_ISOLATED`).
+    pub isolated: Option<bool>,
+    /// The VLANs the port is a member of (`IFLA_AF_SPEC`).
+    pub vlans: Vec<BridgeVlan>,
+    /// `IFLA_BRPORT_*` attributes without a typed field above.
+    pub other_attrs: RawAttrs,
+}
+
+impl BridgePort {
+    /// Parse a `RTM_NEWLINK` message of the `AF_BRIDGE` family.
+    pub fn from_msg(msg: &NlMsg<'_>) -> io::Result<BridgePort> {
+        let header = ifinfomsg::from_bytes(msg.payload())?;
+        let mut port = BridgePort {
+            index: header.ifi_index as u32,
+            ..BridgePort::default()
+        };
+        for attr in msg.attrs(size_of::<ifinfomsg>()) {
+            let attr = attr?;
+            match attr.attr_type() {
+                sys::IFLA_IFNAME => port.name = attr.get_str()?.to_owned(),
+                sys::IFLA_MASTER => port.master = Some(attr.get_u32()?),
+                sys::IFLA_PROTINFO => port.parse_protinfo(attr)?,
+                sys::IFLA_AF_SPEC => port.parse_vlans(attr)?,
+                _ => (),
+            }
+        }
+        Ok(port)
+    }
+
+    fn parse_protinfo(&mut self, attr: Attr<'_>) -> io::Result<()> {
+        for attr in attr.nested() {
+            let attr = attr?;
+            let flag = || attr.get_u8().map(|value| value != 0);
+            match attr.attr_type() {
+                sys::IFLA_BRPORT_STATE => self.state = Some(attr.get_u8()?),
+                sys::IFLA_BRPORT_PRIORITY => self.priority = Some(attr.get_u16()?),
+                sys::IFLA_BRPORT_COST => self.cost = Some(attr.get_u32()?),
+                sys::IFLA_BRPORT_LEARNING => self.learning = Some(flag()?),
+                sys::IFLA_BRPORT_UNICAST_FLOOD => self.flood = Some(flag()?),
+                sys::IFLA_BRPORT_MCAST_FLOOD => self.multicast_flood = Some(flag()?),
+                sys::IFLA_BRPORT_BCAST_FLOOD => self.broadcast_flood = Some(flag()?),
+                sys::IFLA_BRPORT_ISOLATED => self.isolated = Some(flag()?),
+                other => self.other_attrs.push((other, attr.payload().to_vec())),
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_vlans(&mut self, attr: Attr<'_>) -> io::Result<()> {
+        let mut range_begin = None;
+        for attr in attr.nested() {
+            let attr = attr?;
+            if attr.attr_type() != sys::IFLA_BRIDGE_VLAN_INFO {
+                continue;
+            }
+            let info = bridge_vlan_info::from_bytes(attr.payload())?;
+            let flags = info.flags & !(BRIDGE_VLAN_INFO_RANGE_BEGIN | BRIDGE_VLAN_INFO_RANGE_END);
+            if info.flags & BRIDGE_VLAN_INFO_RANGE_BEGIN != 0 {
+                range_begin = Some(info.vid);
+            } else if info.flags & BRIDGE_VLAN_INFO_RANGE_END != 0 {
+                let begin = range_begin.take().unwrap_or(info.vid);
+                self.vlans
+                    .extend((begin..=info.vid).map(|vid| BridgeVlan { vid, flags }));
+            } else {
+                self.vlans.push(BridgeVlan {
+                    vid: info.vid,
+                    flags,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Dump all bridge ports with their VLANs. Bridges with VLAN filtering enabled are included
+/// too, with their own VLANs.
+pub fn bridge_ports(socket: &Socket) -> io::Result<Vec<BridgePort>> {
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let mut msg = new_request(
+        socket,
+        &mut buffer,
+        sys::RTM_GETLINK,
+        libc::NLM_F_DUMP as u16,
+        &bridge_header(0),
+    )?;
+    msg.put_u32(sys::IFLA_EXT_MASK, sys::RTEXT_FILTER_BRVLAN)?;
+    dump(socket, &msg, sys::RTM_NEWLINK, BridgePort::from_msg)
+}
+
+fn bridge_header(index: u32) -> ifinfomsg {
+    ifinfomsg {
+        ifi_family: libc::AF_BRIDGE as u8,
+        ifi_index: index as i32,
+        ..ifinfomsg::default()
+    }
+}
+
+/// Builder for changing the settings of a bridge port. Only the settings given to the builder
+/// are changed.
+///
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// use mnl::rtnl::{self, SetBridgePort};
+///
+/// let socket = mnl::Socket::new(mnl::Bus::Route)?;
+/// let port = rtnl::link_by_name(&socket, "veth0")?;
+/// SetBridgePort::new(port.index)
+///     .learning(false)
+///     .flood(false)
+///     .isolated(true)
+///     .apply(&socket)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetBridgePort {
+    index: u32,
+    priority: Option<u16>,
+    cost: Option<u32>,
+    learning: Option<bool>,
+    flood: Option<bool>,
+    multicast_flood: Option<bool>,
+    broadcast_flood: Option<bool>,
+    isolated: Option<bool>,
+}
+
+impl SetBridgePort {
+    /// Prepare changes to the bridge port with the given interface index.
+    pub fn new(index: u32) -> Self {
+        SetBridgePort {
+            index,
+            priority: None,
+            cost: None,
+            learning: None,
+            flood: None,
+            multicast_flood: None,
+            broadcast_flood: None,
+            isolated: None,
+        }
+    }
+
+    /// Set the STP port priority.
+    pub fn priority(mut self, priority: u16) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Set the STP path cost.
+    pub fn cost(mut self, cost: u32) -> Self {
+        self.cost = Some(cost);
+        self
+    }
+
+    /// Enable or disable learning source addresses.
+    pub fn learning(mut self, learning: bool) -> Self {
+        self.learning = Some(learning);
+        self
+    }
+
+    /// Enable or disable flooding unicast traffic to unknown destinations to the port.
+    pub fn flood(mut self, flood: bool) -> Self {
+        self.flood = Some(flood);
+        self
+    }
+
+    /// Enable or disable flooding unknown multicast traffic to the port.
+    pub fn multicast_flood(mut self, flood: bool) -> Self {
+        self.multicast_flood = Some(flood);
+        self
+    }
+
+    /// Enable or disable flooding broadcast traffic to the port.
+    pub fn broadcast_flood(mut self, flood: bool) -> Self {
+        self.broadcast_flood = Some(flood);
+        self
+    }
+
+    /// Isolate the port. Isolated ports can only talk to ports that are not isolated.
+    pub fn isolated(mut self, isolated: bool) -> Self {
+        self.isolated = Some(isolated);
+        self
+    }
+
+    /// Apply the changes on `socket`, which must be a [`Bus::Route`] socket.
+    ///
+    /// [`Bus::Route`]: crate::Bus::Route
+    pub fn apply(&self, socket: &Socket) -> io::Result<()> {
+        let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+        let mut msg = new_request(
+            socket,
+            &mut buffer,
+            sys::RTM_SETLINK,
+            libc::NLM_F_ACK as u16,
+            &bridge_header(self.index),
+        )?;
+        // The bridge only treats IFLA_PROTINFO as port attributes if it is flagged as nested.
+        msg.put_nested(sys::IFLA_PROTINFO | libc::NLA_F_NESTED as u16, |msg| {
+            self.put_attrs(msg)
+        })?;
+        ack(socket, &msg)
+    }
+
+    fn put_attrs(&self, msg: &mut NlMsgBuilder<'_>) -> io::Result<()> {
+        if let Some(priority) = self.priority {
+            msg.put_u16(sys::IFLA_BRPORT_PRIORITY, priority)?;
+        }
+        if let Some(cost) = self.cost {
+            msg.put_u32(sys::IFLA_BRPORT_COST, cost)?;
+        }
+        let flags = [
+            (sys::IFLA_BRPORT_LEARNING, self.learning),
+            (sys::IFLA_BRPORT_UNICAST_FLOOD, self.flood),
+            (sys::IFLA_BRPORT_MCAST_FLOOD, self.multicast_flood),
+            (sys::IFLA_BRPORT_BCAST_FLOOD, self.broadcast_flood),
+            (sys::IFLA_BRPORT_ISOLATED, self.isolated),
+        ];
+        for (attr_type, value) in flags {
+            if let Some(value) = value {
+                msg.put_u8(attr_type, u8::from(value))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Add the bridge port with the given interface index to a VLAN (`RTM_SETLINK` with
+/// `IFLA_BRIDGE_VLAN_INFO`). Adding it again changes the flags.
+pub fn add_bridge_port_vlan(socket: &Socket, index: u32, vlan: BridgeVlan) -> io::Result<()> {
+    port_vlan_request(socket, sys::RTM_SETLINK, index, vlan)
+}
+
+/// Remove the bridge port with the given interface index from a VLAN (`RTM_DELLINK` with
+/// `IFLA_BRIDGE_VLAN_INFO`).
+pub fn delete_bridge_port_vlan(socket: &Socket, index: u32, vid: u16) -> io::Result<()> {
+    port_vlan_request(
+        socket,
+        sys::RTM_DELLINK,
+        index,
+        BridgeVlan { vid, flags: 0 },
+    )
+}
+
+fn port_vlan_request(
+    socket: &Socket,
+    msg_type: u16,
+    index: u32,
+    vlan: BridgeVlan,
+) -> io::Result<()> {
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let mut msg = new_request(
+        socket,
+        &mut buffer,
+        msg_type,
+        libc::NLM_F_ACK as u16,
+        &bridge_header(index),
+    )?;
+    msg.put_nested(sys::IFLA_AF_SPEC, |msg| {
+        msg.put_u16(sys::IFLA_BRIDGE_FLAGS, sys::BRIDGE_FLAGS_MASTER)?;
+        let info = bridge_vlan_info {
+            flags: vlan.flags,
+            vid: vlan.vid,
+        };
+        msg.put(sys::IFLA_BRIDGE_VLAN_INFO, info.as_bytes())
+    })?;
+    ack(socket, &msg)
+}
+
+/// An entry of the bridge VLAN database, as described by a `RTM_NEWVLAN` message. Unlike
+/// [`BridgeVlan`] this covers a range of VLANs sharing the same settings, and works the same
+/// for bridge ports and bridges.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BridgeVlanEntry {
+    /// Index of the bridge port or bridge.
+    pub index: u32,
+    /// VLAN id, or the first VLAN id of a range.
+    pub vid: u16,
+    /// The last VLAN id of a range (`BRIDGE_VLANDB_ENTRY_RANGE`).
+    pub range_end: Option<u16>,
+    /// Flags, a combination of the `BRIDGE_VLAN_INFO_*` constants.
+    pub flags: u16,
+    /// Per VLAN STP state, one of the `BR_STATE_*` constants (`BRIDGE_VLANDB_ENTRY_STATE`).
+    pub state: Option<u8>,
+    /// Attributes of the entry without a typed field above.
+    pub other_attrs: RawAttrs,
+}
+
+impl BridgeVlanEntry {
+    /// An entry for the given VLAN on the bridge port or bridge with the given index.
+    pub fn new(index: u32, vid: u16) -> Self {
+        BridgeVlanEntry {
+            index,
+            vid,
+            range_end: None,
+            flags: 0,
+            state: None,
+            other_attrs: RawAttrs::new(),
+        }
+    }
+
+    /// Parse a `RTM_NEWVLAN` message, which holds all entries of one bridge port or bridge.
+    pub fn from_msg(msg: &NlMsg<'_>) -> io::Result<Vec<BridgeVlanEntry>> {
+        let header = br_vlan_msg::from_bytes(msg.payload())?;
+        let mut entries = Vec::new();
+        for attr in msg.attrs(size_of::<br_vlan_msg>()) {
+            let attr = attr?;
+            if attr.attr_type() != sys::BRIDGE_VLANDB_ENTRY {
+                continue;
+            }
+            let mut entry = BridgeVlanEntry::new(header.ifindex, 0);
+            for attr in attr.nested() {
+                let attr = attr?;
+                match attr.attr_type() {
+                    sys::BRIDGE_VLANDB_ENTRY_INFO => {
+                        let info = bridge_vlan_info::from_bytes(attr.payload())?;
+                        entry.vid = info.vid;
+                        entry.flags = info.flags;
+                    }
+                    sys::BRIDGE_VLANDB_ENTRY_RANGE => entry.range_end = Some(attr.get_u16()?),
+                    sys::BRIDGE_VLANDB_ENTRY_STATE => entry.state = Some(attr.get_u8()?),
+                    other => entry.other_attrs.push((other, attr.payload().to_vec())),
+                }
+            }
+            entries.push(entry);
+        }
+        Ok(entries)
+    }
+
+    fn put_entry(&self, msg: &mut NlMsgBuilder<'_>, with_options: bool) -> io::Result<()> {
+        msg.put_nested(sys::BRIDGE_VLANDB_ENTRY, |msg| {
+            let info = bridge_vlan_info {
+                flags: self.flags,
+                vid: self.vid,
+            };
+            msg.put(sys::BRIDGE_VLANDB_ENTRY_INFO, info.as_bytes())?;
+            if let Some(range_end) = self.range_end {
+                msg.put_u16(sys::BRIDGE_VLANDB_ENTRY_RANGE, range_end)?;
+            }
+            if let (Some(state), true) = (self.state, with_options) {
+                msg.put_u8(sys::BRIDGE_VLANDB_ENTRY_STATE, state)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Dump the bridge VLAN database of all bridge ports and bridges (`RTM_GETVLAN`).
+pub fn bridge_vlans(socket: &Socket) -> io::Result<Vec<BridgeVlanEntry>> {
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let msg = new_request(
+        socket,
+        &mut buffer,
+        sys::RTM_GETVLAN,
+        libc::NLM_F_DUMP as u16,
+        &vlan_header(0),
+    )?;
+    let entries = dump(socket, &msg, sys::RTM_NEWVLAN, BridgeVlanEntry::from_msg)?;
+    Ok(entries.into_iter().flatten().collect())
+}
+
+fn vlan_header(index: u32) -> br_vlan_msg {
+    br_vlan_msg {
+        family: libc::AF_BRIDGE as u8,
+        ifindex: index,
+        ..br_vlan_msg::default()
+    }
+}
+
+/// Builder for adding a bridge port or bridge to VLANs with `RTM_NEWVLAN`.
+///
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// use mnl::rtnl::{self, NewBridgeVlan};
+///
+/// let socket = mnl::Socket::new(mnl::Bus::Route)?;
+/// let port = rtnl::link_by_name(&socket, "veth0")?;
+/// NewBridgeVlan::new(port.index, 100).range_end(199).add(&socket)?;
+/// NewBridgeVlan::new(port.index, 10)
+///     .flags(rtnl::BRIDGE_VLAN_INFO_PVID | rtnl::BRIDGE_VLAN_INFO_UNTAGGED)
+///     .add(&socket)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewBridgeVlan {
+    entry: BridgeVlanEntry,
+}
+
+impl NewBridgeVlan {
+    /// Prepare adding the bridge port or bridge with the given index to the VLAN `vid`.
+    pub fn new(index: u32, vid: u16) -> Self {
+        NewBridgeVlan {
+            entry: BridgeVlanEntry::new(index, vid),
+        }
+    }
+
+    /// Add all VLANs from the one given to [`new`] up to and including `range_end`.
+    ///
+    /// [`new`]: #method.new
+    pub fn range_end(mut self, range_end: u16) -> Self {
+        self.entry.range_end = Some(range_end);
+        self
+    }
+
+    /// Set the flags, a combination of the `BRIDGE_VLAN_INFO_*` constants.
+    pub fn flags(mut self, flags: u16) -> Self {
+        self.entry.flags = flags;
+        self
+    }
+
+    /// Set the per VLAN STP state, one of the `BR_STATE_*` constants.
+    pub fn state(mut self, state: u8) -> Self {
+        self.entry.state = Some(state);
+        self
+    }
+
+    /// Add the VLANs on `socket`, or change the settings if they exist.
+    pub fn add(&self, socket: &Socket) -> io::Result<()> {
+        let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+        let mut msg = new_request(
+            socket,
+            &mut buffer,
+            sys::RTM_NEWVLAN,
+            libc::NLM_F_ACK as u16,
+            &vlan_header(self.entry.index),
+        )?;
+        self.entry.put_entry(&mut msg, true)?;
+        ack(socket, &msg)
+    }
+}
+
+/// Remove a bridge port or bridge from the VLANs of `entry` with `RTM_DELVLAN`.
+pub fn delete_bridge_vlan(socket: &Socket, entry: &BridgeVlanEntry) -> io::Result<()> {
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let mut msg = new_request(
+        socket,
+        &mut buffer,
+        sys::RTM_DELVLAN,
+        libc::NLM_F_ACK as u16,
+        &vlan_header(entry.index),
+    )?;
+    entry.put_entry(&mut msg, false)?;
+    ack(socket, &msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bridge_port() {
+        let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        msg.set_type(sys::RTM_NEWLINK);
+        msg.put_extra_header(bridge_header(5).as_bytes()).unwrap();
+        msg.put_str(sys::IFLA_IFNAME, "veth0").unwrap();
+        msg.put_u32(sys::IFLA_MASTER, 3).unwrap();
+        let set = SetBridgePort::new(5)
+            .cost(100)
+            .learning(false)
+            .isolated(true);
+        msg.put_nested(sys::IFLA_PROTINFO | libc::NLA_F_NESTED as u16, |msg| {
+            set.put_attrs(msg)
+        })
+        .unwrap();
+        msg.put_nested(sys::IFLA_AF_SPEC, |msg| {
+            let vlans = [
+                (BRIDGE_VLAN_INFO_PVID | BRIDGE_VLAN_INFO_UNTAGGED, 1),
+                (BRIDGE_VLAN_INFO_RANGE_BEGIN, 10),
+                (BRIDGE_VLAN_INFO_RANGE_END, 12),
+            ];
+            for (flags, vid) in vlans {
+                let info = bridge_vlan_info { flags, vid };
+                msg.put(sys::IFLA_BRIDGE_VLAN_INFO, info.as_bytes())?;
+            }
+            Ok(())
+        })
+        .unwrap();
+
+        let port = BridgePort::from_msg(&NlMsg::new(msg.as_bytes()).unwrap()).unwrap();
+        assert_eq!(
+            (port.index, port.name.as_str(), port.master),
+            (5, "veth0", Some(3))
+        );
+        assert_eq!(port.cost, Some(100));
+        assert_eq!(port.learning, Some(false));
+        assert_eq!(port.isolated, Some(true));
+        assert_eq!(port.flood, None);
+        let vids: Vec<_> = port.vlans.iter().map(|vlan| vlan.vid).collect();
+        assert_eq!(vids, [1, 10, 11, 12]);
+        assert!(port.vlans[0].is_pvid() && port.vlans[0].is_untagged());
+        assert_eq!(port.vlans[2].flags, 0);
+    }
+
+    #[test]
+    fn test_bridge_vlan_round_trip() {
+        let flags = BRIDGE_VLAN_INFO_PVID | BRIDGE_VLAN_INFO_UNTAGGED;
+        let new = NewBridgeVlan::new(5, 100)
+            .range_end(199)
+            .flags(flags)
+            .state(3);
+        let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        msg.set_type(sys::RTM_NEWVLAN);
+        msg.put_extra_header(vlan_header(5).as_bytes()).unwrap();
+        new.entry.put_entry(&mut msg, true).unwrap();
+
+        // struct br_vlan_msg, then the entry nest holding struct bridge_vlan_info.
+        let bytes = msg.as_bytes();
+        let header = [&[libc::AF_BRIDGE as u8, 0, 0, 0][..], &5u32.to_ne_bytes()].concat();
+        assert_eq!(bytes[16..24], header);
+        let info = [flags.to_ne_bytes(), 100u16.to_ne_bytes()].concat();
+        assert_eq!(bytes[32..36], info);
+
+        let entries = BridgeVlanEntry::from_msg(&NlMsg::new(bytes).unwrap()).unwrap();
+        assert_eq!(entries, std::slice::from_ref(&new.entry));
+        assert_eq!(entries[0].range_end, Some(199));
+        assert_eq!(entries[0].state, Some(3));
+
+        // Deleting leaves out the options.
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        msg.put_extra_header(vlan_header(5).as_bytes()).unwrap();
+        new.entry.put_entry(&mut msg, false).unwrap();
+        let entries = BridgeVlanEntry::from_msg(&NlMsg::new(msg.as_bytes()).unwrap()).unwrap();
+        assert_eq!(entries[0].state, None);
+    }
+}
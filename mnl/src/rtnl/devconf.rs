@@ -0,0 +1,349 @@
+use mnl_sys::libc;
+use std::io;
+
+use super::{
+    Link, REQUEST_BUFFER_SIZE, RawAttrs, dump, new_request,
+    sys::{self, Pod, netconfmsg},
+};
+use crate::{Attr, NlMsg, Socket, buffer::AlignedBuffer};
+
+/// IPv4 setting `forwarding`.
+pub const IPV4_DEVCONF_FORWARDING: u16 = 1;
+/// IPv4 setting `mc_forwarding`.
+pub const IPV4_DEVCONF_MC_FORWARDING: u16 = 2;
+/// IPv4 setting `proxy_arp`.
+pub const IPV4_DEVCONF_PROXY_ARP: u16 = 3;
+/// IPv4 setting `accept_redirects`.
+pub const IPV4_DEVCONF_ACCEPT_REDIRECTS: u16 = 4;
+/// IPv4 setting `secure_redirects`.
+pub const IPV4_DEVCONF_SECURE_REDIRECTS: u16 = 5;
+/// IPv4 setting `send_redirects`.
+pub const IPV4_DEVCONF_SEND_REDIRECTS: u16 = 6;
+/// IPv4 setting `shared_media`.
+pub const IPV4_DEVCONF_SHARED_MEDIA: u16 = 7;
+/// IPv4 setting `rp_filter`: 0 for no source validation, 1 for strict and 2 for loose mode.
+pub const IPV4_DEVCONF_RP_FILTER: u16 = 8;
+/// IPv4 setting `accept_source_route`.
+pub const IPV4_DEVCONF_ACCEPT_SOURCE_ROUTE: u16 = 9;
+/// IPv4 setting `bootp_relay`.
+pub const IPV4_DEVCONF_BOOTP_RELAY: u16 = 10;
+/// IPv4 setting `log_martians`.
+pub const IPV4_DEVCONF_LOG_MARTIANS: u16 = 11;
+/// IPv4 setting `tag`.
+pub const IPV4_DEVCONF_TAG: u16 = 12;
+/// IPv4 setting `arp_filter`.
+pub const IPV4_DEVCONF_ARPFILTER: u16 = 13;
+/// IPv4 setting `medium_id`.
+pub const IPV4_DEVCONF_MEDIUM_ID: u16 = 14;
+/// IPv4 setting `disable_xfrm`.
+pub const IPV4_DEVCONF_NOXFRM: u16 = 15;
+/// IPv4 setting `disable_policy`.
+pub const IPV4_DEVCONF_NOPOLICY: u16 = 16;
+/// IPv4 setting `force_igmp_version`.
+pub const IPV4_DEVCONF_FORCE_IGMP_VERSION: u16 = 17;
+/// IPv4 setting `arp_announce`.
+pub const IPV4_DEVCONF_ARP_ANNOUNCE: u16 = 18;
+/// IPv4 setting `arp_ignore`.
+pub const IPV4_DEVCONF_ARP_IGNORE: u16 = 19;
+/// IPv4 setting `promote_secondaries`.
+pub const IPV4_DEVCONF_PROMOTE_SECONDARIES: u16 = 20;
+/// IPv4 setting `arp_accept`.
+pub const IPV4_DEVCONF_ARP_ACCEPT: u16 = 21;
+/// IPv4 setting `arp_notify`.
+pub const IPV4_DEVCONF_ARP_NOTIFY: u16 = 22;
+/// IPv4 setting `accept_local`.
+pub const IPV4_DEVCONF_ACCEPT_LOCAL: u16 = 23;
+/// IPv4 setting `src_valid_mark`.
+pub const IPV4_DEVCONF_SRC_VMARK: u16 = 24;
+/// IPv4 setting `proxy_arp_pvlan`.
+pub const IPV4_DEVCONF_PROXY_ARP_PVLAN: u16 = 25;
+/// IPv4 setting `route_localnet`.
+pub const IPV4_DEVCONF_ROUTE_LOCALNET: u16 = 26;
+/// IPv4 setting `ignore_routes_with_linkdown`.
+pub const IPV4_DEVCONF_IGNORE_ROUTES_WITH_LINKDOWN: u16 = 29;
+/// IPv4 setting `drop_unicast_in_l2_multicast`.
+pub const IPV4_DEVCONF_DROP_UNICAST_IN_L2_MULTICAST: u16 = 30;
+/// IPv4 setting `drop_gratuitous_arp`.
+pub const IPV4_DEVCONF_DROP_GRATUITOUS_ARP: u16 = 31;
+/// IPv4 setting `bc_forwarding`.
+pub const IPV4_DEVCONF_BC_FORWARDING: u16 = 32;
+
+/// IPv6 setting `forwarding` (`DEVCONF_FORWARDING`).
+pub const IPV6_DEVCONF_FORWARDING: u16 = 0;
+/// IPv6 setting `hop_limit` (`DEVCONF_HOPLIMIT`).
+pub const IPV6_DEVCONF_HOPLIMIT: u16 = 1;
+/// IPv6 setting `mtu` (`DEVCONF_MTU6`).
+pub const IPV6_DEVCONF_MTU6: u16 = 2;
+/// IPv6 setting `accept_ra` (`DEVCONF_ACCEPT_RA`): 0 to ignore router advertisements, 1 to
+/// accept them unless forwarding and 2 to accept them even when forwarding.
+pub const IPV6_DEVCONF_ACCEPT_RA: u16 = 3;
+/// IPv6 setting `accept_redirects` (`DEVCONF_ACCEPT_REDIRECTS`).
+pub const IPV6_DEVCONF_ACCEPT_REDIRECTS: u16 = 4;
+/// IPv6 setting `autoconf` (`DEVCONF_AUTOCONF`).
+pub const IPV6_DEVCONF_AUTOCONF: u16 = 5;
+/// IPv6 setting `dad_transmits` (`DEVCONF_DAD_TRANSMITS`).
+pub const IPV6_DEVCONF_DAD_TRANSMITS: u16 = 6;
+/// IPv6 setting `use_tempaddr` (`DEVCONF_USE_TEMPADDR`).
+pub const IPV6_DEVCONF_USE_TEMPADDR: u16 = 10;
+/// IPv6 setting `accept_ra_defrtr` (`DEVCONF_ACCEPT_RA_DEFRTR`).
+pub const IPV6_DEVCONF_ACCEPT_RA_DEFRTR: u16 = 17;
+/// IPv6 setting `accept_ra_pinfo` (`DEVCONF_ACCEPT_RA_PINFO`).
+pub const IPV6_DEVCONF_ACCEPT_RA_PINFO: u16 = 18;
+/// IPv6 setting `proxy_ndp` (`DEVCONF_PROXY_NDP`).
+pub const IPV6_DEVCONF_PROXY_NDP: u16 = 22;
+/// IPv6 setting `accept_source_route` (`DEVCONF_ACCEPT_SOURCE_ROUTE`).
+pub const IPV6_DEVCONF_ACCEPT_SOURCE_ROUTE: u16 = 24;
+/// IPv6 setting `mc_forwarding` (`DEVCONF_MC_FORWARDING`).
+pub const IPV6_DEVCONF_MC_FORWARDING: u16 = 25;
+/// IPv6 setting `disable_ipv6` (`DEVCONF_DISABLE_IPV6`).
+pub const IPV6_DEVCONF_DISABLE_IPV6: u16 = 26;
+/// IPv6 setting `accept_dad` (`DEVCONF_ACCEPT_DAD`).
+pub const IPV6_DEVCONF_ACCEPT_DAD: u16 = 27;
+
+/// Netconf index of the settings applying to all interfaces (`all`).
+pub const NETCONFA_IFINDEX_ALL: i32 = -1;
+/// Netconf index of the settings new interfaces start with (`default`).
+pub const NETCONFA_IFINDEX_DEFAULT: i32 = -2;
+
+/// The IPv4 settings of a link (`IFLA_INET_CONF`), as found in
+/// `/proc/sys/net/ipv4/conf/<link>/`. Change them with [`SetLink::ipv4_conf`].
+///
+/// [`SetLink::ipv4_conf`]: super::SetLink::ipv4_conf
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Ipv4DevConf {
+    values: Vec<i32>,
+}
+
+impl Ipv4DevConf {
+    /// Returns the value of the setting with the given `IPV4_DEVCONF_*` id, or `None` if the
+    /// kernel did not report it.
+    pub fn get(&self, id: u16) -> Option<i32> {
+        let index = usize::from(id).checked_sub(1)?;
+        self.values.get(index).copied()
+    }
+
+    /// Returns whether IPv4 packets are forwarded from this link.
+    pub fn forwarding(&self) -> Option<bool> {
+        self.get(IPV4_DEVCONF_FORWARDING).map(|value| value != 0)
+    }
+
+    /// Returns the reverse path filter mode, see [`IPV4_DEVCONF_RP_FILTER`].
+    pub fn rp_filter(&self) -> Option<i32> {
+        self.get(IPV4_DEVCONF_RP_FILTER)
+    }
+}
+
+/// The IPv6 settings of a link (`IFLA_INET6_CONF`), as found in
+/// `/proc/sys/net/ipv6/conf/<link>/`.
+///
+/// The kernel does not accept changes to these settings over netlink, only through the sysctl
+/// files.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Ipv6DevConf {
+    values: Vec<i32>,
+}
+
+impl Ipv6DevConf {
+    /// Returns the value of the setting with the given `IPV6_DEVCONF_*` id, or `None` if the
+    /// kernel did not report it.
+    pub fn get(&self, id: u16) -> Option<i32> {
+        self.values.get(usize::from(id)).copied()
+    }
+
+    /// Returns whether IPv6 packets are forwarded from this link.
+    pub fn forwarding(&self) -> Option<bool> {
+        self.get(IPV6_DEVCONF_FORWARDING).map(|value| value != 0)
+    }
+
+    /// Returns whether IPv6 is disabled on this link.
+    pub fn disable_ipv6(&self) -> Option<bool> {
+        self.get(IPV6_DEVCONF_DISABLE_IPV6).map(|value| value != 0)
+    }
+
+    /// Returns how router advertisements are accepted, see [`IPV6_DEVCONF_ACCEPT_RA`].
+    pub fn accept_ra(&self) -> Option<i32> {
+        self.get(IPV6_DEVCONF_ACCEPT_RA)
+    }
+}
+
+/// Parse `IFLA_INET_CONF` or `IFLA_INET6_CONF`, an array with the value of every setting.
+fn parse_values(payload: &[u8]) -> Vec<i32> {
+    payload
+        .chunks_exact(4)
+        .map(|value| i32::from_ne_bytes(value.try_into().unwrap()))
+        .collect()
+}
+
+/// Parse the IPv4 and IPv6 parts of `IFLA_AF_SPEC` into `link`.
+pub(super) fn parse_af_spec(link: &mut Link, attr: Attr<'_>) -> io::Result<()> {
+    for family in attr.nested() {
+        let family = family?;
+        match i32::from(family.attr_type()) {
+            libc::AF_INET => {
+                for attr in family.nested() {
+                    let attr = attr?;
+                    if attr.attr_type() == sys::IFLA_INET_CONF {
+                        link.ipv4_conf = Some(Ipv4DevConf {
+                            values: parse_values(attr.payload()),
+                        });
+                    }
+                }
+            }
+            libc::AF_INET6 => {
+                for attr in family.nested() {
+                    let attr = attr?;
+                    match attr.attr_type() {
+                        sys::IFLA_INET6_FLAGS => link.ipv6_flags = Some(attr.get_u32()?),
+                        sys::IFLA_INET6_CONF => {
+                            link.ipv6_conf = Some(Ipv6DevConf {
+                                values: parse_values(attr.payload()),
+                            })
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+/// The main IP settings of a link, or of all links, as described by a `RTM_NEWNETCONF`
+/// message. The kernel sends these as notifications when one of them changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetConf {
+    /// Address family, `AF_INET` or `AF_INET6`.
+    pub family: u8,
+    /// Index of the link, or [`NETCONFA_IFINDEX_ALL`] or [`NETCONFA_IFINDEX_DEFAULT`].
+    pub index: i32,
+    /// Whether packets are forwarded (`NETCONFA_FORWARDING`).
+    pub forwarding: Option<bool>,
+    /// Reverse path filter mode, IPv4 only (`NETCONFA_RP_FILTER`).
+    pub rp_filter: Option<i32>,
+    /// Whether multicast packets are forwarded (`NETCONFA_MC_FORWARDING`).
+    pub mc_forwarding: Option<bool>,
+    /// Whether proxy ARP or proxy NDP is enabled (`NETCONFA_PROXY_NEIGH`).
+    pub proxy_neigh: Option<bool>,
+    /// Whether routes through links without carrier are ignored
+    /// (`NETCONFA_IGNORE_ROUTES_WITH_LINKDOWN`).
+    pub ignore_routes_with_linkdown: Option<bool>,
+    /// Attributes without a typed field above.
+    pub other_attrs: RawAttrs,
+}
+
+impl NetConf {
+    /// Parse a `RTM_NEWNETCONF` or `RTM_DELNETCONF` message.
+    pub fn from_msg(msg: &NlMsg<'_>) -> io::Result<NetConf> {
+        let header = netconfmsg::from_bytes(msg.payload())?;
+        let mut netconf = NetConf {
+            family: header.ncm_family,
+            index: 0,
+            forwarding: None,
+            rp_filter: None,
+            mc_forwarding: None,
+            proxy_neigh: None,
+            ignore_routes_with_linkdown: None,
+            other_attrs: RawAttrs::new(),
+        };
+        for attr in msg.attrs(size_of::<netconfmsg>()) {
+            let attr = attr?;
+            let flag = || attr.get_u32().map(|value| value != 0);
+            match attr.attr_type() {
+                sys::NETCONFA_IFINDEX => netconf.index = attr.get_u32()? as i32,
+                sys::NETCONFA_FORWARDING => netconf.forwarding = Some(flag()?),
+                sys::NETCONFA_RP_FILTER => netconf.rp_filter = Some(attr.get_u32()? as i32),
+                sys::NETCONFA_MC_FORWARDING => netconf.mc_forwarding = Some(flag()?),
+                sys::NETCONFA_PROXY_NEIGH => netconf.proxy_neigh = Some(flag()?),
+                sys::NETCONFA_IGNORE_ROUTES_WITH_LINKDOWN => {
+                    netconf.ignore_routes_with_linkdown = Some(flag()?)
+                }
+                other => netconf.other_attrs.push((other, attr.payload().to_vec())),
+            }
+        }
+        Ok(netconf)
+    }
+}
+
+/// Dump the netconf settings of all links, and of `all` and `default`, for every address
+/// family.
+pub fn netconfs(socket: &Socket) -> io::Result<Vec<NetConf>> {
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let msg = new_request(
+        socket,
+        &mut buffer,
+        sys::RTM_GETNETCONF,
+        libc::NLM_F_DUMP as u16,
+        &netconfmsg::default(),
+    )?;
+    dump(socket, &msg, sys::RTM_NEWNETCONF, NetConf::from_msg)
+}
+
+/// Get the netconf settings of one address `family`, `AF_INET` or `AF_INET6`, for the link
+/// with the given `index`, or [`NETCONFA_IFINDEX_ALL`] or [`NETCONFA_IFINDEX_DEFAULT`].
+///
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// use mnl::{mnl_sys::libc, rtnl};
+///
+/// let socket = mnl::Socket::new(mnl::Bus::Route)?;
+/// let all = rtnl::netconf(&socket, libc::AF_INET as u8, rtnl::NETCONFA_IFINDEX_ALL)?;
+/// println!("IPv4 forwarding: {:?}", all.forwarding);
+/// # Ok(())
+/// # }
+/// ```
+pub fn netconf(socket: &Socket, family: u8, index: i32) -> io::Result<NetConf> {
+    let header = netconfmsg {
+        ncm_family: family,
+        ..netconfmsg::default()
+    };
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let mut msg = new_request(socket, &mut buffer, sys::RTM_GETNETCONF, 0, &header)?;
+    msg.put_u32(sys::NETCONFA_IFINDEX, index as u32)?;
+    dump(socket, &msg, sys::RTM_NEWNETCONF, NetConf::from_msg)?
+        .pop()
+        .ok_or_else(|| io::Error::from_raw_os_error(libc::ENODEV))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NlMsgBuilder, rtnl::sys::ifinfomsg};
+
+    #[test]
+    fn test_parse_af_spec() {
+        let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        msg.set_type(sys::RTM_NEWLINK);
+        msg.put_extra_header(ifinfomsg::default().as_bytes())
+            .unwrap();
+        let ipv4: Vec<u8> = [1i32, 0, 0, 0, 0, 0, 0, 2]
+            .iter()
+            .flat_map(|value| value.to_ne_bytes())
+            .collect();
+        let ipv6: Vec<u8> = (0..30i32)
+            .flat_map(|id| i32::from(id == 26).to_ne_bytes())
+            .collect();
+        msg.put_nested(sys::IFLA_AF_SPEC, |msg| {
+            msg.put_nested(libc::AF_INET as u16, |msg| {
+                msg.put(sys::IFLA_INET_CONF, &ipv4)
+            })?;
+            msg.put_nested(libc::AF_INET6 as u16, |msg| {
+                msg.put_u32(sys::IFLA_INET6_FLAGS, 0x8000_0000)?;
+                msg.put(sys::IFLA_INET6_CONF, &ipv6)
+            })
+        })
+        .unwrap();
+
+        let link = Link::from_msg(&NlMsg::new(msg.as_bytes()).unwrap()).unwrap();
+        let ipv4 = link.ipv4_conf.unwrap();
+        assert_eq!(ipv4.forwarding(), Some(true));
+        assert_eq!(ipv4.rp_filter(), Some(2));
+        assert_eq!(ipv4.get(0), None);
+        assert_eq!(ipv4.get(IPV4_DEVCONF_LOG_MARTIANS), None);
+        let ipv6 = link.ipv6_conf.unwrap();
+        assert_eq!(ipv6.forwarding(), Some(false));
+        assert_eq!(ipv6.disable_ipv6(), Some(true));
+        assert_eq!(link.ipv6_flags, Some(0x8000_0000));
+    }
+}
@@ -0,0 +1,269 @@
+use mnl_sys::libc;
+use std::io;
+
+use super::{
+    Ipv4DevConf, Ipv6DevConf, LinkStats64, REQUEST_BUFFER_SIZE, RawAttrs, devconf, dump,
+    sys::{self, Pod, ifinfomsg},
+};
+use crate::{Attr, NlMsg, Socket, buffer::AlignedBuffer};
+
+/// A network interface, as described by a `RTM_NEWLINK` message.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Link {
+    /// Interface index.
+    pub index: u32,
+    /// Interface name (`IFLA_IFNAME`).
+    pub name: String,
+    /// Device type, one of the `ARPHRD_*` constants.
+    pub link_type: u16,
+    /// Device flags, a combination of the `IFF_*` constants.
+    pub flags: u32,
+    /// Maximum transmission unit (`IFLA_MTU`).
+    pub mtu: Option<u32>,
+    /// Link layer address (`IFLA_ADDRESS`), such as the MAC address of an Ethernet device.
+    pub address: Option<Vec<u8>>,
+    /// Link layer broadcast address (`IFLA_BROADCAST`).
+    pub broadcast: Option<Vec<u8>>,
+    /// Operational state (`IFLA_OPERSTATE`).
+    pub operstate: Option<OperState>,
+    /// Index of the master device, such as the bridge a port is attached to (`IFLA_MASTER`).
+    pub master: Option<u32>,
+    /// Index of the underlying device, such as the parent of a VLAN device (`IFLA_LINK`).
+    pub link: Option<u32>,
+    /// Transmit queue length (`IFLA_TXQLEN`).
+    pub txqlen: Option<u32>,
+    /// Interface alias (`IFLA_IFALIAS`).
+    pub alias: Option<String>,
+    /// Kind of virtual device, such as `"veth"` or `"bridge"` (`IFLA_INFO_KIND`).
+    pub kind: Option<String>,
+    /// Raw kind specific attributes (`IFLA_INFO_DATA`).
+    pub info_data: Option<Vec<u8>>,
+    /// Kind of the master device this device is a port of (`IFLA_INFO_SLAVE_KIND`).
+    pub slave_kind: Option<String>,
+    /// Raw attributes describing the device as a port of its master, such as the bond or
+    /// bridge port settings (`IFLA_INFO_SLAVE_DATA`).
+    pub slave_data: Option<Vec<u8>>,
+    /// Attributes nested in `IFLA_LINKINFO` without a typed field above.
+    pub other_info_attrs: RawAttrs,
+    /// Alternative names of the interface (`IFLA_ALT_IFNAME`).
+    pub alt_names: Vec<String>,
+    /// Id of the network namespace the peer or underlying device lives in, relative to the
+    /// namespace of the socket (`IFLA_LINK_NETNSID`).
+    pub netns_id: Option<i32>,
+    /// Standard counters (`IFLA_STATS64`).
+    pub stats64: Option<LinkStats64>,
+    /// IPv4 settings (`IFLA_AF_SPEC`, `IFLA_INET_CONF`).
+    pub ipv4_conf: Option<Ipv4DevConf>,
+    /// IPv6 settings (`IFLA_AF_SPEC`, `IFLA_INET6_CONF`).
+    pub ipv6_conf: Option<Ipv6DevConf>,
+    /// IPv6 state flags, a combination of the kernel `IF_RA_*` and `IF_READY` flags
+    /// (`IFLA_AF_SPEC`, `IFLA_INET6_FLAGS`).
+    pub ipv6_flags: Option<u32>,
+    /// Attributes without a typed field above.
+    pub other_attrs: RawAttrs,
+}
+
+/// Operational state of a link, as defined in RFC 2863. Mirrors the `IF_OPER_*` constants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum OperState {
+    Unknown,
+    NotPresent,
+    Down,
+    LowerLayerDown,
+    Testing,
+    Dormant,
+    Up,
+    /// A state not known to this crate.
+    Other(u8),
+}
+
+impl From<u8> for OperState {
+    fn from(value: u8) -> Self {
+        use OperState::*;
+        match value {
+            0 => Unknown,
+            1 => NotPresent,
+            2 => Down,
+            3 => LowerLayerDown,
+            4 => Testing,
+            5 => Dormant,
+            6 => Up,
+            other => Other(other),
+        }
+    }
+}
+
+impl Link {
+    /// Parse a `RTM_NEWLINK` message. This is both the reply to link requests and the
+    /// notification sent when a link is added or changed.
+    pub fn from_msg(msg: &NlMsg<'_>) -> io::Result<Link> {
+        let header = ifinfomsg::from_bytes(msg.payload())?;
+        let mut link = Link {
+            index: header.ifi_index as u32,
+            link_type: header.ifi_type,
+            flags: header.ifi_flags,
+            ..Link::default()
+        };
+        for attr in msg.attrs(size_of::<ifinfomsg>()) {
+            let attr = attr?;
+            match attr.attr_type() {
+                sys::IFLA_IFNAME => link.name = attr.get_str()?.to_owned(),
+                sys::IFLA_MTU => link.mtu = Some(attr.get_u32()?),
+                sys::IFLA_ADDRESS => link.address = Some(attr.payload().to_vec()),
+                sys::IFLA_BROADCAST => link.broadcast = Some(attr.payload().to_vec()),
+                sys::IFLA_OPERSTATE => link.operstate = Some(attr.get_u8()?.into()),
+                sys::IFLA_MASTER => link.master = Some(attr.get_u32()?),
+                sys::IFLA_LINK => link.link = Some(attr.get_u32()?),
+                sys::IFLA_TXQLEN => link.txqlen = Some(attr.get_u32()?),
+                sys::IFLA_IFALIAS => link.alias = Some(attr.get_str()?.to_owned()),
+                sys::IFLA_LINKINFO => link.parse_linkinfo(attr)?,
+                sys::IFLA_PROP_LIST => {
+                    for prop in attr.nested() {
+                        let prop = prop?;
+                        if prop.attr_type() == sys::IFLA_ALT_IFNAME {
+                            link.alt_names.push(prop.get_str()?.to_owned());
+                        }
+                    }
+                }
+                sys::IFLA_LINK_NETNSID => link.netns_id = Some(attr.get_u32()? as i32),
+                sys::IFLA_STATS64 => link.stats64 = Some(LinkStats64::parse(attr.payload())),
+                sys::IFLA_AF_SPEC => devconf::parse_af_spec(&mut link, attr)?,
+                other => link.other_attrs.push((other, attr.payload().to_vec())),
+            }
+        }
+        Ok(link)
+    }
+
+    fn parse_linkinfo(&mut self, attr: Attr<'_>) -> io::Result<()> {
+        for attr in attr.nested() {
+            let attr = attr?;
+            match attr.attr_type() {
+                sys::IFLA_INFO_KIND => self.kind = Some(attr.get_str()?.to_owned()),
+                sys::IFLA_INFO_DATA => self.info_data = Some(attr.payload().to_vec()),
+                sys::IFLA_INFO_SLAVE_KIND => self.slave_kind = Some(attr.get_str()?.to_owned()),
+                sys::IFLA_INFO_SLAVE_DATA => self.slave_data = Some(attr.payload().to_vec()),
+                other => self.other_info_attrs.push((other, attr.payload().to_vec())),
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns true if the link is administratively up (`IFF_UP`).
+    pub fn is_up(&self) -> bool {
+        self.flags & libc::IFF_UP as u32 != 0
+    }
+
+    /// Returns the value of the first attribute of type `attr_type` not parsed into a typed
+    /// field.
+    pub fn other_attr(&self, attr_type: u16) -> Option<&[u8]> {
+        self.other_attrs
+            .iter()
+            .find(|(other_type, _)| *other_type == attr_type)
+            .map(|(_, payload)| &payload[..])
+    }
+}
+
+/// Dump all links in the network namespace of `socket`, which must be a [`Bus::Route`] socket.
+///
+/// [`Bus::Route`]: crate::Bus::Route
+pub fn links(socket: &Socket) -> io::Result<Vec<Link>> {
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let msg = super::new_request(
+        socket,
+        &mut buffer,
+        sys::RTM_GETLINK,
+        libc::NLM_F_DUMP as u16,
+        &ifinfomsg::default(),
+    )?;
+    dump(socket, &msg, sys::RTM_NEWLINK, Link::from_msg)
+}
+
+/// Get the link with the given interface index. Fails with `ENODEV` if there is no such link.
+pub fn link_by_index(socket: &Socket, index: u32) -> io::Result<Link> {
+    let header = ifinfomsg {
+        ifi_index: index as i32,
+        ..ifinfomsg::default()
+    };
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let msg = super::new_request(socket, &mut buffer, sys::RTM_GETLINK, 0, &header)?;
+    get_one(socket, &msg)
+}
+
+/// Get the link with the given name or alternative name. Fails with `ENODEV` if there is no
+/// such link.
+pub fn link_by_name(socket: &Socket, name: &str) -> io::Result<Link> {
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let mut msg = super::new_request(
+        socket,
+        &mut buffer,
+        sys::RTM_GETLINK,
+        0,
+        &ifinfomsg::default(),
+    )?;
+    msg.put_str(sys::IFLA_IFNAME, name)?;
+    get_one(socket, &msg)
+}
+
+fn get_one(socket: &Socket, msg: &crate::NlMsgBuilder<'_>) -> io::Result<Link> {
+    dump(socket, msg, sys::RTM_NEWLINK, Link::from_msg)?
+        .pop()
+        .ok_or_else(|| io::Error::from_raw_os_error(libc::ENODEV))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NlMsgBuilder;
+
+    #[test]
+    fn test_parse_link() {
+        let mut buffer = AlignedBuffer::new(512);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        msg.set_type(sys::RTM_NEWLINK);
+        let header = ifinfomsg {
+            ifi_type: 1,
+            ifi_index: 7,
+            ifi_flags: (libc::IFF_UP | libc::IFF_BROADCAST) as u32,
+            ..ifinfomsg::default()
+        };
+        msg.put_extra_header(header.as_bytes()).unwrap();
+        msg.put_str(sys::IFLA_IFNAME, "veth0").unwrap();
+        msg.put_u32(sys::IFLA_MTU, 1500).unwrap();
+        msg.put(sys::IFLA_ADDRESS, &[2, 0, 0, 0, 0, 1]).unwrap();
+        msg.put_u8(sys::IFLA_OPERSTATE, 6).unwrap();
+        msg.put_u32(sys::IFLA_MASTER, 3).unwrap();
+        msg.put_nested(sys::IFLA_LINKINFO, |msg| {
+            msg.put_str(sys::IFLA_INFO_KIND, "veth")?;
+            msg.put_str(sys::IFLA_INFO_SLAVE_KIND, "bridge")?;
+            msg.put_nested(sys::IFLA_INFO_SLAVE_DATA, |msg| msg.put_u8(1, 3))?;
+            msg.put_u32(3, 7)
+        })
+        .unwrap();
+        msg.put_nested(sys::IFLA_PROP_LIST, |msg| {
+            msg.put_str(sys::IFLA_ALT_IFNAME, "uplink")
+        })
+        .unwrap();
+        msg.put_u32(sys::IFLA_LINK_NETNSID, 0).unwrap();
+        msg.put_u32(1000, 42).unwrap();
+
+        let link = Link::from_msg(&NlMsg::new(msg.as_bytes()).unwrap()).unwrap();
+        assert_eq!(link.index, 7);
+        assert_eq!(link.name, "veth0");
+        assert!(link.is_up());
+        assert_eq!(link.mtu, Some(1500));
+        assert_eq!(link.address.as_deref(), Some(&[2, 0, 0, 0, 0, 1][..]));
+        assert_eq!(link.operstate, Some(OperState::Up));
+        assert_eq!(link.master, Some(3));
+        assert_eq!(link.kind.as_deref(), Some("veth"));
+        assert_eq!(link.slave_kind.as_deref(), Some("bridge"));
+        assert_eq!(
+            link.slave_data.as_deref(),
+            Some(&[5, 0, 1, 0, 3, 0, 0, 0][..])
+        );
+        assert_eq!(link.other_info_attrs, [(3, 7u32.to_ne_bytes().to_vec())]);
+        assert_eq!(link.alt_names, ["uplink"]);
+        assert_eq!(link.netns_id, Some(0));
+        assert_eq!(link.other_attr(1000), Some(&42u32.to_ne_bytes()[..]));
+    }
+}
@@ -0,0 +1,203 @@
+//! Routing netlink (`NETLINK_ROUTE`) support.
+//!
+//! Typed access to the network configuration of the kernel, such as links (network
+//! interfaces) with their IP settings and statistics, bridge ports and VLANs, addresses,
+//! routes, nexthops, routing policy rules, neighbours and traffic control, as well as network
+//! namespace ids, over a [`Bus::Route`] socket.
+//!
+//! ```no_run
+//! # fn main() -> std::io::Result<()> {
+//! let socket = mnl::Socket::new(mnl::Bus::Route)?;
+//! for link in mnl::rtnl::links(&socket)? {
+//!     println!("{}: {} mtu {:?}", link.index, link.name, link.mtu);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use mnl_sys::libc;
+use std::{
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+use crate::{Attr, Bus, NlMsg, NlMsgBuilder, Socket, buffer::AlignedBuffer};
+
+mod sys;
+use self::sys::Pod;
+
+mod link;
+pub use self::link::*;
+
+mod devconf;
+pub use self::devconf::*;
+
+mod stats;
+pub use self::stats::*;
+
+mod newlink;
+pub use self::newlink::*;
+
+mod setlink;
+pub use self::setlink::*;
+
+mod address;
+pub use self::address::*;
+
+mod route;
+pub use self::route::*;
+
+mod nexthop;
+pub use self::nexthop::*;
+
+mod bridge;
+pub use self::bridge::*;
+
+mod rule;
+pub use self::rule::*;
+
+mod neighbour;
+pub use self::neighbour::*;
+
+mod nsid;
+pub use self::nsid::*;
+
+mod monitor;
+pub use self::monitor::*;
+
+mod tc;
+pub use self::tc::*;
+
+/// Size of the buffer requests are built in.
+const REQUEST_BUFFER_SIZE: usize = 4096;
+
+/// Attributes not parsed into a typed field, as attribute type and payload.
+pub type RawAttrs = Vec<(u16, Vec<u8>)>;
+
+/// The scope of an address or route. Mirrors the `RT_SCOPE_*` constants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Scope {
+    /// Valid everywhere.
+    #[default]
+    Universe,
+    /// Valid within the site.
+    Site,
+    /// Valid on the attached link only.
+    Link,
+    /// Valid on this host only.
+    Host,
+    /// The destination does not exist.
+    Nowhere,
+    /// A scope not known to this crate.
+    Other(u8),
+}
+
+impl From<u8> for Scope {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Scope::Universe,
+            200 => Scope::Site,
+            253 => Scope::Link,
+            254 => Scope::Host,
+            255 => Scope::Nowhere,
+            other => Scope::Other(other),
+        }
+    }
+}
+
+impl From<Scope> for u8 {
+    fn from(scope: Scope) -> u8 {
+        match scope {
+            Scope::Universe => 0,
+            Scope::Site => 200,
+            Scope::Link => 253,
+            Scope::Host => 254,
+            Scope::Nowhere => 255,
+            Scope::Other(other) => other,
+        }
+    }
+}
+
+fn check_bus(socket: &Socket) -> io::Result<()> {
+    if socket.bus() != Bus::Route {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "rtnetlink requests can only be sent on a Bus::Route socket",
+        ));
+    }
+    Ok(())
+}
+
+/// Start a request of the given type with `header` as extra header in `buffer`.
+fn new_request<'a, H: Pod>(
+    socket: &Socket,
+    buffer: &'a mut AlignedBuffer,
+    msg_type: u16,
+    flags: u16,
+    header: &H,
+) -> io::Result<NlMsgBuilder<'a>> {
+    check_bus(socket)?;
+    let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes())?;
+    msg.set_type(msg_type);
+    msg.set_flags(flags | libc::NLM_F_REQUEST as u16);
+    msg.set_seq(socket.next_seq());
+    msg.put_extra_header(header.as_bytes())?;
+    Ok(msg)
+}
+
+/// Send a dump request and parse every reply of type `reply_type` with `parse`.
+fn dump<T, F>(
+    socket: &Socket,
+    msg: &NlMsgBuilder<'_>,
+    reply_type: u16,
+    parse: F,
+) -> io::Result<Vec<T>>
+where
+    F: Fn(&NlMsg<'_>) -> io::Result<T>,
+{
+    let mut result = Vec::new();
+    socket.request(msg.as_bytes(), |reply| {
+        if reply.msg_type() == reply_type {
+            result.push(parse(&reply)?);
+        }
+        Ok(())
+    })?;
+    Ok(result)
+}
+
+/// Send a request that has `NLM_F_ACK` set and wait for the acknowledgement.
+fn ack(socket: &Socket, msg: &NlMsgBuilder<'_>) -> io::Result<()> {
+    debug_assert!(msg.header().nlmsg_flags & libc::NLM_F_ACK as u16 != 0);
+    socket.request(msg.as_bytes(), |_| Ok(()))
+}
+
+/// Returns the `AF_*` address family of `addr`.
+fn family_of(addr: &IpAddr) -> u8 {
+    match addr {
+        IpAddr::V4(_) => libc::AF_INET as u8,
+        IpAddr::V6(_) => libc::AF_INET6 as u8,
+    }
+}
+
+/// Add an attribute holding `addr` in network byte order.
+fn put_ip(msg: &mut NlMsgBuilder<'_>, attr_type: u16, addr: &IpAddr) -> io::Result<()> {
+    match addr {
+        IpAddr::V4(addr) => msg.put(attr_type, &addr.octets()),
+        IpAddr::V6(addr) => msg.put(attr_type, &addr.octets()),
+    }
+}
+
+/// Parse an IPv4 or IPv6 address attribute, telling them apart by length.
+fn get_ip(attr: &Attr<'_>) -> io::Result<IpAddr> {
+    match attr.payload().len() {
+        4 => Ok(Ipv4Addr::from(attr.get_array::<4>()?).into()),
+        16 => Ok(Ipv6Addr::from(attr.get_array::<16>()?).into()),
+        len => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Attribute {} has length {len}, expected an IP address",
+                attr.attr_type()
+            ),
+        )),
+    }
+}
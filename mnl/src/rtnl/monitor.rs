@@ -0,0 +1,291 @@
+use mnl_sys::libc;
+use std::{
+    io,
+    os::unix::io::{AsRawFd, RawFd},
+};
+
+use super::{Address, Link, Neighbour, NetConf, Route, Rule, sys};
+use crate::{Bus, NlMessages, NlMsg, Socket, buffer::AlignedBuffer, socket::RECV_BUFFER_SIZE};
+
+/// A class of changes a [`RouteMonitor`] can subscribe to. Mirrors the `RTNLGRP_*` multicast
+/// groups.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonitorGroup {
+    /// Links being added, changed or removed.
+    Link,
+    /// IPv4 addresses being added or removed.
+    Ipv4Address,
+    /// IPv6 addresses being added or removed.
+    Ipv6Address,
+    /// IPv4 routes being added or removed.
+    Ipv4Route,
+    /// IPv6 routes being added or removed.
+    Ipv6Route,
+    /// IPv4 routing policy rules being added or removed.
+    Ipv4Rule,
+    /// IPv6 routing policy rules being added or removed.
+    Ipv6Rule,
+    /// Neighbour entries changing state or being removed.
+    Neighbour,
+    /// IPv4 netconf settings changing.
+    Ipv4NetConf,
+    /// IPv6 netconf settings changing.
+    Ipv6NetConf,
+}
+
+impl MonitorGroup {
+    fn id(self) -> u32 {
+        match self {
+            MonitorGroup::Link => sys::RTNLGRP_LINK,
+            MonitorGroup::Ipv4Address => sys::RTNLGRP_IPV4_IFADDR,
+            MonitorGroup::Ipv6Address => sys::RTNLGRP_IPV6_IFADDR,
+            MonitorGroup::Ipv4Route => sys::RTNLGRP_IPV4_ROUTE,
+            MonitorGroup::Ipv6Route => sys::RTNLGRP_IPV6_ROUTE,
+            MonitorGroup::Ipv4Rule => sys::RTNLGRP_IPV4_RULE,
+            MonitorGroup::Ipv6Rule => sys::RTNLGRP_IPV6_RULE,
+            MonitorGroup::Neighbour => sys::RTNLGRP_NEIGH,
+            MonitorGroup::Ipv4NetConf => sys::RTNLGRP_IPV4_NETCONF,
+            MonitorGroup::Ipv6NetConf => sys::RTNLGRP_IPV6_NETCONF,
+        }
+    }
+}
+
+/// A change in the network configuration, as reported by a [`RouteMonitor`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouteEvent {
+    /// A link was registered.
+    LinkAdded(Link),
+    /// The settings or state of a link changed.
+    LinkChanged(Link),
+    /// A link was removed.
+    LinkRemoved(Link),
+    /// An address was assigned to a link.
+    AddressAdded(Address),
+    /// An address was removed from a link.
+    AddressRemoved(Address),
+    /// A route was added or replaced.
+    RouteAdded(Route),
+    /// A route was removed.
+    RouteRemoved(Route),
+    /// A routing policy rule was added.
+    RuleAdded(Rule),
+    /// A routing policy rule was removed.
+    RuleRemoved(Rule),
+    /// A neighbour entry was added or changed state.
+    NeighbourChanged(Neighbour),
+    /// A neighbour entry was removed.
+    NeighbourRemoved(Neighbour),
+    /// Netconf settings changed.
+    NetConfChanged(NetConf),
+    /// The netconf settings of a link were removed along with the link.
+    NetConfRemoved(NetConf),
+    /// The kernel dropped notifications because the socket receive buffer was full
+    /// (`ENOBUFS`). Any state kept up to date from events must be dumped again.
+    Resync,
+}
+
+impl RouteEvent {
+    /// Parse a notification received on a socket subscribed to rtnetlink multicast groups.
+    /// Returns `None` for message types without a corresponding event.
+    pub fn from_msg(msg: &NlMsg<'_>) -> io::Result<Option<RouteEvent>> {
+        let event = match msg.msg_type() {
+            sys::RTM_NEWLINK => {
+                let link = Link::from_msg(msg)?;
+                // The kernel announces newly registered links with every change bit set.
+                if link_change(msg) == u32::MAX {
+                    RouteEvent::LinkAdded(link)
+                } else {
+                    RouteEvent::LinkChanged(link)
+                }
+            }
+            sys::RTM_DELLINK => RouteEvent::LinkRemoved(Link::from_msg(msg)?),
+            sys::RTM_NEWADDR => RouteEvent::AddressAdded(Address::from_msg(msg)?),
+            sys::RTM_DELADDR => RouteEvent::AddressRemoved(Address::from_msg(msg)?),
+            sys::RTM_NEWROUTE => RouteEvent::RouteAdded(Route::from_msg(msg)?),
+            sys::RTM_DELROUTE => RouteEvent::RouteRemoved(Route::from_msg(msg)?),
+            sys::RTM_NEWRULE => RouteEvent::RuleAdded(Rule::from_msg(msg)?),
+            sys::RTM_DELRULE => RouteEvent::RuleRemoved(Rule::from_msg(msg)?),
+            sys::RTM_NEWNEIGH => RouteEvent::NeighbourChanged(Neighbour::from_msg(msg)?),
+            sys::RTM_DELNEIGH => RouteEvent::NeighbourRemoved(Neighbour::from_msg(msg)?),
+            sys::RTM_NEWNETCONF => RouteEvent::NetConfChanged(NetConf::from_msg(msg)?),
+            sys::RTM_DELNETCONF => RouteEvent::NetConfRemoved(NetConf::from_msg(msg)?),
+            _ => return Ok(None),
+        };
+        Ok(Some(event))
+    }
+}
+
+/// Returns the `ifi_change` field of a link message.
+fn link_change(msg: &NlMsg<'_>) -> u32 {
+    msg.payload()
+        .get(12..16)
+        .map_or(0, |change| u32::from_ne_bytes(change.try_into().unwrap()))
+}
+
+/// Subscribes to rtnetlink multicast groups on its own [`Bus::Route`] socket and turns the
+/// notifications into [`RouteEvent`]s.
+///
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// use mnl::rtnl::{MonitorGroup, RouteEvent, RouteMonitor};
+///
+/// let mut monitor = RouteMonitor::new(&[MonitorGroup::Link, MonitorGroup::Ipv4Route])?;
+/// loop {
+///     for event in monitor.recv()? {
+///         match event {
+///             RouteEvent::Resync => println!("Missed events, dump again"),
+///             event => println!("{event:?}"),
+///         }
+///     }
+/// }
+/// # }
+/// ```
+pub struct RouteMonitor {
+    socket: Socket,
+    buffer: AlignedBuffer,
+}
+
+impl RouteMonitor {
+    /// Open a socket and subscribe to `groups`.
+    pub fn new(groups: &[MonitorGroup]) -> io::Result<Self> {
+        let socket = Socket::new(Bus::Route)?;
+        for group in groups {
+            socket.join_group(group.id())?;
+        }
+        Ok(RouteMonitor {
+            socket,
+            buffer: AlignedBuffer::new(RECV_BUFFER_SIZE),
+        })
+    }
+
+    /// Returns the socket the monitor receives on, for example to subscribe to more groups.
+    pub fn socket(&self) -> &Socket {
+        &self.socket
+    }
+
+    /// Wait for notifications and return the events in them. Notifications that fail to parse
+    /// are logged and skipped, so that they don't take the other events down with them.
+    pub fn recv(&mut self) -> io::Result<Vec<RouteEvent>> {
+        into_events(self.socket.recv(self.buffer.as_mut_bytes()))
+    }
+
+    /// Return the events in pending notifications without blocking, or `None` if there are
+    /// none.
+    pub fn try_recv(&mut self) -> io::Result<Option<Vec<RouteEvent>>> {
+        self.socket
+            .try_recv(self.buffer.as_mut_bytes())
+            .transpose()
+            .map(into_events)
+            .transpose()
+    }
+}
+
+/// Turn the result of receiving notifications into events, reporting `ENOBUFS` as
+/// [`RouteEvent::Resync`].
+fn into_events(received: io::Result<NlMessages<'_>>) -> io::Result<Vec<RouteEvent>> {
+    match received {
+        Ok(messages) => Ok(parse_events(messages)),
+        Err(e) if e.raw_os_error() == Some(libc::ENOBUFS) => Ok(vec![RouteEvent::Resync]),
+        Err(e) => Err(e),
+    }
+}
+
+fn parse_events(messages: NlMessages<'_>) -> Vec<RouteEvent> {
+    let mut events = Vec::new();
+    for message in messages {
+        // A broken header means the rest of the buffer can't be split into messages either.
+        let msg = match message.and_then(NlMsg::new) {
+            Ok(msg) => msg,
+            Err(e) => {
+                log::warn!("Ignoring rest of malformed rtnetlink notifications: {e}");
+                break;
+            }
+        };
+        match RouteEvent::from_msg(&msg) {
+            Ok(Some(event)) => events.push(event),
+            Ok(None) => (),
+            Err(e) => log::warn!(
+                "Ignoring malformed rtnetlink notification of type {}: {e}",
+                msg.msg_type()
+            ),
+        }
+    }
+    events
+}
+
+impl AsRawFd for RouteMonitor {
+    fn as_raw_fd(&self) -> RawFd {
+        self.socket.as_raw_fd()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NlMsgBuilder, rtnl::sys::Pod};
+
+    #[test]
+    fn test_link_events() {
+        let mut buffer = AlignedBuffer::new(256);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        msg.set_type(sys::RTM_NEWLINK);
+        let mut header = sys::ifinfomsg {
+            ifi_index: 5,
+            ifi_change: u32::MAX,
+            ..sys::ifinfomsg::default()
+        };
+        msg.put_extra_header(header.as_bytes()).unwrap();
+        msg.put_str(sys::IFLA_IFNAME, "wg0").unwrap();
+        let event = RouteEvent::from_msg(&NlMsg::new(msg.as_bytes()).unwrap()).unwrap();
+        assert!(matches!(event, Some(RouteEvent::LinkAdded(link)) if link.name == "wg0"));
+
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        msg.set_type(sys::RTM_NEWLINK);
+        header.ifi_change = libc::IFF_UP as u32;
+        msg.put_extra_header(header.as_bytes()).unwrap();
+        let event = RouteEvent::from_msg(&NlMsg::new(msg.as_bytes()).unwrap()).unwrap();
+        assert!(matches!(event, Some(RouteEvent::LinkChanged(link)) if link.index == 5));
+    }
+
+    #[test]
+    fn test_resync() {
+        let events = into_events(Err(io::Error::from_raw_os_error(libc::ENOBUFS))).unwrap();
+        assert_eq!(events, [RouteEvent::Resync]);
+        let error = into_events(Err(io::Error::from_raw_os_error(libc::EINTR))).unwrap_err();
+        assert_eq!(error.raw_os_error(), Some(libc::EINTR));
+    }
+
+    #[test]
+    fn test_malformed_event() {
+        let mut bytes = Vec::new();
+        for (index, header_len) in [
+            (1, size_of::<sys::ifinfomsg>()),
+            (2, 4),
+            (3, size_of::<sys::ifinfomsg>()),
+        ] {
+            let mut buffer = AlignedBuffer::new(256);
+            let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+            msg.set_type(sys::RTM_NEWLINK);
+            let header = sys::ifinfomsg {
+                ifi_index: index,
+                ..sys::ifinfomsg::default()
+            };
+            // The second message is too short to hold an ifinfomsg.
+            msg.put_extra_header(&header.as_bytes()[..header_len])
+                .unwrap();
+            bytes.extend_from_slice(msg.as_bytes());
+        }
+        let mut buffer = AlignedBuffer::new(bytes.len());
+        buffer.as_mut_bytes().copy_from_slice(&bytes);
+
+        let events = parse_events(NlMessages::new(buffer.as_mut_bytes()));
+        let indexes: Vec<u32> = events
+            .iter()
+            .map(|event| match event {
+                RouteEvent::LinkChanged(link) => link.index,
+                event => panic!("Unexpected event {event:?}"),
+            })
+            .collect();
+        assert_eq!(indexes, [1, 3]);
+    }
+}
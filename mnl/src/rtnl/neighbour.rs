@@ -0,0 +1,352 @@
+use mnl_sys::libc;
+use std::{io, net::IpAddr};
+
+use super::{
+    REQUEST_BUFFER_SIZE, RawAttrs, ack, dump, family_of, get_ip, new_request, put_ip,
+    sys::{self, Pod, nda_cacheinfo, ndmsg},
+};
+use crate::{NlMsg, NlMsgBuilder, Socket, buffer::AlignedBuffer};
+
+/// Neighbour state: address resolution is in progress.
+pub const NUD_INCOMPLETE: u16 = 0x01;
+/// Neighbour state: the neighbour was recently confirmed reachable.
+pub const NUD_REACHABLE: u16 = 0x02;
+/// Neighbour state: the entry is valid but has not been confirmed recently.
+pub const NUD_STALE: u16 = 0x04;
+/// Neighbour state: waiting for confirmation before probing.
+pub const NUD_DELAY: u16 = 0x08;
+/// Neighbour state: probing the neighbour.
+pub const NUD_PROBE: u16 = 0x10;
+/// Neighbour state: address resolution failed.
+pub const NUD_FAILED: u16 = 0x20;
+/// Neighbour state: the neighbour needs no address resolution.
+pub const NUD_NOARP: u16 = 0x40;
+/// Neighbour state: a static entry that never expires.
+pub const NUD_PERMANENT: u16 = 0x80;
+
+/// Neighbour flag: the entry is in use.
+pub const NTF_USE: u8 = 0x01;
+/// Neighbour flag: the FDB entry belongs to the device itself rather than its master.
+pub const NTF_SELF: u8 = 0x02;
+/// Neighbour flag: the FDB entry belongs to the master device, such as a bridge.
+pub const NTF_MASTER: u8 = 0x04;
+/// Neighbour flag: a proxy ARP or proxy NDP entry.
+pub const NTF_PROXY: u8 = 0x08;
+/// Neighbour flag: the FDB entry was learned externally.
+pub const NTF_EXT_LEARNED: u8 = 0x10;
+/// Neighbour flag: the FDB entry is offloaded to hardware.
+pub const NTF_OFFLOADED: u8 = 0x20;
+/// Neighbour flag: the FDB entry may not move to another port.
+pub const NTF_STICKY: u8 = 0x40;
+/// Neighbour flag: the IPv6 neighbour is a router.
+pub const NTF_ROUTER: u8 = 0x80;
+
+/// An entry in a neighbour table, as described by a `RTM_NEWNEIGH` message. This is either an
+/// ARP or NDP entry, or an entry in the forwarding database of a bridge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Neighbour {
+    /// Address family, `AF_INET`, `AF_INET6` or `AF_BRIDGE` for forwarding database entries.
+    pub family: u8,
+    /// Index of the interface the entry is on.
+    pub index: u32,
+    /// Network layer address of the neighbour (`NDA_DST`). For forwarding database entries of
+    /// tunnel devices such as VXLAN, the address of the remote endpoint.
+    pub destination: Option<IpAddr>,
+    /// Link layer address of the neighbour (`NDA_LLADDR`).
+    pub lladdr: Option<Vec<u8>>,
+    /// State, a combination of the `NUD_*` constants in this module.
+    pub state: u16,
+    /// Flags, a combination of the `NTF_*` constants in this module.
+    pub flags: u8,
+    /// Route type of the destination address, one of the `RTN_*` constants.
+    pub neigh_type: u8,
+    /// Timing data (`NDA_CACHEINFO`).
+    pub cache_info: Option<NeighbourCacheInfo>,
+    /// Number of probes sent (`NDA_PROBES`).
+    pub probes: Option<u32>,
+    /// VLAN id of a forwarding database entry (`NDA_VLAN`).
+    pub vlan: Option<u16>,
+    /// Index of the master device of a forwarding database entry (`NDA_MASTER`).
+    pub master: Option<u32>,
+    /// Attributes without a typed field above.
+    pub other_attrs: RawAttrs,
+}
+
+/// Timing data of a neighbour entry. Mirrors `struct nda_cacheinfo`. All times are in clock
+/// ticks (`USER_HZ`) ago.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NeighbourCacheInfo {
+    /// When the neighbour was last confirmed reachable.
+    pub confirmed: u32,
+    /// When the entry was last used.
+    pub used: u32,
+    /// When the entry was last updated.
+    pub updated: u32,
+    /// Number of references held to the entry.
+    pub refcnt: u32,
+}
+
+impl Neighbour {
+    fn new(family: libc::c_int, index: u32) -> Self {
+        Neighbour {
+            family: family as u8,
+            index,
+            destination: None,
+            lladdr: None,
+            state: 0,
+            flags: 0,
+            neigh_type: 0,
+            cache_info: None,
+            probes: None,
+            vlan: None,
+            master: None,
+            other_attrs: RawAttrs::new(),
+        }
+    }
+
+    /// Parse a `RTM_NEWNEIGH` or `RTM_DELNEIGH` message.
+    pub fn from_msg(msg: &NlMsg<'_>) -> io::Result<Neighbour> {
+        let header = ndmsg::from_bytes(msg.payload())?;
+        let mut neighbour = Neighbour {
+            state: header.ndm_state,
+            flags: header.ndm_flags,
+            neigh_type: header.ndm_type,
+            ..Neighbour::new(i32::from(header.ndm_family), header.ndm_ifindex as u32)
+        };
+        for attr in msg.attrs(size_of::<ndmsg>()) {
+            let attr = attr?;
+            match attr.attr_type() {
+                sys::NDA_DST => neighbour.destination = Some(get_ip(&attr)?),
+                sys::NDA_LLADDR => neighbour.lladdr = Some(attr.payload().to_vec()),
+                sys::NDA_CACHEINFO => {
+                    let info = nda_cacheinfo::from_bytes(attr.payload())?;
+                    neighbour.cache_info = Some(NeighbourCacheInfo {
+                        confirmed: info.ndm_confirmed,
+                        used: info.ndm_used,
+                        updated: info.ndm_updated,
+                        refcnt: info.ndm_refcnt,
+                    });
+                }
+                sys::NDA_PROBES => neighbour.probes = Some(attr.get_u32()?),
+                sys::NDA_VLAN => neighbour.vlan = Some(attr.get_u16()?),
+                sys::NDA_MASTER => neighbour.master = Some(attr.get_u32()?),
+                other => neighbour.other_attrs.push((other, attr.payload().to_vec())),
+            }
+        }
+        Ok(neighbour)
+    }
+
+    fn header(&self) -> ndmsg {
+        ndmsg {
+            ndm_family: self.family,
+            ndm_ifindex: self.index as i32,
+            ndm_state: self.state,
+            ndm_flags: self.flags,
+            ndm_type: self.neigh_type,
+            ..ndmsg::default()
+        }
+    }
+
+    fn put_attrs(&self, msg: &mut NlMsgBuilder<'_>) -> io::Result<()> {
+        if let Some(destination) = &self.destination {
+            put_ip(msg, sys::NDA_DST, destination)?;
+        }
+        if let Some(lladdr) = &self.lladdr {
+            msg.put(sys::NDA_LLADDR, lladdr)?;
+        }
+        if let Some(vlan) = self.vlan {
+            msg.put_u16(sys::NDA_VLAN, vlan)?;
+        }
+        if let Some(master) = self.master {
+            msg.put_u32(sys::NDA_MASTER, master)?;
+        }
+        Ok(())
+    }
+
+    /// Returns true if this is an entry in the forwarding database of a bridge.
+    pub fn is_fdb(&self) -> bool {
+        i32::from(self.family) == libc::AF_BRIDGE
+    }
+}
+
+/// Dump the ARP and NDP tables in the network namespace of `socket`, which must be a
+/// [`Bus::Route`] socket.
+///
+/// [`Bus::Route`]: crate::Bus::Route
+pub fn neighbours(socket: &Socket) -> io::Result<Vec<Neighbour>> {
+    let mut neighbours = dump_family(socket, libc::AF_INET)?;
+    neighbours.extend(dump_family(socket, libc::AF_INET6)?);
+    Ok(neighbours)
+}
+
+/// Dump the forwarding databases of all bridges and other devices with a forwarding database,
+/// such as VXLAN devices.
+pub fn fdb_entries(socket: &Socket) -> io::Result<Vec<Neighbour>> {
+    dump_family(socket, libc::AF_BRIDGE)
+}
+
+fn dump_family(socket: &Socket, family: libc::c_int) -> io::Result<Vec<Neighbour>> {
+    let header = ndmsg {
+        ndm_family: family as u8,
+        ..ndmsg::default()
+    };
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let msg = new_request(
+        socket,
+        &mut buffer,
+        sys::RTM_GETNEIGH,
+        libc::NLM_F_DUMP as u16,
+        &header,
+    )?;
+    dump(socket, &msg, sys::RTM_NEWNEIGH, Neighbour::from_msg)
+}
+
+/// Builder for adding a neighbour or forwarding database entry with `RTM_NEWNEIGH`.
+///
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// use mnl::rtnl::{self, NewNeighbour};
+///
+/// let socket = mnl::Socket::new(mnl::Bus::Route)?;
+/// let port = rtnl::link_by_name(&socket, "veth0")?;
+/// let mac = [0x02, 0, 0, 0, 0, 0x01];
+/// NewNeighbour::new(port.index, "10.0.0.2".parse().unwrap(), mac).add(&socket)?;
+/// NewNeighbour::fdb(port.index, mac).vlan(10).add(&socket)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewNeighbour {
+    neighbour: Neighbour,
+}
+
+impl NewNeighbour {
+    /// Describe a permanent ARP or NDP entry mapping `destination` to `lladdr` on the
+    /// interface with the given index.
+    pub fn new(index: u32, destination: IpAddr, lladdr: impl Into<Vec<u8>>) -> Self {
+        let mut neighbour = Neighbour::new(i32::from(family_of(&destination)), index);
+        neighbour.destination = Some(destination);
+        neighbour.lladdr = Some(lladdr.into());
+        neighbour.state = NUD_PERMANENT;
+        NewNeighbour { neighbour }
+    }
+
+    /// Describe a static entry for `lladdr` in the forwarding database of the bridge the
+    /// interface with the given index is a port of.
+    pub fn fdb(index: u32, lladdr: impl Into<Vec<u8>>) -> Self {
+        let mut neighbour = Neighbour::new(libc::AF_BRIDGE, index);
+        neighbour.lladdr = Some(lladdr.into());
+        neighbour.state = NUD_PERMANENT;
+        neighbour.flags = NTF_MASTER;
+        NewNeighbour { neighbour }
+    }
+
+    /// Set the state, a combination of the `NUD_*` constants in this module. Defaults to
+    /// [`NUD_PERMANENT`].
+    pub fn state(mut self, state: u16) -> Self {
+        self.neighbour.state = state;
+        self
+    }
+
+    /// Set the flags, a combination of the `NTF_*` constants in this module. Defaults to
+    /// [`NTF_MASTER`] for forwarding database entries, so that they are added to the bridge.
+    /// Use [`NTF_SELF`] to add them to the device itself instead.
+    pub fn flags(mut self, flags: u8) -> Self {
+        self.neighbour.flags = flags;
+        self
+    }
+
+    /// Set the VLAN id of a forwarding database entry.
+    pub fn vlan(mut self, vlan: u16) -> Self {
+        self.neighbour.vlan = Some(vlan);
+        self
+    }
+
+    /// Set the remote endpoint of a forwarding database entry of a tunnel device.
+    pub fn destination(mut self, destination: IpAddr) -> Self {
+        self.neighbour.destination = Some(destination);
+        self
+    }
+
+    /// Add the entry on `socket`, which must be a [`Bus::Route`] socket. Fails with `EEXIST`
+    /// if an entry for the same address already exists.
+    ///
+    /// [`Bus::Route`]: crate::Bus::Route
+    pub fn add(&self, socket: &Socket) -> io::Result<()> {
+        self.send(socket, libc::NLM_F_EXCL)
+    }
+
+    /// Add the entry on `socket`, or replace an existing entry for the same address.
+    pub fn replace(&self, socket: &Socket) -> io::Result<()> {
+        self.send(socket, libc::NLM_F_REPLACE)
+    }
+
+    fn send(&self, socket: &Socket, flags: libc::c_int) -> io::Result<()> {
+        let flags = flags | libc::NLM_F_CREATE | libc::NLM_F_ACK;
+        let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+        let mut msg = new_request(
+            socket,
+            &mut buffer,
+            sys::RTM_NEWNEIGH,
+            flags as u16,
+            &self.neighbour.header(),
+        )?;
+        self.neighbour.put_attrs(&mut msg)?;
+        ack(socket, &msg)
+    }
+}
+
+/// Delete a neighbour or forwarding database entry with `RTM_DELNEIGH`. An entry returned by
+/// [`neighbours`] or [`fdb_entries`] can be passed as is.
+pub fn delete_neighbour(socket: &Socket, neighbour: &Neighbour) -> io::Result<()> {
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let mut msg = new_request(
+        socket,
+        &mut buffer,
+        sys::RTM_DELNEIGH,
+        libc::NLM_F_ACK as u16,
+        &neighbour.header(),
+    )?;
+    neighbour.put_attrs(&mut msg)?;
+    ack(socket, &msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_neighbour() {
+        let header = ndmsg {
+            ndm_family: libc::AF_INET6 as u8,
+            ndm_ifindex: 3,
+            ndm_state: NUD_REACHABLE,
+            ndm_flags: NTF_ROUTER,
+            ndm_type: 1,
+            ..ndmsg::default()
+        };
+        let info = nda_cacheinfo {
+            ndm_confirmed: 10,
+            ndm_used: 20,
+            ndm_updated: 30,
+            ndm_refcnt: 1,
+        };
+        let mut buffer = AlignedBuffer::new(256);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        msg.put_extra_header(header.as_bytes()).unwrap();
+        put_ip(&mut msg, sys::NDA_DST, &"fe80::1".parse().unwrap()).unwrap();
+        msg.put(sys::NDA_LLADDR, &[2, 0, 0, 0, 0, 1]).unwrap();
+        msg.put(sys::NDA_CACHEINFO, info.as_bytes()).unwrap();
+        msg.put_u32(sys::NDA_PROBES, 0).unwrap();
+
+        let neighbour = Neighbour::from_msg(&NlMsg::new(msg.as_bytes()).unwrap()).unwrap();
+        assert_eq!(neighbour.index, 3);
+        assert!(!neighbour.is_fdb());
+        assert_eq!(neighbour.destination, Some("fe80::1".parse().unwrap()));
+        assert_eq!(neighbour.lladdr.as_deref(), Some(&[2, 0, 0, 0, 0, 1][..]));
+        assert_eq!(neighbour.state, NUD_REACHABLE);
+        assert_eq!(neighbour.flags, NTF_ROUTER);
+        assert_eq!(neighbour.cache_info.unwrap().updated, 30);
+        assert_eq!(neighbour.probes, Some(0));
+    }
+}
@@ -0,0 +1,254 @@
+use mnl_sys::libc;
+use std::io;
+
+use super::{
+    REQUEST_BUFFER_SIZE, ack, new_request,
+    sys::{self, Pod, ifinfomsg},
+};
+use crate::{NlMsgBuilder, Socket, buffer::AlignedBuffer};
+
+/// The kind of virtual link to create with [`NewLink`], with its kind specific settings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkKind {
+    /// A dummy device, dropping all traffic sent to it.
+    Dummy,
+    /// A pair of virtual Ethernet devices connected to each other.
+    Veth {
+        /// Name of the other end of the pair.
+        peer_name: String,
+    },
+    /// An Ethernet bridge.
+    Bridge,
+    /// An 802.1Q VLAN device on top of another device.
+    Vlan {
+        /// Index of the underlying device.
+        parent: u32,
+        /// The VLAN id.
+        id: u16,
+    },
+    /// A MACVLAN device on top of another device.
+    Macvlan {
+        /// Index of the underlying device.
+        parent: u32,
+        /// How traffic between MACVLAN devices on the same parent is handled.
+        mode: MacvlanMode,
+    },
+}
+
+/// The mode of a MACVLAN device. Mirrors the `MACVLAN_MODE_*` constants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+#[allow(missing_docs)]
+pub enum MacvlanMode {
+    Private = 1,
+    Vepa = 2,
+    Bridge = 4,
+    Passthru = 8,
+    Source = 16,
+}
+
+impl LinkKind {
+    fn name(&self) -> &'static str {
+        match self {
+            LinkKind::Dummy => "dummy",
+            LinkKind::Veth { .. } => "veth",
+            LinkKind::Bridge => "bridge",
+            LinkKind::Vlan { .. } => "vlan",
+            LinkKind::Macvlan { .. } => "macvlan",
+        }
+    }
+
+    fn parent(&self) -> Option<u32> {
+        match self {
+            LinkKind::Vlan { parent, .. } | LinkKind::Macvlan { parent, .. } => Some(*parent),
+            _ => None,
+        }
+    }
+
+    fn put_info_data(&self, msg: &mut NlMsgBuilder<'_>) -> io::Result<()> {
+        match self {
+            LinkKind::Dummy | LinkKind::Bridge => Ok(()),
+            LinkKind::Veth { peer_name } => msg.put_nested(sys::IFLA_INFO_DATA, |msg| {
+                msg.put_nested(sys::VETH_INFO_PEER, |msg| {
+                    msg.put_extra_header(ifinfomsg::default().as_bytes())?;
+                    msg.put_str(sys::IFLA_IFNAME, peer_name)
+                })
+            }),
+            LinkKind::Vlan { id, .. } => msg.put_nested(sys::IFLA_INFO_DATA, |msg| {
+                msg.put_u16(sys::IFLA_VLAN_ID, *id)
+            }),
+            LinkKind::Macvlan { mode, .. } => msg.put_nested(sys::IFLA_INFO_DATA, |msg| {
+                msg.put_u32(sys::IFLA_MACVLAN_MODE, *mode as u32)
+            }),
+        }
+    }
+}
+
+/// Builder for creating a virtual link with `RTM_NEWLINK`.
+///
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// use mnl::rtnl::{LinkKind, NewLink};
+///
+/// let socket = mnl::Socket::new(mnl::Bus::Route)?;
+/// NewLink::new("veth0", LinkKind::Veth { peer_name: "veth1".to_owned() })
+///     .mtu(1400)
+///     .up()
+///     .create(&socket)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewLink {
+    name: String,
+    kind: LinkKind,
+    mtu: Option<u32>,
+    address: Option<Vec<u8>>,
+    master: Option<u32>,
+    up: bool,
+}
+
+impl NewLink {
+    /// Describe a new link with the given name and kind.
+    pub fn new(name: impl Into<String>, kind: LinkKind) -> Self {
+        NewLink {
+            name: name.into(),
+            kind,
+            mtu: None,
+            address: None,
+            master: None,
+            up: false,
+        }
+    }
+
+    /// Set the MTU of the new link.
+    pub fn mtu(mut self, mtu: u32) -> Self {
+        self.mtu = Some(mtu);
+        self
+    }
+
+    /// Set the link layer address of the new link, such as a MAC address.
+    pub fn address(mut self, address: impl Into<Vec<u8>>) -> Self {
+        self.address = Some(address.into());
+        self
+    }
+
+    /// Attach the new link to the master device with the given index, such as a bridge.
+    pub fn master(mut self, master: u32) -> Self {
+        self.master = Some(master);
+        self
+    }
+
+    /// Bring the new link up when it is created.
+    pub fn up(mut self) -> Self {
+        self.up = true;
+        self
+    }
+
+    /// Create the link on `socket`, which must be a [`Bus::Route`] socket. Fails with `EEXIST`
+    /// if a link with the same name already exists.
+    ///
+    /// [`Bus::Route`]: crate::Bus::Route
+    pub fn create(&self, socket: &Socket) -> io::Result<()> {
+        let mut header = ifinfomsg::default();
+        if self.up {
+            header.ifi_flags = libc::IFF_UP as u32;
+            header.ifi_change = libc::IFF_UP as u32;
+        }
+        let flags = libc::NLM_F_CREATE | libc::NLM_F_EXCL | libc::NLM_F_ACK;
+        let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+        let mut msg = new_request(socket, &mut buffer, sys::RTM_NEWLINK, flags as u16, &header)?;
+        self.put_attrs(&mut msg)?;
+        ack(socket, &msg)
+    }
+
+    fn put_attrs(&self, msg: &mut NlMsgBuilder<'_>) -> io::Result<()> {
+        msg.put_str(sys::IFLA_IFNAME, &self.name)?;
+        if let Some(mtu) = self.mtu {
+            msg.put_u32(sys::IFLA_MTU, mtu)?;
+        }
+        if let Some(address) = &self.address {
+            msg.put(sys::IFLA_ADDRESS, address)?;
+        }
+        if let Some(master) = self.master {
+            msg.put_u32(sys::IFLA_MASTER, master)?;
+        }
+        if let Some(parent) = self.kind.parent() {
+            msg.put_u32(sys::IFLA_LINK, parent)?;
+        }
+        msg.put_nested(sys::IFLA_LINKINFO, |msg| {
+            msg.put_str(sys::IFLA_INFO_KIND, self.kind.name())?;
+            self.kind.put_info_data(msg)
+        })
+    }
+}
+
+/// Delete the link with the given interface index with `RTM_DELLINK`. Deleting one end of a
+/// veth pair deletes both.
+pub fn delete_link(socket: &Socket, index: u32) -> io::Result<()> {
+    let header = ifinfomsg {
+        ifi_index: index as i32,
+        ..ifinfomsg::default()
+    };
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let msg = new_request(
+        socket,
+        &mut buffer,
+        sys::RTM_DELLINK,
+        libc::NLM_F_ACK as u16,
+        &header,
+    )?;
+    ack(socket, &msg)
+}
+
+/// Delete the link with the given name with `RTM_DELLINK`.
+pub fn delete_link_by_name(socket: &Socket, name: &str) -> io::Result<()> {
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let mut msg = new_request(
+        socket,
+        &mut buffer,
+        sys::RTM_DELLINK,
+        libc::NLM_F_ACK as u16,
+        &ifinfomsg::default(),
+    )?;
+    msg.put_str(sys::IFLA_IFNAME, name)?;
+    ack(socket, &msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Attrs, NlMsg};
+
+    #[test]
+    fn test_veth_peer_encoding() {
+        let link = NewLink::new(
+            "veth0",
+            LinkKind::Veth {
+                peer_name: "veth1".to_owned(),
+            },
+        );
+        let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        msg.put_extra_header(ifinfomsg::default().as_bytes())
+            .unwrap();
+        link.put_attrs(&mut msg).unwrap();
+
+        let msg = NlMsg::new(msg.as_bytes()).unwrap();
+        let attrs = msg
+            .attrs(size_of::<ifinfomsg>())
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(attrs[0].get_str().unwrap(), "veth0");
+        let linkinfo = attrs[1].nested().collect::<io::Result<Vec<_>>>().unwrap();
+        assert_eq!(linkinfo[0].get_str().unwrap(), "veth");
+        assert_eq!(linkinfo[1].attr_type(), sys::IFLA_INFO_DATA);
+        let peer = linkinfo[1].nested().next().unwrap().unwrap();
+        assert_eq!(peer.attr_type(), sys::VETH_INFO_PEER);
+        let peer_attrs = Attrs::new(&peer.payload()[size_of::<ifinfomsg>()..])
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(peer_attrs[0].attr_type(), sys::IFLA_IFNAME);
+        assert_eq!(peer_attrs[0].get_str().unwrap(), "veth1");
+    }
+}
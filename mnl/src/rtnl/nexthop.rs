@@ -0,0 +1,564 @@
+use mnl_sys::libc;
+use std::{io, net::IpAddr};
+
+use super::{
+    REQUEST_BUFFER_SIZE, RTPROT_BOOT, RawAttrs, Scope, ack, dump, get_ip, new_request, put_ip,
+    sys::{self, Pod, nexthop_grp, nhmsg},
+};
+use crate::{NlMsg, NlMsgBuilder, Socket, buffer::AlignedBuffer};
+
+/// A nexthop object, as described by a `RTM_NEWNEXTHOP` message without `NHA_GROUP`.
+///
+/// Nexthop objects are managed separately from routes, which refer to them by id with
+/// [`NewRoute::nexthop_id`]. Not to be confused with [`NextHop`], a next hop embedded in a
+/// multipath route.
+///
+/// [`NewRoute::nexthop_id`]: super::NewRoute::nexthop_id
+/// [`NextHop`]: super::NextHop
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Nexthop {
+    /// Id of the nexthop (`NHA_ID`), shared with nexthop groups.
+    pub id: u32,
+    /// Address family, `AF_INET` or `AF_INET6`.
+    pub family: u8,
+    /// Scope of the nexthop.
+    pub scope: Scope,
+    /// The origin of the nexthop, such as [`RTPROT_STATIC`].
+    ///
+    /// [`RTPROT_STATIC`]: super::RTPROT_STATIC
+    pub protocol: u8,
+    /// Nexthop flags, a combination of the `RTNH_F_*` constants widened with `u32::from`.
+    pub flags: u32,
+    /// Gateway address (`NHA_GATEWAY`).
+    pub gateway: Option<IpAddr>,
+    /// Index of the outgoing interface (`NHA_OIF`).
+    pub oif: Option<u32>,
+    /// Silently drop packets using the nexthop (`NHA_BLACKHOLE`).
+    pub blackhole: bool,
+    /// The nexthop is used by bridge forwarding database entries rather than routes
+    /// (`NHA_FDB`).
+    pub fdb: bool,
+    /// Attributes without a typed field above.
+    pub other_attrs: RawAttrs,
+}
+
+/// The type of a nexthop group. Mirrors the `NEXTHOP_GRP_TYPE_*` constants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NexthopGroupType {
+    /// Flows are hashed over the members, like a multipath route.
+    #[default]
+    Multipath,
+    /// Flows are hashed to buckets, which are only moved to other members when needed.
+    Resilient,
+    /// A type not known to this crate.
+    Other(u16),
+}
+
+impl From<u16> for NexthopGroupType {
+    fn from(value: u16) -> Self {
+        match value {
+            sys::NEXTHOP_GRP_TYPE_MPATH => NexthopGroupType::Multipath,
+            sys::NEXTHOP_GRP_TYPE_RES => NexthopGroupType::Resilient,
+            other => NexthopGroupType::Other(other),
+        }
+    }
+}
+
+impl From<NexthopGroupType> for u16 {
+    fn from(group_type: NexthopGroupType) -> u16 {
+        match group_type {
+            NexthopGroupType::Multipath => sys::NEXTHOP_GRP_TYPE_MPATH,
+            NexthopGroupType::Resilient => sys::NEXTHOP_GRP_TYPE_RES,
+            NexthopGroupType::Other(other) => other,
+        }
+    }
+}
+
+/// A member of a [`NexthopGroup`] (`struct nexthop_grp`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NexthopGroupMember {
+    /// Id of the member nexthop.
+    pub id: u32,
+    /// Relative weight of the member, starting at 1. Kernels before 6.12 support weights up
+    /// to 256.
+    pub weight: u16,
+}
+
+/// A nexthop group, as described by a `RTM_NEWNEXTHOP` message with `NHA_GROUP`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NexthopGroup {
+    /// Id of the group (`NHA_ID`), shared with nexthops.
+    pub id: u32,
+    /// The origin of the group, such as [`RTPROT_STATIC`].
+    ///
+    /// [`RTPROT_STATIC`]: super::RTPROT_STATIC
+    pub protocol: u8,
+    /// Type of the group (`NHA_GROUP_TYPE`).
+    pub group_type: NexthopGroupType,
+    /// Members of the group (`NHA_GROUP`).
+    pub members: Vec<NexthopGroupMember>,
+    /// Number of hash buckets of a resilient group (`NHA_RES_GROUP_BUCKETS`).
+    pub buckets: Option<u16>,
+    /// The group is used by bridge forwarding database entries rather than routes
+    /// (`NHA_FDB`).
+    pub fdb: bool,
+    /// Attributes without a typed field above.
+    pub other_attrs: RawAttrs,
+}
+
+/// A nexthop message parsed before telling nexthops and groups apart.
+enum NexthopMsg {
+    Nexthop(Nexthop),
+    Group(NexthopGroup),
+}
+
+impl NexthopMsg {
+    fn parse(msg: &NlMsg<'_>) -> io::Result<NexthopMsg> {
+        let header = nhmsg::from_bytes(msg.payload())?;
+        let mut nexthop = Nexthop {
+            id: 0,
+            family: header.nh_family,
+            scope: header.nh_scope.into(),
+            protocol: header.nh_protocol,
+            flags: header.nh_flags,
+            gateway: None,
+            oif: None,
+            blackhole: false,
+            fdb: false,
+            other_attrs: RawAttrs::new(),
+        };
+        let mut group_type = NexthopGroupType::default();
+        let mut members = None;
+        let mut buckets = None;
+        for attr in msg.attrs(size_of::<nhmsg>()) {
+            let attr = attr?;
+            match attr.attr_type() {
+                sys::NHA_ID => nexthop.id = attr.get_u32()?,
+                sys::NHA_GATEWAY => nexthop.gateway = Some(get_ip(&attr)?),
+                sys::NHA_OIF => nexthop.oif = Some(attr.get_u32()?),
+                sys::NHA_BLACKHOLE => nexthop.blackhole = true,
+                sys::NHA_FDB => nexthop.fdb = true,
+                sys::NHA_GROUP => members = Some(parse_group(attr.payload())?),
+                sys::NHA_GROUP_TYPE => group_type = attr.get_u16()?.into(),
+                sys::NHA_RES_GROUP => {
+                    for attr in attr.nested() {
+                        let attr = attr?;
+                        if attr.attr_type() == sys::NHA_RES_GROUP_BUCKETS {
+                            buckets = Some(attr.get_u16()?);
+                        }
+                    }
+                }
+                other => nexthop.other_attrs.push((other, attr.payload().to_vec())),
+            }
+        }
+        Ok(match members {
+            Some(members) => NexthopMsg::Group(NexthopGroup {
+                id: nexthop.id,
+                protocol: nexthop.protocol,
+                group_type,
+                members,
+                buckets,
+                fdb: nexthop.fdb,
+                other_attrs: nexthop.other_attrs,
+            }),
+            None => NexthopMsg::Nexthop(nexthop),
+        })
+    }
+}
+
+fn parse_group(payload: &[u8]) -> io::Result<Vec<NexthopGroupMember>> {
+    payload
+        .chunks(size_of::<nexthop_grp>())
+        .map(|entry| {
+            let entry = nexthop_grp::from_bytes(entry)?;
+            Ok(NexthopGroupMember {
+                id: entry.id,
+                weight: u16::from_le_bytes([entry.weight, entry.weight_high]).saturating_add(1),
+            })
+        })
+        .collect()
+}
+
+fn encode_group(members: &[NexthopGroupMember]) -> io::Result<Vec<u8>> {
+    let mut payload = Vec::new();
+    for member in members {
+        let [weight, weight_high] = member
+            .weight
+            .checked_sub(1)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Invalid nexthop weight {}", member.weight),
+                )
+            })?
+            .to_le_bytes();
+        let entry = nexthop_grp {
+            id: member.id,
+            weight,
+            weight_high,
+            resvd2: 0,
+        };
+        payload.extend_from_slice(entry.as_bytes());
+    }
+    Ok(payload)
+}
+
+fn wrong_kind(expected: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Nexthop message does not describe a {expected}"),
+    )
+}
+
+impl Nexthop {
+    /// Parse a `RTM_NEWNEXTHOP` or `RTM_DELNEXTHOP` message. Fails for messages describing a
+    /// nexthop group.
+    pub fn from_msg(msg: &NlMsg<'_>) -> io::Result<Nexthop> {
+        match NexthopMsg::parse(msg)? {
+            NexthopMsg::Nexthop(nexthop) => Ok(nexthop),
+            NexthopMsg::Group(_) => Err(wrong_kind("nexthop")),
+        }
+    }
+
+    fn header(&self) -> nhmsg {
+        nhmsg {
+            nh_family: self.family,
+            nh_scope: self.scope.into(),
+            nh_protocol: self.protocol,
+            resvd: 0,
+            nh_flags: self.flags,
+        }
+    }
+
+    fn put_attrs(&self, msg: &mut NlMsgBuilder<'_>) -> io::Result<()> {
+        msg.put_u32(sys::NHA_ID, self.id)?;
+        if let Some(gateway) = &self.gateway {
+            put_ip(msg, sys::NHA_GATEWAY, gateway)?;
+        }
+        if let Some(oif) = self.oif {
+            msg.put_u32(sys::NHA_OIF, oif)?;
+        }
+        if self.blackhole {
+            msg.put_flag(sys::NHA_BLACKHOLE)?;
+        }
+        if self.fdb {
+            msg.put_flag(sys::NHA_FDB)?;
+        }
+        Ok(())
+    }
+}
+
+impl NexthopGroup {
+    /// Parse a `RTM_NEWNEXTHOP` or `RTM_DELNEXTHOP` message. Fails for messages describing a
+    /// single nexthop.
+    pub fn from_msg(msg: &NlMsg<'_>) -> io::Result<NexthopGroup> {
+        match NexthopMsg::parse(msg)? {
+            NexthopMsg::Group(group) => Ok(group),
+            NexthopMsg::Nexthop(_) => Err(wrong_kind("nexthop group")),
+        }
+    }
+
+    fn header(&self) -> nhmsg {
+        nhmsg {
+            nh_family: libc::AF_UNSPEC as u8,
+            nh_protocol: self.protocol,
+            ..nhmsg::default()
+        }
+    }
+
+    fn put_attrs(&self, msg: &mut NlMsgBuilder<'_>) -> io::Result<()> {
+        msg.put_u32(sys::NHA_ID, self.id)?;
+        msg.put(sys::NHA_GROUP, &encode_group(&self.members)?)?;
+        msg.put_u16(sys::NHA_GROUP_TYPE, self.group_type.into())?;
+        if let Some(buckets) = self.buckets {
+            msg.put_nested(sys::NHA_RES_GROUP, |msg| {
+                msg.put_u16(sys::NHA_RES_GROUP_BUCKETS, buckets)
+            })?;
+        }
+        if self.fdb {
+            msg.put_flag(sys::NHA_FDB)?;
+        }
+        Ok(())
+    }
+}
+
+fn dump_nexthops(socket: &Socket, groups_only: bool) -> io::Result<Vec<NexthopMsg>> {
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let mut msg = new_request(
+        socket,
+        &mut buffer,
+        sys::RTM_GETNEXTHOP,
+        libc::NLM_F_DUMP as u16,
+        &nhmsg::default(),
+    )?;
+    if groups_only {
+        msg.put_flag(sys::NHA_GROUPS)?;
+    }
+    dump(socket, &msg, sys::RTM_NEWNEXTHOP, NexthopMsg::parse)
+}
+
+/// Dump the nexthops in the network namespace of `socket`, which must be a [`Bus::Route`]
+/// socket. Nexthop groups are left out, see [`nexthop_groups`].
+///
+/// [`Bus::Route`]: crate::Bus::Route
+pub fn nexthops(socket: &Socket) -> io::Result<Vec<Nexthop>> {
+    let nexthops = dump_nexthops(socket, false)?;
+    Ok(nexthops
+        .into_iter()
+        .filter_map(|nexthop| match nexthop {
+            NexthopMsg::Nexthop(nexthop) => Some(nexthop),
+            NexthopMsg::Group(_) => None,
+        })
+        .collect())
+}
+
+/// Dump the nexthop groups in the network namespace of `socket`.
+pub fn nexthop_groups(socket: &Socket) -> io::Result<Vec<NexthopGroup>> {
+    let groups = dump_nexthops(socket, true)?;
+    Ok(groups
+        .into_iter()
+        .filter_map(|group| match group {
+            NexthopMsg::Group(group) => Some(group),
+            NexthopMsg::Nexthop(_) => None,
+        })
+        .collect())
+}
+
+/// Builder for adding a nexthop with `RTM_NEWNEXTHOP`.
+///
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// use mnl::rtnl::{self, NewNexthop, NewNexthopGroup, NewRoute};
+///
+/// let socket = mnl::Socket::new(mnl::Bus::Route)?;
+/// let eth0 = rtnl::link_by_name(&socket, "eth0")?;
+/// let eth1 = rtnl::link_by_name(&socket, "eth1")?;
+/// NewNexthop::v4(1)
+///     .gateway("192.168.1.1".parse().unwrap())
+///     .oif(eth0.index)
+///     .add(&socket)?;
+/// NewNexthop::v4(2)
+///     .gateway("192.168.2.1".parse().unwrap())
+///     .oif(eth1.index)
+///     .add(&socket)?;
+/// NewNexthopGroup::new(100).member(1, 1).member(2, 2).add(&socket)?;
+/// NewRoute::new("0.0.0.0".parse().unwrap(), 0)
+///     .nexthop_id(100)
+///     .add(&socket)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewNexthop {
+    nexthop: Nexthop,
+}
+
+impl NewNexthop {
+    fn new(id: u32, family: i32) -> Self {
+        NewNexthop {
+            nexthop: Nexthop {
+                id,
+                family: family as u8,
+                scope: Scope::Universe,
+                protocol: RTPROT_BOOT,
+                flags: 0,
+                gateway: None,
+                oif: None,
+                blackhole: false,
+                fdb: false,
+                other_attrs: RawAttrs::new(),
+            },
+        }
+    }
+
+    /// Describe an IPv4 nexthop with the given id, which must not be 0.
+    pub fn v4(id: u32) -> Self {
+        Self::new(id, libc::AF_INET)
+    }
+
+    /// Describe an IPv6 nexthop with the given id, which must not be 0.
+    pub fn v6(id: u32) -> Self {
+        Self::new(id, libc::AF_INET6)
+    }
+
+    /// Send packets through `gateway`, which must be of the family of the nexthop.
+    pub fn gateway(mut self, gateway: IpAddr) -> Self {
+        self.nexthop.gateway = Some(gateway);
+        self
+    }
+
+    /// Send packets out on the interface with the given index.
+    pub fn oif(mut self, oif: u32) -> Self {
+        self.nexthop.oif = Some(oif);
+        self
+    }
+
+    /// Silently drop packets instead. Can't be combined with a gateway or interface.
+    pub fn blackhole(mut self) -> Self {
+        self.nexthop.blackhole = true;
+        self
+    }
+
+    /// Set the nexthop flags, a combination of the `RTNH_F_*` constants widened with
+    /// `u32::from`, such as `u32::from(RTNH_F_ONLINK)` for [`RTNH_F_ONLINK`].
+    ///
+    /// [`RTNH_F_ONLINK`]: super::RTNH_F_ONLINK
+    pub fn flags(mut self, flags: u32) -> Self {
+        self.nexthop.flags = flags;
+        self
+    }
+
+    /// Set the origin of the nexthop. Defaults to [`RTPROT_BOOT`].
+    pub fn protocol(mut self, protocol: u8) -> Self {
+        self.nexthop.protocol = protocol;
+        self
+    }
+
+    /// Use the nexthop for bridge forwarding database entries rather than routes.
+    pub fn fdb(mut self) -> Self {
+        self.nexthop.fdb = true;
+        self
+    }
+
+    /// Add the nexthop on `socket`, which must be a [`Bus::Route`] socket. Fails with
+    /// `EEXIST` if a nexthop or group with the same id exists.
+    ///
+    /// [`Bus::Route`]: crate::Bus::Route
+    pub fn add(&self, socket: &Socket) -> io::Result<()> {
+        self.send(socket, libc::NLM_F_EXCL)
+    }
+
+    /// Add the nexthop on `socket`, or replace the nexthop with the same id. Routes using it
+    /// are updated in place.
+    pub fn replace(&self, socket: &Socket) -> io::Result<()> {
+        self.send(socket, libc::NLM_F_REPLACE)
+    }
+
+    fn send(&self, socket: &Socket, flags: libc::c_int) -> io::Result<()> {
+        let flags = flags | libc::NLM_F_CREATE | libc::NLM_F_ACK;
+        let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+        let mut msg = new_request(
+            socket,
+            &mut buffer,
+            sys::RTM_NEWNEXTHOP,
+            flags as u16,
+            &self.nexthop.header(),
+        )?;
+        self.nexthop.put_attrs(&mut msg)?;
+        ack(socket, &msg)
+    }
+}
+
+/// Builder for adding a nexthop group with `RTM_NEWNEXTHOP`. See [`NewNexthop`] for an
+/// example.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewNexthopGroup {
+    group: NexthopGroup,
+}
+
+impl NewNexthopGroup {
+    /// Describe a multipath nexthop group with the given id, which must not be 0.
+    pub fn new(id: u32) -> Self {
+        NewNexthopGroup {
+            group: NexthopGroup {
+                id,
+                protocol: RTPROT_BOOT,
+                group_type: NexthopGroupType::Multipath,
+                members: Vec::new(),
+                buckets: None,
+                fdb: false,
+                other_attrs: RawAttrs::new(),
+            },
+        }
+    }
+
+    /// Add the nexthop with the given id to the group, with a relative weight starting at 1.
+    pub fn member(mut self, id: u32, weight: u16) -> Self {
+        self.group.members.push(NexthopGroupMember { id, weight });
+        self
+    }
+
+    /// Make this a resilient group with the given number of hash buckets.
+    pub fn resilient(mut self, buckets: u16) -> Self {
+        self.group.group_type = NexthopGroupType::Resilient;
+        self.group.buckets = Some(buckets);
+        self
+    }
+
+    /// Set the origin of the group. Defaults to [`RTPROT_BOOT`].
+    pub fn protocol(mut self, protocol: u8) -> Self {
+        self.group.protocol = protocol;
+        self
+    }
+
+    /// Use the group for bridge forwarding database entries rather than routes. All members
+    /// must be forwarding database nexthops.
+    pub fn fdb(mut self) -> Self {
+        self.group.fdb = true;
+        self
+    }
+
+    /// Add the group on `socket`, which must be a [`Bus::Route`] socket. Fails with `EEXIST`
+    /// if a nexthop or group with the same id exists.
+    ///
+    /// [`Bus::Route`]: crate::Bus::Route
+    pub fn add(&self, socket: &Socket) -> io::Result<()> {
+        self.send(socket, libc::NLM_F_EXCL)
+    }
+
+    /// Add the group on `socket`, or replace the members of the group with the same id.
+    pub fn replace(&self, socket: &Socket) -> io::Result<()> {
+        self.send(socket, libc::NLM_F_REPLACE)
+    }
+
+    fn send(&self, socket: &Socket, flags: libc::c_int) -> io::Result<()> {
+        let flags = flags | libc::NLM_F_CREATE | libc::NLM_F_ACK;
+        let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+        let mut msg = new_request(
+            socket,
+            &mut buffer,
+            sys::RTM_NEWNEXTHOP,
+            flags as u16,
+            &self.group.header(),
+        )?;
+        self.group.put_attrs(&mut msg)?;
+        ack(socket, &msg)
+    }
+}
+
+/// Delete the nexthop or nexthop group with the given id with `RTM_DELNEXTHOP`. Routes using
+/// it are deleted as well, and a deleted nexthop is removed from the groups it is a member of.
+pub fn delete_nexthop(socket: &Socket, id: u32) -> io::Result<()> {
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let mut msg = new_request(
+        socket,
+        &mut buffer,
+        sys::RTM_DELNEXTHOP,
+        libc::NLM_F_ACK as u16,
+        &nhmsg::default(),
+    )?;
+    msg.put_u32(sys::NHA_ID, id)?;
+    ack(socket, &msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_round_trip() {
+        let group = NewNexthopGroup::new(100)
+            .member(1, 1)
+            .member(2, 300)
+            .resilient(64)
+            .group;
+        let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        msg.put_extra_header(group.header().as_bytes()).unwrap();
+        group.put_attrs(&mut msg).unwrap();
+
+        let msg = NlMsg::new(msg.as_bytes()).unwrap();
+        assert_eq!(NexthopGroup::from_msg(&msg).unwrap(), group);
+        assert!(Nexthop::from_msg(&msg).is_err());
+    }
+}
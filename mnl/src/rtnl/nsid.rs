@@ -0,0 +1,205 @@
+use mnl_sys::libc;
+use std::io;
+
+use super::{
+    Netns, REQUEST_BUFFER_SIZE, ack, dump, new_request,
+    sys::{self, rtgenmsg},
+};
+use crate::{NlMsg, NlMsgBuilder, Socket, buffer::AlignedBuffer};
+
+/// Returns the id the namespace `netns` has in the namespace of the socket (`RTM_GETNSID`), or
+/// `None` if it has not been assigned one.
+///
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// use mnl::rtnl::{self, Netns};
+/// use std::{fs::File, os::unix::io::AsRawFd};
+///
+/// let socket = mnl::Socket::new(mnl::Bus::Route)?;
+/// let netns = File::open("/run/netns/blue")?;
+/// let nsid = match rtnl::netns_id(&socket, Netns::Fd(netns.as_raw_fd()))? {
+///     Some(nsid) => nsid,
+///     None => rtnl::assign_netns_id(&socket, Netns::Fd(netns.as_raw_fd()), None)?,
+/// };
+/// println!("blue has nsid {nsid}");
+/// # Ok(())
+/// # }
+/// ```
+pub fn netns_id(socket: &Socket, netns: Netns) -> io::Result<Option<i32>> {
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let mut msg = new_request(socket, &mut buffer, sys::RTM_GETNSID, 0, &header())?;
+    put_netns(&mut msg, netns)?;
+    let mut nsid = None;
+    socket.request(msg.as_bytes(), |reply| {
+        if reply.msg_type() == sys::RTM_NEWNSID {
+            nsid = parse_nsid(&reply)?;
+        }
+        Ok(())
+    })?;
+    Ok(nsid)
+}
+
+/// Assign an id to the namespace `netns` in the namespace of the socket (`RTM_NEWNSID`) and
+/// return it. With `None` the kernel picks the lowest free id.
+///
+/// Fails with `EEXIST` if the namespace already has an id, or if the requested id is taken.
+pub fn assign_netns_id(socket: &Socket, netns: Netns, nsid: Option<i32>) -> io::Result<i32> {
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let mut msg = new_request(
+        socket,
+        &mut buffer,
+        sys::RTM_NEWNSID,
+        libc::NLM_F_ACK as u16,
+        &header(),
+    )?;
+    put_netns(&mut msg, netns)?;
+    let requested = nsid.unwrap_or(sys::NETNSA_NSID_NOT_ASSIGNED);
+    msg.put_u32(sys::NETNSA_NSID, requested as u32)?;
+    ack(socket, &msg)?;
+
+    match nsid {
+        Some(nsid) => Ok(nsid),
+        None => netns_id(socket, netns)?
+            .ok_or_else(|| io::Error::other("Kernel did not assign a netns id")),
+    }
+}
+
+/// A namespace id, as listed by [`netns_ids`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NetnsId {
+    /// The id of the namespace in the listed namespace (`NETNSA_NSID`).
+    pub nsid: i32,
+    /// The id the same namespace has in the namespace of the socket, when listing the ids of
+    /// another namespace (`NETNSA_CURRENT_NSID`). `None` if it has no id there, or when
+    /// listing the namespace of the socket itself.
+    pub current_nsid: Option<i32>,
+}
+
+impl NetnsId {
+    /// Parse an `RTM_NEWNSID` message from a dump.
+    pub fn from_msg(msg: &NlMsg<'_>) -> io::Result<NetnsId> {
+        let mut nsid = None;
+        let mut current_nsid = None;
+        for attr in msg.attrs(size_of::<rtgenmsg>()) {
+            let attr = attr?;
+            match attr.attr_type() {
+                sys::NETNSA_NSID => nsid = Some(attr.get_u32()? as i32),
+                sys::NETNSA_CURRENT_NSID => current_nsid = Some(attr.get_u32()? as i32),
+                _ => (),
+            }
+        }
+        Ok(NetnsId {
+            nsid: nsid.ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Netns id message without NETNSA_NSID",
+                )
+            })?,
+            current_nsid: current_nsid.filter(|nsid| *nsid != sys::NETNSA_NSID_NOT_ASSIGNED),
+        })
+    }
+}
+
+/// Returns all namespace ids assigned in the namespace of the socket, or with `target` in the
+/// namespace with that id. When listing another namespace, every entry also tells which
+/// namespace the id refers to, by the id that namespace has in the namespace of the socket.
+///
+/// Alternatively use a socket opened in another namespace with [`Socket::new_in_netns`] to list
+/// the ids of that one.
+pub fn netns_ids(socket: &Socket, target: Option<i32>) -> io::Result<Vec<NetnsId>> {
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let mut msg = new_request(
+        socket,
+        &mut buffer,
+        sys::RTM_GETNSID,
+        libc::NLM_F_DUMP as u16,
+        &header(),
+    )?;
+    if let Some(target) = target {
+        msg.put_u32(sys::NETNSA_TARGET_NSID, target as u32)?;
+    }
+    dump(socket, &msg, sys::RTM_NEWNSID, NetnsId::from_msg)
+}
+
+fn header() -> rtgenmsg {
+    rtgenmsg {
+        rtgen_family: libc::AF_UNSPEC as u8,
+        ..Default::default()
+    }
+}
+
+fn put_netns(msg: &mut NlMsgBuilder<'_>, netns: Netns) -> io::Result<()> {
+    match netns {
+        Netns::Fd(fd) => msg.put_u32(sys::NETNSA_FD, fd as u32),
+        Netns::Pid(pid) => msg.put_u32(sys::NETNSA_PID, pid),
+    }
+}
+
+/// Parse the `NETNSA_NSID` of an `RTM_NEWNSID` message, mapping unassigned to `None`.
+fn parse_nsid(msg: &NlMsg<'_>) -> io::Result<Option<i32>> {
+    for attr in msg.attrs(size_of::<rtgenmsg>()) {
+        let attr = attr?;
+        if attr.attr_type() == sys::NETNSA_NSID {
+            return match attr.get_u32()? as i32 {
+                sys::NETNSA_NSID_NOT_ASSIGNED => Ok(None),
+                nsid => Ok(Some(nsid)),
+            };
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "Netns id message without NETNSA_NSID",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rtnl::sys::Pod;
+
+    #[test]
+    fn test_parse_nsid() {
+        for nsid in [Some(0), Some(42), None] {
+            let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+            let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+            msg.set_type(sys::RTM_NEWNSID);
+            msg.put_extra_header(header().as_bytes()).unwrap();
+            let value = nsid.unwrap_or(sys::NETNSA_NSID_NOT_ASSIGNED);
+            msg.put_u32(sys::NETNSA_NSID, value as u32).unwrap();
+
+            let msg = NlMsg::new(msg.as_bytes()).unwrap();
+            assert_eq!(parse_nsid(&msg).unwrap(), nsid);
+        }
+    }
+
+    #[test]
+    fn test_parse_netns_ids() {
+        for (current_nsid, expected) in [(None, None), (Some(-1), None), (Some(3), Some(3))] {
+            let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+            let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+            msg.set_type(sys::RTM_NEWNSID);
+            msg.set_flags(libc::NLM_F_MULTI as u16);
+            msg.put_extra_header(header().as_bytes()).unwrap();
+            msg.put_u32(sys::NETNSA_NSID, 7).unwrap();
+            if let Some(current_nsid) = current_nsid {
+                msg.put_u32(sys::NETNSA_CURRENT_NSID, current_nsid as u32)
+                    .unwrap();
+            }
+
+            let msg = NlMsg::new(msg.as_bytes()).unwrap();
+            let nsid = NetnsId::from_msg(&msg).unwrap();
+            assert_eq!(
+                nsid,
+                NetnsId {
+                    nsid: 7,
+                    current_nsid: expected
+                }
+            );
+        }
+
+        let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        msg.put_extra_header(header().as_bytes()).unwrap();
+        assert!(NetnsId::from_msg(&NlMsg::new(msg.as_bytes()).unwrap()).is_err());
+    }
+}
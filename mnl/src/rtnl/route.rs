@@ -0,0 +1,753 @@
+use mnl_sys::libc;
+use std::{
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+use super::{
+    REQUEST_BUFFER_SIZE, RawAttrs, Scope, ack, dump, family_of, get_ip, new_request, put_ip,
+    sys::{self, Pod, rtmsg, rtnexthop},
+};
+use crate::{Attr, Attrs, NlMsg, NlMsgBuilder, Socket, buffer::AlignedBuffer};
+
+/// The main routing table, used when no table is given.
+pub const RT_TABLE_MAIN: u32 = 254;
+/// The local routing table, holding local and broadcast routes maintained by the kernel.
+pub const RT_TABLE_LOCAL: u32 = 255;
+/// The default routing table, consulted by the default rule after the main table.
+pub const RT_TABLE_DEFAULT: u32 = 253;
+
+/// Route protocol: the route was installed by the kernel.
+pub const RTPROT_KERNEL: u8 = 2;
+/// Route protocol: the route was installed during boot. Used by default for new routes.
+pub const RTPROT_BOOT: u8 = 3;
+/// Route protocol: the route was installed by the administrator.
+pub const RTPROT_STATIC: u8 = 4;
+
+/// Next hop flag: the next hop is unusable.
+pub const RTNH_F_DEAD: u8 = 0x01;
+/// Next hop flag: the gateway is reachable on the interface even if no address of the
+/// interface covers it.
+pub const RTNH_F_ONLINK: u8 = 0x04;
+/// Next hop flag: the carrier of the interface is down.
+pub const RTNH_F_LINKDOWN: u8 = 0x10;
+
+/// The type of a route. Mirrors the `RTN_*` constants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RouteType {
+    /// A gateway or direct route.
+    #[default]
+    Unicast,
+    /// A local interface address.
+    Local,
+    /// A broadcast address, sent as broadcast.
+    Broadcast,
+    /// A local broadcast address, sent as unicast.
+    Anycast,
+    /// A multicast route.
+    Multicast,
+    /// Silently drop matching packets.
+    Blackhole,
+    /// Drop matching packets and report the destination as unreachable.
+    Unreachable,
+    /// Drop matching packets and report them as administratively prohibited.
+    Prohibit,
+    /// Continue the route lookup in the next table.
+    Throw,
+    /// A type not known to this crate.
+    Other(u8),
+}
+
+impl From<u8> for RouteType {
+    fn from(value: u8) -> Self {
+        use RouteType::*;
+        match value {
+            1 => Unicast,
+            2 => Local,
+            3 => Broadcast,
+            4 => Anycast,
+            5 => Multicast,
+            6 => Blackhole,
+            7 => Unreachable,
+            8 => Prohibit,
+            9 => Throw,
+            other => Other(other),
+        }
+    }
+}
+
+impl From<RouteType> for u8 {
+    fn from(route_type: RouteType) -> u8 {
+        use RouteType::*;
+        match route_type {
+            Unicast => 1,
+            Local => 2,
+            Broadcast => 3,
+            Anycast => 4,
+            Multicast => 5,
+            Blackhole => 6,
+            Unreachable => 7,
+            Prohibit => 8,
+            Throw => 9,
+            Other(other) => other,
+        }
+    }
+}
+
+/// One of the next hops of a multipath route (`struct rtnexthop`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NextHop {
+    /// Gateway of this next hop (`RTA_GATEWAY`), if any.
+    pub gateway: Option<IpAddr>,
+    /// Index of the outgoing interface.
+    pub oif: u32,
+    /// Relative weight of this next hop, starting at 1.
+    pub weight: u16,
+    /// Next hop flags, a combination of the `RTNH_F_*` constants in this module.
+    pub flags: u8,
+}
+
+impl NextHop {
+    /// A next hop through `gateway` on the interface with index `oif`, with weight 1.
+    pub fn new(gateway: Option<IpAddr>, oif: u32) -> Self {
+        NextHop {
+            gateway,
+            oif,
+            weight: 1,
+            flags: 0,
+        }
+    }
+}
+
+/// Metrics of a route (`RTA_METRICS`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RouteMetrics {
+    /// Path MTU (`RTAX_MTU`).
+    pub mtu: Option<u32>,
+    /// Advertised TCP maximum segment size (`RTAX_ADVMSS`).
+    pub advmss: Option<u32>,
+    /// Other metrics, as `RTAX_*` type and value.
+    pub other: Vec<(u16, u32)>,
+}
+
+impl RouteMetrics {
+    fn is_empty(&self) -> bool {
+        self.mtu.is_none() && self.advmss.is_none() && self.other.is_empty()
+    }
+}
+
+/// A route, as described by a `RTM_NEWROUTE` message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    /// Destination prefix address. The unspecified address for a default route.
+    pub destination: IpAddr,
+    /// Length of the destination prefix.
+    pub dst_len: u8,
+    /// Source prefix for source specific routes (`RTA_SRC`), with its length.
+    pub source: Option<(IpAddr, u8)>,
+    /// Routing table id (`RTA_TABLE`), such as [`RT_TABLE_MAIN`].
+    pub table: u32,
+    /// The origin of the route, such as [`RTPROT_STATIC`].
+    pub protocol: u8,
+    /// Scope of the route.
+    pub scope: Scope,
+    /// Type of the route.
+    pub route_type: RouteType,
+    /// Route flags, a combination of the `RTM_F_*` and `RTNH_F_*` constants. The latter are
+    /// `u8` like [`NextHop::flags`], and widened with `u32::from`.
+    pub flags: u32,
+    /// Type of service to match.
+    pub tos: u8,
+    /// Gateway address (`RTA_GATEWAY`).
+    pub gateway: Option<IpAddr>,
+    /// Index of the outgoing interface (`RTA_OIF`).
+    pub oif: Option<u32>,
+    /// Index of the incoming interface (`RTA_IIF`).
+    pub iif: Option<u32>,
+    /// Route priority, also known as metric (`RTA_PRIORITY`).
+    pub priority: Option<u32>,
+    /// Preferred source address for packets using the route (`RTA_PREFSRC`).
+    pub prefsrc: Option<IpAddr>,
+    /// Route metrics (`RTA_METRICS`).
+    pub metrics: RouteMetrics,
+    /// Next hops of a multipath route (`RTA_MULTIPATH`).
+    pub multipath: Vec<NextHop>,
+    /// Id of the [`Nexthop`] or [`NexthopGroup`] object the route uses (`RTA_NH_ID`). The
+    /// kernel still reports the resolved gateway, interface or next hops of such routes.
+    ///
+    /// [`Nexthop`]: super::Nexthop
+    /// [`NexthopGroup`]: super::NexthopGroup
+    pub nexthop_id: Option<u32>,
+    /// Firewall mark (`RTA_MARK`), only set in replies to route lookups.
+    pub mark: Option<u32>,
+    /// Attributes without a typed field above.
+    pub other_attrs: RawAttrs,
+}
+
+impl Route {
+    /// A unicast route to `destination/dst_len` in the main table, without any next hop.
+    pub fn new(destination: IpAddr, dst_len: u8) -> Self {
+        Route {
+            destination,
+            dst_len,
+            source: None,
+            table: RT_TABLE_MAIN,
+            protocol: RTPROT_BOOT,
+            scope: Scope::Universe,
+            route_type: RouteType::Unicast,
+            flags: 0,
+            tos: 0,
+            gateway: None,
+            oif: None,
+            iif: None,
+            priority: None,
+            prefsrc: None,
+            metrics: RouteMetrics::default(),
+            multipath: Vec::new(),
+            nexthop_id: None,
+            mark: None,
+            other_attrs: RawAttrs::new(),
+        }
+    }
+
+    /// Parse a `RTM_NEWROUTE` or `RTM_DELROUTE` message.
+    pub fn from_msg(msg: &NlMsg<'_>) -> io::Result<Route> {
+        let header = rtmsg::from_bytes(msg.payload())?;
+        let unspecified = match i32::from(header.rtm_family) {
+            libc::AF_INET => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            libc::AF_INET6 => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+            family => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unsupported route address family {family}"),
+                ));
+            }
+        };
+        let mut route = Route {
+            table: u32::from(header.rtm_table),
+            protocol: header.rtm_protocol,
+            scope: header.rtm_scope.into(),
+            route_type: header.rtm_type.into(),
+            flags: header.rtm_flags,
+            tos: header.rtm_tos,
+            ..Route::new(unspecified, header.rtm_dst_len)
+        };
+        let mut source = None;
+        for attr in msg.attrs(size_of::<rtmsg>()) {
+            let attr = attr?;
+            match attr.attr_type() {
+                sys::RTA_DST => route.destination = get_ip(&attr)?,
+                sys::RTA_SRC => source = Some(get_ip(&attr)?),
+                sys::RTA_TABLE => route.table = attr.get_u32()?,
+                sys::RTA_GATEWAY => route.gateway = Some(get_ip(&attr)?),
+                sys::RTA_OIF => route.oif = Some(attr.get_u32()?),
+                sys::RTA_IIF => route.iif = Some(attr.get_u32()?),
+                sys::RTA_PRIORITY => route.priority = Some(attr.get_u32()?),
+                sys::RTA_PREFSRC => route.prefsrc = Some(get_ip(&attr)?),
+                sys::RTA_METRICS => route.metrics = parse_metrics(attr)?,
+                sys::RTA_MULTIPATH => route.multipath = parse_multipath(attr.payload())?,
+                sys::RTA_NH_ID => route.nexthop_id = Some(attr.get_u32()?),
+                sys::RTA_MARK => route.mark = Some(attr.get_u32()?),
+                other => route.other_attrs.push((other, attr.payload().to_vec())),
+            }
+        }
+        route.source = source.map(|source| (source, header.rtm_src_len));
+        Ok(route)
+    }
+
+    fn header(&self) -> rtmsg {
+        rtmsg {
+            rtm_family: family_of(&self.destination),
+            rtm_dst_len: self.dst_len,
+            rtm_src_len: self.source.map_or(0, |(_, len)| len),
+            rtm_tos: self.tos,
+            rtm_table: u8::try_from(self.table).unwrap_or(sys::RT_TABLE_UNSPEC),
+            rtm_protocol: self.protocol,
+            rtm_scope: self.scope.into(),
+            rtm_type: self.route_type.into(),
+            rtm_flags: self.flags,
+        }
+    }
+
+    fn put_attrs(&self, msg: &mut NlMsgBuilder<'_>) -> io::Result<()> {
+        if self.dst_len > 0 {
+            put_ip(msg, sys::RTA_DST, &self.destination)?;
+        }
+        if let Some((source, _)) = &self.source {
+            put_ip(msg, sys::RTA_SRC, source)?;
+        }
+        msg.put_u32(sys::RTA_TABLE, self.table)?;
+        // The kernel reports the next hops resolved from a nexthop object, but rejects them
+        // in requests together with the nexthop id.
+        let nexthop_object = self.nexthop_id.is_some();
+        if let Some(gateway) = self.gateway.as_ref().filter(|_| !nexthop_object) {
+            put_ip(msg, sys::RTA_GATEWAY, gateway)?;
+        }
+        if let Some(oif) = self.oif.filter(|_| !nexthop_object) {
+            msg.put_u32(sys::RTA_OIF, oif)?;
+        }
+        if let Some(iif) = self.iif {
+            msg.put_u32(sys::RTA_IIF, iif)?;
+        }
+        if let Some(priority) = self.priority {
+            msg.put_u32(sys::RTA_PRIORITY, priority)?;
+        }
+        if let Some(prefsrc) = &self.prefsrc {
+            put_ip(msg, sys::RTA_PREFSRC, prefsrc)?;
+        }
+        if !self.metrics.is_empty() {
+            msg.put_nested(sys::RTA_METRICS, |msg| {
+                if let Some(mtu) = self.metrics.mtu {
+                    msg.put_u32(sys::RTAX_MTU, mtu)?;
+                }
+                if let Some(advmss) = self.metrics.advmss {
+                    msg.put_u32(sys::RTAX_ADVMSS, advmss)?;
+                }
+                for (metric, value) in &self.metrics.other {
+                    msg.put_u32(*metric, *value)?;
+                }
+                Ok(())
+            })?;
+        }
+        if !self.multipath.is_empty() && !nexthop_object {
+            msg.put(sys::RTA_MULTIPATH, &encode_multipath(&self.multipath)?)?;
+        }
+        if let Some(nexthop_id) = self.nexthop_id {
+            msg.put_u32(sys::RTA_NH_ID, nexthop_id)?;
+        }
+        if let Some(mark) = self.mark {
+            msg.put_u32(sys::RTA_MARK, mark)?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_metrics(attr: Attr<'_>) -> io::Result<RouteMetrics> {
+    let mut metrics = RouteMetrics::default();
+    for attr in attr.nested() {
+        let attr = attr?;
+        match attr.attr_type() {
+            sys::RTAX_MTU => metrics.mtu = Some(attr.get_u32()?),
+            sys::RTAX_ADVMSS => metrics.advmss = Some(attr.get_u32()?),
+            // Some metrics, such as the congestion control algorithm, are not integers.
+            other => {
+                if let Ok(value) = attr.get_u32() {
+                    metrics.other.push((other, value));
+                }
+            }
+        }
+    }
+    Ok(metrics)
+}
+
+fn parse_multipath(mut payload: &[u8]) -> io::Result<Vec<NextHop>> {
+    let mut hops = Vec::new();
+    while payload.len() >= size_of::<rtnexthop>() {
+        let header = rtnexthop::from_bytes(payload)?;
+        let len = usize::from(header.rtnh_len);
+        if len < size_of::<rtnexthop>() || len > payload.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid next hop length {len}"),
+            ));
+        }
+        let mut hop = NextHop {
+            gateway: None,
+            oif: header.rtnh_ifindex as u32,
+            weight: u16::from(header.rtnh_hops) + 1,
+            flags: header.rtnh_flags,
+        };
+        for attr in Attrs::new(&payload[size_of::<rtnexthop>()..len]) {
+            let attr = attr?;
+            if attr.attr_type() == sys::RTA_GATEWAY {
+                hop.gateway = Some(get_ip(&attr)?);
+            }
+        }
+        hops.push(hop);
+        payload = payload.get(len.next_multiple_of(4)..).unwrap_or(&[]);
+    }
+    Ok(hops)
+}
+
+fn encode_multipath(hops: &[NextHop]) -> io::Result<Vec<u8>> {
+    let mut payload = Vec::new();
+    for hop in hops {
+        let hops = hop
+            .weight
+            .checked_sub(1)
+            .and_then(|hops| u8::try_from(hops).ok())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Invalid next hop weight {}", hop.weight),
+                )
+            })?;
+        let gateway = match hop.gateway {
+            Some(IpAddr::V4(addr)) => addr.octets().to_vec(),
+            Some(IpAddr::V6(addr)) => addr.octets().to_vec(),
+            None => Vec::new(),
+        };
+        let attr_len = if gateway.is_empty() {
+            0
+        } else {
+            4 + gateway.len()
+        };
+        let header = rtnexthop {
+            rtnh_len: (size_of::<rtnexthop>() + attr_len) as u16,
+            rtnh_flags: hop.flags,
+            rtnh_hops: hops,
+            rtnh_ifindex: hop.oif as i32,
+        };
+        payload.extend_from_slice(header.as_bytes());
+        if !gateway.is_empty() {
+            payload.extend_from_slice(&(attr_len as u16).to_ne_bytes());
+            payload.extend_from_slice(&sys::RTA_GATEWAY.to_ne_bytes());
+            payload.extend_from_slice(&gateway);
+        }
+    }
+    Ok(payload)
+}
+
+/// Dump all IPv4 and IPv6 routes in all routing tables in the network namespace of `socket`,
+/// which must be a [`Bus::Route`] socket.
+///
+/// [`Bus::Route`]: crate::Bus::Route
+pub fn routes(socket: &Socket) -> io::Result<Vec<Route>> {
+    let mut routes = Vec::new();
+    for family in [libc::AF_INET, libc::AF_INET6] {
+        let header = rtmsg {
+            rtm_family: family as u8,
+            ..rtmsg::default()
+        };
+        let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+        let msg = new_request(
+            socket,
+            &mut buffer,
+            sys::RTM_GETROUTE,
+            libc::NLM_F_DUMP as u16,
+            &header,
+        )?;
+        routes.extend(dump(socket, &msg, sys::RTM_NEWROUTE, Route::from_msg)?);
+    }
+    Ok(routes)
+}
+
+/// Optional selectors for [`route_get`], describing the packet to look up a route for.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RouteGetOptions {
+    /// Source address of the packet.
+    pub source: Option<IpAddr>,
+    /// Index of the interface the packet arrives on. Simulates forwarding when set.
+    pub iif: Option<u32>,
+    /// Index of the interface the packet must be sent out on.
+    pub oif: Option<u32>,
+    /// Firewall mark of the packet.
+    pub mark: Option<u32>,
+    /// User id of the socket sending the packet.
+    pub uid: Option<u32>,
+    /// IP protocol of the packet, such as `IPPROTO_TCP`.
+    pub ip_proto: Option<u8>,
+    /// Source port of the packet.
+    pub sport: Option<u16>,
+    /// Destination port of the packet.
+    pub dport: Option<u16>,
+    /// Report the table the route was found in rather than the local table id used for
+    /// routes to the host itself (`RTM_F_LOOKUP_TABLE`).
+    pub lookup_table: bool,
+    /// Return the matching route as it is in the routing table, rather than the route as
+    /// resolved for this particular destination (`RTM_F_FIB_MATCH`).
+    pub fib_match: bool,
+}
+
+/// Look up the route the kernel would use for a packet to `destination`, like `ip route get`.
+/// Fails with `ENETUNREACH` if there is no route.
+///
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// use mnl::rtnl::{self, RouteGetOptions};
+///
+/// let socket = mnl::Socket::new(mnl::Bus::Route)?;
+/// let options = RouteGetOptions {
+///     mark: Some(0x6d6f6c65),
+///     ..RouteGetOptions::default()
+/// };
+/// let route = rtnl::route_get(&socket, "1.1.1.1".parse().unwrap(), &options)?;
+/// println!("via {:?} on {:?}", route.gateway, route.oif);
+/// # Ok(())
+/// # }
+/// ```
+pub fn route_get(
+    socket: &Socket,
+    destination: IpAddr,
+    options: &RouteGetOptions,
+) -> io::Result<Route> {
+    let mut flags = 0;
+    if options.lookup_table {
+        flags |= sys::RTM_F_LOOKUP_TABLE;
+    }
+    if options.fib_match {
+        flags |= sys::RTM_F_FIB_MATCH;
+    }
+    let full_len = |addr: &IpAddr| if addr.is_ipv4() { 32 } else { 128 };
+    let header = rtmsg {
+        rtm_family: family_of(&destination),
+        rtm_dst_len: full_len(&destination),
+        rtm_src_len: options.source.as_ref().map_or(0, full_len),
+        rtm_flags: flags,
+        ..rtmsg::default()
+    };
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let mut msg = new_request(socket, &mut buffer, sys::RTM_GETROUTE, 0, &header)?;
+    put_ip(&mut msg, sys::RTA_DST, &destination)?;
+    if let Some(source) = &options.source {
+        put_ip(&mut msg, sys::RTA_SRC, source)?;
+    }
+    if let Some(iif) = options.iif {
+        msg.put_u32(sys::RTA_IIF, iif)?;
+    }
+    if let Some(oif) = options.oif {
+        msg.put_u32(sys::RTA_OIF, oif)?;
+    }
+    if let Some(mark) = options.mark {
+        msg.put_u32(sys::RTA_MARK, mark)?;
+    }
+    if let Some(uid) = options.uid {
+        msg.put_u32(sys::RTA_UID, uid)?;
+    }
+    if let Some(ip_proto) = options.ip_proto {
+        msg.put_u8(sys::RTA_IP_PROTO, ip_proto)?;
+    }
+    if let Some(sport) = options.sport {
+        msg.put(sys::RTA_SPORT, &sport.to_be_bytes())?;
+    }
+    if let Some(dport) = options.dport {
+        msg.put(sys::RTA_DPORT, &dport.to_be_bytes())?;
+    }
+    dump(socket, &msg, sys::RTM_NEWROUTE, Route::from_msg)?
+        .pop()
+        .ok_or_else(|| io::Error::from_raw_os_error(libc::ENETUNREACH))
+}
+
+/// Builder for adding a route with `RTM_NEWROUTE`.
+///
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// use mnl::rtnl::{self, NewRoute, RouteType};
+///
+/// let socket = mnl::Socket::new(mnl::Bus::Route)?;
+/// let tun = rtnl::link_by_name(&socket, "wg0")?;
+/// NewRoute::new("0.0.0.0".parse().unwrap(), 0)
+///     .table(51820)
+///     .oif(tun.index)
+///     .mtu(1380)
+///     .add(&socket)?;
+/// NewRoute::new("::".parse().unwrap(), 0)
+///     .table(51820)
+///     .route_type(RouteType::Unreachable)
+///     .add(&socket)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewRoute {
+    route: Route,
+    scope: Option<Scope>,
+}
+
+impl NewRoute {
+    /// Describe a unicast route to `destination/dst_len` in the main table. Use the
+    /// unspecified address with length 0 for a default route.
+    pub fn new(destination: IpAddr, dst_len: u8) -> Self {
+        NewRoute {
+            route: Route::new(destination, dst_len),
+            scope: None,
+        }
+    }
+
+    /// Only use the route for packets from `source/src_len`. Only supported for IPv6.
+    pub fn source(mut self, source: IpAddr, src_len: u8) -> Self {
+        self.route.source = Some((source, src_len));
+        self
+    }
+
+    /// Put the route in the given routing table. Defaults to [`RT_TABLE_MAIN`].
+    pub fn table(mut self, table: u32) -> Self {
+        self.route.table = table;
+        self
+    }
+
+    /// Set the origin of the route. Defaults to [`RTPROT_BOOT`].
+    pub fn protocol(mut self, protocol: u8) -> Self {
+        self.route.protocol = protocol;
+        self
+    }
+
+    /// Set the scope of the route. Defaults to [`Scope::Host`] for local routes,
+    /// [`Scope::Link`] for unicast routes without gateway or nexthop object and
+    /// [`Scope::Universe`] otherwise.
+    pub fn scope(mut self, scope: Scope) -> Self {
+        self.scope = Some(scope);
+        self
+    }
+
+    /// Set the type of the route. Defaults to [`RouteType::Unicast`].
+    pub fn route_type(mut self, route_type: RouteType) -> Self {
+        self.route.route_type = route_type;
+        self
+    }
+
+    /// Send matching packets through `gateway`.
+    pub fn gateway(mut self, gateway: IpAddr) -> Self {
+        self.route.gateway = Some(gateway);
+        self
+    }
+
+    /// Send matching packets out on the interface with the given index.
+    pub fn oif(mut self, oif: u32) -> Self {
+        self.route.oif = Some(oif);
+        self
+    }
+
+    /// Set the priority, also known as metric. Routes with lower values are preferred.
+    pub fn priority(mut self, priority: u32) -> Self {
+        self.route.priority = Some(priority);
+        self
+    }
+
+    /// Set the preferred source address for packets using the route.
+    pub fn prefsrc(mut self, prefsrc: IpAddr) -> Self {
+        self.route.prefsrc = Some(prefsrc);
+        self
+    }
+
+    /// Set the path MTU metric.
+    pub fn mtu(mut self, mtu: u32) -> Self {
+        self.route.metrics.mtu = Some(mtu);
+        self
+    }
+
+    /// Set the advertised TCP maximum segment size metric.
+    pub fn advmss(mut self, advmss: u32) -> Self {
+        self.route.metrics.advmss = Some(advmss);
+        self
+    }
+
+    /// Add a next hop, making this a multipath route. Use instead of [`NewRoute::gateway`] and
+    /// [`NewRoute::oif`].
+    pub fn nexthop(mut self, nexthop: NextHop) -> Self {
+        self.route.multipath.push(nexthop);
+        self
+    }
+
+    /// Use the nexthop or nexthop group object with the given id, added with [`NewNexthop`] or
+    /// [`NewNexthopGroup`]. Use instead of a gateway, interface or next hops.
+    ///
+    /// [`NewNexthop`]: super::NewNexthop
+    /// [`NewNexthopGroup`]: super::NewNexthopGroup
+    pub fn nexthop_id(mut self, id: u32) -> Self {
+        self.route.nexthop_id = Some(id);
+        self
+    }
+
+    /// Add the route on `socket`, which must be a [`Bus::Route`] socket. Fails with `EEXIST`
+    /// if the same route already exists.
+    ///
+    /// [`Bus::Route`]: crate::Bus::Route
+    pub fn add(&self, socket: &Socket) -> io::Result<()> {
+        self.send(socket, libc::NLM_F_EXCL)
+    }
+
+    /// Add the route on `socket`, or replace the route with the same destination, table,
+    /// type of service and priority.
+    pub fn replace(&self, socket: &Socket) -> io::Result<()> {
+        self.send(socket, libc::NLM_F_REPLACE)
+    }
+
+    fn send(&self, socket: &Socket, flags: libc::c_int) -> io::Result<()> {
+        let route = self.route();
+        let flags = flags | libc::NLM_F_CREATE | libc::NLM_F_ACK;
+        let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+        let mut msg = new_request(
+            socket,
+            &mut buffer,
+            sys::RTM_NEWROUTE,
+            flags as u16,
+            &route.header(),
+        )?;
+        route.put_attrs(&mut msg)?;
+        ack(socket, &msg)
+    }
+
+    fn route(&self) -> Route {
+        let route = &self.route;
+        let scope = self.scope.unwrap_or(match route.route_type {
+            RouteType::Local => Scope::Host,
+            RouteType::Unicast
+                if route.gateway.is_none()
+                    && route.multipath.is_empty()
+                    && route.nexthop_id.is_none() =>
+            {
+                Scope::Link
+            }
+            _ => Scope::Universe,
+        });
+        Route {
+            scope,
+            ..route.clone()
+        }
+    }
+}
+
+/// Delete a route with `RTM_DELROUTE`. The kernel deletes the first route matching the
+/// destination, table, type of service and every optional field that is set, so a route
+/// returned by [`routes`] can be passed as is.
+pub fn delete_route(socket: &Socket, route: &Route) -> io::Result<()> {
+    let header = rtmsg {
+        // Match routes of any scope.
+        rtm_scope: Scope::Nowhere.into(),
+        ..route.header()
+    };
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let mut msg = new_request(
+        socket,
+        &mut buffer,
+        sys::RTM_DELROUTE,
+        libc::NLM_F_ACK as u16,
+        &header,
+    )?;
+    route.put_attrs(&mut msg)?;
+    ack(socket, &msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_round_trip() {
+        let new = NewRoute::new("10.0.0.0".parse().unwrap(), 8)
+            .table(1000)
+            .prefsrc("10.1.1.1".parse().unwrap())
+            .mtu(1380)
+            .advmss(1340)
+            .nexthop(NextHop::new(Some("192.168.1.1".parse().unwrap()), 2))
+            .nexthop(NextHop {
+                weight: 3,
+                flags: RTNH_F_ONLINK,
+                ..NextHop::new(None, 3)
+            });
+        let route = new.route();
+        let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        msg.put_extra_header(route.header().as_bytes()).unwrap();
+        route.put_attrs(&mut msg).unwrap();
+
+        let parsed = Route::from_msg(&NlMsg::new(msg.as_bytes()).unwrap()).unwrap();
+        assert_eq!(parsed, route);
+        assert_eq!(parsed.scope, Scope::Universe);
+        assert_eq!(parsed.table, 1000);
+        assert_eq!(parsed.metrics.mtu, Some(1380));
+        assert_eq!(parsed.multipath[1].weight, 3);
+        assert_eq!(parsed.multipath[1].flags, RTNH_F_ONLINK);
+    }
+}
@@ -0,0 +1,493 @@
+use mnl_sys::libc;
+use std::{io, net::IpAddr, ops::RangeInclusive};
+
+use super::{
+    REQUEST_BUFFER_SIZE, RT_TABLE_MAIN, RawAttrs, ack, dump, get_ip, new_request, put_ip,
+    sys::{self, Pod, fib_rule_hdr, fib_rule_port_range, fib_rule_uid_range},
+};
+use crate::{Attr, NlMsg, NlMsgBuilder, Socket, buffer::AlignedBuffer};
+
+/// What to do with packets matching a rule. Mirrors the `FR_ACT_*` constants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RuleAction {
+    /// Look up the route in the rule's table.
+    #[default]
+    ToTable,
+    /// Continue with the rule with the priority given by [`Rule::goto`].
+    Goto,
+    /// Do nothing and continue with the next rule.
+    Nop,
+    /// Silently drop the packet.
+    Blackhole,
+    /// Drop the packet and report the destination as unreachable.
+    Unreachable,
+    /// Drop the packet and report it as administratively prohibited.
+    Prohibit,
+    /// An action not known to this crate.
+    Other(u8),
+}
+
+impl From<u8> for RuleAction {
+    fn from(value: u8) -> Self {
+        use RuleAction::*;
+        match value {
+            1 => ToTable,
+            2 => Goto,
+            3 => Nop,
+            6 => Blackhole,
+            7 => Unreachable,
+            8 => Prohibit,
+            other => Other(other),
+        }
+    }
+}
+
+impl From<RuleAction> for u8 {
+    fn from(action: RuleAction) -> u8 {
+        use RuleAction::*;
+        match action {
+            ToTable => 1,
+            Goto => 2,
+            Nop => 3,
+            Blackhole => 6,
+            Unreachable => 7,
+            Prohibit => 8,
+            Other(other) => other,
+        }
+    }
+}
+
+/// A routing policy rule, as described by a `RTM_NEWRULE` message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    /// Address family of the rule, `AF_INET` or `AF_INET6`.
+    pub family: u8,
+    /// Priority of the rule (`FRA_PRIORITY`). Rules are evaluated in order of increasing
+    /// priority.
+    pub priority: Option<u32>,
+    /// What to do with matching packets.
+    pub action: RuleAction,
+    /// Routing table to look up for [`RuleAction::ToTable`] (`FRA_TABLE`).
+    pub table: u32,
+    /// Priority of the rule to continue with for [`RuleAction::Goto`] (`FRA_GOTO`).
+    pub goto: Option<u32>,
+    /// Match packets not matching the selectors instead (`FIB_RULE_INVERT`).
+    pub invert: bool,
+    /// Source prefix to match (`FRA_SRC`), with its length.
+    pub source: Option<(IpAddr, u8)>,
+    /// Destination prefix to match (`FRA_DST`), with its length.
+    pub destination: Option<(IpAddr, u8)>,
+    /// Type of service to match.
+    pub tos: u8,
+    /// Firewall mark to match (`FRA_FWMARK`).
+    pub fwmark: Option<u32>,
+    /// Mask applied to the firewall mark before matching (`FRA_FWMASK`).
+    pub fwmask: Option<u32>,
+    /// Name of the incoming interface to match (`FRA_IIFNAME`).
+    pub iif: Option<String>,
+    /// Name of the outgoing interface to match (`FRA_OIFNAME`).
+    pub oif: Option<String>,
+    /// Range of user ids of the sending socket to match (`FRA_UID_RANGE`).
+    pub uid_range: Option<RangeInclusive<u32>>,
+    /// Reject routing decisions with a prefix length of this or less (`FRA_SUPPRESS_PREFIXLEN`).
+    pub suppress_prefixlen: Option<u32>,
+    /// IP protocol to match, such as `IPPROTO_TCP` (`FRA_IP_PROTO`).
+    pub ip_proto: Option<u8>,
+    /// Range of source ports to match (`FRA_SPORT_RANGE`).
+    pub sport_range: Option<RangeInclusive<u16>>,
+    /// Range of destination ports to match (`FRA_DPORT_RANGE`).
+    pub dport_range: Option<RangeInclusive<u16>>,
+    /// The origin of the rule, using the same values as the protocol of routes
+    /// (`FRA_PROTOCOL`).
+    pub protocol: Option<u8>,
+    /// Attributes without a typed field above.
+    pub other_attrs: RawAttrs,
+}
+
+impl Rule {
+    fn new(family: libc::c_int) -> Self {
+        Rule {
+            family: family as u8,
+            priority: None,
+            action: RuleAction::ToTable,
+            table: RT_TABLE_MAIN,
+            goto: None,
+            invert: false,
+            source: None,
+            destination: None,
+            tos: 0,
+            fwmark: None,
+            fwmask: None,
+            iif: None,
+            oif: None,
+            uid_range: None,
+            suppress_prefixlen: None,
+            ip_proto: None,
+            sport_range: None,
+            dport_range: None,
+            protocol: None,
+            other_attrs: RawAttrs::new(),
+        }
+    }
+
+    /// Parse a `RTM_NEWRULE` or `RTM_DELRULE` message.
+    pub fn from_msg(msg: &NlMsg<'_>) -> io::Result<Rule> {
+        let header = fib_rule_hdr::from_bytes(msg.payload())?;
+        let mut rule = Rule {
+            action: header.action.into(),
+            table: u32::from(header.table),
+            invert: header.flags & sys::FIB_RULE_INVERT != 0,
+            tos: header.tos,
+            ..Rule::new(i32::from(header.family))
+        };
+        for attr in msg.attrs(size_of::<fib_rule_hdr>()) {
+            let attr = attr?;
+            match attr.attr_type() {
+                sys::FRA_PRIORITY => rule.priority = Some(attr.get_u32()?),
+                sys::FRA_TABLE => rule.table = attr.get_u32()?,
+                sys::FRA_GOTO => rule.goto = Some(attr.get_u32()?),
+                sys::FRA_SRC => rule.source = Some((get_ip(&attr)?, header.src_len)),
+                sys::FRA_DST => rule.destination = Some((get_ip(&attr)?, header.dst_len)),
+                sys::FRA_FWMARK => rule.fwmark = Some(attr.get_u32()?),
+                sys::FRA_FWMASK => rule.fwmask = Some(attr.get_u32()?),
+                sys::FRA_IIFNAME => rule.iif = Some(attr.get_str()?.to_owned()),
+                sys::FRA_OIFNAME => rule.oif = Some(attr.get_str()?.to_owned()),
+                sys::FRA_UID_RANGE => {
+                    let range = fib_rule_uid_range::from_bytes(attr.payload())?;
+                    rule.uid_range = Some(range.start..=range.end);
+                }
+                sys::FRA_SUPPRESS_PREFIXLEN => {
+                    // The kernel reports -1 when the selector is not in use.
+                    rule.suppress_prefixlen = Some(attr.get_u32()?).filter(|len| *len != u32::MAX)
+                }
+                sys::FRA_IP_PROTO => rule.ip_proto = Some(attr.get_u8()?),
+                sys::FRA_SPORT_RANGE => rule.sport_range = Some(get_port_range(&attr)?),
+                sys::FRA_DPORT_RANGE => rule.dport_range = Some(get_port_range(&attr)?),
+                sys::FRA_PROTOCOL => rule.protocol = Some(attr.get_u8()?),
+                other => rule.other_attrs.push((other, attr.payload().to_vec())),
+            }
+        }
+        Ok(rule)
+    }
+
+    fn header(&self) -> fib_rule_hdr {
+        let table = match self.action {
+            RuleAction::ToTable => u8::try_from(self.table).unwrap_or(sys::RT_TABLE_UNSPEC),
+            _ => sys::RT_TABLE_UNSPEC,
+        };
+        fib_rule_hdr {
+            family: self.family,
+            dst_len: self.destination.map_or(0, |(_, len)| len),
+            src_len: self.source.map_or(0, |(_, len)| len),
+            tos: self.tos,
+            table,
+            action: self.action.into(),
+            flags: if self.invert { sys::FIB_RULE_INVERT } else { 0 },
+            ..fib_rule_hdr::default()
+        }
+    }
+
+    fn put_attrs(&self, msg: &mut NlMsgBuilder<'_>) -> io::Result<()> {
+        if let Some(priority) = self.priority {
+            msg.put_u32(sys::FRA_PRIORITY, priority)?;
+        }
+        if self.action == RuleAction::ToTable {
+            msg.put_u32(sys::FRA_TABLE, self.table)?;
+        }
+        if let Some(goto) = self.goto {
+            msg.put_u32(sys::FRA_GOTO, goto)?;
+        }
+        if let Some((source, _)) = &self.source {
+            put_ip(msg, sys::FRA_SRC, source)?;
+        }
+        if let Some((destination, _)) = &self.destination {
+            put_ip(msg, sys::FRA_DST, destination)?;
+        }
+        if let Some(fwmark) = self.fwmark {
+            msg.put_u32(sys::FRA_FWMARK, fwmark)?;
+        }
+        if let Some(fwmask) = self.fwmask {
+            msg.put_u32(sys::FRA_FWMASK, fwmask)?;
+        }
+        if let Some(iif) = &self.iif {
+            msg.put_str(sys::FRA_IIFNAME, iif)?;
+        }
+        if let Some(oif) = &self.oif {
+            msg.put_str(sys::FRA_OIFNAME, oif)?;
+        }
+        if let Some(range) = &self.uid_range {
+            let range = fib_rule_uid_range {
+                start: *range.start(),
+                end: *range.end(),
+            };
+            msg.put(sys::FRA_UID_RANGE, range.as_bytes())?;
+        }
+        if let Some(len) = self.suppress_prefixlen {
+            msg.put_u32(sys::FRA_SUPPRESS_PREFIXLEN, len)?;
+        }
+        if let Some(ip_proto) = self.ip_proto {
+            msg.put_u8(sys::FRA_IP_PROTO, ip_proto)?;
+        }
+        if let Some(range) = &self.sport_range {
+            put_port_range(msg, sys::FRA_SPORT_RANGE, range)?;
+        }
+        if let Some(range) = &self.dport_range {
+            put_port_range(msg, sys::FRA_DPORT_RANGE, range)?;
+        }
+        if let Some(protocol) = self.protocol {
+            msg.put_u8(sys::FRA_PROTOCOL, protocol)?;
+        }
+        Ok(())
+    }
+}
+
+fn get_port_range(attr: &Attr<'_>) -> io::Result<RangeInclusive<u16>> {
+    let range = fib_rule_port_range::from_bytes(attr.payload())?;
+    Ok(range.start..=range.end)
+}
+
+fn put_port_range(
+    msg: &mut NlMsgBuilder<'_>,
+    attr_type: u16,
+    range: &RangeInclusive<u16>,
+) -> io::Result<()> {
+    let range = fib_rule_port_range {
+        start: *range.start(),
+        end: *range.end(),
+    };
+    msg.put(attr_type, range.as_bytes())
+}
+
+/// Dump all IPv4 and IPv6 routing policy rules in the network namespace of `socket`, which
+/// must be a [`Bus::Route`] socket.
+///
+/// [`Bus::Route`]: crate::Bus::Route
+pub fn rules(socket: &Socket) -> io::Result<Vec<Rule>> {
+    let mut rules = Vec::new();
+    for family in [libc::AF_INET, libc::AF_INET6] {
+        let header = fib_rule_hdr {
+            family: family as u8,
+            ..fib_rule_hdr::default()
+        };
+        let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+        let msg = new_request(
+            socket,
+            &mut buffer,
+            sys::RTM_GETRULE,
+            libc::NLM_F_DUMP as u16,
+            &header,
+        )?;
+        rules.extend(dump(socket, &msg, sys::RTM_NEWRULE, Rule::from_msg)?);
+    }
+    Ok(rules)
+}
+
+/// Builder for adding a routing policy rule with `RTM_NEWRULE`.
+///
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// use mnl::rtnl::NewRule;
+///
+/// let socket = mnl::Socket::new(mnl::Bus::Route)?;
+/// // Route everything not marked with 0x6d6f6c65 through table 51820.
+/// NewRule::v4()
+///     .priority(100)
+///     .fwmark(0x6d6f6c65, u32::MAX)
+///     .invert()
+///     .table(51820)
+///     .add(&socket)?;
+/// // But let the main table handle everything more specific than the default route.
+/// NewRule::v4()
+///     .priority(99)
+///     .table(mnl::rtnl::RT_TABLE_MAIN)
+///     .suppress_prefixlen(0)
+///     .add(&socket)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewRule {
+    rule: Rule,
+}
+
+impl NewRule {
+    /// Describe an IPv4 rule looking up the main table for all packets.
+    pub fn v4() -> Self {
+        NewRule {
+            rule: Rule::new(libc::AF_INET),
+        }
+    }
+
+    /// Describe an IPv6 rule looking up the main table for all packets.
+    pub fn v6() -> Self {
+        NewRule {
+            rule: Rule::new(libc::AF_INET6),
+        }
+    }
+
+    /// Set the priority of the rule. The kernel picks one if not set.
+    pub fn priority(mut self, priority: u32) -> Self {
+        self.rule.priority = Some(priority);
+        self
+    }
+
+    /// Look up the given routing table for matching packets. Defaults to [`RT_TABLE_MAIN`].
+    pub fn table(mut self, table: u32) -> Self {
+        self.rule.table = table;
+        self.rule.action = RuleAction::ToTable;
+        self
+    }
+
+    /// Set what to do with matching packets. Defaults to [`RuleAction::ToTable`].
+    pub fn action(mut self, action: RuleAction) -> Self {
+        self.rule.action = action;
+        self
+    }
+
+    /// Continue with the rule with the given priority for matching packets.
+    pub fn goto(mut self, priority: u32) -> Self {
+        self.rule.goto = Some(priority);
+        self.rule.action = RuleAction::Goto;
+        self
+    }
+
+    /// Match packets that do not match the selectors instead.
+    pub fn invert(mut self) -> Self {
+        self.rule.invert = true;
+        self
+    }
+
+    /// Match packets from `source/src_len`.
+    pub fn source(mut self, source: IpAddr, src_len: u8) -> Self {
+        self.rule.source = Some((source, src_len));
+        self
+    }
+
+    /// Match packets to `destination/dst_len`.
+    pub fn destination(mut self, destination: IpAddr, dst_len: u8) -> Self {
+        self.rule.destination = Some((destination, dst_len));
+        self
+    }
+
+    /// Match packets whose firewall mark equals `fwmark` after applying `mask`.
+    pub fn fwmark(mut self, fwmark: u32, mask: u32) -> Self {
+        self.rule.fwmark = Some(fwmark);
+        self.rule.fwmask = Some(mask);
+        self
+    }
+
+    /// Match packets arriving on the interface with the given name. Use `"lo"` to match
+    /// locally generated packets.
+    pub fn iif(mut self, iif: impl Into<String>) -> Self {
+        self.rule.iif = Some(iif.into());
+        self
+    }
+
+    /// Match packets sent from sockets bound to the interface with the given name.
+    pub fn oif(mut self, oif: impl Into<String>) -> Self {
+        self.rule.oif = Some(oif.into());
+        self
+    }
+
+    /// Match packets sent from sockets owned by a user in `range`.
+    pub fn uid_range(mut self, range: RangeInclusive<u32>) -> Self {
+        self.rule.uid_range = Some(range);
+        self
+    }
+
+    /// Reject routing decisions with a prefix length of `len` or less, so that the next rule is
+    /// tried instead.
+    pub fn suppress_prefixlen(mut self, len: u32) -> Self {
+        self.rule.suppress_prefixlen = Some(len);
+        self
+    }
+
+    /// Match packets of the given IP protocol, such as `IPPROTO_UDP`.
+    pub fn ip_proto(mut self, ip_proto: u8) -> Self {
+        self.rule.ip_proto = Some(ip_proto);
+        self
+    }
+
+    /// Match packets with a source port in `range`.
+    pub fn sport_range(mut self, range: RangeInclusive<u16>) -> Self {
+        self.rule.sport_range = Some(range);
+        self
+    }
+
+    /// Match packets with a destination port in `range`.
+    pub fn dport_range(mut self, range: RangeInclusive<u16>) -> Self {
+        self.rule.dport_range = Some(range);
+        self
+    }
+
+    /// Set the origin of the rule, such as [`RTPROT_STATIC`].
+    ///
+    /// [`RTPROT_STATIC`]: super::RTPROT_STATIC
+    pub fn protocol(mut self, protocol: u8) -> Self {
+        self.rule.protocol = Some(protocol);
+        self
+    }
+
+    /// Add the rule on `socket`, which must be a [`Bus::Route`] socket. Fails with `EEXIST` if
+    /// an identical rule already exists.
+    ///
+    /// [`Bus::Route`]: crate::Bus::Route
+    pub fn add(&self, socket: &Socket) -> io::Result<()> {
+        let flags = libc::NLM_F_CREATE | libc::NLM_F_EXCL | libc::NLM_F_ACK;
+        let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+        let mut msg = new_request(
+            socket,
+            &mut buffer,
+            sys::RTM_NEWRULE,
+            flags as u16,
+            &self.rule.header(),
+        )?;
+        self.rule.put_attrs(&mut msg)?;
+        ack(socket, &msg)
+    }
+}
+
+/// Delete a routing policy rule with `RTM_DELRULE`. The kernel deletes the first rule matching
+/// every selector that is set, so a rule returned by [`rules`] can be passed as is.
+pub fn delete_rule(socket: &Socket, rule: &Rule) -> io::Result<()> {
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let mut msg = new_request(
+        socket,
+        &mut buffer,
+        sys::RTM_DELRULE,
+        libc::NLM_F_ACK as u16,
+        &rule.header(),
+    )?;
+    rule.put_attrs(&mut msg)?;
+    ack(socket, &msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rule_round_trip() {
+        let new = NewRule::v6()
+            .priority(10)
+            .table(51820)
+            .fwmark(0x6d6f6c65, 0xffff)
+            .invert()
+            .uid_range(1000..=1999)
+            .source("fd00::".parse().unwrap(), 8)
+            .iif("lo")
+            .ip_proto(libc::IPPROTO_UDP as u8)
+            .dport_range(53..=53);
+        let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        msg.put_extra_header(new.rule.header().as_bytes()).unwrap();
+        new.rule.put_attrs(&mut msg).unwrap();
+
+        let rule = Rule::from_msg(&NlMsg::new(msg.as_bytes()).unwrap()).unwrap();
+        assert_eq!(rule, new.rule);
+        assert_eq!(rule.family, libc::AF_INET6 as u8);
+        assert!(rule.invert);
+        assert_eq!(rule.uid_range, Some(1000..=1999));
+        assert_eq!(rule.dport_range, Some(53..=53));
+    }
+}
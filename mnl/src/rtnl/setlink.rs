@@ -0,0 +1,255 @@
+use mnl_sys::libc;
+use std::{io, os::unix::io::RawFd};
+
+use super::{
+    REQUEST_BUFFER_SIZE, ack, new_request,
+    sys::{self, ifinfomsg},
+};
+use crate::{NlMsgBuilder, Socket, buffer::AlignedBuffer};
+
+/// Builder for changing the settings of an existing link with `RTM_SETLINK`. Only the settings
+/// given to the builder are changed.
+///
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// use mnl::rtnl::{self, SetLink};
+///
+/// let socket = mnl::Socket::new(mnl::Bus::Route)?;
+/// let bridge = rtnl::link_by_name(&socket, "br0")?;
+/// let port = rtnl::link_by_name(&socket, "veth0")?;
+/// SetLink::new(port.index)
+///     .master(bridge.index)
+///     .mtu(1400)
+///     .up()
+///     .apply(&socket)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetLink {
+    index: u32,
+    up: Option<bool>,
+    mtu: Option<u32>,
+    address: Option<Vec<u8>>,
+    name: Option<String>,
+    txqlen: Option<u32>,
+    master: Option<u32>,
+    netns: Option<Netns>,
+    ipv4_conf: Vec<(u16, i32)>,
+}
+
+/// A network namespace, for example to move a link to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Netns {
+    /// The namespace referred to by a file descriptor, such as an open `/proc/<pid>/ns/net` or
+    /// `/run/netns/<name>` file (`IFLA_NET_NS_FD`).
+    Fd(RawFd),
+    /// The namespace of the process with the given pid (`IFLA_NET_NS_PID`).
+    Pid(u32),
+}
+
+impl SetLink {
+    /// Prepare changes to the link with the given interface index.
+    pub fn new(index: u32) -> Self {
+        SetLink {
+            index,
+            up: None,
+            mtu: None,
+            address: None,
+            name: None,
+            txqlen: None,
+            master: None,
+            netns: None,
+            ipv4_conf: Vec::new(),
+        }
+    }
+
+    /// Set the link administratively up.
+    pub fn up(mut self) -> Self {
+        self.up = Some(true);
+        self
+    }
+
+    /// Set the link administratively down.
+    pub fn down(mut self) -> Self {
+        self.up = Some(false);
+        self
+    }
+
+    /// Change the MTU.
+    pub fn mtu(mut self, mtu: u32) -> Self {
+        self.mtu = Some(mtu);
+        self
+    }
+
+    /// Change the link layer address, such as the MAC address.
+    pub fn address(mut self, address: impl Into<Vec<u8>>) -> Self {
+        self.address = Some(address.into());
+        self
+    }
+
+    /// Rename the link. Most devices must be down to be renamed.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Change the transmit queue length.
+    pub fn txqlen(mut self, txqlen: u32) -> Self {
+        self.txqlen = Some(txqlen);
+        self
+    }
+
+    /// Attach the link to the master device with the given index, such as a bridge.
+    pub fn master(mut self, master: u32) -> Self {
+        self.master = Some(master);
+        self
+    }
+
+    /// Detach the link from its master device.
+    pub fn nomaster(mut self) -> Self {
+        self.master = Some(0);
+        self
+    }
+
+    /// Move the link to another network namespace. The link keeps its index if possible, but
+    /// must be looked up by a socket in the new namespace from then on.
+    pub fn netns(mut self, netns: Netns) -> Self {
+        self.netns = Some(netns);
+        self
+    }
+
+    /// Change the IPv4 setting with the given `IPV4_DEVCONF_*` id, like writing to
+    /// `/proc/sys/net/ipv4/conf/<link>/`. Can be given several times to change more settings.
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// use mnl::rtnl::{self, SetLink};
+    ///
+    /// let socket = mnl::Socket::new(mnl::Bus::Route)?;
+    /// let link = rtnl::link_by_name(&socket, "eth0")?;
+    /// SetLink::new(link.index)
+    ///     .ipv4_conf(rtnl::IPV4_DEVCONF_FORWARDING, 1)
+    ///     .ipv4_conf(rtnl::IPV4_DEVCONF_RP_FILTER, 2)
+    ///     .apply(&socket)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn ipv4_conf(mut self, id: u16, value: i32) -> Self {
+        self.ipv4_conf.push((id, value));
+        self
+    }
+
+    /// Apply the changes on `socket`, which must be a [`Bus::Route`] socket.
+    ///
+    /// [`Bus::Route`]: crate::Bus::Route
+    pub fn apply(&self, socket: &Socket) -> io::Result<()> {
+        let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+        let mut msg = new_request(
+            socket,
+            &mut buffer,
+            sys::RTM_SETLINK,
+            libc::NLM_F_ACK as u16,
+            &self.header(),
+        )?;
+        self.put_attrs(&mut msg)?;
+        ack(socket, &msg)
+    }
+
+    /// Returns the `ifinfomsg` header, which changes only the flags in `ifi_change`.
+    fn header(&self) -> ifinfomsg {
+        let mut header = ifinfomsg {
+            ifi_index: self.index as i32,
+            ..ifinfomsg::default()
+        };
+        if let Some(up) = self.up {
+            header.ifi_flags = if up { libc::IFF_UP as u32 } else { 0 };
+            header.ifi_change = libc::IFF_UP as u32;
+        }
+        header
+    }
+
+    fn put_attrs(&self, msg: &mut NlMsgBuilder<'_>) -> io::Result<()> {
+        if let Some(mtu) = self.mtu {
+            msg.put_u32(sys::IFLA_MTU, mtu)?;
+        }
+        if let Some(address) = &self.address {
+            msg.put(sys::IFLA_ADDRESS, address)?;
+        }
+        if let Some(name) = &self.name {
+            msg.put_str(sys::IFLA_IFNAME, name)?;
+        }
+        if let Some(txqlen) = self.txqlen {
+            msg.put_u32(sys::IFLA_TXQLEN, txqlen)?;
+        }
+        if let Some(master) = self.master {
+            msg.put_u32(sys::IFLA_MASTER, master)?;
+        }
+        match self.netns {
+            Some(Netns::Fd(fd)) => msg.put_u32(sys::IFLA_NET_NS_FD, fd as u32)?,
+            Some(Netns::Pid(pid)) => msg.put_u32(sys::IFLA_NET_NS_PID, pid)?,
+            None => (),
+        }
+        if !self.ipv4_conf.is_empty() {
+            msg.put_nested(sys::IFLA_AF_SPEC, |msg| {
+                msg.put_nested(libc::AF_INET as u16, |msg| {
+                    msg.put_nested(sys::IFLA_INET_CONF, |msg| {
+                        for (id, value) in &self.ipv4_conf {
+                            msg.put_u32(*id, *value as u32)?;
+                        }
+                        Ok(())
+                    })
+                })
+            })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NlMsg, rtnl::Link};
+    use sys::Pod;
+
+    /// Encode the changes and decode them again, returning the `ifi_change` mask and the
+    /// link as the kernel would see it.
+    fn round_trip(set_link: &SetLink) -> (u32, Link) {
+        let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        msg.put_extra_header(set_link.header().as_bytes()).unwrap();
+        set_link.put_attrs(&mut msg).unwrap();
+        let msg = NlMsg::new(msg.as_bytes()).unwrap();
+        let change = ifinfomsg::from_bytes(msg.payload()).unwrap().ifi_change;
+        (change, Link::from_msg(&msg).unwrap())
+    }
+
+    #[test]
+    fn test_set_link_attrs() {
+        let set_link = SetLink::new(7)
+            .mtu(1400)
+            .name("uplink")
+            .address([2, 0, 0, 0, 0, 1])
+            .master(3);
+        let (change, link) = round_trip(&set_link);
+        // Without up or down, no flags are changed.
+        assert_eq!((change, link.flags), (0, 0));
+        assert_eq!(link.index, 7);
+        assert_eq!(link.mtu, Some(1400));
+        assert_eq!(link.name, "uplink");
+        assert_eq!(link.address.as_deref(), Some(&[2, 0, 0, 0, 0, 1][..]));
+        assert_eq!(link.master, Some(3));
+        assert!(link.other_attrs.is_empty());
+
+        let (change, link) = round_trip(&SetLink::new(7).up());
+        assert_eq!(
+            (change, link.flags),
+            (libc::IFF_UP as u32, libc::IFF_UP as u32)
+        );
+        assert_eq!(link.mtu, None);
+
+        let (change, link) = round_trip(&SetLink::new(7).down().nomaster());
+        assert_eq!((change, link.flags), (libc::IFF_UP as u32, 0));
+        assert_eq!(link.master, Some(0));
+    }
+}
@@ -0,0 +1,240 @@
+use mnl_sys::libc;
+use std::{collections::BTreeMap, io};
+
+use super::{
+    REQUEST_BUFFER_SIZE, RawAttrs, dump, new_request,
+    sys::{self, Pod, if_stats_msg},
+};
+use crate::{NlMsg, Socket, buffer::AlignedBuffer};
+
+/// Statistics filter: the standard counters ([`LinkStats::stats64`]).
+pub const IFLA_STATS_FILTER_LINK_64: u32 = 1 << (sys::IFLA_STATS_LINK_64 - 1);
+/// Statistics filter: the statistics specific to the kind of link ([`LinkStats::xstats`]).
+pub const IFLA_STATS_FILTER_LINK_XSTATS: u32 = 1 << (sys::IFLA_STATS_LINK_XSTATS - 1);
+/// Statistics filter: the statistics specific to the kind of the master of the link
+/// ([`LinkStats::xstats_slave`]).
+pub const IFLA_STATS_FILTER_LINK_XSTATS_SLAVE: u32 = 1 << (sys::IFLA_STATS_LINK_XSTATS_SLAVE - 1);
+/// Statistics filter: the counters of traffic handled in software on links that offload
+/// forwarding to hardware ([`LinkStats::offload_cpu_hit`]).
+pub const IFLA_STATS_FILTER_LINK_OFFLOAD_XSTATS: u32 =
+    1 << (sys::IFLA_STATS_LINK_OFFLOAD_XSTATS - 1);
+/// Statistics filter: the statistics of each address family ([`LinkStats::af_spec`]).
+pub const IFLA_STATS_FILTER_AF_SPEC: u32 = 1 << (sys::IFLA_STATS_AF_SPEC - 1);
+
+/// The standard counters of a link, `struct rtnl_link_stats64`. Counters the running kernel
+/// does not report are zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub struct LinkStats64 {
+    pub rx_packets: u64,
+    pub tx_packets: u64,
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    pub rx_errors: u64,
+    pub tx_errors: u64,
+    pub rx_dropped: u64,
+    pub tx_dropped: u64,
+    /// Received multicast packets.
+    pub multicast: u64,
+    pub collisions: u64,
+    pub rx_length_errors: u64,
+    pub rx_over_errors: u64,
+    pub rx_crc_errors: u64,
+    pub rx_frame_errors: u64,
+    pub rx_fifo_errors: u64,
+    pub rx_missed_errors: u64,
+    pub tx_aborted_errors: u64,
+    pub tx_carrier_errors: u64,
+    pub tx_fifo_errors: u64,
+    pub tx_heartbeat_errors: u64,
+    pub tx_window_errors: u64,
+    pub rx_compressed: u64,
+    pub tx_compressed: u64,
+    /// Packets dropped because no protocol handled them.
+    pub rx_nohandler: u64,
+    /// Packets dropped because they were addressed to another host.
+    pub rx_otherhost_dropped: u64,
+}
+
+impl LinkStats64 {
+    /// Parse a `struct rtnl_link_stats64`, which grows at the end in newer kernels.
+    pub(super) fn parse(payload: &[u8]) -> LinkStats64 {
+        let mut values = payload
+            .chunks_exact(8)
+            .map(|value| u64::from_ne_bytes(value.try_into().unwrap()));
+        let mut next = || values.next().unwrap_or(0);
+        LinkStats64 {
+            rx_packets: next(),
+            tx_packets: next(),
+            rx_bytes: next(),
+            tx_bytes: next(),
+            rx_errors: next(),
+            tx_errors: next(),
+            rx_dropped: next(),
+            tx_dropped: next(),
+            multicast: next(),
+            collisions: next(),
+            rx_length_errors: next(),
+            rx_over_errors: next(),
+            rx_crc_errors: next(),
+            rx_frame_errors: next(),
+            rx_fifo_errors: next(),
+            rx_missed_errors: next(),
+            tx_aborted_errors: next(),
+            tx_carrier_errors: next(),
+            tx_fifo_errors: next(),
+            tx_heartbeat_errors: next(),
+            tx_window_errors: next(),
+            rx_compressed: next(),
+            tx_compressed: next(),
+            rx_nohandler: next(),
+            rx_otherhost_dropped: next(),
+        }
+    }
+}
+
+/// The statistics of a link, as described by a `RTM_NEWSTATS` message. Only the parts selected
+/// by the filter mask of the request are present.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkStats {
+    /// Interface index.
+    pub index: u32,
+    /// Standard counters (`IFLA_STATS_LINK_64`).
+    pub stats64: Option<LinkStats64>,
+    /// Raw statistics specific to the kind of link, such as bridge multicast statistics
+    /// (`IFLA_STATS_LINK_XSTATS`).
+    pub xstats: Option<Vec<u8>>,
+    /// Raw statistics specific to the kind of the master of the link
+    /// (`IFLA_STATS_LINK_XSTATS_SLAVE`).
+    pub xstats_slave: Option<Vec<u8>>,
+    /// Counters of traffic handled in software on a link that offloads forwarding to hardware
+    /// (`IFLA_STATS_LINK_OFFLOAD_XSTATS`, `IFLA_OFFLOAD_XSTATS_CPU_HIT`).
+    pub offload_cpu_hit: Option<LinkStats64>,
+    /// Raw statistics of each address family (`IFLA_STATS_AF_SPEC`).
+    pub af_spec: Option<Vec<u8>>,
+    /// Attributes without a typed field above.
+    pub other_attrs: RawAttrs,
+}
+
+impl LinkStats {
+    /// Parse a `RTM_NEWSTATS` message.
+    pub fn from_msg(msg: &NlMsg<'_>) -> io::Result<LinkStats> {
+        let header = if_stats_msg::from_bytes(msg.payload())?;
+        let mut stats = LinkStats {
+            index: header.ifindex,
+            ..LinkStats::default()
+        };
+        for attr in msg.attrs(size_of::<if_stats_msg>()) {
+            let attr = attr?;
+            match attr.attr_type() {
+                sys::IFLA_STATS_LINK_64 => stats.stats64 = Some(LinkStats64::parse(attr.payload())),
+                sys::IFLA_STATS_LINK_XSTATS => stats.xstats = Some(attr.payload().to_vec()),
+                sys::IFLA_STATS_LINK_XSTATS_SLAVE => {
+                    stats.xstats_slave = Some(attr.payload().to_vec())
+                }
+                sys::IFLA_STATS_LINK_OFFLOAD_XSTATS => {
+                    for attr in attr.nested() {
+                        let attr = attr?;
+                        if attr.attr_type() == sys::IFLA_OFFLOAD_XSTATS_CPU_HIT {
+                            stats.offload_cpu_hit = Some(LinkStats64::parse(attr.payload()));
+                        }
+                    }
+                }
+                sys::IFLA_STATS_AF_SPEC => stats.af_spec = Some(attr.payload().to_vec()),
+                other => stats.other_attrs.push((other, attr.payload().to_vec())),
+            }
+        }
+        Ok(stats)
+    }
+}
+
+/// Dump the statistics of all links (`RTM_GETSTATS`), keyed by interface index. `filter` is a
+/// combination of the `IFLA_STATS_FILTER_*` constants selecting what to return, the kernel
+/// rejects an empty filter.
+///
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// use mnl::rtnl;
+///
+/// let socket = mnl::Socket::new(mnl::Bus::Route)?;
+/// for (index, stats) in rtnl::link_stats(&socket, rtnl::IFLA_STATS_FILTER_LINK_64)? {
+///     if let Some(stats) = stats.stats64 {
+///         println!("{index}: rx {} tx {}", stats.rx_bytes, stats.tx_bytes);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn link_stats(socket: &Socket, filter: u32) -> io::Result<BTreeMap<u32, LinkStats>> {
+    let header = if_stats_msg {
+        filter_mask: filter,
+        ..if_stats_msg::default()
+    };
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let msg = new_request(
+        socket,
+        &mut buffer,
+        sys::RTM_GETSTATS,
+        libc::NLM_F_DUMP as u16,
+        &header,
+    )?;
+    let stats = dump(socket, &msg, sys::RTM_NEWSTATS, LinkStats::from_msg)?;
+    Ok(stats
+        .into_iter()
+        .map(|stats| (stats.index, stats))
+        .collect())
+}
+
+/// Get the statistics of the link with the given interface index, see [`link_stats`]. Fails
+/// with `ENODEV` if there is no such link.
+pub fn link_stats_by_index(socket: &Socket, index: u32, filter: u32) -> io::Result<LinkStats> {
+    let header = if_stats_msg {
+        ifindex: index,
+        filter_mask: filter,
+        ..if_stats_msg::default()
+    };
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let msg = new_request(socket, &mut buffer, sys::RTM_GETSTATS, 0, &header)?;
+    dump(socket, &msg, sys::RTM_NEWSTATS, LinkStats::from_msg)?
+        .pop()
+        .ok_or_else(|| io::Error::from_raw_os_error(libc::ENODEV))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NlMsgBuilder;
+
+    #[test]
+    fn test_parse_stats() {
+        let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        msg.set_type(sys::RTM_NEWSTATS);
+        let header = if_stats_msg {
+            ifindex: 3,
+            filter_mask: IFLA_STATS_FILTER_LINK_64 | IFLA_STATS_FILTER_LINK_OFFLOAD_XSTATS,
+            ..if_stats_msg::default()
+        };
+        msg.put_extra_header(header.as_bytes()).unwrap();
+        // A kernel predating rx_otherhost_dropped.
+        let stats64: Vec<u8> = (1..=24u64).flat_map(|value| value.to_ne_bytes()).collect();
+        msg.put(sys::IFLA_STATS_LINK_64, &stats64).unwrap();
+        msg.put_nested(sys::IFLA_STATS_LINK_OFFLOAD_XSTATS, |msg| {
+            msg.put(sys::IFLA_OFFLOAD_XSTATS_CPU_HIT, &stats64[..16])
+        })
+        .unwrap();
+
+        let stats = LinkStats::from_msg(&NlMsg::new(msg.as_bytes()).unwrap()).unwrap();
+        assert_eq!(stats.index, 3);
+        let stats64 = stats.stats64.unwrap();
+        assert_eq!(stats64.rx_packets, 1);
+        assert_eq!(stats64.tx_bytes, 4);
+        assert_eq!(stats64.multicast, 9);
+        assert_eq!(stats64.rx_nohandler, 24);
+        assert_eq!(stats64.rx_otherhost_dropped, 0);
+        let cpu_hit = stats.offload_cpu_hit.unwrap();
+        assert_eq!((cpu_hit.rx_packets, cpu_hit.tx_packets), (1, 2));
+        assert_eq!(cpu_hit.rx_bytes, 0);
+        assert!(stats.xstats.is_none());
+    }
+}
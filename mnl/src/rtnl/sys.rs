@@ -0,0 +1,629 @@
+//! Definitions from the kernel rtnetlink UAPI headers (`linux/rtnetlink.h`, `linux/if_link.h`
+//! and friends) that are not yet exposed by `libc`.
+
+#![allow(dead_code)]
+
+use std::{io, slice};
+
+/// Plain C structs that can be copied to and from netlink messages as raw bytes.
+///
+/// # Safety
+///
+/// Implementors must be `repr(C)`, have no padding bytes and be valid for any bit pattern.
+pub(crate) unsafe trait Pod: Copy {
+    /// Returns the raw bytes of the struct.
+    fn as_bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts((self as *const Self).cast::<u8>(), size_of::<Self>()) }
+    }
+
+    /// Read the struct from the start of `bytes`.
+    fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        if bytes.len() < size_of::<Self>() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Expected at least {} bytes, got {}",
+                    size_of::<Self>(),
+                    bytes.len()
+                ),
+            ));
+        }
+        Ok(unsafe { bytes.as_ptr().cast::<Self>().read_unaligned() })
+    }
+}
+
+// Message types.
+pub const RTM_NEWLINK: u16 = 16;
+pub const RTM_DELLINK: u16 = 17;
+pub const RTM_GETLINK: u16 = 18;
+pub const RTM_SETLINK: u16 = 19;
+pub const RTM_NEWADDR: u16 = 20;
+pub const RTM_DELADDR: u16 = 21;
+pub const RTM_GETADDR: u16 = 22;
+pub const RTM_NEWROUTE: u16 = 24;
+pub const RTM_DELROUTE: u16 = 25;
+pub const RTM_GETROUTE: u16 = 26;
+pub const RTM_NEWNEIGH: u16 = 28;
+pub const RTM_DELNEIGH: u16 = 29;
+pub const RTM_GETNEIGH: u16 = 30;
+pub const RTM_NEWRULE: u16 = 32;
+pub const RTM_DELRULE: u16 = 33;
+pub const RTM_GETRULE: u16 = 34;
+pub const RTM_NEWQDISC: u16 = 36;
+pub const RTM_DELQDISC: u16 = 37;
+pub const RTM_GETQDISC: u16 = 38;
+pub const RTM_NEWTCLASS: u16 = 40;
+pub const RTM_DELTCLASS: u16 = 41;
+pub const RTM_GETTCLASS: u16 = 42;
+pub const RTM_NEWTFILTER: u16 = 44;
+pub const RTM_DELTFILTER: u16 = 45;
+pub const RTM_GETTFILTER: u16 = 46;
+pub const RTM_NEWNETCONF: u16 = 80;
+pub const RTM_DELNETCONF: u16 = 81;
+pub const RTM_GETNETCONF: u16 = 82;
+pub const RTM_NEWNSID: u16 = 88;
+pub const RTM_DELNSID: u16 = 89;
+pub const RTM_GETNSID: u16 = 90;
+pub const RTM_NEWSTATS: u16 = 92;
+pub const RTM_GETSTATS: u16 = 94;
+pub const RTM_NEWNEXTHOP: u16 = 104;
+pub const RTM_DELNEXTHOP: u16 = 105;
+pub const RTM_GETNEXTHOP: u16 = 106;
+pub const RTM_NEWVLAN: u16 = 112;
+pub const RTM_DELVLAN: u16 = 113;
+pub const RTM_GETVLAN: u16 = 114;
+
+// Link attributes.
+pub const IFLA_ADDRESS: u16 = 1;
+pub const IFLA_BROADCAST: u16 = 2;
+pub const IFLA_IFNAME: u16 = 3;
+pub const IFLA_MTU: u16 = 4;
+pub const IFLA_LINK: u16 = 5;
+pub const IFLA_QDISC: u16 = 6;
+pub const IFLA_MASTER: u16 = 10;
+pub const IFLA_PROTINFO: u16 = 12;
+pub const IFLA_TXQLEN: u16 = 13;
+pub const IFLA_OPERSTATE: u16 = 16;
+pub const IFLA_LINKINFO: u16 = 18;
+pub const IFLA_NET_NS_PID: u16 = 19;
+pub const IFLA_IFALIAS: u16 = 20;
+pub const IFLA_STATS64: u16 = 23;
+pub const IFLA_AF_SPEC: u16 = 26;
+pub const IFLA_GROUP: u16 = 27;
+pub const IFLA_NET_NS_FD: u16 = 28;
+pub const IFLA_EXT_MASK: u16 = 29;
+pub const IFLA_LINK_NETNSID: u16 = 37;
+pub const IFLA_PROP_LIST: u16 = 52;
+pub const IFLA_ALT_IFNAME: u16 = 53;
+pub const IFLA_PERM_ADDRESS: u16 = 54;
+
+// Nested in IFLA_INFO_DATA for the respective link kinds.
+pub const VETH_INFO_PEER: u16 = 1;
+pub const IFLA_VLAN_ID: u16 = 1;
+pub const IFLA_VLAN_PROTOCOL: u16 = 5;
+pub const IFLA_MACVLAN_MODE: u16 = 1;
+
+// Nested in IFLA_LINKINFO.
+pub const IFLA_INFO_KIND: u16 = 1;
+pub const IFLA_INFO_DATA: u16 = 2;
+pub const IFLA_INFO_SLAVE_KIND: u16 = 4;
+pub const IFLA_INFO_SLAVE_DATA: u16 = 5;
+
+// Nested in IFLA_AF_SPEC, in AF_INET and AF_INET6 respectively.
+pub const IFLA_INET_CONF: u16 = 1;
+pub const IFLA_INET6_FLAGS: u16 = 1;
+pub const IFLA_INET6_CONF: u16 = 2;
+
+pub const RTEXT_FILTER_BRVLAN: u32 = 1 << 1;
+
+// Nested in IFLA_PROTINFO for bridge ports.
+pub const IFLA_BRPORT_STATE: u16 = 1;
+pub const IFLA_BRPORT_PRIORITY: u16 = 2;
+pub const IFLA_BRPORT_COST: u16 = 3;
+pub const IFLA_BRPORT_MODE: u16 = 4;
+pub const IFLA_BRPORT_LEARNING: u16 = 8;
+pub const IFLA_BRPORT_UNICAST_FLOOD: u16 = 9;
+pub const IFLA_BRPORT_MCAST_FLOOD: u16 = 27;
+pub const IFLA_BRPORT_BCAST_FLOOD: u16 = 30;
+pub const IFLA_BRPORT_ISOLATED: u16 = 33;
+
+// Nested in IFLA_AF_SPEC for AF_BRIDGE.
+pub const IFLA_BRIDGE_FLAGS: u16 = 0;
+pub const IFLA_BRIDGE_VLAN_INFO: u16 = 2;
+
+pub const BRIDGE_FLAGS_MASTER: u16 = 1;
+
+// Bridge VLAN database attributes.
+pub const BRIDGE_VLANDB_ENTRY: u16 = 1;
+
+// Nested in BRIDGE_VLANDB_ENTRY.
+pub const BRIDGE_VLANDB_ENTRY_INFO: u16 = 1;
+pub const BRIDGE_VLANDB_ENTRY_RANGE: u16 = 2;
+pub const BRIDGE_VLANDB_ENTRY_STATE: u16 = 3;
+
+// Link statistics attributes.
+pub const IFLA_STATS_LINK_64: u16 = 1;
+pub const IFLA_STATS_LINK_XSTATS: u16 = 2;
+pub const IFLA_STATS_LINK_XSTATS_SLAVE: u16 = 3;
+pub const IFLA_STATS_LINK_OFFLOAD_XSTATS: u16 = 4;
+pub const IFLA_STATS_AF_SPEC: u16 = 5;
+
+// Nested in IFLA_STATS_LINK_OFFLOAD_XSTATS.
+pub const IFLA_OFFLOAD_XSTATS_CPU_HIT: u16 = 1;
+
+// Address attributes.
+pub const IFA_ADDRESS: u16 = 1;
+pub const IFA_LOCAL: u16 = 2;
+pub const IFA_LABEL: u16 = 3;
+pub const IFA_BROADCAST: u16 = 4;
+pub const IFA_ANYCAST: u16 = 5;
+pub const IFA_CACHEINFO: u16 = 6;
+pub const IFA_MULTICAST: u16 = 7;
+pub const IFA_FLAGS: u16 = 8;
+
+// Route attributes.
+pub const RTA_DST: u16 = 1;
+pub const RTA_SRC: u16 = 2;
+pub const RTA_IIF: u16 = 3;
+pub const RTA_OIF: u16 = 4;
+pub const RTA_GATEWAY: u16 = 5;
+pub const RTA_PRIORITY: u16 = 6;
+pub const RTA_PREFSRC: u16 = 7;
+pub const RTA_METRICS: u16 = 8;
+pub const RTA_MULTIPATH: u16 = 9;
+pub const RTA_FLOW: u16 = 11;
+pub const RTA_CACHEINFO: u16 = 12;
+pub const RTA_TABLE: u16 = 15;
+pub const RTA_MARK: u16 = 16;
+pub const RTA_VIA: u16 = 18;
+pub const RTA_PREF: u16 = 20;
+pub const RTA_EXPIRES: u16 = 23;
+pub const RTA_UID: u16 = 25;
+pub const RTA_IP_PROTO: u16 = 27;
+pub const RTA_SPORT: u16 = 28;
+pub const RTA_DPORT: u16 = 29;
+pub const RTA_NH_ID: u16 = 30;
+
+// Route message flags.
+pub const RTM_F_LOOKUP_TABLE: u32 = 0x1000;
+pub const RTM_F_FIB_MATCH: u32 = 0x2000;
+
+// Nested in RTA_METRICS.
+pub const RTAX_MTU: u16 = 2;
+pub const RTAX_ADVMSS: u16 = 8;
+
+pub const RT_TABLE_UNSPEC: u8 = 0;
+
+// Rule attributes.
+pub const FRA_DST: u16 = 1;
+pub const FRA_SRC: u16 = 2;
+pub const FRA_IIFNAME: u16 = 3;
+pub const FRA_GOTO: u16 = 4;
+pub const FRA_PRIORITY: u16 = 6;
+pub const FRA_FWMARK: u16 = 10;
+pub const FRA_FLOW: u16 = 11;
+pub const FRA_SUPPRESS_IFGROUP: u16 = 13;
+pub const FRA_SUPPRESS_PREFIXLEN: u16 = 14;
+pub const FRA_TABLE: u16 = 15;
+pub const FRA_FWMASK: u16 = 16;
+pub const FRA_OIFNAME: u16 = 17;
+pub const FRA_L3MDEV: u16 = 19;
+pub const FRA_UID_RANGE: u16 = 20;
+pub const FRA_PROTOCOL: u16 = 21;
+pub const FRA_IP_PROTO: u16 = 22;
+pub const FRA_SPORT_RANGE: u16 = 23;
+pub const FRA_DPORT_RANGE: u16 = 24;
+
+pub const FIB_RULE_INVERT: u32 = 0x2;
+
+// Neighbour attributes.
+pub const NDA_DST: u16 = 1;
+pub const NDA_LLADDR: u16 = 2;
+pub const NDA_CACHEINFO: u16 = 3;
+pub const NDA_PROBES: u16 = 4;
+pub const NDA_VLAN: u16 = 5;
+pub const NDA_PORT: u16 = 6;
+pub const NDA_VNI: u16 = 7;
+pub const NDA_IFINDEX: u16 = 8;
+pub const NDA_MASTER: u16 = 9;
+
+// Nexthop attributes.
+pub const NHA_ID: u16 = 1;
+pub const NHA_GROUP: u16 = 2;
+pub const NHA_GROUP_TYPE: u16 = 3;
+pub const NHA_BLACKHOLE: u16 = 4;
+pub const NHA_OIF: u16 = 5;
+pub const NHA_GATEWAY: u16 = 6;
+pub const NHA_GROUPS: u16 = 9;
+pub const NHA_FDB: u16 = 11;
+pub const NHA_RES_GROUP: u16 = 12;
+
+// Nested in NHA_RES_GROUP.
+pub const NHA_RES_GROUP_BUCKETS: u16 = 1;
+
+pub const NEXTHOP_GRP_TYPE_MPATH: u16 = 0;
+pub const NEXTHOP_GRP_TYPE_RES: u16 = 1;
+
+// Netconf attributes.
+pub const NETCONFA_IFINDEX: u16 = 1;
+pub const NETCONFA_FORWARDING: u16 = 2;
+pub const NETCONFA_RP_FILTER: u16 = 3;
+pub const NETCONFA_MC_FORWARDING: u16 = 4;
+pub const NETCONFA_PROXY_NEIGH: u16 = 5;
+pub const NETCONFA_IGNORE_ROUTES_WITH_LINKDOWN: u16 = 6;
+
+// Network namespace id attributes.
+pub const NETNSA_NSID: u16 = 1;
+pub const NETNSA_PID: u16 = 2;
+pub const NETNSA_FD: u16 = 3;
+pub const NETNSA_TARGET_NSID: u16 = 4;
+pub const NETNSA_CURRENT_NSID: u16 = 5;
+
+pub const NETNSA_NSID_NOT_ASSIGNED: i32 = -1;
+
+// Traffic control attributes.
+pub const TCA_KIND: u16 = 1;
+pub const TCA_OPTIONS: u16 = 2;
+pub const TCA_STATS: u16 = 3;
+pub const TCA_XSTATS: u16 = 4;
+pub const TCA_STATS2: u16 = 7;
+pub const TCA_CHAIN: u16 = 11;
+
+// Nested in TCA_STATS2.
+pub const TCA_STATS_BASIC: u16 = 1;
+pub const TCA_STATS_RATE_EST: u16 = 2;
+pub const TCA_STATS_QUEUE: u16 = 3;
+pub const TCA_STATS_RATE_EST64: u16 = 5;
+pub const TCA_STATS_PKT64: u16 = 8;
+
+// fq_codel options.
+pub const TCA_FQ_CODEL_TARGET: u16 = 1;
+pub const TCA_FQ_CODEL_LIMIT: u16 = 2;
+pub const TCA_FQ_CODEL_INTERVAL: u16 = 3;
+pub const TCA_FQ_CODEL_ECN: u16 = 4;
+pub const TCA_FQ_CODEL_FLOWS: u16 = 5;
+pub const TCA_FQ_CODEL_QUANTUM: u16 = 6;
+pub const TCA_FQ_CODEL_CE_THRESHOLD: u16 = 7;
+pub const TCA_FQ_CODEL_DROP_BATCH_SIZE: u16 = 8;
+pub const TCA_FQ_CODEL_MEMORY_LIMIT: u16 = 9;
+
+// htb options.
+pub const TCA_HTB_PARMS: u16 = 1;
+pub const TCA_HTB_INIT: u16 = 2;
+pub const TCA_HTB_DIRECT_QLEN: u16 = 5;
+pub const TCA_HTB_RATE64: u16 = 6;
+pub const TCA_HTB_CEIL64: u16 = 7;
+pub const TC_HTB_PROTOVER: u32 = 3;
+pub const TC_LINKLAYER_ETHERNET: u8 = 1;
+
+// u32 filter options.
+pub const TCA_U32_CLASSID: u16 = 1;
+pub const TCA_U32_SEL: u16 = 5;
+pub const TCA_U32_FLAGS: u16 = 11;
+pub const TC_U32_TERMINAL: u8 = 1;
+
+// matchall filter options.
+pub const TCA_MATCHALL_CLASSID: u16 = 1;
+pub const TCA_MATCHALL_FLAGS: u16 = 3;
+
+// bpf filter options.
+pub const TCA_BPF_CLASSID: u16 = 3;
+pub const TCA_BPF_FD: u16 = 6;
+pub const TCA_BPF_NAME: u16 = 7;
+pub const TCA_BPF_FLAGS: u16 = 8;
+pub const TCA_BPF_FLAGS_GEN: u16 = 9;
+pub const TCA_BPF_TAG: u16 = 10;
+pub const TCA_BPF_ID: u16 = 11;
+pub const TCA_BPF_FLAG_ACT_DIRECT: u32 = 1;
+
+// Multicast groups (`enum rtnetlink_groups`).
+pub const RTNLGRP_LINK: u32 = 1;
+pub const RTNLGRP_NEIGH: u32 = 3;
+pub const RTNLGRP_IPV4_IFADDR: u32 = 5;
+pub const RTNLGRP_IPV4_ROUTE: u32 = 7;
+pub const RTNLGRP_IPV4_RULE: u32 = 8;
+pub const RTNLGRP_IPV6_IFADDR: u32 = 9;
+pub const RTNLGRP_IPV6_ROUTE: u32 = 11;
+pub const RTNLGRP_IPV6_RULE: u32 = 19;
+pub const RTNLGRP_IPV4_NETCONF: u32 = 24;
+pub const RTNLGRP_IPV6_NETCONF: u32 = 25;
+
+/// `struct rtgenmsg`, the extra header of messages that only carry an address family, padded
+/// to the netlink alignment.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct rtgenmsg {
+    pub rtgen_family: u8,
+    pub rtgen_pad: [u8; 3],
+}
+
+unsafe impl Pod for rtgenmsg {}
+
+/// `struct netconfmsg`, the extra header of netconf messages, padded to the netlink alignment.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct netconfmsg {
+    pub ncm_family: u8,
+    pub ncm_pad: [u8; 3],
+}
+
+unsafe impl Pod for netconfmsg {}
+
+/// `struct if_stats_msg`, the extra header of link statistics messages.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct if_stats_msg {
+    pub family: u8,
+    pub pad1: u8,
+    pub pad2: u16,
+    pub ifindex: u32,
+    pub filter_mask: u32,
+}
+
+unsafe impl Pod for if_stats_msg {}
+
+/// `struct bridge_vlan_info`, the payload of `IFLA_BRIDGE_VLAN_INFO` and
+/// `BRIDGE_VLANDB_ENTRY_INFO`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct bridge_vlan_info {
+    pub flags: u16,
+    pub vid: u16,
+}
+
+unsafe impl Pod for bridge_vlan_info {}
+
+/// `struct br_vlan_msg`, the extra header of bridge VLAN database messages.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct br_vlan_msg {
+    pub family: u8,
+    pub reserved1: u8,
+    pub reserved2: u16,
+    pub ifindex: u32,
+}
+
+unsafe impl Pod for br_vlan_msg {}
+
+/// `struct ifinfomsg`, the extra header of link messages.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ifinfomsg {
+    pub ifi_family: u8,
+    pub __ifi_pad: u8,
+    pub ifi_type: u16,
+    pub ifi_index: i32,
+    pub ifi_flags: u32,
+    pub ifi_change: u32,
+}
+
+unsafe impl Pod for ifinfomsg {}
+
+/// `struct ifaddrmsg`, the extra header of address messages.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ifaddrmsg {
+    pub ifa_family: u8,
+    pub ifa_prefixlen: u8,
+    pub ifa_flags: u8,
+    pub ifa_scope: u8,
+    pub ifa_index: u32,
+}
+
+unsafe impl Pod for ifaddrmsg {}
+
+/// `struct ifa_cacheinfo`, the payload of `IFA_CACHEINFO`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ifa_cacheinfo {
+    pub ifa_prefered: u32,
+    pub ifa_valid: u32,
+    pub cstamp: u32,
+    pub tstamp: u32,
+}
+
+unsafe impl Pod for ifa_cacheinfo {}
+
+/// `struct rtmsg`, the extra header of route messages.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct rtmsg {
+    pub rtm_family: u8,
+    pub rtm_dst_len: u8,
+    pub rtm_src_len: u8,
+    pub rtm_tos: u8,
+    pub rtm_table: u8,
+    pub rtm_protocol: u8,
+    pub rtm_scope: u8,
+    pub rtm_type: u8,
+    pub rtm_flags: u32,
+}
+
+unsafe impl Pod for rtmsg {}
+
+/// `struct rtnexthop`, the header of each next hop in `RTA_MULTIPATH`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct rtnexthop {
+    pub rtnh_len: u16,
+    pub rtnh_flags: u8,
+    pub rtnh_hops: u8,
+    pub rtnh_ifindex: i32,
+}
+
+unsafe impl Pod for rtnexthop {}
+
+/// `struct fib_rule_hdr`, the extra header of rule messages.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct fib_rule_hdr {
+    pub family: u8,
+    pub dst_len: u8,
+    pub src_len: u8,
+    pub tos: u8,
+    pub table: u8,
+    pub res1: u8,
+    pub res2: u8,
+    pub action: u8,
+    pub flags: u32,
+}
+
+unsafe impl Pod for fib_rule_hdr {}
+
+/// `struct fib_rule_uid_range`, the payload of `FRA_UID_RANGE`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct fib_rule_uid_range {
+    pub start: u32,
+    pub end: u32,
+}
+
+unsafe impl Pod for fib_rule_uid_range {}
+
+/// `struct fib_rule_port_range`, the payload of `FRA_SPORT_RANGE` and `FRA_DPORT_RANGE`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct fib_rule_port_range {
+    pub start: u16,
+    pub end: u16,
+}
+
+unsafe impl Pod for fib_rule_port_range {}
+
+/// `struct ndmsg`, the extra header of neighbour messages.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ndmsg {
+    pub ndm_family: u8,
+    pub ndm_pad1: u8,
+    pub ndm_pad2: u16,
+    pub ndm_ifindex: i32,
+    pub ndm_state: u16,
+    pub ndm_flags: u8,
+    pub ndm_type: u8,
+}
+
+unsafe impl Pod for ndmsg {}
+
+/// `struct nda_cacheinfo`, the payload of `NDA_CACHEINFO`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct nda_cacheinfo {
+    pub ndm_confirmed: u32,
+    pub ndm_used: u32,
+    pub ndm_updated: u32,
+    pub ndm_refcnt: u32,
+}
+
+unsafe impl Pod for nda_cacheinfo {}
+
+/// `struct tcmsg`, the extra header of traffic control messages.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct tcmsg {
+    pub tcm_family: u8,
+    pub tcm_pad1: u8,
+    pub tcm_pad2: u16,
+    pub tcm_ifindex: i32,
+    pub tcm_handle: u32,
+    pub tcm_parent: u32,
+    pub tcm_info: u32,
+}
+
+unsafe impl Pod for tcmsg {}
+
+/// `struct tc_ratespec`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct tc_ratespec {
+    pub cell_log: u8,
+    pub linklayer: u8,
+    pub overhead: u16,
+    pub cell_align: i16,
+    pub mpu: u16,
+    pub rate: u32,
+}
+
+unsafe impl Pod for tc_ratespec {}
+
+/// `struct tc_htb_opt`, the payload of `TCA_HTB_PARMS`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct tc_htb_opt {
+    pub rate: tc_ratespec,
+    pub ceil: tc_ratespec,
+    pub buffer: u32,
+    pub cbuffer: u32,
+    pub quantum: u32,
+    pub level: u32,
+    pub prio: u32,
+}
+
+unsafe impl Pod for tc_htb_opt {}
+
+/// `struct tc_htb_glob`, the payload of `TCA_HTB_INIT`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct tc_htb_glob {
+    pub version: u32,
+    pub rate2quantum: u32,
+    pub defcls: u32,
+    pub debug: u32,
+    pub direct_pkts: u32,
+}
+
+unsafe impl Pod for tc_htb_glob {}
+
+/// `struct tc_u32_sel` without the trailing keys.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct tc_u32_sel {
+    pub flags: u8,
+    pub offshift: u8,
+    pub nkeys: u8,
+    pub __pad: u8,
+    pub offmask: u16,
+    pub off: u16,
+    pub offoff: i16,
+    pub hoff: i16,
+    pub hmask: u32,
+}
+
+unsafe impl Pod for tc_u32_sel {}
+
+/// `struct tc_u32_key`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct tc_u32_key {
+    pub mask: u32,
+    pub val: u32,
+    pub off: i32,
+    pub offmask: i32,
+}
+
+unsafe impl Pod for tc_u32_key {}
+
+/// `struct nhmsg`, the extra header of nexthop messages.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct nhmsg {
+    pub nh_family: u8,
+    pub nh_scope: u8,
+    pub nh_protocol: u8,
+    pub resvd: u8,
+    pub nh_flags: u32,
+}
+
+unsafe impl Pod for nhmsg {}
+
+/// `struct nexthop_grp`, an entry of `NHA_GROUP`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct nexthop_grp {
+    pub id: u32,
+    pub weight: u8,
+    pub weight_high: u8,
+    pub resvd2: u16,
+}
+
+unsafe impl Pod for nexthop_grp {}
@@ -0,0 +1,317 @@
+use mnl_sys::libc;
+use std::io;
+
+use super::{TcMsg, TcStats, tc_request};
+use crate::{
+    NlMsg, NlMsgBuilder, Socket,
+    buffer::AlignedBuffer,
+    rtnl::{
+        REQUEST_BUFFER_SIZE, RawAttrs, ack, dump, new_request,
+        sys::{self, Pod, tc_htb_opt, tc_ratespec, tcmsg},
+    },
+};
+
+/// Length of a psched tick in nanoseconds, the unit of the HTB buffer sizes.
+const PSCHED_TICK_NS: u64 = 64;
+
+/// A traffic class of a classful qdisc, as described by a `RTM_NEWTCLASS` message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TcClass {
+    /// Index of the device the class belongs to.
+    pub index: u32,
+    /// Handle of the class, such as `tc_handle(1, 10)` for `1:10`.
+    pub handle: u32,
+    /// Handle of the parent class or qdisc.
+    pub parent: u32,
+    /// Kind and kind specific options.
+    pub options: ClassOptions,
+    /// Statistics (`TCA_STATS2`).
+    pub stats: Option<TcStats>,
+    /// Attributes without a typed field above.
+    pub other_attrs: RawAttrs,
+}
+
+/// The kind of a class with its kind specific options.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClassOptions {
+    /// A class of the `htb` qdisc.
+    Htb(HtbClass),
+    /// A kind not known to this crate, with its raw options.
+    Other {
+        /// The kind, the same as the kind of the qdisc.
+        kind: String,
+        /// Raw payload of `TCA_OPTIONS`.
+        options: Vec<u8>,
+    },
+}
+
+/// Options of a class of the `htb` qdisc.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HtbClass {
+    /// Guaranteed rate in bytes per second.
+    pub rate: u64,
+    /// Maximum rate in bytes per second when borrowing from the parent class.
+    pub ceil: u64,
+    /// Number of bytes that can be sent at once above `rate`.
+    pub burst: u32,
+    /// Number of bytes that can be sent at once above `ceil`.
+    pub cburst: u32,
+    /// Priority when borrowing. Classes with lower values are offered excess bandwidth first.
+    pub prio: u32,
+    /// Number of bytes sent per round when borrowing. Derived from `rate` by the kernel if 0.
+    pub quantum: u32,
+}
+
+impl HtbClass {
+    /// A class with the guaranteed and maximum rate `rate`, in bytes per second. The bursts
+    /// default to an MTU sized packet plus a millisecond worth of traffic.
+    pub fn new(rate: u64) -> Self {
+        let burst = u32::try_from(rate / 1000 + 1600).unwrap_or(u32::MAX);
+        HtbClass {
+            rate,
+            ceil: rate,
+            burst,
+            cburst: burst,
+            prio: 0,
+            quantum: 0,
+        }
+    }
+}
+
+/// Convert a burst size in bytes at `rate` bytes per second to psched ticks.
+fn burst_to_ticks(burst: u32, rate: u64) -> u32 {
+    let ns = u128::from(burst) * 1_000_000_000 / u128::from(rate.max(1));
+    u32::try_from(ns / u128::from(PSCHED_TICK_NS)).unwrap_or(u32::MAX)
+}
+
+/// Convert a buffer size in psched ticks at `rate` bytes per second to bytes.
+fn ticks_to_burst(ticks: u32, rate: u64) -> u32 {
+    let bytes = u128::from(ticks) * u128::from(PSCHED_TICK_NS) * u128::from(rate) / 1_000_000_000;
+    u32::try_from(bytes).unwrap_or(u32::MAX)
+}
+
+fn ratespec(rate: u64) -> tc_ratespec {
+    tc_ratespec {
+        linklayer: sys::TC_LINKLAYER_ETHERNET,
+        rate: u32::try_from(rate).unwrap_or(u32::MAX),
+        ..tc_ratespec::default()
+    }
+}
+
+impl ClassOptions {
+    /// Returns the kind of the class, such as `"htb"`.
+    pub fn kind(&self) -> &str {
+        match self {
+            ClassOptions::Htb(_) => "htb",
+            ClassOptions::Other { kind, .. } => kind,
+        }
+    }
+
+    fn parse(tc: &TcMsg<'_>) -> io::Result<Self> {
+        Ok(match tc.kind {
+            "htb" => {
+                let mut opt = None;
+                let mut rate64 = None;
+                let mut ceil64 = None;
+                for attr in tc.nested_options() {
+                    let attr = attr?;
+                    match attr.attr_type() {
+                        sys::TCA_HTB_PARMS => opt = Some(tc_htb_opt::from_bytes(attr.payload())?),
+                        sys::TCA_HTB_RATE64 => rate64 = Some(attr.get_u64()?),
+                        sys::TCA_HTB_CEIL64 => ceil64 = Some(attr.get_u64()?),
+                        _ => (),
+                    }
+                }
+                let opt = opt.ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "htb class without TCA_HTB_PARMS",
+                    )
+                })?;
+                let rate = rate64.unwrap_or(opt.rate.rate.into());
+                let ceil = ceil64.unwrap_or(opt.ceil.rate.into());
+                ClassOptions::Htb(HtbClass {
+                    rate,
+                    ceil,
+                    burst: ticks_to_burst(opt.buffer, rate),
+                    cburst: ticks_to_burst(opt.cbuffer, ceil),
+                    prio: opt.prio,
+                    quantum: opt.quantum,
+                })
+            }
+            kind => ClassOptions::Other {
+                kind: kind.to_owned(),
+                options: tc.raw_options(),
+            },
+        })
+    }
+
+    fn put(&self, msg: &mut NlMsgBuilder<'_>) -> io::Result<()> {
+        match self {
+            ClassOptions::Htb(class) => msg.put_nested(sys::TCA_OPTIONS, |msg| {
+                let opt = tc_htb_opt {
+                    rate: ratespec(class.rate),
+                    ceil: ratespec(class.ceil),
+                    buffer: burst_to_ticks(class.burst, class.rate),
+                    cbuffer: burst_to_ticks(class.cburst, class.ceil),
+                    quantum: class.quantum,
+                    level: 0,
+                    prio: class.prio,
+                };
+                if class.rate > u64::from(u32::MAX) {
+                    msg.put_u64(sys::TCA_HTB_RATE64, class.rate)?;
+                }
+                if class.ceil > u64::from(u32::MAX) {
+                    msg.put_u64(sys::TCA_HTB_CEIL64, class.ceil)?;
+                }
+                msg.put(sys::TCA_HTB_PARMS, opt.as_bytes())
+            }),
+            ClassOptions::Other { options, .. } if options.is_empty() => Ok(()),
+            ClassOptions::Other { options, .. } => msg.put(sys::TCA_OPTIONS, options),
+        }
+    }
+}
+
+impl TcClass {
+    /// Parse a `RTM_NEWTCLASS` or `RTM_DELTCLASS` message.
+    pub fn from_msg(msg: &NlMsg<'_>) -> io::Result<TcClass> {
+        let tc = TcMsg::parse(msg)?;
+        Ok(TcClass {
+            index: tc.header.tcm_ifindex as u32,
+            handle: tc.header.tcm_handle,
+            parent: tc.header.tcm_parent,
+            options: ClassOptions::parse(&tc)?,
+            stats: tc.stats,
+            other_attrs: tc.other_attrs,
+        })
+    }
+
+    fn header(&self) -> tcmsg {
+        tcmsg {
+            tcm_ifindex: self.index as i32,
+            tcm_handle: self.handle,
+            tcm_parent: self.parent,
+            ..tcmsg::default()
+        }
+    }
+}
+
+/// Dump the classes of the device with the given index.
+pub fn classes(socket: &Socket, index: u32) -> io::Result<Vec<TcClass>> {
+    let header = tcmsg {
+        tcm_ifindex: index as i32,
+        ..tcmsg::default()
+    };
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let msg = new_request(
+        socket,
+        &mut buffer,
+        sys::RTM_GETTCLASS,
+        libc::NLM_F_DUMP as u16,
+        &header,
+    )?;
+    dump(socket, &msg, sys::RTM_NEWTCLASS, TcClass::from_msg)
+}
+
+/// Builder for adding a class to a classful qdisc with `RTM_NEWTCLASS`.
+///
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// use mnl::rtnl::{self, ClassOptions, Htb, HtbClass, NewClass, NewQdisc, QdiscOptions, tc_handle};
+///
+/// let socket = mnl::Socket::new(mnl::Bus::Route)?;
+/// let link = rtnl::link_by_name(&socket, "eth0")?;
+/// let htb = Htb {
+///     default_class: 10,
+///     ..Htb::default()
+/// };
+/// NewQdisc::new(link.index, QdiscOptions::Htb(htb))
+///     .handle(tc_handle(1, 0))
+///     .add(&socket)?;
+/// // Limit unclassified traffic to 10 Mbit/s.
+/// let class = HtbClass::new(10_000_000 / 8);
+/// NewClass::new(link.index, tc_handle(1, 10), ClassOptions::Htb(class)).add(&socket)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewClass {
+    class: TcClass,
+}
+
+impl NewClass {
+    /// Describe a class with the given handle on the device with the given index. The parent
+    /// defaults to the qdisc with the same major number as `handle`.
+    pub fn new(index: u32, handle: u32, options: ClassOptions) -> Self {
+        NewClass {
+            class: TcClass {
+                index,
+                handle,
+                parent: handle & 0xffff_0000,
+                options,
+                stats: None,
+                other_attrs: RawAttrs::new(),
+            },
+        }
+    }
+
+    /// Make the class a child of the class with the given handle.
+    pub fn parent(mut self, parent: u32) -> Self {
+        self.class.parent = parent;
+        self
+    }
+
+    /// Add the class on `socket`, which must be a [`Bus::Route`] socket. Fails with `EEXIST`
+    /// if a class with the same handle already exists.
+    ///
+    /// [`Bus::Route`]: crate::Bus::Route
+    pub fn add(&self, socket: &Socket) -> io::Result<()> {
+        self.send(socket, libc::NLM_F_EXCL)
+    }
+
+    /// Add the class on `socket`, or change the options of the class with the same handle.
+    pub fn replace(&self, socket: &Socket) -> io::Result<()> {
+        self.send(socket, libc::NLM_F_REPLACE)
+    }
+
+    fn send(&self, socket: &Socket, flags: libc::c_int) -> io::Result<()> {
+        let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+        let mut msg = tc_request(
+            socket,
+            &mut buffer,
+            sys::RTM_NEWTCLASS,
+            flags | libc::NLM_F_CREATE | libc::NLM_F_ACK,
+            &self.class.header(),
+            self.class.options.kind(),
+        )?;
+        self.class.options.put(&mut msg)?;
+        ack(socket, &msg)
+    }
+}
+
+/// Delete a class with `RTM_DELTCLASS`. Fails with `EBUSY` while filters or child classes
+/// refer to it.
+pub fn delete_class(socket: &Socket, class: &TcClass) -> io::Result<()> {
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let msg = tc_request(
+        socket,
+        &mut buffer,
+        sys::RTM_DELTCLASS,
+        libc::NLM_F_ACK,
+        &class.header(),
+        class.options.kind(),
+    )?;
+    ack(socket, &msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_htb_burst_ticks() {
+        // 1 Mbyte/s for 10000 bytes is 10 ms, or 156250 ticks of 64 ns.
+        assert_eq!(burst_to_ticks(10_000, 1_000_000), 156_250);
+        assert_eq!(ticks_to_burst(156_250, 1_000_000), 10_000);
+    }
+}
@@ -0,0 +1,485 @@
+use mnl_sys::libc;
+use std::{io, net::Ipv4Addr, os::unix::io::RawFd};
+
+use super::{TcMsg, TcStats, tc_request};
+use crate::{
+    NlMsg, NlMsgBuilder, Socket,
+    buffer::AlignedBuffer,
+    rtnl::{
+        REQUEST_BUFFER_SIZE, RawAttrs, ack, dump, new_request,
+        sys::{self, Pod, tc_u32_key, tc_u32_sel, tcmsg},
+    },
+};
+
+/// Filter flag: don't offload the filter to hardware.
+pub const TCA_CLS_FLAGS_SKIP_HW: u32 = 1;
+/// Filter flag: only run the filter in hardware.
+pub const TCA_CLS_FLAGS_SKIP_SW: u32 = 2;
+
+/// Protocol matching packets of all protocols, the default for new filters.
+pub const ETH_P_ALL: u16 = 0x0003;
+/// Protocol matching IPv4 packets.
+pub const ETH_P_IP: u16 = 0x0800;
+/// Protocol matching IPv6 packets.
+pub const ETH_P_IPV6: u16 = 0x86dd;
+
+/// A filter classifying packets of a qdisc, as described by a `RTM_NEWTFILTER` message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    /// Index of the device the filter is attached to.
+    pub index: u32,
+    /// Handle of the filter, which is kind specific.
+    pub handle: u32,
+    /// Handle of the qdisc or class the filter is attached to, or one of
+    /// [`TC_H_INGRESS_FILTERS`] and [`TC_H_EGRESS_FILTERS`].
+    ///
+    /// [`TC_H_INGRESS_FILTERS`]: super::TC_H_INGRESS_FILTERS
+    /// [`TC_H_EGRESS_FILTERS`]: super::TC_H_EGRESS_FILTERS
+    pub parent: u32,
+    /// Priority of the filter. Filters with lower values are run first.
+    pub priority: u16,
+    /// Ethernet protocol of the packets the filter applies to, such as [`ETH_P_IP`].
+    pub protocol: u16,
+    /// The filter chain the filter belongs to (`TCA_CHAIN`).
+    pub chain: Option<u32>,
+    /// Kind and kind specific options.
+    pub options: FilterOptions,
+    /// Statistics (`TCA_STATS2`).
+    pub stats: Option<TcStats>,
+    /// Attributes without a typed field above.
+    pub other_attrs: RawAttrs,
+}
+
+/// The kind of a filter with its kind specific options.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterOptions {
+    /// The `u32` filter, matching 32 bit words of the packet.
+    U32(U32Filter),
+    /// The `matchall` filter, matching every packet.
+    Matchall(Matchall),
+    /// The `bpf` filter, running an eBPF program.
+    Bpf(BpfFilter),
+    /// A kind not known to this crate, with its raw options.
+    Other {
+        /// The kind, such as `"flower"`.
+        kind: String,
+        /// Raw payload of `TCA_OPTIONS`.
+        options: Vec<u8>,
+    },
+}
+
+/// Options of the `u32` filter.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct U32Filter {
+    /// Class matching packets are sent to (`TCA_U32_CLASSID`).
+    pub classid: Option<u32>,
+    /// Keys that must all match. Empty for hash table entries in dumps.
+    pub keys: Vec<U32Key>,
+    /// Filter flags, a combination of the `TCA_CLS_FLAGS_*` constants in this module.
+    pub flags: u32,
+}
+
+/// A key of a `u32` filter, matching the 32 bit word at `offset` in the packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct U32Key {
+    /// The value to match, in the byte order of the packet read as big endian.
+    pub value: u32,
+    /// Bits of the word to compare, in the same byte order as `value`.
+    pub mask: u32,
+    /// Offset of the word from the start of the network header, in bytes. Must be a multiple of
+    /// 4.
+    pub offset: i32,
+}
+
+impl U32Key {
+    /// Match IPv4 packets from `addr/prefix_len`.
+    pub fn ipv4_src(addr: Ipv4Addr, prefix_len: u8) -> Self {
+        Self::ipv4_prefix(addr, prefix_len, 12)
+    }
+
+    /// Match IPv4 packets to `addr/prefix_len`.
+    pub fn ipv4_dst(addr: Ipv4Addr, prefix_len: u8) -> Self {
+        Self::ipv4_prefix(addr, prefix_len, 16)
+    }
+
+    fn ipv4_prefix(addr: Ipv4Addr, prefix_len: u8, offset: i32) -> Self {
+        let mask = u32::MAX
+            .checked_shl(32 - u32::from(prefix_len.min(32)))
+            .unwrap_or(0);
+        U32Key {
+            value: u32::from(addr) & mask,
+            mask,
+            offset,
+        }
+    }
+}
+
+/// Options of the `matchall` filter.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Matchall {
+    /// Class packets are sent to (`TCA_MATCHALL_CLASSID`).
+    pub classid: Option<u32>,
+    /// Filter flags, a combination of the `TCA_CLS_FLAGS_*` constants in this module.
+    pub flags: u32,
+}
+
+/// Options of the `bpf` filter.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BpfFilter {
+    /// File descriptor of a loaded `BPF_PROG_TYPE_SCHED_CLS` program to attach. Only used when
+    /// adding the filter.
+    pub fd: Option<RawFd>,
+    /// Name of the program, for display purposes (`TCA_BPF_NAME`).
+    pub name: Option<String>,
+    /// Use the return value of the program as the action instead of as the class
+    /// (`TCA_BPF_FLAG_ACT_DIRECT`).
+    pub direct_action: bool,
+    /// Class packets are sent to unless the program says otherwise (`TCA_BPF_CLASSID`).
+    pub classid: Option<u32>,
+    /// Filter flags, a combination of the `TCA_CLS_FLAGS_*` constants in this module.
+    pub flags: u32,
+    /// Id of the attached program (`TCA_BPF_ID`). Only set in dumps.
+    pub id: Option<u32>,
+    /// Tag of the attached program (`TCA_BPF_TAG`). Only set in dumps.
+    pub tag: Option<[u8; 8]>,
+}
+
+impl FilterOptions {
+    /// Returns the kind of the filter, such as `"u32"`.
+    pub fn kind(&self) -> &str {
+        match self {
+            FilterOptions::U32(_) => "u32",
+            FilterOptions::Matchall(_) => "matchall",
+            FilterOptions::Bpf(_) => "bpf",
+            FilterOptions::Other { kind, .. } => kind,
+        }
+    }
+
+    fn parse(tc: &TcMsg<'_>) -> io::Result<Self> {
+        Ok(match tc.kind {
+            "u32" => {
+                let mut options = U32Filter::default();
+                for attr in tc.nested_options() {
+                    let attr = attr?;
+                    match attr.attr_type() {
+                        sys::TCA_U32_CLASSID => options.classid = Some(attr.get_u32()?),
+                        sys::TCA_U32_FLAGS => options.flags = attr.get_u32()?,
+                        sys::TCA_U32_SEL => options.keys = parse_u32_keys(attr.payload())?,
+                        _ => (),
+                    }
+                }
+                FilterOptions::U32(options)
+            }
+            "matchall" => {
+                let mut options = Matchall::default();
+                for attr in tc.nested_options() {
+                    let attr = attr?;
+                    match attr.attr_type() {
+                        sys::TCA_MATCHALL_CLASSID => options.classid = Some(attr.get_u32()?),
+                        sys::TCA_MATCHALL_FLAGS => options.flags = attr.get_u32()?,
+                        _ => (),
+                    }
+                }
+                FilterOptions::Matchall(options)
+            }
+            "bpf" => {
+                let mut options = BpfFilter::default();
+                for attr in tc.nested_options() {
+                    let attr = attr?;
+                    match attr.attr_type() {
+                        sys::TCA_BPF_NAME => options.name = Some(attr.get_str()?.to_owned()),
+                        sys::TCA_BPF_FLAGS => {
+                            options.direct_action =
+                                attr.get_u32()? & sys::TCA_BPF_FLAG_ACT_DIRECT != 0
+                        }
+                        sys::TCA_BPF_CLASSID => options.classid = Some(attr.get_u32()?),
+                        sys::TCA_BPF_FLAGS_GEN => options.flags = attr.get_u32()?,
+                        sys::TCA_BPF_ID => options.id = Some(attr.get_u32()?),
+                        sys::TCA_BPF_TAG => options.tag = Some(attr.get_array()?),
+                        _ => (),
+                    }
+                }
+                FilterOptions::Bpf(options)
+            }
+            kind => FilterOptions::Other {
+                kind: kind.to_owned(),
+                options: tc.raw_options(),
+            },
+        })
+    }
+
+    fn put(&self, msg: &mut NlMsgBuilder<'_>) -> io::Result<()> {
+        match self {
+            FilterOptions::U32(options) => msg.put_nested(sys::TCA_OPTIONS, |msg| {
+                if let Some(classid) = options.classid {
+                    msg.put_u32(sys::TCA_U32_CLASSID, classid)?;
+                }
+                if options.flags != 0 {
+                    msg.put_u32(sys::TCA_U32_FLAGS, options.flags)?;
+                }
+                msg.put(
+                    sys::TCA_U32_SEL,
+                    &encode_u32_sel(&options.keys, options.classid.is_some())?,
+                )
+            }),
+            FilterOptions::Matchall(options) => msg.put_nested(sys::TCA_OPTIONS, |msg| {
+                if let Some(classid) = options.classid {
+                    msg.put_u32(sys::TCA_MATCHALL_CLASSID, classid)?;
+                }
+                if options.flags != 0 {
+                    msg.put_u32(sys::TCA_MATCHALL_FLAGS, options.flags)?;
+                }
+                Ok(())
+            }),
+            FilterOptions::Bpf(options) => msg.put_nested(sys::TCA_OPTIONS, |msg| {
+                if let Some(fd) = options.fd {
+                    msg.put_u32(sys::TCA_BPF_FD, fd as u32)?;
+                }
+                if let Some(name) = &options.name {
+                    msg.put_str(sys::TCA_BPF_NAME, name)?;
+                }
+                if options.direct_action {
+                    msg.put_u32(sys::TCA_BPF_FLAGS, sys::TCA_BPF_FLAG_ACT_DIRECT)?;
+                }
+                if let Some(classid) = options.classid {
+                    msg.put_u32(sys::TCA_BPF_CLASSID, classid)?;
+                }
+                if options.flags != 0 {
+                    msg.put_u32(sys::TCA_BPF_FLAGS_GEN, options.flags)?;
+                }
+                Ok(())
+            }),
+            FilterOptions::Other { options, .. } if options.is_empty() => Ok(()),
+            FilterOptions::Other { options, .. } => msg.put(sys::TCA_OPTIONS, options),
+        }
+    }
+}
+
+fn parse_u32_keys(payload: &[u8]) -> io::Result<Vec<U32Key>> {
+    let sel = tc_u32_sel::from_bytes(payload)?;
+    let mut keys = Vec::with_capacity(usize::from(sel.nkeys));
+    let mut rest = &payload[size_of::<tc_u32_sel>()..];
+    for _ in 0..sel.nkeys {
+        let key = tc_u32_key::from_bytes(rest)?;
+        keys.push(U32Key {
+            value: u32::from_be(key.val),
+            mask: u32::from_be(key.mask),
+            offset: key.off,
+        });
+        rest = &rest[size_of::<tc_u32_key>()..];
+    }
+    Ok(keys)
+}
+
+fn encode_u32_sel(keys: &[U32Key], terminal: bool) -> io::Result<Vec<u8>> {
+    let sel = tc_u32_sel {
+        flags: if terminal { sys::TC_U32_TERMINAL } else { 0 },
+        nkeys: u8::try_from(keys.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Too many u32 filter keys"))?,
+        ..tc_u32_sel::default()
+    };
+    let mut payload = sel.as_bytes().to_vec();
+    for key in keys {
+        let key = tc_u32_key {
+            mask: key.mask.to_be(),
+            val: key.value.to_be(),
+            off: key.offset,
+            offmask: 0,
+        };
+        payload.extend_from_slice(key.as_bytes());
+    }
+    Ok(payload)
+}
+
+impl Filter {
+    /// Parse a `RTM_NEWTFILTER` or `RTM_DELTFILTER` message.
+    pub fn from_msg(msg: &NlMsg<'_>) -> io::Result<Filter> {
+        let tc = TcMsg::parse(msg)?;
+        Ok(Filter {
+            index: tc.header.tcm_ifindex as u32,
+            handle: tc.header.tcm_handle,
+            parent: tc.header.tcm_parent,
+            priority: (tc.header.tcm_info >> 16) as u16,
+            protocol: u16::from_be(tc.header.tcm_info as u16),
+            chain: tc.chain,
+            options: FilterOptions::parse(&tc)?,
+            stats: tc.stats,
+            other_attrs: tc.other_attrs,
+        })
+    }
+
+    fn header(&self) -> tcmsg {
+        tcmsg {
+            tcm_ifindex: self.index as i32,
+            tcm_handle: self.handle,
+            tcm_parent: self.parent,
+            tcm_info: (u32::from(self.priority) << 16) | u32::from(self.protocol.to_be()),
+            ..tcmsg::default()
+        }
+    }
+
+    fn put_chain(&self, msg: &mut NlMsgBuilder<'_>) -> io::Result<()> {
+        match self.chain {
+            Some(chain) => msg.put_u32(sys::TCA_CHAIN, chain),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Dump the filters attached to the qdisc or class `parent` of the device with the given
+/// index.
+pub fn filters(socket: &Socket, index: u32, parent: u32) -> io::Result<Vec<Filter>> {
+    let header = tcmsg {
+        tcm_ifindex: index as i32,
+        tcm_parent: parent,
+        ..tcmsg::default()
+    };
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let msg = new_request(
+        socket,
+        &mut buffer,
+        sys::RTM_GETTFILTER,
+        libc::NLM_F_DUMP as u16,
+        &header,
+    )?;
+    dump(socket, &msg, sys::RTM_NEWTFILTER, Filter::from_msg)
+}
+
+/// Builder for attaching a filter to a qdisc or class with `RTM_NEWTFILTER`.
+///
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// use mnl::rtnl::{self, FilterOptions, NewFilter, U32Filter, U32Key, tc_handle};
+///
+/// let socket = mnl::Socket::new(mnl::Bus::Route)?;
+/// let link = rtnl::link_by_name(&socket, "eth0")?;
+/// // Send traffic to 10.0.0.0/8 to class 1:20.
+/// let u32 = U32Filter {
+///     classid: Some(tc_handle(1, 20)),
+///     keys: vec![U32Key::ipv4_dst("10.0.0.0".parse().unwrap(), 8)],
+///     ..U32Filter::default()
+/// };
+/// NewFilter::new(link.index, tc_handle(1, 0), FilterOptions::U32(u32))
+///     .protocol(rtnl::ETH_P_IP)
+///     .priority(10)
+///     .add(&socket)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewFilter {
+    filter: Filter,
+}
+
+impl NewFilter {
+    /// Describe a filter of the given kind attached to `parent` on the device with the given
+    /// index, applying to all protocols.
+    pub fn new(index: u32, parent: u32, options: FilterOptions) -> Self {
+        NewFilter {
+            filter: Filter {
+                index,
+                handle: 0,
+                parent,
+                priority: 0,
+                protocol: ETH_P_ALL,
+                chain: None,
+                options,
+                stats: None,
+                other_attrs: RawAttrs::new(),
+            },
+        }
+    }
+
+    /// Set the handle of the filter. Picked by the kernel if not set.
+    pub fn handle(mut self, handle: u32) -> Self {
+        self.filter.handle = handle;
+        self
+    }
+
+    /// Set the priority of the filter. Picked by the kernel if not set.
+    pub fn priority(mut self, priority: u16) -> Self {
+        self.filter.priority = priority;
+        self
+    }
+
+    /// Only apply the filter to packets of the given Ethernet protocol, such as [`ETH_P_IP`].
+    pub fn protocol(mut self, protocol: u16) -> Self {
+        self.filter.protocol = protocol;
+        self
+    }
+
+    /// Add the filter to the given filter chain rather than chain 0.
+    pub fn chain(mut self, chain: u32) -> Self {
+        self.filter.chain = Some(chain);
+        self
+    }
+
+    /// Attach the filter on `socket`, which must be a [`Bus::Route`] socket.
+    ///
+    /// [`Bus::Route`]: crate::Bus::Route
+    pub fn add(&self, socket: &Socket) -> io::Result<()> {
+        self.send(socket, libc::NLM_F_EXCL)
+    }
+
+    /// Attach the filter on `socket`, or replace the filter with the same priority, protocol
+    /// and handle.
+    pub fn replace(&self, socket: &Socket) -> io::Result<()> {
+        self.send(socket, libc::NLM_F_REPLACE)
+    }
+
+    fn send(&self, socket: &Socket, flags: libc::c_int) -> io::Result<()> {
+        let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+        let mut msg = tc_request(
+            socket,
+            &mut buffer,
+            sys::RTM_NEWTFILTER,
+            flags | libc::NLM_F_CREATE | libc::NLM_F_ACK,
+            &self.filter.header(),
+            self.filter.options.kind(),
+        )?;
+        self.filter.put_chain(&mut msg)?;
+        self.filter.options.put(&mut msg)?;
+        ack(socket, &msg)
+    }
+}
+
+/// Detach a filter with `RTM_DELTFILTER`. Deleting a filter with handle 0 deletes all filters
+/// with the same priority and protocol.
+pub fn delete_filter(socket: &Socket, filter: &Filter) -> io::Result<()> {
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let mut msg = tc_request(
+        socket,
+        &mut buffer,
+        sys::RTM_DELTFILTER,
+        libc::NLM_F_ACK,
+        &filter.header(),
+        filter.options.kind(),
+    )?;
+    filter.put_chain(&mut msg)?;
+    ack(socket, &msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_u32_round_trip() {
+        let keys = vec![
+            U32Key::ipv4_dst(Ipv4Addr::new(10, 1, 2, 3), 8),
+            U32Key {
+                value: 0x0050,
+                mask: 0xffff,
+                offset: 20,
+            },
+        ];
+        assert_eq!(keys[0].value, 0x0a00_0000);
+        assert_eq!(keys[0].mask, 0xff00_0000);
+
+        let payload = encode_u32_sel(&keys, true).unwrap();
+        assert_eq!(payload.len(), 16 + 2 * 16);
+        // The value is stored in packet byte order.
+        assert_eq!(payload[20..24], [10, 0, 0, 0]);
+        assert_eq!(parse_u32_keys(&payload).unwrap(), keys);
+    }
+}
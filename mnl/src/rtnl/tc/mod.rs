@@ -0,0 +1,221 @@
+use mnl_sys::libc;
+use std::io;
+
+use super::{
+    RawAttrs, new_request,
+    sys::{self, Pod, tcmsg},
+};
+use crate::{Attr, Attrs, NlMsg, NlMsgBuilder, Socket, buffer::AlignedBuffer};
+
+mod qdisc;
+pub use self::qdisc::*;
+
+mod class;
+pub use self::class::*;
+
+mod filter;
+pub use self::filter::*;
+
+/// Parent handle of the root qdisc of a device.
+pub const TC_H_ROOT: u32 = 0xffff_ffff;
+/// Parent handle of the ingress and clsact qdiscs.
+pub const TC_H_INGRESS: u32 = 0xffff_fff1;
+/// Parent handle of the clsact qdisc. Same as [`TC_H_INGRESS`].
+pub const TC_H_CLSACT: u32 = TC_H_INGRESS;
+/// Parent of filters attached to the ingress hook of an ingress or clsact qdisc.
+pub const TC_H_INGRESS_FILTERS: u32 = 0xffff_fff2;
+/// Parent of filters attached to the egress hook of a clsact qdisc.
+pub const TC_H_EGRESS_FILTERS: u32 = 0xffff_fff3;
+
+/// Build a traffic control handle from its major and minor numbers, written `major:minor` by
+/// `tc`.
+pub const fn tc_handle(major: u16, minor: u16) -> u32 {
+    ((major as u32) << 16) | minor as u32
+}
+
+/// Statistics of a qdisc, class or filter (`TCA_STATS2`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TcStats {
+    /// Number of bytes sent.
+    pub bytes: u64,
+    /// Number of packets sent.
+    pub packets: u64,
+    /// Number of packets in the queue.
+    pub qlen: u32,
+    /// Number of bytes in the queue.
+    pub backlog: u32,
+    /// Number of dropped packets.
+    pub drops: u32,
+    /// Number of requeued packets.
+    pub requeues: u32,
+    /// Number of times the rate limit was exceeded.
+    pub overlimits: u32,
+    /// Estimated rate in bytes per second, if a rate estimator is attached.
+    pub bps: u64,
+    /// Estimated rate in packets per second, if a rate estimator is attached.
+    pub pps: u64,
+}
+
+impl TcStats {
+    fn parse(attr: Attr<'_>) -> io::Result<TcStats> {
+        let mut stats = TcStats::default();
+        for attr in attr.nested() {
+            let attr = attr?;
+            let payload = attr.payload();
+            match attr.attr_type() {
+                // struct gnet_stats_basic, 12 bytes padded to 16.
+                sys::TCA_STATS_BASIC => {
+                    stats.bytes = u64::from_ne_bytes(array_at(payload, 0)?);
+                    stats.packets = u32::from_ne_bytes(array_at(payload, 8)?).into();
+                }
+                sys::TCA_STATS_PKT64 => stats.packets = attr.get_u64()?,
+                // struct gnet_stats_queue.
+                sys::TCA_STATS_QUEUE => {
+                    stats.qlen = u32::from_ne_bytes(array_at(payload, 0)?);
+                    stats.backlog = u32::from_ne_bytes(array_at(payload, 4)?);
+                    stats.drops = u32::from_ne_bytes(array_at(payload, 8)?);
+                    stats.requeues = u32::from_ne_bytes(array_at(payload, 12)?);
+                    stats.overlimits = u32::from_ne_bytes(array_at(payload, 16)?);
+                }
+                // struct gnet_stats_rate_est.
+                sys::TCA_STATS_RATE_EST => {
+                    stats.bps = u32::from_ne_bytes(array_at(payload, 0)?).into();
+                    stats.pps = u32::from_ne_bytes(array_at(payload, 4)?).into();
+                }
+                // struct gnet_stats_rate_est64.
+                sys::TCA_STATS_RATE_EST64 => {
+                    stats.bps = u64::from_ne_bytes(array_at(payload, 0)?);
+                    stats.pps = u64::from_ne_bytes(array_at(payload, 8)?);
+                }
+                _ => (),
+            }
+        }
+        Ok(stats)
+    }
+}
+
+fn array_at<const N: usize>(payload: &[u8], offset: usize) -> io::Result<[u8; N]> {
+    payload
+        .get(offset..offset + N)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Truncated statistics"))
+}
+
+/// The parts common to qdisc, class and filter messages.
+struct TcMsg<'a> {
+    header: tcmsg,
+    kind: &'a str,
+    options: Option<Attr<'a>>,
+    stats: Option<TcStats>,
+    chain: Option<u32>,
+    other_attrs: RawAttrs,
+}
+
+impl<'a> TcMsg<'a> {
+    fn parse(msg: &NlMsg<'a>) -> io::Result<Self> {
+        let mut tc = TcMsg {
+            header: tcmsg::from_bytes(msg.payload())?,
+            kind: "",
+            options: None,
+            stats: None,
+            chain: None,
+            other_attrs: RawAttrs::new(),
+        };
+        for attr in msg.attrs(size_of::<tcmsg>()) {
+            let attr = attr?;
+            match attr.attr_type() {
+                sys::TCA_KIND => tc.kind = attr.get_str()?,
+                sys::TCA_OPTIONS => tc.options = Some(attr),
+                sys::TCA_STATS2 => tc.stats = Some(TcStats::parse(attr)?),
+                sys::TCA_CHAIN => tc.chain = Some(attr.get_u32()?),
+                // Superseded by TCA_STATS2.
+                sys::TCA_STATS | sys::TCA_XSTATS => (),
+                other => tc.other_attrs.push((other, attr.payload().to_vec())),
+            }
+        }
+        Ok(tc)
+    }
+
+    /// Returns the raw payload of `TCA_OPTIONS`.
+    fn raw_options(&self) -> Vec<u8> {
+        self.options
+            .map_or_else(Vec::new, |attr| attr.payload().to_vec())
+    }
+
+    /// Returns the attributes nested in `TCA_OPTIONS`.
+    fn nested_options(&self) -> Attrs<'a> {
+        Attrs::new(self.options.map_or(&[], |attr| attr.payload()))
+    }
+}
+
+/// Start a traffic control request with `header` as extra header and `kind` as `TCA_KIND`.
+fn tc_request<'a>(
+    socket: &Socket,
+    buffer: &'a mut AlignedBuffer,
+    msg_type: u16,
+    flags: libc::c_int,
+    header: &tcmsg,
+    kind: &str,
+) -> io::Result<NlMsgBuilder<'a>> {
+    let mut msg = new_request(socket, buffer, msg_type, flags as u16, header)?;
+    msg.put_str(sys::TCA_KIND, kind)?;
+    Ok(msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stats() {
+        let mut buffer = AlignedBuffer::new(256);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        msg.put_nested(sys::TCA_STATS2, |msg| {
+            // struct gnet_stats_basic is 12 bytes, but the kernel sends it padded to 16.
+            let mut basic = 1500u64.to_ne_bytes().to_vec();
+            basic.extend_from_slice(&3u32.to_ne_bytes());
+            basic.extend_from_slice(&[0; 4]);
+            msg.put(sys::TCA_STATS_BASIC, &basic)?;
+            let queue: Vec<u8> = [2u32, 3000, 4, 5, 6]
+                .iter()
+                .flat_map(|value| value.to_ne_bytes())
+                .collect();
+            msg.put(sys::TCA_STATS_QUEUE, &queue)?;
+            let rate_est64 = [(1u64 << 33).to_ne_bytes(), 7u64.to_ne_bytes()].concat();
+            msg.put(sys::TCA_STATS_RATE_EST64, &rate_est64)
+        })
+        .unwrap();
+        let attr = Attrs::new(&msg.as_bytes()[size_of::<libc::nlmsghdr>()..])
+            .next()
+            .unwrap()
+            .unwrap();
+
+        let stats = TcStats::parse(attr).unwrap();
+        assert_eq!(
+            stats,
+            TcStats {
+                bytes: 1500,
+                packets: 3,
+                qlen: 2,
+                backlog: 3000,
+                drops: 4,
+                requeues: 5,
+                overlimits: 6,
+                bps: 1 << 33,
+                pps: 7,
+            }
+        );
+
+        // A truncated struct is an error rather than garbage.
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        msg.put_nested(sys::TCA_STATS2, |msg| {
+            msg.put(sys::TCA_STATS_QUEUE, &[0; 8])
+        })
+        .unwrap();
+        let attr = Attrs::new(&msg.as_bytes()[size_of::<libc::nlmsghdr>()..])
+            .next()
+            .unwrap()
+            .unwrap();
+        assert!(TcStats::parse(attr).is_err());
+    }
+}
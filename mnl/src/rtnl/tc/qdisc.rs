@@ -0,0 +1,354 @@
+use mnl_sys::libc;
+use std::io;
+
+use super::{TC_H_INGRESS, TC_H_ROOT, TcMsg, TcStats, tc_handle, tc_request};
+use crate::{
+    NlMsg, NlMsgBuilder, Socket,
+    buffer::AlignedBuffer,
+    rtnl::{
+        REQUEST_BUFFER_SIZE, RawAttrs, ack, dump, new_request,
+        sys::{self, Pod, tc_htb_glob, tcmsg},
+    },
+};
+
+/// A queueing discipline attached to a device, as described by a `RTM_NEWQDISC` message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Qdisc {
+    /// Index of the device the qdisc is attached to.
+    pub index: u32,
+    /// Handle of the qdisc. Only the major number is used.
+    pub handle: u32,
+    /// Handle of the parent class, or [`TC_H_ROOT`] or [`TC_H_INGRESS`].
+    pub parent: u32,
+    /// Kind and kind specific options.
+    pub options: QdiscOptions,
+    /// Statistics (`TCA_STATS2`).
+    pub stats: Option<TcStats>,
+    /// Attributes without a typed field above.
+    pub other_attrs: RawAttrs,
+}
+
+/// The kind of a qdisc with its kind specific options.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QdiscOptions {
+    /// Fair queueing with controlled delay (`fq_codel`).
+    FqCodel(FqCodel),
+    /// Hierarchical token bucket (`htb`), shaping traffic with [`HtbClass`]es.
+    ///
+    /// [`HtbClass`]: super::HtbClass
+    Htb(Htb),
+    /// The `ingress` qdisc, for attaching filters to incoming traffic.
+    Ingress,
+    /// The `clsact` qdisc, for attaching filters to incoming and outgoing traffic.
+    Clsact,
+    /// A kind not known to this crate, with its raw options.
+    Other {
+        /// The kind, such as `"noqueue"`.
+        kind: String,
+        /// Raw payload of `TCA_OPTIONS`.
+        options: Vec<u8>,
+    },
+}
+
+/// Options of the `fq_codel` qdisc. Unset options keep the kernel defaults.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FqCodel {
+    /// Maximum number of queued packets.
+    pub limit: Option<u32>,
+    /// Number of flow queues.
+    pub flows: Option<u32>,
+    /// Acceptable minimum queueing delay, in microseconds.
+    pub target: Option<u32>,
+    /// Interval the queueing delay is measured over, in microseconds.
+    pub interval: Option<u32>,
+    /// Number of bytes dequeued from a flow per round.
+    pub quantum: Option<u32>,
+    /// Mark packets with ECN instead of dropping them.
+    pub ecn: Option<bool>,
+    /// Mark packets with ECN above this queueing delay, in microseconds.
+    pub ce_threshold: Option<u32>,
+    /// Maximum number of packets dropped at once when the limit is exceeded.
+    pub drop_batch_size: Option<u32>,
+    /// Maximum number of bytes used for queued packets.
+    pub memory_limit: Option<u32>,
+}
+
+/// Options of the `htb` qdisc.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Htb {
+    /// Minor number of the class unclassified traffic is sent to. Unclassified traffic bypasses
+    /// shaping if 0 or no such class exists.
+    pub default_class: u16,
+    /// Queue length for traffic bypassing shaping.
+    pub direct_qlen: Option<u32>,
+}
+
+impl QdiscOptions {
+    /// Returns the kind of the qdisc, such as `"fq_codel"`.
+    pub fn kind(&self) -> &str {
+        match self {
+            QdiscOptions::FqCodel(_) => "fq_codel",
+            QdiscOptions::Htb(_) => "htb",
+            QdiscOptions::Ingress => "ingress",
+            QdiscOptions::Clsact => "clsact",
+            QdiscOptions::Other { kind, .. } => kind,
+        }
+    }
+
+    fn parse(tc: &TcMsg<'_>) -> io::Result<Self> {
+        Ok(match tc.kind {
+            "fq_codel" => {
+                let mut options = FqCodel::default();
+                for attr in tc.nested_options() {
+                    let attr = attr?;
+                    let field = match attr.attr_type() {
+                        sys::TCA_FQ_CODEL_LIMIT => &mut options.limit,
+                        sys::TCA_FQ_CODEL_FLOWS => &mut options.flows,
+                        sys::TCA_FQ_CODEL_TARGET => &mut options.target,
+                        sys::TCA_FQ_CODEL_INTERVAL => &mut options.interval,
+                        sys::TCA_FQ_CODEL_QUANTUM => &mut options.quantum,
+                        sys::TCA_FQ_CODEL_CE_THRESHOLD => &mut options.ce_threshold,
+                        sys::TCA_FQ_CODEL_DROP_BATCH_SIZE => &mut options.drop_batch_size,
+                        sys::TCA_FQ_CODEL_MEMORY_LIMIT => &mut options.memory_limit,
+                        sys::TCA_FQ_CODEL_ECN => {
+                            options.ecn = Some(attr.get_u32()? != 0);
+                            continue;
+                        }
+                        _ => continue,
+                    };
+                    *field = Some(attr.get_u32()?);
+                }
+                QdiscOptions::FqCodel(options)
+            }
+            "htb" => {
+                let mut options = Htb::default();
+                for attr in tc.nested_options() {
+                    let attr = attr?;
+                    match attr.attr_type() {
+                        sys::TCA_HTB_INIT => {
+                            let glob = tc_htb_glob::from_bytes(attr.payload())?;
+                            options.default_class = glob.defcls as u16;
+                        }
+                        sys::TCA_HTB_DIRECT_QLEN => options.direct_qlen = Some(attr.get_u32()?),
+                        _ => (),
+                    }
+                }
+                QdiscOptions::Htb(options)
+            }
+            "ingress" => QdiscOptions::Ingress,
+            "clsact" => QdiscOptions::Clsact,
+            kind => QdiscOptions::Other {
+                kind: kind.to_owned(),
+                options: tc.raw_options(),
+            },
+        })
+    }
+
+    fn put(&self, msg: &mut NlMsgBuilder<'_>) -> io::Result<()> {
+        match self {
+            QdiscOptions::FqCodel(options) => msg.put_nested(sys::TCA_OPTIONS, |msg| {
+                let values = [
+                    (sys::TCA_FQ_CODEL_LIMIT, options.limit),
+                    (sys::TCA_FQ_CODEL_FLOWS, options.flows),
+                    (sys::TCA_FQ_CODEL_TARGET, options.target),
+                    (sys::TCA_FQ_CODEL_INTERVAL, options.interval),
+                    (sys::TCA_FQ_CODEL_QUANTUM, options.quantum),
+                    (sys::TCA_FQ_CODEL_ECN, options.ecn.map(u32::from)),
+                    (sys::TCA_FQ_CODEL_CE_THRESHOLD, options.ce_threshold),
+                    (sys::TCA_FQ_CODEL_DROP_BATCH_SIZE, options.drop_batch_size),
+                    (sys::TCA_FQ_CODEL_MEMORY_LIMIT, options.memory_limit),
+                ];
+                for (attr_type, value) in values {
+                    if let Some(value) = value {
+                        msg.put_u32(attr_type, value)?;
+                    }
+                }
+                Ok(())
+            }),
+            QdiscOptions::Htb(options) => msg.put_nested(sys::TCA_OPTIONS, |msg| {
+                let glob = tc_htb_glob {
+                    version: sys::TC_HTB_PROTOVER,
+                    rate2quantum: 10,
+                    defcls: u32::from(options.default_class),
+                    ..tc_htb_glob::default()
+                };
+                msg.put(sys::TCA_HTB_INIT, glob.as_bytes())?;
+                if let Some(direct_qlen) = options.direct_qlen {
+                    msg.put_u32(sys::TCA_HTB_DIRECT_QLEN, direct_qlen)?;
+                }
+                Ok(())
+            }),
+            QdiscOptions::Ingress | QdiscOptions::Clsact => Ok(()),
+            QdiscOptions::Other { options, .. } if options.is_empty() => Ok(()),
+            QdiscOptions::Other { options, .. } => msg.put(sys::TCA_OPTIONS, options),
+        }
+    }
+}
+
+impl Qdisc {
+    /// Parse a `RTM_NEWQDISC` or `RTM_DELQDISC` message.
+    pub fn from_msg(msg: &NlMsg<'_>) -> io::Result<Qdisc> {
+        let tc = TcMsg::parse(msg)?;
+        Ok(Qdisc {
+            index: tc.header.tcm_ifindex as u32,
+            handle: tc.header.tcm_handle,
+            parent: tc.header.tcm_parent,
+            options: QdiscOptions::parse(&tc)?,
+            stats: tc.stats,
+            other_attrs: tc.other_attrs,
+        })
+    }
+
+    fn header(&self) -> tcmsg {
+        tcmsg {
+            tcm_ifindex: self.index as i32,
+            tcm_handle: self.handle,
+            tcm_parent: self.parent,
+            ..tcmsg::default()
+        }
+    }
+}
+
+/// Dump the qdiscs of all devices in the network namespace of `socket`, which must be a
+/// [`Bus::Route`] socket.
+///
+/// [`Bus::Route`]: crate::Bus::Route
+pub fn qdiscs(socket: &Socket) -> io::Result<Vec<Qdisc>> {
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let msg = new_request(
+        socket,
+        &mut buffer,
+        sys::RTM_GETQDISC,
+        libc::NLM_F_DUMP as u16,
+        &tcmsg::default(),
+    )?;
+    dump(socket, &msg, sys::RTM_NEWQDISC, Qdisc::from_msg)
+}
+
+/// Builder for attaching a qdisc to a device with `RTM_NEWQDISC`.
+///
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// use mnl::rtnl::{self, FqCodel, NewQdisc, QdiscOptions};
+///
+/// let socket = mnl::Socket::new(mnl::Bus::Route)?;
+/// let link = rtnl::link_by_name(&socket, "eth0")?;
+/// let fq_codel = FqCodel {
+///     ecn: Some(true),
+///     ..FqCodel::default()
+/// };
+/// NewQdisc::new(link.index, QdiscOptions::FqCodel(fq_codel)).replace(&socket)?;
+/// NewQdisc::new(link.index, QdiscOptions::Clsact).add(&socket)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewQdisc {
+    qdisc: Qdisc,
+}
+
+impl NewQdisc {
+    /// Describe a qdisc of the given kind on the device with the given index. Ingress and
+    /// clsact qdiscs get the parent [`TC_H_INGRESS`] and handle `ffff:`, other qdiscs become
+    /// the root qdisc with a handle picked by the kernel.
+    pub fn new(index: u32, options: QdiscOptions) -> Self {
+        let (handle, parent) = match options {
+            QdiscOptions::Ingress | QdiscOptions::Clsact => (tc_handle(0xffff, 0), TC_H_INGRESS),
+            _ => (0, TC_H_ROOT),
+        };
+        NewQdisc {
+            qdisc: Qdisc {
+                index,
+                handle,
+                parent,
+                options,
+                stats: None,
+                other_attrs: RawAttrs::new(),
+            },
+        }
+    }
+
+    /// Set the handle of the qdisc, such as `tc_handle(1, 0)` for `1:`.
+    pub fn handle(mut self, handle: u32) -> Self {
+        self.qdisc.handle = handle;
+        self
+    }
+
+    /// Attach the qdisc to the class with the given handle rather than the root of the device.
+    pub fn parent(mut self, parent: u32) -> Self {
+        self.qdisc.parent = parent;
+        self
+    }
+
+    /// Attach the qdisc on `socket`, which must be a [`Bus::Route`] socket. Fails with `EEXIST`
+    /// if the parent already has a qdisc other than the default one.
+    ///
+    /// [`Bus::Route`]: crate::Bus::Route
+    pub fn add(&self, socket: &Socket) -> io::Result<()> {
+        self.send(socket, libc::NLM_F_EXCL)
+    }
+
+    /// Attach the qdisc on `socket`, replacing any qdisc already attached to the parent.
+    pub fn replace(&self, socket: &Socket) -> io::Result<()> {
+        self.send(socket, libc::NLM_F_REPLACE)
+    }
+
+    fn send(&self, socket: &Socket, flags: libc::c_int) -> io::Result<()> {
+        let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+        let mut msg = tc_request(
+            socket,
+            &mut buffer,
+            sys::RTM_NEWQDISC,
+            flags | libc::NLM_F_CREATE | libc::NLM_F_ACK,
+            &self.qdisc.header(),
+            self.qdisc.options.kind(),
+        )?;
+        self.qdisc.options.put(&mut msg)?;
+        ack(socket, &msg)
+    }
+}
+
+/// Remove a qdisc with `RTM_DELQDISC`, restoring the default qdisc if it was the root qdisc.
+pub fn delete_qdisc(socket: &Socket, qdisc: &Qdisc) -> io::Result<()> {
+    let mut buffer = AlignedBuffer::new(REQUEST_BUFFER_SIZE);
+    let msg = tc_request(
+        socket,
+        &mut buffer,
+        sys::RTM_DELQDISC,
+        libc::NLM_F_ACK,
+        &qdisc.header(),
+        qdisc.options.kind(),
+    )?;
+    ack(socket, &msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fq_codel_round_trip() {
+        let options = QdiscOptions::FqCodel(FqCodel {
+            limit: Some(1000),
+            target: Some(5000),
+            ecn: Some(true),
+            ..FqCodel::default()
+        });
+        let mut buffer = AlignedBuffer::new(256);
+        let mut msg = NlMsgBuilder::new(buffer.as_mut_bytes()).unwrap();
+        msg.set_type(sys::RTM_NEWQDISC);
+        let header = tcmsg {
+            tcm_ifindex: 3,
+            tcm_parent: TC_H_ROOT,
+            ..tcmsg::default()
+        };
+        msg.put_extra_header(header.as_bytes()).unwrap();
+        msg.put_str(sys::TCA_KIND, options.kind()).unwrap();
+        options.put(&mut msg).unwrap();
+
+        let qdisc = Qdisc::from_msg(&NlMsg::new(msg.as_bytes()).unwrap()).unwrap();
+        assert_eq!(qdisc.index, 3);
+        assert_eq!(qdisc.parent, TC_H_ROOT);
+        assert_eq!(qdisc.options, options);
+    }
+}
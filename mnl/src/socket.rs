@@ -4,11 +4,25 @@ use mnl_sys::{
     libc::{c_uint, c_void, pid_t},
 };
 use std::{
-    io, mem,
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    io,
+    mem::{self, ManuallyDrop},
     os::unix::io::{AsRawFd, RawFd},
+    ptr, thread,
+    time::{SystemTime, UNIX_EPOCH},
 };
 
-use crate::{NlMessages, cvt::cvt};
+use crate::{NlMessages, NlMsg, buffer::AlignedBuffer, cvt::cvt, genl::GenlFamily};
+
+/// Size of the buffer used to receive replies in [`Socket::request`] and notifications in
+/// other receive loops. Large enough for any message the kernel sends during dumps. Same as
+/// `MNL_SOCKET_DUMP_SIZE` in newer libmnl.
+pub(crate) const RECV_BUFFER_SIZE: usize = 32768;
+
+/// Socket option and control message type for receiving notifications from all peer network
+/// namespaces. Not exported by every libc target.
+const NETLINK_LISTEN_ALL_NSID: libc::c_int = 8;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 #[allow(missing_docs)]
@@ -79,6 +93,9 @@ impl Bus {
 /// [`close`]: #method.close
 pub struct Socket {
     socket: *mut mnl_sys::mnl_socket,
+    bus: Bus,
+    seq: Cell<u32>,
+    pub(crate) genl_families: RefCell<HashMap<String, GenlFamily>>,
 }
 
 impl Socket {
@@ -97,9 +114,80 @@ impl Socket {
 
     /// Open a new Netlink socket to the given bus ID.
     pub fn open(bus: Bus) -> io::Result<Self> {
-        Ok(Socket {
-            socket: cvt(unsafe { mnl_sys::mnl_socket_open(bus as i32) })?,
-        })
+        let socket = cvt(unsafe { mnl_sys::mnl_socket_open(bus as i32) })?;
+        Ok(Self::from_raw(socket, bus))
+    }
+
+    /// Open a new Netlink socket to the given bus ID in the network namespace referred to by
+    /// `netns`, such as an open `/proc/<pid>/ns/net` or `/run/netns/<name>` file, and bind it
+    /// like [`new`].
+    ///
+    /// The socket is created on a short-lived thread that enters the namespace, so the
+    /// namespace of the calling thread is left untouched. A netlink socket stays in the
+    /// namespace it was created in, the returned socket can be used from any thread.
+    /// Entering a namespace requires `CAP_SYS_ADMIN`.
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// use std::{fs::File, os::unix::io::AsRawFd};
+    ///
+    /// let netns = File::open("/run/netns/blue")?;
+    /// let socket = mnl::Socket::new_in_netns(mnl::Bus::Route, netns.as_raw_fd())?;
+    /// for link in mnl::rtnl::links(&socket)? {
+    ///     println!("{}: {}", link.index, link.name);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`new`]: #method.new
+    pub fn new_in_netns(bus: Bus, netns: RawFd) -> io::Result<Self> {
+        struct SendSocket(*mut mnl_sys::mnl_socket);
+        // SAFETY: The mnl_socket is a plain heap allocation wrapping the fd, not tied to the
+        // thread that created it, and it is only used again after that thread has finished.
+        unsafe impl Send for SendSocket {}
+
+        let socket = thread::Builder::new()
+            .name("mnl-netns".to_owned())
+            .spawn(move || -> io::Result<SendSocket> {
+                cvt(unsafe { libc::setns(netns, libc::CLONE_NEWNET) })?;
+                let socket = cvt(unsafe { mnl_sys::mnl_socket_open(bus as i32) })?;
+                Ok(SendSocket(socket))
+            })?
+            .join()
+            .map_err(|_| io::Error::other("Thread opening the socket panicked"))??;
+
+        let socket = Self::from_raw(socket.0, bus);
+        socket.bind(0, mnl_sys::MNL_SOCKET_AUTOPID)?;
+        Ok(socket)
+    }
+
+    fn from_raw(socket: *mut mnl_sys::mnl_socket, bus: Bus) -> Self {
+        // Same as libmnl examples, use the current time as the initial sequence number to make
+        // it unlikely to mix up replies between program runs.
+        let seq = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_secs() as u32)
+            .unwrap_or(0);
+        Socket {
+            socket,
+            bus,
+            seq: Cell::new(seq),
+            genl_families: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the bus this socket was opened on.
+    pub fn bus(&self) -> Bus {
+        self.bus
+    }
+
+    /// Returns a new sequence number to use for a request on this socket. Every call returns a
+    /// different number.
+    pub fn next_seq(&self) -> u32 {
+        let seq = self.seq.get().wrapping_add(1);
+        self.seq.set(seq);
+        seq
     }
 
     /// Bind the Netlink socket.
@@ -174,6 +262,177 @@ impl Socket {
         Ok(result as usize)
     }
 
+    /// Receive Netlink messages from the socket without blocking.
+    ///
+    /// Returns `None` if there is nothing to receive at the moment. Otherwise works like
+    /// [`recv`], including the alignment requirement on `buffer`.
+    ///
+    /// [`recv`]: #method.recv
+    pub fn try_recv<'a>(&self, buffer: &'a mut [u8]) -> io::Result<Option<NlMessages<'a>>> {
+        debug_assert!(
+            buffer.as_ptr().cast::<nlmsghdr>().is_aligned(),
+            "`buffer` must be aligned to nlmsghdr",
+        );
+
+        let len = buffer.len();
+        let ptr = buffer.as_mut_ptr().cast::<c_void>();
+        let flags = libc::MSG_DONTWAIT | libc::MSG_TRUNC;
+        match cvt(unsafe { libc::recv(self.as_raw_fd(), ptr, len, flags) }) {
+            // Same as mnl_socket_recvfrom, report truncated messages as ENOSPC.
+            Ok(n) if n as usize > len => Err(io::Error::from_raw_os_error(libc::ENOSPC)),
+            Ok(n) => Ok(Some(NlMessages::new(&buffer[..n as usize]))),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Receive Netlink messages from the socket together with the id of the network namespace
+    /// they originate from.
+    ///
+    /// The id is only reported when [`set_listen_all_nsid`] is enabled and the message comes
+    /// from a peer namespace that has an id assigned in the namespace of this socket, see
+    /// [`rtnl::assign_netns_id`]. Messages from the namespace of the socket itself are returned
+    /// with `None`. Otherwise works like [`recv`], including the alignment requirement on
+    /// `buffer`.
+    ///
+    /// [`set_listen_all_nsid`]: #method.set_listen_all_nsid
+    /// [`recv`]: #method.recv
+    /// [`rtnl::assign_netns_id`]: crate::rtnl::assign_netns_id
+    pub fn recv_with_nsid<'a>(
+        &self,
+        buffer: &'a mut [u8],
+    ) -> io::Result<(NlMessages<'a>, Option<i32>)> {
+        debug_assert!(
+            buffer.as_ptr().cast::<nlmsghdr>().is_aligned(),
+            "`buffer` must be aligned to nlmsghdr",
+        );
+
+        let mut iov = libc::iovec {
+            iov_base: buffer.as_mut_ptr().cast::<c_void>(),
+            iov_len: buffer.len(),
+        };
+        // Room for a few control messages, aligned like cmsghdr.
+        let mut control = [0usize; 16];
+        let mut hdr: libc::msghdr = unsafe { mem::zeroed() };
+        hdr.msg_iov = &mut iov;
+        hdr.msg_iovlen = 1;
+        hdr.msg_control = control.as_mut_ptr().cast::<c_void>();
+        hdr.msg_controllen = mem::size_of_val(&control) as _;
+        let n = cvt(unsafe { libc::recvmsg(self.as_raw_fd(), &mut hdr, 0) })? as usize;
+        // Same as mnl_socket_recvfrom, report truncated messages as ENOSPC.
+        if hdr.msg_flags & libc::MSG_TRUNC != 0 {
+            return Err(io::Error::from_raw_os_error(libc::ENOSPC));
+        }
+
+        // The control lengths are `socklen_t` rather than `size_t` on some targets.
+        #[allow(clippy::unnecessary_cast)]
+        let control_len = (hdr.msg_controllen as usize).min(mem::size_of_val(&control));
+        let control =
+            unsafe { std::slice::from_raw_parts(control.as_ptr().cast::<u8>(), control_len) };
+        let nsid = nsid_from_control(control);
+        Ok((NlMessages::new(&buffer[..n]), nsid))
+    }
+
+    /// Send a request message and process the replies until the request is complete. The
+    /// request is considered complete when an acknowledgement, an error or `NLMSG_DONE` is
+    /// received, or after the first reply if the request neither asks for an acknowledgement
+    /// (`NLM_F_ACK`) nor results in a multipart reply.
+    ///
+    /// `f` is called with every reply carrying the sequence number of the request, except the
+    /// messages signaling completion. Errors reported by the kernel are returned as `Err`.
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// use mnl::mnl_sys::libc;
+    ///
+    /// const RTM_GETLINK: u16 = 18;
+    ///
+    /// let socket = mnl::Socket::new(mnl::Bus::Route)?;
+    /// let mut buffer = vec![0; 256];
+    /// let mut msg = mnl::NlMsgBuilder::new(&mut buffer)?;
+    /// msg.set_type(RTM_GETLINK);
+    /// msg.set_flags((libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16);
+    /// msg.set_seq(socket.next_seq());
+    /// msg.put_extra_header(&[0; 16])?;
+    /// socket.request(msg.as_bytes(), |reply| {
+    ///     println!("Received message of type {}", reply.msg_type());
+    ///     Ok(())
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn request<F>(&self, msg: &[u8], mut f: F) -> io::Result<()>
+    where
+        F: FnMut(NlMsg<'_>) -> io::Result<()>,
+    {
+        let request = NlMsg::new(msg)?;
+        let wants_ack = request.flags() & libc::NLM_F_ACK as u16 != 0;
+        self.send_all([msg])?;
+
+        let mut buffer = AlignedBuffer::new(RECV_BUFFER_SIZE);
+        loop {
+            for message in self.recv(buffer.as_mut_bytes())? {
+                let reply = NlMsg::new(message?)?;
+                if reply.seq() != request.seq() {
+                    log::debug!("Ignoring netlink message with seq {}", reply.seq());
+                    continue;
+                }
+                if let Some(result) = reply.error() {
+                    return result;
+                }
+                if reply.is_done() {
+                    // A dump that fails half way through reports the error in NLMSG_DONE.
+                    return match reply
+                        .payload()
+                        .get(..4)
+                        .map(|e| i32::from_ne_bytes(e.try_into().unwrap()))
+                    {
+                        Some(errno) if errno < 0 => Err(io::Error::from_raw_os_error(-errno)),
+                        _ => Ok(()),
+                    };
+                }
+                f(reply)?;
+                if reply.flags() & libc::NLM_F_MULTI as u16 == 0 && !wants_ack {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Subscribe to the multicast group with the given id, using `NETLINK_ADD_MEMBERSHIP`.
+    /// Unlike the groups given to [`bind`], this works for group ids above 32.
+    ///
+    /// [`bind`]: #method.bind
+    pub fn join_group(&self, group: u32) -> io::Result<()> {
+        self.set_membership(libc::NETLINK_ADD_MEMBERSHIP, group)
+    }
+
+    /// Unsubscribe from the multicast group with the given id, using `NETLINK_DROP_MEMBERSHIP`.
+    pub fn leave_group(&self, group: u32) -> io::Result<()> {
+        self.set_membership(libc::NETLINK_DROP_MEMBERSHIP, group)
+    }
+
+    /// Enable or disable `NETLINK_LISTEN_ALL_NSID`. While enabled, the socket also receives the
+    /// notifications of the multicast groups it is subscribed to from every peer network
+    /// namespace that has an id assigned in the namespace of the socket. Use
+    /// [`recv_with_nsid`] to tell them apart.
+    ///
+    /// [`recv_with_nsid`]: #method.recv_with_nsid
+    pub fn set_listen_all_nsid(&self, enable: bool) -> io::Result<()> {
+        self.set_option(NETLINK_LISTEN_ALL_NSID, u32::from(enable))
+    }
+
+    fn set_membership(&self, option: i32, group: u32) -> io::Result<()> {
+        self.set_option(option, group)
+    }
+
+    fn set_option(&self, option: i32, mut value: u32) -> io::Result<()> {
+        let ptr = &mut value as *mut u32 as *mut c_void;
+        let len = size_of::<u32>() as libc::socklen_t;
+        cvt(unsafe { mnl_sys::mnl_socket_setsockopt(self.socket, option, ptr, len) })?;
+        Ok(())
+    }
+
     /// Obtain Netlink PortID from netlink socket.
     pub fn portid(&self) -> c_uint {
         unsafe { mnl_sys::mnl_socket_get_portid(self.socket) }
@@ -181,9 +440,12 @@ impl Socket {
 
     /// Try to close the socket, returns the corresponding error on failure.
     pub fn close(self) -> io::Result<()> {
-        cvt(unsafe { mnl_sys::mnl_socket_close(self.socket) })?;
-        mem::forget(self);
-        Ok(())
+        // mnl_socket_close frees the socket even if closing the fd fails, so never run the
+        // Drop impl after this.
+        let mut socket = ManuallyDrop::new(self);
+        let result = cvt(unsafe { mnl_sys::mnl_socket_close(socket.socket) });
+        unsafe { ptr::drop_in_place(&mut socket.genl_families) };
+        result.map(|_| ())
     }
 
     /// Return the pointer to the underlying C struct. Can be used with the `mnl_sys` crate to
@@ -204,3 +466,84 @@ impl AsRawFd for Socket {
         unsafe { mnl_sys::mnl_socket_get_fd(self.socket) }
     }
 }
+
+/// Find the `NETLINK_LISTEN_ALL_NSID` control message in the ancillary data of a `recvmsg` call
+/// and return the namespace id it carries.
+fn nsid_from_control(mut control: &[u8]) -> Option<i32> {
+    let align = |len: usize| (len + mem::size_of::<usize>() - 1) & !(mem::size_of::<usize>() - 1);
+    let header_len = align(mem::size_of::<libc::cmsghdr>());
+    while control.len() >= mem::size_of::<libc::cmsghdr>() {
+        let cmsg = unsafe { control.as_ptr().cast::<libc::cmsghdr>().read_unaligned() };
+        #[allow(clippy::unnecessary_cast)]
+        let len = cmsg.cmsg_len as usize;
+        if len < header_len || len > control.len() {
+            break;
+        }
+        if cmsg.cmsg_level == libc::SOL_NETLINK && cmsg.cmsg_type == NETLINK_LISTEN_ALL_NSID {
+            let data = control.get(header_len..header_len + 4)?;
+            return Some(i32::from_ne_bytes(data.try_into().unwrap()));
+        }
+        control = control.get(align(len)..).unwrap_or_default();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cmsg(level: libc::c_int, cmsg_type: libc::c_int, data: &[u8]) -> Vec<u8> {
+        let header_len = mem::size_of::<libc::cmsghdr>();
+        let mut header: libc::cmsghdr = unsafe { mem::zeroed() };
+        header.cmsg_len = (header_len + data.len()) as _;
+        header.cmsg_level = level;
+        header.cmsg_type = cmsg_type;
+        let header = unsafe {
+            std::slice::from_raw_parts((&header as *const libc::cmsghdr).cast::<u8>(), header_len)
+        };
+        let mut bytes = [header, data].concat();
+        bytes.resize(bytes.len().next_multiple_of(mem::size_of::<usize>()), 0);
+        bytes
+    }
+
+    #[test]
+    fn test_nsid_from_control() {
+        assert_eq!(nsid_from_control(&[]), None);
+
+        let other = cmsg(libc::SOL_SOCKET, 1, &[0xff; 12]);
+        let nsid = cmsg(
+            libc::SOL_NETLINK,
+            NETLINK_LISTEN_ALL_NSID,
+            &7i32.to_ne_bytes(),
+        );
+        assert_eq!(nsid_from_control(&other), None);
+        assert_eq!(nsid_from_control(&[other, nsid].concat()), Some(7));
+    }
+
+    #[test]
+    fn test_close_error() {
+        let socket = Socket::new(Bus::Netfilter).unwrap();
+        // Close the fd behind the back of the socket, so closing it again fails. The socket
+        // must still be freed exactly once.
+        unsafe { libc::close(socket.as_raw_fd()) };
+        let error = socket.close().unwrap_err();
+        assert_eq!(error.raw_os_error(), Some(libc::EBADF));
+    }
+
+    #[test]
+    fn test_new_in_netns_error() {
+        let netns = || std::fs::read_link("/proc/thread-self/ns/net").unwrap();
+        let before = netns();
+
+        let error = Socket::new_in_netns(Bus::Route, -1).err().unwrap();
+        assert_eq!(error.raw_os_error(), Some(libc::EBADF));
+        // A file that is not a network namespace.
+        let file = std::fs::File::open("/proc/self/status").unwrap();
+        let error = Socket::new_in_netns(Bus::Route, file.as_raw_fd())
+            .err()
+            .unwrap();
+        assert_eq!(error.raw_os_error(), Some(libc::EINVAL));
+
+        assert_eq!(netns(), before);
+    }
+}